@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    // Git commit hash of the build. It can be provided via the `SWS_GIT_HASH` env
+    // when building outside of a Git checkout (e.g. Docker images or crates.io sources).
+    let hash = std::env::var("SWS_GIT_HASH")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .or_else(|| {
+            Command::new("git")
+                .args(["rev-parse", "--short", "HEAD"])
+                .output()
+                .ok()
+                .filter(|out| out.status.success())
+                .and_then(|out| String::from_utf8(out.stdout).ok())
+        })
+        .unwrap_or_default();
+    println!("cargo:rustc-env=SWS_GIT_HASH={}", hash.trim());
+
+    println!("cargo:rerun-if-env-changed=SWS_GIT_HASH");
+    for path in [".git/HEAD", ".git/refs"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+}
@@ -0,0 +1,274 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to log every request exactly once after its completion.
+//!
+//! The log entry is emitted when its guard is dropped, that is once the response body
+//! is fully sent, the connection is dropped mid-response or the request handler fails,
+//! so errored and aborted requests are logged too along with their status,
+//! duration and number of body bytes sent.
+//!
+
+use hyper::{
+    body::{Bytes, HttpBody, SizeHint},
+    header::CONTENT_LENGTH,
+    Body, HeaderMap, Method, Request, Response, StatusCode, Uri,
+};
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use crate::{request_id::RequestId, stats::InFlightRequest, Error, Result};
+
+#[cfg(feature = "http2")]
+use crate::tls::ClientCert;
+
+/// Access log entry of a request, logged once dropped.
+#[derive(Debug)]
+pub struct AccessLog {
+    method: Method,
+    uri: Uri,
+    remote_addr: Option<SocketAddr>,
+    real_remote_ip: Option<IpAddr>,
+    request_id: Option<RequestId>,
+    client_cn: Option<String>,
+    started: Instant,
+    status: Option<StatusCode>,
+    bytes_sent: u64,
+    aborted: bool,
+    error: Option<String>,
+    /// Probe requests (e.g. health checks) are logged at the debug level.
+    probe: bool,
+    /// The request is counted as in-flight until its entry is logged.
+    _in_flight: Option<InFlightRequest>,
+}
+
+impl AccessLog {
+    /// Creates the access log entry of a request.
+    /// The remote address (and the real one if forwarded) is only logged if provided.
+    pub fn new(req: &Request<Body>, remote_addr: Option<SocketAddr>, probe: bool) -> Self {
+        #[cfg(feature = "http2")]
+        let client_cn = req
+            .extensions()
+            .get::<ClientCert>()
+            .and_then(|cert| cert.common_name.clone());
+        #[cfg(not(feature = "http2"))]
+        let client_cn = None;
+        Self {
+            method: req.method().clone(),
+            uri: req.uri().clone(),
+            remote_addr,
+            real_remote_ip: remote_addr.and_then(|_| real_remote_ip(req.headers())),
+            request_id: req.extensions().get::<RequestId>().cloned(),
+            client_cn,
+            started: Instant::now(),
+            status: None,
+            bytes_sent: 0,
+            aborted: false,
+            error: None,
+            probe,
+            _in_flight: None,
+        }
+    }
+
+    /// Keeps the request counted as in-flight until its response body is sent or dropped.
+    pub fn with_in_flight(mut self, in_flight: InFlightRequest) -> Self {
+        self._in_flight = Some(in_flight);
+        self
+    }
+
+    /// Attaches the entry to the response body so it's logged once the body is sent or dropped.
+    /// A handler error is logged right away as a `500` status.
+    pub fn respond(
+        mut self,
+        result: Result<Response<Body>, Error>,
+    ) -> Result<Response<LoggedBody>, Error> {
+        match result {
+            Ok(resp) => {
+                self.status = Some(resp.status());
+                let no_body = self.method == Method::HEAD
+                    || resp.status() == StatusCode::NO_CONTENT
+                    || resp.status() == StatusCode::NOT_MODIFIED;
+                // Bodies of a known length are not polled to their end once fully sent
+                let content_length = resp
+                    .headers()
+                    .get(CONTENT_LENGTH)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .or_else(|| resp.body().size_hint().exact());
+                Ok(resp.map(|body| LoggedBody {
+                    inner: body,
+                    finished: no_body,
+                    content_length,
+                    log: self,
+                }))
+            }
+            Err(err) => {
+                self.status = Some(StatusCode::INTERNAL_SERVER_ERROR);
+                self.error = Some(err.to_string());
+                Err(err)
+            }
+        }
+    }
+}
+
+impl Drop for AccessLog {
+    fn drop(&mut self) {
+        // A request dropped without a response means the connection was closed meanwhile
+        let status = match self.status {
+            Some(status) => status.as_u16().to_string(),
+            None => {
+                self.aborted = true;
+                String::from("-")
+            }
+        };
+        let mut extra = String::new();
+        if let Some(remote_addr) = self.remote_addr {
+            extra.push_str(&format!(" remote_addr={remote_addr}"));
+        }
+        if let Some(real_remote_ip) = self.real_remote_ip {
+            extra.push_str(&format!(" real_remote_ip={real_remote_ip}"));
+        }
+        if let Some(request_id) = &self.request_id {
+            extra.push_str(&format!(" request_id={}", request_id.as_str()));
+        }
+        if let Some(client_cn) = &self.client_cn {
+            extra.push_str(&format!(" client_cn={client_cn}"));
+        }
+        if self.aborted {
+            extra.push_str(" aborted=true");
+        }
+        if let Some(error) = &self.error {
+            extra.push_str(&format!(" error={error:?}"));
+        }
+        let duration_ms = self.started.elapsed().as_millis();
+        if self.probe && self.error.is_none() {
+            tracing::debug!(
+                "request completed: method={} uri={} status={} duration_ms={} bytes_sent={}{}",
+                self.method,
+                self.uri,
+                status,
+                duration_ms,
+                self.bytes_sent,
+                extra,
+            );
+        } else {
+            tracing::info!(
+                "request completed: method={} uri={} status={} duration_ms={} bytes_sent={}{}",
+                self.method,
+                self.uri,
+                status,
+                duration_ms,
+                self.bytes_sent,
+                extra,
+            );
+        }
+    }
+}
+
+/// Returns the client IP of the `X-Forwarded-For` header (its first entry) if valid.
+/// Invalid values are ignored to prevent log poisoning.
+pub fn real_remote_ip(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get("X-Forwarded-For")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.split(',').next())
+        .and_then(|s| s.trim().parse::<IpAddr>().ok())
+}
+
+/// Response body counting the bytes sent and logging its access log entry once dropped.
+#[derive(Debug)]
+pub struct LoggedBody {
+    inner: Body,
+    finished: bool,
+    content_length: Option<u64>,
+    log: AccessLog,
+}
+
+impl HttpBody for LoggedBody {
+    type Data = Bytes;
+    type Error = hyper::Error;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<std::result::Result<Self::Data, Self::Error>>> {
+        let poll = Pin::new(&mut self.inner).poll_data(cx);
+        match &poll {
+            Poll::Ready(Some(Ok(data))) => self.log.bytes_sent += data.len() as u64,
+            Poll::Ready(Some(Err(err))) => self.log.error = Some(err.to_string()),
+            Poll::Ready(None) => self.finished = true,
+            Poll::Pending => {}
+        }
+        poll
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<std::result::Result<Option<HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.inner).poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Drop for LoggedBody {
+    fn drop(&mut self) {
+        let sent = self
+            .content_length
+            .is_some_and(|len| self.log.bytes_sent >= len);
+        self.log.aborted = !self.finished && !sent && !self.inner.is_end_stream();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{real_remote_ip, AccessLog};
+    use hyper::{body::HttpBody, Body, HeaderMap, Request, Response, StatusCode};
+
+    #[tokio::test]
+    async fn access_log_body() {
+        let req = Request::get("/index.html").body(Body::empty()).unwrap();
+
+        let resp = AccessLog::new(&req, None, false)
+            .respond(Ok(Response::new(Body::from("hello"))))
+            .unwrap();
+        let mut body = resp.into_body();
+        assert_eq!(body.size_hint().exact(), Some(5));
+        while let Some(data) = body.data().await {
+            data.unwrap();
+        }
+        assert_eq!(body.log.bytes_sent, 5);
+        assert!(body.finished);
+        assert_eq!(body.log.status, Some(StatusCode::OK));
+
+        let err = AccessLog::new(&req, None, false).respond(Err(crate::anyhow!("failure")));
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn forwarded_real_remote_ip() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(real_remote_ip(&headers), None);
+        headers.insert(
+            "x-forwarded-for",
+            "203.0.113.195, 10.0.0.1".parse().unwrap(),
+        );
+        assert_eq!(
+            real_remote_ip(&headers),
+            Some("203.0.113.195".parse().unwrap())
+        );
+        headers.insert("x-forwarded-for", "<iframe src=//x>".parse().unwrap());
+        assert_eq!(real_remote_ip(&headers), None);
+    }
+}
@@ -0,0 +1,514 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to obtain and renew TLS certificates automatically via ACME (e.g. Let's Encrypt).
+//!
+//! Certificates are requested using the `http-01` challenge whose files are served
+//! by the HTTP to HTTPS redirect server. The ACME account, the certificate and its private key
+//! are cached in a directory and the certificate is renewed in the background before it expires.
+//!
+
+use instant_acme::{
+    Account, AccountCredentials, AuthorizationStatus, ChallengeType, Identifier, NewAccount,
+    NewOrder, Order, OrderStatus,
+};
+use rcgen::{CertificateParams, DistinguishedName};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::time::sleep;
+use tokio_rustls::rustls::{
+    server::{ClientHello, ResolvesServerCert},
+    sign::{self, CertifiedKey},
+};
+use x509_parser::extensions::GeneralName;
+
+use crate::{readiness::Readiness, tls, Context, Result};
+
+/// URL path of the `http-01` challenge files.
+pub const ACME_CHALLENGE_PATH: &str = "/.well-known/acme-challenge/";
+
+/// Time before the certificate expiration to renew it.
+const RENEW_BEFORE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+/// Maximum time to wait before checking the certificate expiration again.
+const CHECK_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
+/// Time to wait before retrying a failed certificate request.
+const RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Maximum number of order status checks.
+const MAX_ORDER_CHECKS: u32 = 10;
+
+/// ACME options.
+pub struct AcmeOpts {
+    /// Contact email of the ACME account.
+    pub email: String,
+    /// Domain names of the certificate.
+    pub domains: Vec<String>,
+    /// Directory to cache the account, certificate and private key.
+    pub cache_dir: PathBuf,
+    /// Directory URL of the ACME server.
+    pub directory_url: String,
+    /// Whether the operator agreed to the terms of service of the ACME server.
+    pub tos_agreed: bool,
+}
+
+impl AcmeOpts {
+    /// Creates new ACME options from a comma-separated list of domain names.
+    pub fn new(
+        email: &str,
+        domains: &str,
+        cache_dir: PathBuf,
+        directory_url: &str,
+        tos_agreed: bool,
+    ) -> Result<Self> {
+        Ok(Self {
+            email: email.trim().to_owned(),
+            domains: parse_domains(domains)?,
+            cache_dir,
+            directory_url: directory_url.trim().to_owned(),
+            tos_agreed,
+        })
+    }
+
+    /// Returns the directory containing the `http-01` challenge files.
+    pub fn challenge_dir(&self) -> PathBuf {
+        self.cache_dir.join("challenges")
+    }
+
+    fn account_path(&self) -> PathBuf {
+        self.cache_dir.join("account.json")
+    }
+
+    fn cert_path(&self) -> PathBuf {
+        self.cache_dir.join("cert.pem")
+    }
+
+    fn key_path(&self) -> PathBuf {
+        self.cache_dir.join("key.pem")
+    }
+}
+
+/// Parses a comma-separated list of domain names.
+pub fn parse_domains(domains: &str) -> Result<Vec<String>> {
+    let domains = domains
+        .split(',')
+        .map(|s| s.trim().trim_end_matches('.').to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+    if domains.is_empty() {
+        bail!("acme requires at least one domain name");
+    }
+    for domain in &domains {
+        let valid = domain.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+        if !valid {
+            bail!("invalid acme domain name (wildcards are not supported): {domain}");
+        }
+    }
+    Ok(domains)
+}
+
+/// Certificate resolver which allows to replace the certificate at runtime.
+#[derive(Default)]
+pub struct CertResolver {
+    certified_key: RwLock<Option<Arc<CertifiedKey>>>,
+}
+
+impl CertResolver {
+    /// Replaces the current certificate.
+    pub fn set(&self, certified_key: Arc<CertifiedKey>) {
+        match self.certified_key.write() {
+            Ok(mut current) => *current = Some(certified_key),
+            Err(err) => tracing::error!("unable to replace the acme certificate: {:?}", err),
+        }
+    }
+}
+
+impl ResolvesServerCert for CertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        let certified_key = self.certified_key.read().ok()?.clone();
+        if certified_key.is_none() {
+            tracing::warn!("tls handshake rejected, the acme certificate is not available yet");
+        }
+        certified_key
+    }
+}
+
+/// Certificate obtained via ACME.
+pub struct AcmeCert {
+    /// Certificate chain and its signing key.
+    pub certified_key: Arc<CertifiedKey>,
+    /// Expiration time of the certificate.
+    pub not_after: SystemTime,
+    /// DNS names of the certificate.
+    pub names: Vec<String>,
+}
+
+impl AcmeCert {
+    /// Parses a PEM-encoded certificate chain and its private key.
+    pub fn from_pem(cert_pem: &[u8], key_pem: &[u8]) -> Result<Self> {
+        let certs = tls::read_certs(cert_pem).with_context(|| "invalid acme certificate")?;
+        let key = tls::read_private_key(key_pem).with_context(|| "invalid acme private key")?;
+        let signing_key =
+            sign::any_supported_type(&key).with_context(|| "unsupported acme private key")?;
+
+        let end_entity = match certs.first() {
+            Some(cert) => cert,
+            None => bail!("acme certificate chain is empty"),
+        };
+        let (_, x509) = x509_parser::parse_x509_certificate(&end_entity.0)
+            .with_context(|| "unable to parse acme certificate")?;
+        let not_after = SystemTime::UNIX_EPOCH
+            + Duration::from_secs(x509.validity().not_after.timestamp().max(0) as u64);
+        let names = match x509.subject_alternative_name() {
+            Ok(Some(san)) => san
+                .value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(name) => Some(name.to_lowercase()),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        };
+
+        Ok(Self {
+            certified_key: Arc::new(CertifiedKey::new(certs, signing_key)),
+            not_after,
+            names,
+        })
+    }
+
+    /// Checks if the certificate is valid for all the given domain names.
+    pub fn covers(&self, domains: &[String]) -> bool {
+        domains.iter().all(|domain| self.names.contains(domain))
+    }
+
+    /// Returns the time to wait until the certificate should be renewed.
+    pub fn renewal_delay(&self, now: SystemTime) -> Duration {
+        self.not_after
+            .checked_sub(RENEW_BEFORE)
+            .and_then(|renew_at| renew_at.duration_since(now).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Loads the cached certificate if it's valid for the configured domain names.
+pub fn load_cached(opts: &AcmeOpts) -> Option<AcmeCert> {
+    let cert_pem = fs::read(opts.cert_path()).ok()?;
+    let key_pem = fs::read(opts.key_path()).ok()?;
+    match AcmeCert::from_pem(&cert_pem, &key_pem) {
+        Ok(cert) if cert.covers(&opts.domains) => Some(cert),
+        Ok(_) => {
+            tracing::info!("acme cached certificate does not match the configured domains");
+            None
+        }
+        Err(err) => {
+            tracing::warn!("unable to load the acme cached certificate: {:?}", err);
+            None
+        }
+    }
+}
+
+/// Keeps the certificate of the given resolver up to date.
+///
+/// A new certificate is requested right away if `cert` is `None`,
+/// otherwise once the current certificate is about to expire.
+/// The server is marked as ready once a certificate is obtained.
+pub async fn run(
+    opts: AcmeOpts,
+    resolver: Arc<CertResolver>,
+    mut cert: Option<AcmeCert>,
+    ready: Arc<Readiness>,
+) {
+    loop {
+        if let Some(ref cert) = cert {
+            let delay = cert.renewal_delay(SystemTime::now());
+            if !delay.is_zero() {
+                sleep(delay.min(CHECK_INTERVAL)).await;
+                continue;
+            }
+        }
+
+        tracing::info!(
+            "acme: requesting a certificate for {}",
+            opts.domains.join(", ")
+        );
+        match provision(&opts).await {
+            Ok(new_cert) => {
+                tracing::info!("acme: certificate obtained for {}", opts.domains.join(", "));
+                resolver.set(new_cert.certified_key.clone());
+                ready.set_ready();
+                cert = Some(new_cert);
+            }
+            Err(err) => {
+                tracing::error!(
+                    "acme: unable to obtain a certificate, retrying in {}s: {:?}",
+                    RETRY_INTERVAL.as_secs(),
+                    err
+                );
+                sleep(RETRY_INTERVAL).await;
+            }
+        }
+    }
+}
+
+/// Requests a new certificate and stores it in the cache directory.
+async fn provision(opts: &AcmeOpts) -> Result<AcmeCert> {
+    fs::create_dir_all(opts.challenge_dir()).with_context(|| {
+        format!(
+            "unable to create acme cache directory {}",
+            opts.cache_dir.display()
+        )
+    })?;
+
+    let account = account(opts).await?;
+    let identifiers = opts
+        .domains
+        .iter()
+        .map(|domain| Identifier::Dns(domain.to_owned()))
+        .collect::<Vec<_>>();
+    let mut order = account
+        .new_order(&NewOrder {
+            identifiers: &identifiers,
+        })
+        .await
+        .with_context(|| "unable to create acme order")?;
+
+    // Write the challenge files served by the redirect server
+    let mut challenges = vec![];
+    for authz in order
+        .authorizations()
+        .await
+        .with_context(|| "unable to get acme authorizations")?
+    {
+        match authz.status {
+            AuthorizationStatus::Pending => {}
+            AuthorizationStatus::Valid => continue,
+            status => bail!("unexpected acme authorization status: {:?}", status),
+        }
+        let challenge = match authz
+            .challenges
+            .iter()
+            .find(|c| c.r#type == ChallengeType::Http01)
+        {
+            Some(challenge) => challenge,
+            None => bail!("acme server did not offer an http-01 challenge"),
+        };
+        if !challenge
+            .token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!("invalid acme challenge token: {}", challenge.token);
+        }
+        let path = opts.challenge_dir().join(&challenge.token);
+        fs::write(&path, order.key_authorization(challenge).as_str())
+            .with_context(|| format!("unable to write acme challenge {}", path.display()))?;
+        challenges.push((path, challenge.url.clone()));
+    }
+
+    let result = validate(&mut order, &challenges).await;
+    for (path, _) in &challenges {
+        if let Err(err) = fs::remove_file(path) {
+            tracing::warn!(
+                "unable to remove acme challenge {}: {:?}",
+                path.display(),
+                err
+            );
+        }
+    }
+    result?;
+
+    // Finalize the order with a new private key
+    let mut params = CertificateParams::new(opts.domains.clone());
+    params.distinguished_name = DistinguishedName::new();
+    let key_cert = rcgen::Certificate::from_params(params)
+        .with_context(|| "unable to generate acme private key")?;
+    let csr = key_cert
+        .serialize_request_der()
+        .with_context(|| "unable to create acme certificate signing request")?;
+    order
+        .finalize(&csr)
+        .await
+        .with_context(|| "unable to finalize acme order")?;
+
+    let mut checks = 0;
+    let cert_pem = loop {
+        match order
+            .certificate()
+            .await
+            .with_context(|| "unable to download acme certificate")?
+        {
+            Some(cert_pem) => break cert_pem,
+            None if checks < MAX_ORDER_CHECKS => {
+                checks += 1;
+                sleep(Duration::from_secs(1)).await;
+            }
+            None => bail!("acme certificate is not ready after {checks} checks"),
+        }
+    };
+    let key_pem = key_cert.serialize_private_key_pem();
+    let cert = AcmeCert::from_pem(cert_pem.as_bytes(), key_pem.as_bytes())?;
+
+    write_private(&opts.key_path(), key_pem.as_bytes())?;
+    fs::write(opts.cert_path(), cert_pem)
+        .with_context(|| "unable to write acme certificate to the cache directory")?;
+
+    Ok(cert)
+}
+
+/// Marks the challenges as ready and waits for the order to be validated.
+async fn validate(order: &mut Order, challenges: &[(PathBuf, String)]) -> Result {
+    for (_, url) in challenges {
+        order
+            .set_challenge_ready(url)
+            .await
+            .with_context(|| "unable to set acme challenge ready")?;
+    }
+
+    let mut delay = Duration::from_millis(500);
+    for _ in 0..MAX_ORDER_CHECKS {
+        sleep(delay).await;
+        let state = order
+            .refresh()
+            .await
+            .with_context(|| "unable to refresh acme order")?;
+        match state.status {
+            OrderStatus::Ready => return Ok(()),
+            OrderStatus::Invalid => bail!("acme order is invalid: {:?}", state.error),
+            _ => delay = (delay * 2).min(Duration::from_secs(10)),
+        }
+    }
+    bail!("acme order is not ready after {MAX_ORDER_CHECKS} checks")
+}
+
+/// Restores the cached ACME account or creates a new one.
+async fn account(opts: &AcmeOpts) -> Result<Account> {
+    let path = opts.account_path();
+    if let Ok(data) = fs::read(&path) {
+        let credentials: AccountCredentials = serde_json::from_slice(&data)
+            .with_context(|| format!("invalid acme account file {}", path.display()))?;
+        return Account::from_credentials(credentials)
+            .await
+            .with_context(|| "unable to restore acme account");
+    }
+
+    // The terms of service are only agreed on behalf of the operator explicitly
+    if !opts.tos_agreed {
+        bail!("the terms of service of the acme server must be agreed to create an account");
+    }
+
+    let contact = format!("mailto:{}", opts.email);
+    let contact = if opts.email.is_empty() {
+        vec![]
+    } else {
+        vec![contact.as_str()]
+    };
+    let (account, credentials) = Account::create(
+        &NewAccount {
+            contact: &contact,
+            terms_of_service_agreed: opts.tos_agreed,
+            only_return_existing: false,
+        },
+        &opts.directory_url,
+        None,
+    )
+    .await
+    .with_context(|| format!("unable to create acme account on {}", opts.directory_url))?;
+
+    let data = serde_json::to_vec(&credentials)?;
+    write_private(&path, &data)?;
+    tracing::info!("acme: account created on {}", opts.directory_url);
+
+    Ok(account)
+}
+
+/// Writes a file readable by the current user only.
+fn write_private(path: &Path, data: &[u8]) -> Result {
+    let mut file_opts = fs::OpenOptions::new();
+    file_opts.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut file_opts, 0o600);
+    file_opts
+        .open(path)
+        .and_then(|mut file| file.write_all(data))
+        .with_context(|| format!("unable to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_domains, AcmeCert, AcmeOpts, CertResolver};
+    use std::time::Duration;
+
+    fn self_signed(domains: &[&str]) -> (String, String) {
+        let names = domains.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+        let cert = rcgen::generate_simple_self_signed(names).unwrap();
+        (
+            cert.serialize_pem().unwrap(),
+            cert.serialize_private_key_pem(),
+        )
+    }
+
+    #[test]
+    fn parse_acme_domains() {
+        assert_eq!(
+            parse_domains("Example.com, www.example.com.,").unwrap(),
+            vec!["example.com", "www.example.com"]
+        );
+        assert!(parse_domains(" , ").is_err());
+        assert!(parse_domains("*.example.com").is_err());
+        assert!(parse_domains("-example.com").is_err());
+        assert!(parse_domains("example..com").is_err());
+    }
+
+    #[test]
+    fn acme_cert_from_pem() {
+        let (cert_pem, key_pem) = self_signed(&["local.dev", "www.local.dev"]);
+        let cert = AcmeCert::from_pem(cert_pem.as_bytes(), key_pem.as_bytes()).unwrap();
+        assert!(cert.covers(&["local.dev".to_owned(), "www.local.dev".to_owned()]));
+        assert!(!cert.covers(&["example.com".to_owned()]));
+        assert!(AcmeCert::from_pem(cert_pem.as_bytes(), b"").is_err());
+
+        // Renew 30 days before the expiration
+        let renew_at = cert.not_after - Duration::from_secs(30 * 24 * 60 * 60);
+        assert_eq!(
+            cert.renewal_delay(renew_at - Duration::from_secs(60)),
+            Duration::from_secs(60)
+        );
+        assert_eq!(cert.renewal_delay(renew_at), Duration::ZERO);
+        assert_eq!(cert.renewal_delay(cert.not_after), Duration::ZERO);
+
+        let resolver = CertResolver::default();
+        assert!(resolver.certified_key.read().unwrap().is_none());
+        resolver.set(cert.certified_key.clone());
+        assert!(resolver.certified_key.read().unwrap().is_some());
+    }
+
+    #[test]
+    fn acme_load_cached() {
+        let cache_dir = std::env::temp_dir().join("sws-acme-cache-test");
+        std::fs::create_dir_all(&cache_dir).unwrap();
+        let (cert_pem, key_pem) = self_signed(&["local.dev"]);
+        std::fs::write(cache_dir.join("cert.pem"), cert_pem).unwrap();
+        std::fs::write(cache_dir.join("key.pem"), key_pem).unwrap();
+
+        let opts = AcmeOpts::new("", "local.dev", cache_dir.clone(), "", true).unwrap();
+        assert!(super::load_cached(&opts).is_some());
+
+        // Certificates not covering all domains are requested again
+        let opts =
+            AcmeOpts::new("", "local.dev, example.com", cache_dir.clone(), "", true).unwrap();
+        assert!(super::load_cached(&opts).is_none());
+
+        std::fs::remove_dir_all(cache_dir).unwrap();
+    }
+}
@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to reject requests whose host is not allowed, preventing Host header attacks.
+//!
+//! The allowed hosts are exact hostnames (e.g. `example.com`) or wildcard patterns
+//! matching any subdomain (e.g. `*.example.com`). The hosts of the virtual hosts
+//! are allowed implicitly. Requests with a non-matching or missing host are rejected.
+//!
+
+use hyper::{header::HOST, http::uri::Authority, HeaderMap, StatusCode, Uri};
+
+use crate::{settings::VirtualHosts, Result};
+
+/// Default status code of the requests whose host is not allowed.
+pub const DEFAULT_STATUS: u16 = 421;
+
+/// Allowed hosts options.
+#[derive(Debug)]
+pub struct AllowedHosts {
+    /// Lowercase hostnames or wildcard suffixes like `.example.com`.
+    patterns: Vec<String>,
+    /// Status code of the requests whose host is not allowed.
+    pub status: StatusCode,
+}
+
+impl AllowedHosts {
+    /// Parses and validates a comma-separated list of allowed hosts along with
+    /// the rejection status code, adding the virtual hosts to them.
+    /// It returns `None` if the list is empty.
+    pub fn new(
+        hosts: &str,
+        status: u16,
+        virtual_hosts: Option<&Vec<VirtualHosts>>,
+    ) -> Result<Option<Self>> {
+        let mut patterns = Vec::new();
+        for host in hosts.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let pattern = parse_pattern(host)?;
+            if !patterns.contains(&pattern) {
+                patterns.push(pattern);
+            }
+        }
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+
+        for vhost in virtual_hosts.into_iter().flatten() {
+            match vhost.host.parse::<Authority>() {
+                Ok(authority) => patterns.push(hostname(&authority)),
+                Err(_) => tracing::warn!("virtual host {:?} can not be allowed", vhost.host),
+            }
+        }
+
+        let status = match StatusCode::from_u16(status) {
+            Ok(status) if status.is_client_error() || status.is_server_error() => status,
+            _ => bail!("invalid allowed hosts status code: {}", status),
+        };
+        Ok(Some(Self { patterns, status }))
+    }
+
+    /// Checks if the request host (from its URI or `Host` header) is allowed.
+    /// Requests without a (valid) host are never allowed.
+    pub fn is_allowed(&self, uri: &Uri, headers: &HeaderMap) -> bool {
+        let authority = match uri.authority() {
+            Some(authority) => authority.to_owned(),
+            None => match headers
+                .get(HOST)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<Authority>().ok())
+            {
+                Some(authority) => authority,
+                None => return false,
+            },
+        };
+        let hostname = hostname(&authority);
+        self.patterns.iter().any(|pattern| {
+            if pattern.starts_with('.') {
+                hostname.ends_with(pattern.as_str())
+            } else {
+                &hostname == pattern
+            }
+        })
+    }
+}
+
+/// Returns the lowercase hostname of an authority without its trailing dot.
+fn hostname(authority: &Authority) -> String {
+    authority.host().trim_end_matches('.').to_lowercase()
+}
+
+/// Parses an allowed host pattern like `example.com` or `*.example.com`.
+/// Wildcards are turned into their dot-prefixed suffix (e.g. `.example.com`).
+fn parse_pattern(pattern: &str) -> Result<String> {
+    let (wildcard, host) = match pattern.strip_prefix("*.") {
+        Some(host) => (true, host),
+        None => (false, pattern),
+    };
+    let authority = match host.parse::<Authority>() {
+        Ok(authority) if !host.contains(['@', '*']) => authority,
+        _ => bail!("invalid allowed host pattern: {:?}", pattern),
+    };
+    let hostname = hostname(&authority);
+    if hostname.is_empty() || authority.as_str().len() > authority.host().len() {
+        bail!(
+            "invalid allowed host pattern (ports are not allowed): {:?}",
+            pattern
+        );
+    }
+    Ok(if wildcard {
+        [".", &hostname].concat()
+    } else {
+        hostname
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AllowedHosts;
+    use crate::settings::VirtualHosts;
+    use hyper::{header::HOST, HeaderMap, StatusCode, Uri};
+
+    fn allowed(hosts: &AllowedHosts, host: Option<&str>) -> bool {
+        let mut headers = HeaderMap::new();
+        if let Some(host) = host {
+            headers.insert(HOST, host.parse().unwrap());
+        }
+        hosts.is_allowed(&Uri::from_static("/"), &headers)
+    }
+
+    #[test]
+    fn allowed_hosts_patterns() {
+        assert!(AllowedHosts::new(" , ", 421, None).unwrap().is_none());
+        for pattern in ["example.com:80", "*", "a.*.com", "user@example.com", "*."] {
+            assert!(AllowedHosts::new(pattern, 421, None).is_err(), "{pattern}");
+        }
+        assert!(AllowedHosts::new("example.com", 200, None).is_err());
+
+        let vhosts = vec![VirtualHosts {
+            host: "static.test:8080".to_owned(),
+            root: "/var/www".into(),
+        }];
+        let hosts = AllowedHosts::new("Example.com, *.example.org, [::1]", 403, Some(&vhosts))
+            .unwrap()
+            .unwrap();
+        assert_eq!(hosts.status, StatusCode::FORBIDDEN);
+
+        for host in [
+            "example.com",
+            "EXAMPLE.com.",
+            "example.com:8787",
+            "www.example.org",
+            "a.b.example.org",
+            "[::1]:8787",
+            "static.test",
+        ] {
+            assert!(allowed(&hosts, Some(host)), "{host}");
+        }
+        for host in [
+            "www.example.com",
+            "example.org",
+            "badexample.org",
+            "127.0.0.1",
+        ] {
+            assert!(!allowed(&hosts, Some(host)), "{host}");
+        }
+        assert!(!allowed(&hosts, None));
+
+        // HTTP/2 requests carry the host in their URI
+        let uri = Uri::from_static("https://www.example.org/index.html");
+        assert!(hosts.is_allowed(&uri, &HeaderMap::new()));
+    }
+}
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to override the allowed HTTP methods per request path.
+//!
+//! The `[[advanced.allowed-methods]]` rules override the global `allowed-methods` option
+//! for the matching request paths, including the `Allow` header advertised by their
+//! `405 Method Not Allowed` and `OPTIONS` responses.
+//! Rules may list methods not supported by the server (e.g. `POST` for paths handled
+//! by an external system), those are advertised but get a `501 Not Implemented` response.
+//!
+
+use hyper::Method;
+
+use crate::{settings::AllowedMethodsRule, Result};
+
+/// Parses the HTTP methods of an allowed methods rule (e.g. `["GET", "POST"]`).
+/// Any valid method token is accepted and duplicates are ignored.
+pub fn parse_methods(methods: &[String]) -> Result<Vec<Method>> {
+    let mut allowed: Vec<Method> = Vec::new();
+    for name in methods.iter().map(|s| s.trim()) {
+        let method = match Method::from_bytes(name.to_uppercase().as_bytes()) {
+            Ok(method) if !name.is_empty() => method,
+            _ => bail!("invalid allowed method: {:?}", name),
+        };
+        if !allowed.contains(&method) {
+            allowed.push(method);
+        }
+    }
+    if allowed.is_empty() {
+        bail!("at least one allowed method is required");
+    }
+    Ok(allowed)
+}
+
+/// Returns the allowed methods of the last rule matching the given request path.
+pub fn find_methods<'a>(
+    uri_path: &str,
+    rules: &'a Option<Vec<AllowedMethodsRule>>,
+) -> Option<&'a [Method]> {
+    rules
+        .as_ref()?
+        .iter()
+        .rev()
+        .find(|rule| {
+            rule.source.is_match(uri_path) && !rule.exclude.iter().any(|m| m.is_match(uri_path))
+        })
+        .map(|rule| rule.methods.as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_methods, parse_methods};
+    use crate::settings::AllowedMethodsRule;
+    use globset::Glob;
+    use hyper::Method;
+
+    fn rule(source: &str, methods: &[&str]) -> AllowedMethodsRule {
+        AllowedMethodsRule {
+            source: Glob::new(source).unwrap().compile_matcher(),
+            exclude: vec![],
+            methods: parse_methods(&methods.iter().map(|m| m.to_string()).collect::<Vec<_>>())
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn allowed_methods_rules() {
+        assert_eq!(
+            parse_methods(&["get".into(), " POST".into(), "GET".into()]).unwrap(),
+            vec![Method::GET, Method::POST]
+        );
+        assert!(parse_methods(&[]).is_err());
+        assert!(parse_methods(&["".into()]).is_err());
+        assert!(parse_methods(&["G ET".into()]).is_err());
+
+        let rules = Some(vec![
+            rule("/api/**", &["GET", "POST"]),
+            rule("/api/docs/**", &["GET"]),
+        ]);
+        assert_eq!(
+            find_methods("/api/users", &rules),
+            Some(&[Method::GET, Method::POST][..])
+        );
+        assert_eq!(
+            find_methods("/api/docs/index.html", &rules),
+            Some(&[Method::GET][..])
+        );
+        assert_eq!(find_methods("/assets/main.js", &rules), None);
+        assert_eq!(find_methods("/api/users", &None), None);
+    }
+}
@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module that allows to serve files from a `.tar` or `.tar.gz` archive root.
+//!
+//! The archive entries are unpacked once at startup into a private temporary directory
+//! which is then used as the root directory. So all static file features
+//! (content-type guessing, conditional requests, ranges, directory listing, etc.) are preserved.
+//!
+
+use flate2::read::GzDecoder;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::{Context, Result};
+
+/// Supported archive file extensions.
+const ARCHIVE_EXTENSIONS: [&str; 3] = [".tar", ".tar.gz", ".tgz"];
+
+/// Checks if the given root path is a supported archive file.
+pub fn is_archive(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .is_some_and(|name| {
+                let name = name.to_lowercase();
+                ARCHIVE_EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+            })
+}
+
+/// Root directory holding the unpacked archive entries.
+/// The directory is removed once dropped.
+#[derive(Debug)]
+pub struct ArchiveRoot {
+    dir: PathBuf,
+}
+
+impl ArchiveRoot {
+    /// Unpacks the given `.tar` or `.tar.gz` archive file into a private temporary directory.
+    pub fn unpack(archive_path: &Path) -> Result<Self> {
+        let root = Self {
+            dir: create_private_dir()?,
+        };
+
+        let file = File::open(archive_path)
+            .with_context(|| format!("unable to open archive {}", archive_path.display()))?;
+        let name = archive_path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        let reader: Box<dyn Read> = if name.ends_with(".tar") {
+            Box::new(file)
+        } else {
+            Box::new(GzDecoder::new(file))
+        };
+
+        // Note that entries containing `..` or absolute paths are skipped by `tar`
+        let mut archive = tar::Archive::new(reader);
+        archive.set_preserve_mtime(true);
+        archive.set_preserve_permissions(false);
+        archive
+            .unpack(&root.dir)
+            .with_context(|| format!("unable to unpack archive {}", archive_path.display()))?;
+
+        Ok(root)
+    }
+
+    /// Returns the directory path containing the archive entries.
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+}
+
+/// Creates a new randomly named temporary directory only accessible by the current user.
+///
+/// The creation fails if the directory already exists, so a directory planted
+/// by another user is never reused.
+fn create_private_dir() -> Result<PathBuf> {
+    let mut random = [0u8; 16];
+    getrandom::getrandom(&mut random).map_err(|err| {
+        anyhow!(
+            "unable to get random bytes for the archive directory: {}",
+            err
+        )
+    })?;
+    let dir = std::env::temp_dir().join(format!(
+        "static-web-server-{:032x}",
+        u128::from_be_bytes(random)
+    ));
+
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder
+        .create(&dir)
+        .with_context(|| format!("unable to create archive directory {}", dir.display()))?;
+    Ok(dir)
+}
+
+impl Drop for ArchiveRoot {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_dir_all(&self.dir) {
+            tracing::warn!(
+                "unable to remove archive directory {}: {:?}",
+                self.dir.display(),
+                err
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_archive, ArchiveRoot};
+    use std::path::Path;
+
+    #[test]
+    fn unpack_tar_archive() {
+        let tar_path = std::env::temp_dir().join("sws-archive-test.tar");
+        let mut builder = tar::Builder::new(std::fs::File::create(&tar_path).unwrap());
+        builder
+            .append_dir_all("assets", "docker/public/assets")
+            .unwrap();
+        builder
+            .append_path_with_name("docker/public/index.html", "index.html")
+            .unwrap();
+        builder.finish().unwrap();
+        drop(builder);
+
+        assert!(is_archive(&tar_path));
+        assert!(!is_archive(Path::new("docker/public")));
+
+        let root = ArchiveRoot::unpack(&tar_path).unwrap();
+        let dir = root.path().to_owned();
+        assert!(dir.join("index.html").is_file());
+        assert!(dir.join("assets/main.css").is_file());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+
+        // Every unpacking gets its own directory
+        let other = ArchiveRoot::unpack(&tar_path).unwrap();
+        assert_ne!(other.path(), dir);
+        drop(other);
+
+        drop(root);
+        assert!(!dir.exists());
+        std::fs::remove_file(tar_path).unwrap();
+    }
+}
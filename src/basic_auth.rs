@@ -7,10 +7,51 @@
 //!
 
 use bcrypt::verify as bcrypt_verify;
-use headers::{authorization::Basic, Authorization, HeaderMapExt};
+use headers::{authorization::Basic, Authorization, HeaderMapExt, HeaderValue};
 use http::HeaderMap;
 use hyper::StatusCode;
 
+use crate::{path_normalization::lookup_path, settings::BasicAuth, Context, Result};
+
+/// Default realm of the `Basic` HTTP Authentication challenge.
+pub const DEFAULT_REALM: &str = "Static Web Server";
+
+/// Returns the `WWW-Authenticate` header value of a `Basic` challenge using the given realm.
+/// The realm can not be empty nor contain double quotes, backslashes or non-printable ASCII characters.
+pub fn www_authenticate(realm: &str) -> Result<HeaderValue> {
+    if realm.is_empty()
+        || !realm
+            .bytes()
+            .all(|c| (b' '..=b'~').contains(&c) && c != b'"' && c != b'\\')
+    {
+        bail!("invalid basic authentication realm: {:?}", realm);
+    }
+    HeaderValue::from_str(&format!("Basic realm=\"{realm}\", charset=\"UTF-8\""))
+        .with_context(|| format!("invalid basic authentication realm: {realm:?}"))
+}
+
+/// Returns the first basic auth rule matching the given request path.
+///
+/// Rules are also matched against the path as resolved by the file lookup,
+/// so percent-encoded or non-canonical variants of a protected path can not bypass them.
+/// Directory paths without a trailing slash are matched in their trailing slash form as well.
+///
+/// Exclude patterns are only matched against the resolved path, so the excluded
+/// files are the ones actually served by the file lookup.
+pub fn find_rule<'a>(rules: &'a [BasicAuth], uri_path: &str) -> Option<&'a BasicAuth> {
+    let lookup_path = lookup_path(uri_path);
+    let dir_path = [lookup_path.trim_end_matches('/'), "/"].concat();
+    rules.iter().find(|rule| {
+        (rule.source.is_match(uri_path)
+            || rule.source.is_match(&lookup_path)
+            || rule.source.is_match(&dir_path))
+            && !rule
+                .exclude
+                .iter()
+                .any(|m| m.is_match(&lookup_path) || m.is_match(&dir_path))
+    })
+}
+
 /// Check for a `Basic` HTTP Authorization Schema of an incoming request
 /// and uses `bcrypt` for password hashing verification.
 pub fn check_request(headers: &HeaderMap, userid: &str, password: &str) -> Result<(), StatusCode> {
@@ -34,9 +75,59 @@ pub fn check_request(headers: &HeaderMap, userid: &str, password: &str) -> Resul
 
 #[cfg(test)]
 mod tests {
-    use super::check_request;
+    use super::{check_request, find_rule, www_authenticate, DEFAULT_REALM};
+    use crate::settings::BasicAuth;
     use headers::HeaderMap;
 
+    #[test]
+    fn test_find_rule_path_variants() {
+        let rules = [BasicAuth {
+            source: globset::Glob::new("/admin/**").unwrap().compile_matcher(),
+            exclude: vec![globset::Glob::new("/admin/public/**")
+                .unwrap()
+                .compile_matcher()],
+            user_id: "jq".to_owned(),
+            password: "".to_owned(),
+            www_authenticate: www_authenticate(DEFAULT_REALM).unwrap(),
+        }];
+        for path in [
+            "/admin/index.html",
+            "/admin/missing.html",
+            "/%61dmin/index.html",
+            "//admin/index.html",
+            "/./admin/index.html",
+            "/admin/../index.html",
+            "/admin",
+        ] {
+            assert!(find_rule(&rules, path).is_some(), "{path}");
+        }
+        assert!(find_rule(&rules, "/index.html").is_none());
+        assert!(find_rule(&rules, "/administrator").is_none());
+
+        // Excluded paths are matched in their resolved form
+        assert!(find_rule(&rules, "/admin/public/logo.png").is_none());
+        assert!(find_rule(&rules, "/admin//public/logo.png").is_none());
+        assert!(find_rule(&rules, "/admin/publicity.html").is_some());
+        assert!(find_rule(&rules, "/admin/%70ublic.html").is_some());
+    }
+
+    #[test]
+    fn test_www_authenticate_realm() {
+        assert_eq!(
+            www_authenticate(DEFAULT_REALM).unwrap(),
+            "Basic realm=\"Static Web Server\", charset=\"UTF-8\""
+        );
+        assert_eq!(
+            www_authenticate("Admin area").unwrap(),
+            "Basic realm=\"Admin area\", charset=\"UTF-8\""
+        );
+        assert!(www_authenticate("").is_err());
+        assert!(www_authenticate("a\"b").is_err());
+        assert!(www_authenticate("a\\b").is_err());
+        assert!(www_authenticate("a\r\nb").is_err());
+        assert!(www_authenticate("área").is_err());
+    }
+
     #[test]
     fn test_valid_auth() {
         let mut headers = HeaderMap::new();
@@ -17,6 +17,11 @@ use static_web_server::{Result, Settings};
 fn main() -> Result {
     let opts = Settings::get(true)?;
 
+    if let Some(format) = opts.general.print_config {
+        print!("{}", opts.effective_config(format)?);
+        return Ok(());
+    }
+
     #[cfg(windows)]
     {
         use static_web_server::settings::Commands;
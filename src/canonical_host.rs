@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to redirect requests whose host does not match the canonical one.
+//! For example `www.example.com` to `example.com` or vice versa.
+//!
+
+use hyper::{
+    header::{HOST, LOCATION},
+    http::uri::Authority,
+    Body, HeaderMap, Response, StatusCode, Uri,
+};
+
+use crate::Result;
+
+/// Canonical host options.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalHost {
+    /// Lowercase canonical hostname.
+    pub hostname: String,
+    /// Optional canonical port.
+    pub port: Option<u16>,
+    /// Whether the server is secure (HTTPS) or not.
+    pub secure: bool,
+}
+
+impl CanonicalHost {
+    /// Parses and validates a canonical host value like `example.com` or `example.com:8443`.
+    pub fn new(host: &str, secure: bool) -> Result<Self> {
+        let host = host.trim();
+        if host.is_empty() || host.contains(['@', '/', '?', '#']) {
+            bail!("invalid canonical host value: {:?}", host);
+        }
+        let authority = match host.parse::<Authority>() {
+            Ok(authority) => authority,
+            Err(err) => bail!("invalid canonical host value {:?}: {}", host, err),
+        };
+        let hostname = authority.host().trim_end_matches('.').to_lowercase();
+        // Make sure that a port is valid if present
+        let has_port = authority.as_str().len() > authority.host().len();
+        if hostname.is_empty() || (has_port && authority.port_u16().is_none()) {
+            bail!("invalid canonical host value: {:?}", host);
+        }
+        Ok(Self {
+            hostname,
+            port: authority.port_u16(),
+            secure,
+        })
+    }
+}
+
+/// It returns a permanent redirect response to the canonical host
+/// if the request host does not match it, otherwise `None`.
+///
+/// Requests without a (valid) host are never redirected.
+pub fn redirect(
+    canonical: &CanonicalHost,
+    uri: &Uri,
+    headers: &HeaderMap,
+) -> Option<Response<Body>> {
+    let authority = match uri.authority() {
+        Some(authority) => authority.to_owned(),
+        None => headers
+            .get(HOST)?
+            .to_str()
+            .ok()?
+            .parse::<Authority>()
+            .ok()?,
+    };
+
+    let hostname = authority.host().trim_end_matches('.').to_lowercase();
+    let default_port = if canonical.secure { 443 } else { 80 };
+    let port = authority.port_u16();
+
+    let host_matches = hostname == canonical.hostname;
+    let port_matches = match canonical.port {
+        Some(canonical_port) => port.unwrap_or(default_port) == canonical_port,
+        None => true,
+    };
+    if host_matches && port_matches {
+        return None;
+    }
+
+    // Keep the request port unless the canonical host defines one
+    let target_port = canonical.port.or(port).filter(|p| *p != default_port);
+    let target = match target_port {
+        Some(p) => format!("{}:{}", canonical.hostname, p),
+        None => canonical.hostname.to_owned(),
+    };
+
+    let scheme = if canonical.secure { "https" } else { "http" };
+    let path_and_query = uri.path_and_query().map_or("/", |p| p.as_str());
+    let url = format!("{scheme}://{target}{path_and_query}");
+
+    let location = match url.parse() {
+        Ok(location) => location,
+        Err(err) => {
+            tracing::error!("invalid canonical host redirect location: {:?}", err);
+            return None;
+        }
+    };
+    tracing::debug!("canonical host redirect to {}", url);
+
+    let mut resp = Response::new(Body::empty());
+    *resp.status_mut() = StatusCode::MOVED_PERMANENTLY;
+    resp.headers_mut().insert(LOCATION, location);
+    Some(resp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{redirect, CanonicalHost};
+    use hyper::{header::HOST, HeaderMap, StatusCode, Uri};
+
+    fn location(canonical: &CanonicalHost, host: &str, uri: &str) -> Option<String> {
+        let mut headers = HeaderMap::new();
+        headers.insert(HOST, host.parse().unwrap());
+        let uri: Uri = uri.parse().unwrap();
+        redirect(canonical, &uri, &headers).map(|resp| {
+            assert_eq!(resp.status(), StatusCode::MOVED_PERMANENTLY);
+            resp.headers()["location"].to_str().unwrap().to_owned()
+        })
+    }
+
+    #[test]
+    fn invalid_canonical_host() {
+        assert!(CanonicalHost::new("", false).is_err());
+        assert!(CanonicalHost::new("user@example.com", false).is_err());
+        assert!(CanonicalHost::new("example.com/path", false).is_err());
+        assert!(CanonicalHost::new("example.com:abc", false).is_err());
+    }
+
+    #[test]
+    fn www_to_non_www() {
+        let canonical = CanonicalHost::new("Example.com", true).unwrap();
+        assert_eq!(
+            location(&canonical, "www.example.com", "/a/b.html?c=d"),
+            Some("https://example.com/a/b.html?c=d".to_owned())
+        );
+        assert_eq!(location(&canonical, "EXAMPLE.com", "/"), None);
+    }
+
+    #[test]
+    fn non_www_to_www() {
+        let canonical = CanonicalHost::new("www.example.com", false).unwrap();
+        assert_eq!(
+            location(&canonical, "example.com:8080", "/index.html"),
+            Some("http://www.example.com:8080/index.html".to_owned())
+        );
+        assert_eq!(location(&canonical, "www.example.com:8080", "/"), None);
+    }
+
+    #[test]
+    fn canonical_port() {
+        let canonical = CanonicalHost::new("example.com:8443", true).unwrap();
+        assert_eq!(
+            location(&canonical, "example.com", "/"),
+            Some("https://example.com:8443/".to_owned())
+        );
+        assert_eq!(location(&canonical, "example.com:8443", "/"), None);
+    }
+}
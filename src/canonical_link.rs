@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to append a canonical `Link` header to the HTML responses.
+//!
+//! The canonical URL is made of a base URL followed by the normalized request path,
+//! so the query string variations of a page point to the same canonical URL.
+//!
+
+use hyper::{
+    header::{HeaderValue, CONTENT_TYPE, LINK},
+    Body, Response, Uri,
+};
+
+use crate::{path_normalization, Result};
+
+/// Canonical link header options.
+#[derive(Debug, Clone)]
+pub struct CanonicalLink {
+    /// Base URL of the canonical URLs without a trailing slash.
+    base_url: String,
+    /// Whether the requests with a query string are skipped.
+    skip_query: bool,
+}
+
+impl CanonicalLink {
+    /// Creates the canonical link options from the base URL (e.g. `https://example.com`).
+    /// It returns `None` if the base URL is empty.
+    pub fn new(base_url: &str, skip_query: bool) -> Result<Option<Self>> {
+        let base_url = base_url.trim();
+        if base_url.is_empty() {
+            return Ok(None);
+        }
+        let valid = base_url.parse::<Uri>().is_ok_and(|uri| {
+            matches!(uri.scheme_str(), Some("http" | "https"))
+                && uri.host().is_some_and(|host| !host.is_empty())
+                && uri.query().is_none()
+        });
+        if !valid || base_url.contains(['#', '"', '<', '>']) {
+            bail!("invalid canonical base url: {:?}", base_url);
+        }
+        Ok(Some(Self {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            skip_query,
+        }))
+    }
+
+    /// Appends the canonical `Link` header to a successful HTML response of the given request URI.
+    /// Existing `Link` headers (e.g. preload links) are preserved.
+    pub fn append_header(&self, uri: &Uri, resp: &mut Response<Body>) {
+        if !resp.status().is_success() || (self.skip_query && uri.query().is_some()) {
+            return;
+        }
+        let is_html = resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.trim_start().starts_with("text/html"));
+        if !is_html {
+            return;
+        }
+
+        let path = path_normalization::normalize(uri.path());
+        let link = format!("<{}{}>; rel=\"canonical\"", self.base_url, path);
+        match HeaderValue::from_str(&link) {
+            Ok(val) => {
+                resp.headers_mut().append(LINK, val);
+            }
+            Err(err) => tracing::error!("invalid canonical link header value: {:?}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CanonicalLink;
+    use hyper::{Body, Response, StatusCode};
+
+    fn link(canonical: &CanonicalLink, uri: &str, content_type: &str) -> Option<String> {
+        let mut resp = Response::new(Body::empty());
+        resp.headers_mut()
+            .insert("content-type", content_type.parse().unwrap());
+        canonical.append_header(&uri.parse().unwrap(), &mut resp);
+        resp.headers()
+            .get("link")
+            .map(|v| v.to_str().unwrap().to_owned())
+    }
+
+    #[test]
+    fn canonical_link_header() {
+        assert!(CanonicalLink::new(" ", false).unwrap().is_none());
+        for url in ["example.com", "ftp://example.com", "https://a.b?x=1"] {
+            assert!(CanonicalLink::new(url, false).is_err(), "{url}");
+        }
+
+        let canonical = CanonicalLink::new("https://example.com/", false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            link(
+                &canonical,
+                "/docs//a/../intro.html?ref=x",
+                "text/html; charset=utf-8"
+            )
+            .as_deref(),
+            Some("<https://example.com/docs/intro.html>; rel=\"canonical\"")
+        );
+        assert_eq!(link(&canonical, "/main.css", "text/css"), None);
+
+        let mut resp = Response::new(Body::empty());
+        *resp.status_mut() = StatusCode::NOT_FOUND;
+        resp.headers_mut()
+            .insert("content-type", "text/html".parse().unwrap());
+        canonical.append_header(&"/missing".parse().unwrap(), &mut resp);
+        assert!(!resp.headers().contains_key("link"));
+
+        // Query string variations are skipped if configured
+        let canonical = CanonicalLink::new("https://example.com", true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(link(&canonical, "/?page=2", "text/html"), None);
+        assert_eq!(
+            link(&canonical, "/", "text/html").as_deref(),
+            Some("<https://example.com/>; rel=\"canonical\"")
+        );
+    }
+}
@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Case-insensitive path matching module used as a fallback
+//! when an exact-case file lookup misses.
+//!
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Maximum number of resolved and missing paths kept in memory.
+const CACHE_MAX_ENTRIES: usize = 1_024;
+
+/// Time during which a missing path is not looked up again,
+/// so files created afterwards are found once it elapses.
+const CACHE_MISS_TTL: Duration = Duration::from_secs(10);
+
+/// Cached lookup of a requested path.
+struct CachedPath {
+    /// Real path on disk or `None` if the path is missing or ambiguous.
+    resolved: Option<PathBuf>,
+    cached_at: Instant,
+}
+
+/// Cache of previously looked up paths (requested path -> real path on disk if any).
+fn cache() -> &'static Mutex<HashMap<PathBuf, CachedPath>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, CachedPath>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Resolves the given `path` (relative to `base`) ignoring the case of its components
+/// by scanning every parent directory entry on disk.
+///
+/// Note that the `path` should be previously sanitized since only its normal components are considered.
+/// It returns `None` if a component does not exist or it is ambiguous,
+/// that is, if more than one directory entry matches the same component.
+/// Both resolved and missing paths are cached, the latter for a short time only.
+pub fn resolve_path(base: &Path, path: &Path) -> Option<PathBuf> {
+    let tail = path.strip_prefix(base).ok()?;
+
+    if let Ok(cache) = cache().lock() {
+        match cache.get(path) {
+            // Make sure that the cached path still exists
+            Some(CachedPath {
+                resolved: Some(resolved),
+                ..
+            }) if resolved.exists() => {
+                tracing::trace!("case-insensitive path found in cache: {:?}", resolved);
+                return Some(resolved.to_owned());
+            }
+            Some(CachedPath {
+                resolved: None,
+                cached_at,
+            }) if cached_at.elapsed() < CACHE_MISS_TTL => {
+                tracing::trace!("case-insensitive path missing in cache: {:?}", path);
+                return None;
+            }
+            _ => {}
+        }
+    }
+
+    let resolved = lookup(base.to_path_buf(), tail);
+
+    if let Ok(mut cache) = cache().lock() {
+        if cache.len() >= CACHE_MAX_ENTRIES {
+            cache.clear();
+        }
+        cache.insert(
+            path.to_path_buf(),
+            CachedPath {
+                resolved: resolved.clone(),
+                cached_at: Instant::now(),
+            },
+        );
+    }
+
+    resolved
+}
+
+/// Looks up the `tail` path components within the `resolved` directory ignoring their case.
+fn lookup(mut resolved: PathBuf, tail: &Path) -> Option<PathBuf> {
+    for component in tail.components() {
+        let name = match component {
+            Component::Normal(name) => name,
+            _ => return None,
+        };
+
+        // Exact-case match first
+        let exact = resolved.join(name);
+        if exact.exists() {
+            resolved = exact;
+            continue;
+        }
+
+        let name = name.to_str()?.to_lowercase();
+        let mut matches = std::fs::read_dir(&resolved)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|s| s.to_lowercase() == name)
+            });
+
+        let entry = matches.next()?;
+        if matches.next().is_some() {
+            tracing::debug!(
+                "case-insensitive path is ambiguous for {:?} in {:?}",
+                name,
+                resolved
+            );
+            return None;
+        }
+        resolved.push(entry.file_name());
+    }
+
+    tracing::trace!("case-insensitive path resolved: {:?}", resolved);
+
+    Some(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cache, resolve_path};
+    use std::path::PathBuf;
+
+    fn root_dir() -> PathBuf {
+        PathBuf::from("docker/public")
+    }
+
+    #[test]
+    fn resolve_mixed_case_path() {
+        let base = root_dir();
+        assert_eq!(
+            resolve_path(&base, &base.join("INDEX.html")),
+            Some(base.join("index.html"))
+        );
+        assert_eq!(
+            resolve_path(&base, &base.join("Assets/Favicon.ICO")),
+            Some(base.join("assets").join("favicon.ico"))
+        );
+    }
+
+    #[test]
+    fn resolve_missing_path() {
+        let base = root_dir();
+        assert_eq!(resolve_path(&base, &base.join("missing.html")), None);
+        assert_eq!(resolve_path(&base, &PathBuf::from("/etc/passwd")), None);
+    }
+
+    #[test]
+    fn resolve_missing_path_cached() {
+        let base = std::env::temp_dir().join("sws-case-insensitive-miss-test");
+        std::fs::create_dir_all(&base).unwrap();
+        let path = base.join("Missing.TXT");
+        assert_eq!(resolve_path(&base, &path), None);
+        assert!(cache().lock().unwrap().get(&path).is_some());
+
+        // The miss is served from the cache without scanning the directory again
+        std::fs::write(base.join("missing.txt"), "").unwrap();
+        assert_eq!(resolve_path(&base, &path), None);
+
+        std::fs::remove_dir_all(base).unwrap();
+    }
+}
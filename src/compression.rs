@@ -10,41 +10,37 @@
 
 #[cfg(feature = "compression-brotli")]
 use async_compression::tokio::bufread::BrotliEncoder;
-#[cfg(feature = "compression-deflate")]
-use async_compression::tokio::bufread::DeflateEncoder;
 #[cfg(feature = "compression-gzip")]
 use async_compression::tokio::bufread::GzipEncoder;
+#[cfg(feature = "compression-deflate")]
+use async_compression::tokio::bufread::ZlibEncoder;
 #[cfg(feature = "compression-zstd")]
 use async_compression::tokio::bufread::ZstdEncoder;
+use async_compression::Level;
 
 use bytes::Bytes;
 use futures_util::Stream;
-use headers::{AcceptEncoding, ContentCoding, ContentType, HeaderMap, HeaderMapExt};
+use globset::{GlobBuilder, GlobMatcher};
+use headers::{AcceptEncoding, ContentCoding, ContentLength, ContentType, HeaderMap, HeaderMapExt};
 use hyper::{
-    header::{HeaderValue, CONTENT_ENCODING, CONTENT_LENGTH},
+    header::{
+        HeaderValue, ACCEPT_ENCODING, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_LENGTH, ETAG,
+        USER_AGENT,
+    },
     Body, Method, Response,
 };
 use mime_guess::Mime;
 use pin_project::pin_project;
 use std::pin::Pin;
+use std::sync::OnceLock;
 use std::task::{Context, Poll};
 use tokio_util::io::{ReaderStream, StreamReader};
 
-use crate::{exts::http::MethodExt, Result};
-
-/// Contains a fixed list of common text-based MIME types in order to apply compression.
-pub const TEXT_MIME_TYPES: [&str; 24] = [
-    "text/html",
-    "text/css",
-    "text/javascript",
-    "text/xml",
-    "text/plain",
-    "text/csv",
-    "text/calendar",
-    "text/markdown",
-    "text/x-yaml",
-    "text/x-toml",
-    "text/x-component",
+use crate::{exts::http::MethodExt, helpers, settings::CompressionRule, Context as _, Result};
+
+/// Contains the default list of text-based MIME type patterns in order to apply compression.
+pub const TEXT_MIME_TYPES: [&str; 14] = [
+    "text/*",
     "application/rtf",
     "application/xhtml+xml",
     "application/javascript",
@@ -60,58 +56,316 @@ pub const TEXT_MIME_TYPES: [&str; 24] = [
     "application/wasm",
 ];
 
+/// Parses a comma-separated list of compressible MIME type patterns (e.g. `text/*`).
+/// The default [`TEXT_MIME_TYPES`] list is used when the value is empty.
+pub fn parse_types(types: &str) -> Result<Vec<GlobMatcher>> {
+    let types = if types.trim().is_empty() {
+        TEXT_MIME_TYPES.join(",")
+    } else {
+        types.to_lowercase()
+    };
+    helpers::parse_glob_list(&types)
+}
+
+/// Contains the default order of preference of the auto-compression encodings.
+pub const DEFAULT_ENCODINGS: [&str; 4] = ["zstd", "br", "gzip", "deflate"];
+
+/// Parses a comma-separated list of auto-compression encodings (e.g. `zstd,br,gzip`)
+/// in order of preference. The default [`DEFAULT_ENCODINGS`] order is used when the value is empty.
+pub fn parse_encodings(encodings: &str) -> Result<Vec<ContentCoding>> {
+    let encodings = if encodings.trim().is_empty() {
+        DEFAULT_ENCODINGS.join(",")
+    } else {
+        encodings.to_lowercase()
+    };
+    let mut codings: Vec<ContentCoding> = Vec::new();
+    for name in encodings
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+    {
+        let coding = match name {
+            #[cfg(feature = "compression-zstd")]
+            "zstd" => ContentCoding::ZSTD,
+            #[cfg(feature = "compression-brotli")]
+            "br" => ContentCoding::BROTLI,
+            #[cfg(feature = "compression-gzip")]
+            "gzip" => ContentCoding::GZIP,
+            #[cfg(feature = "compression-deflate")]
+            "deflate" => ContentCoding::DEFLATE,
+            _ => bail!("unsupported compression encoding: {}", name),
+        };
+        if !codings.contains(&coding) {
+            codings.push(coding);
+        }
+    }
+    Ok(codings)
+}
+
+/// Compression level of the auto-compression encoders.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CompressionLevel {
+    /// Fastest level of every encoder.
+    Fastest,
+    /// Default level of every encoder.
+    #[default]
+    Default,
+    /// Best level of every encoder, except for `zstd` which uses its highest non-ultra level (`19`).
+    Best,
+}
+
+impl CompressionLevel {
+    /// Returns the encoder quality of the level for the given coding.
+    fn quality(self, coding: ContentCoding) -> Level {
+        match self {
+            Self::Fastest => Level::Fastest,
+            Self::Default => Level::Default,
+            Self::Best if coding == ContentCoding::ZSTD => Level::Precise(19),
+            Self::Best => Level::Best,
+        }
+    }
+}
+
+/// Adaptive compression level based on the server load,
+/// measured as the number of in-flight requests (including the current one).
+///
+/// Bodies get compressed using the best level while the server is idle,
+/// the fastest level while it's busy and the default level otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveCompression {
+    /// Maximum number of in-flight requests considered as idle.
+    idle_requests: u64,
+    /// Minimum number of in-flight requests considered as busy.
+    busy_requests: u64,
+}
+
+impl AdaptiveCompression {
+    /// Creates the adaptive compression thresholds.
+    /// The idle threshold must be lower than the busy one.
+    pub fn new(idle_requests: u64, busy_requests: u64) -> Result<Self> {
+        if idle_requests >= busy_requests {
+            bail!(
+                "adaptive compression idle requests ({}) must be lower than the busy requests ({})",
+                idle_requests,
+                busy_requests
+            );
+        }
+        Ok(Self {
+            idle_requests,
+            busy_requests,
+        })
+    }
+
+    /// Returns the compression level for the given number of in-flight requests.
+    pub fn level(&self, in_flight_requests: u64) -> CompressionLevel {
+        if in_flight_requests >= self.busy_requests {
+            CompressionLevel::Fastest
+        } else if in_flight_requests <= self.idle_requests {
+            CompressionLevel::Best
+        } else {
+            CompressionLevel::Default
+        }
+    }
+}
+
+/// Parses a comma-separated list of `User-Agent` glob patterns (e.g. `*MSIE [1-6].*`)
+/// matched case-insensitively against the clients denied compressed responses.
+pub fn parse_user_agents(user_agents: &str) -> Result<Vec<GlobMatcher>> {
+    user_agents
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            GlobBuilder::new(s)
+                .case_insensitive(true)
+                .build()
+                .with_context(|| format!("can not compile user agent glob pattern: {s}"))
+                .map(|g| g.compile_matcher())
+        })
+        .collect()
+}
+
+/// Checks if the `User-Agent` request header matches any of the denied patterns.
+pub fn is_user_agent_denied(headers: &HeaderMap<HeaderValue>, denylist: &[GlobMatcher]) -> bool {
+    if denylist.is_empty() {
+        return false;
+    }
+    headers
+        .get(USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|ua| denylist.iter().any(|m| m.is_match(ua)))
+        .unwrap_or(false)
+}
+
+/// Returns whether the auto-compression is forced on (`true`) or off (`false`)
+/// by the last compression rule matching the given request path.
+pub fn forced_by_rules(uri_path: &str, rules: &Option<Vec<CompressionRule>>) -> Option<bool> {
+    rules
+        .as_ref()?
+        .iter()
+        .rev()
+        .find(|rule| {
+            rule.source.is_match(uri_path) && !rule.exclude.iter().any(|m| m.is_match(uri_path))
+        })
+        .map(|rule| rule.enabled)
+}
+
+/// Returns a MIME type pattern list matching any type,
+/// used to compress responses regardless of their content type.
+pub fn any_types() -> &'static [GlobMatcher] {
+    static ANY_TYPES: OnceLock<Vec<GlobMatcher>> = OnceLock::new();
+    ANY_TYPES.get_or_init(|| helpers::parse_glob_list("*").unwrap_or_default())
+}
+
 /// Create a wrapping handler that compresses the Body of a [`Response`](hyper::Response)
 /// using `gzip`, `deflate`, `brotli` or `zstd` if is specified in the `Accept-Encoding` header, adding
 /// `content-encoding: <coding>` to the Response's [`HeaderMap`](hyper::HeaderMap)
-/// It also provides the ability to apply compression for text-based MIME types only.
+/// It also provides the ability to apply compression for the given MIME type patterns only.
+/// The `encodings` list determines the available encodings in order of preference.
+/// The `level` determines the compression level of the chosen encoder.
 pub fn auto(
     method: &Method,
     headers: &HeaderMap<HeaderValue>,
-    resp: Response<Body>,
+    mut resp: Response<Body>,
+    types: &[GlobMatcher],
+    encodings: &[ContentCoding],
+    level: CompressionLevel,
 ) -> Result<Response<Body>> {
     // Skip compression for HEAD and OPTIONS request methods
     if method.is_head() || method.is_options() {
         return Ok(resp);
     }
 
+    // Skip compression for responses which must not be transformed
+    if is_no_transform(resp.headers()) {
+        return Ok(resp);
+    }
+
+    // Skip compression for responses which are already encoded
+    // (e.g. `.svgz` files served with a `Content-Encoding: gzip` header)
+    if is_encoded(resp.headers()) {
+        return Ok(resp);
+    }
+
     // Compress response based on Accept-Encoding header
-    if let Some(encoding) = get_prefered_encoding(headers) {
-        // Skip compression for non-text-based MIME types
+    if let Some(encoding) = negotiate_encoding(headers, encodings) {
+        // Skip compression for MIME types not matching the allowed ones
+        // Note that MIME parameters like `charset` are not considered
         if let Some(content_type) = resp.headers().typed_get::<ContentType>() {
             let mime = Mime::from(content_type);
-            if !TEXT_MIME_TYPES.iter().any(|h| *h == mime) {
+            if !types.iter().any(|m| m.is_match(mime.essence_str())) {
                 return Ok(resp);
             }
         }
 
+        // The compressed body is not byte-for-byte identical to the file anymore
+        weaken_etag(resp.headers_mut());
+
         #[cfg(feature = "compression-gzip")]
         if encoding == ContentCoding::GZIP {
             let (head, body) = resp.into_parts();
-            return Ok(gzip(head, body.into()));
+            return Ok(gzip(head, body.into(), level));
         }
 
         #[cfg(feature = "compression-deflate")]
         if encoding == ContentCoding::DEFLATE {
             let (head, body) = resp.into_parts();
-            return Ok(deflate(head, body.into()));
+            return Ok(deflate(head, body.into(), level));
         }
 
         #[cfg(feature = "compression-brotli")]
         if encoding == ContentCoding::BROTLI {
             let (head, body) = resp.into_parts();
-            return Ok(brotli(head, body.into()));
+            return Ok(brotli(head, body.into(), level));
         }
 
         #[cfg(feature = "compression-zstd")]
         if encoding == ContentCoding::ZSTD {
             let (head, body) = resp.into_parts();
-            return Ok(zstd(head, body.into()));
+            return Ok(zstd(head, body.into(), level));
         }
     }
 
     Ok(resp)
 }
 
+/// Checks if the `Cache-Control` headers contain a `no-transform` directive.
+fn is_no_transform(headers: &HeaderMap<HeaderValue>) -> bool {
+    headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|directive| directive.trim().eq_ignore_ascii_case("no-transform"))
+}
+
+/// Checks if the `Content-Encoding` header contains an encoding other than `identity`.
+fn is_encoded(headers: &HeaderMap<HeaderValue>) -> bool {
+    headers
+        .get_all(CONTENT_ENCODING)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|coding| {
+            let coding = coding.trim();
+            !coding.is_empty() && !coding.eq_ignore_ascii_case("identity")
+        })
+}
+
+/// Turns a strong `ETag` header value into a weak one (e.g. `W/"abc"`).
+fn weaken_etag(headers: &mut HeaderMap<HeaderValue>) {
+    if let Some(etag) = headers
+        .get(ETAG)
+        .filter(|v| !v.as_bytes().starts_with(b"W/"))
+    {
+        let weak = [b"W/", etag.as_bytes()].concat();
+        if let Ok(weak) = HeaderValue::from_bytes(&weak) {
+            headers.insert(ETAG, weak);
+        }
+    }
+}
+
+/// Same as [`auto`] but responses whose uncompressed body size is below `buffer_size` bytes
+/// are compressed in memory entirely in order to send a definite `Content-Length` header
+/// instead of using a chunked transfer encoding. Larger responses are still streamed.
+/// A `buffer_size` value of `0` disables the buffering.
+///
+/// Responses whose body size is below `min_size` bytes are not compressed at all.
+/// Responses of unknown size are always compressed.
+#[allow(clippy::too_many_arguments)]
+pub async fn auto_sized(
+    method: &Method,
+    headers: &HeaderMap<HeaderValue>,
+    resp: Response<Body>,
+    buffer_size: u64,
+    min_size: u64,
+    types: &[GlobMatcher],
+    encodings: &[ContentCoding],
+    level: CompressionLevel,
+) -> Result<Response<Body>> {
+    let content_length = resp.headers().typed_get::<ContentLength>();
+    // Small bodies are still compressed if the client refuses identity responses
+    if matches!(content_length, Some(ContentLength(len)) if len < min_size)
+        && is_identity_acceptable(headers)
+    {
+        return Ok(resp);
+    }
+    let resp = auto(method, headers, resp, types, encodings, level)?;
+
+    // The `Content-Length` header is only removed when the body gets compressed
+    match content_length {
+        Some(ContentLength(len))
+            if len < buffer_size && !resp.headers().contains_key(CONTENT_LENGTH) =>
+        {
+            let (mut head, body) = resp.into_parts();
+            let buf = hyper::body::to_bytes(body).await?;
+            head.headers.typed_insert(ContentLength(buf.len() as u64));
+            Ok(Response::from_parts(head, Body::from(buf)))
+        }
+        _ => Ok(resp),
+    }
+}
+
 /// Create a wrapping handler that compresses the Body of a [`Response`](hyper::Response)
 /// using gzip, adding `content-encoding: gzip` to the Response's [`HeaderMap`](hyper::HeaderMap)
 #[cfg(feature = "compression-gzip")]
@@ -119,10 +373,14 @@ pub fn auto(
 pub fn gzip(
     mut head: http::response::Parts,
     body: CompressableBody<Body, hyper::Error>,
+    level: CompressionLevel,
 ) -> Response<Body> {
     tracing::trace!("compressing response body on the fly using gzip");
 
-    let body = Body::wrap_stream(ReaderStream::new(GzipEncoder::new(StreamReader::new(body))));
+    let body = Body::wrap_stream(ReaderStream::new(GzipEncoder::with_quality(
+        StreamReader::new(body),
+        level.quality(ContentCoding::GZIP),
+    )));
     let header = create_encoding_header(head.headers.remove(CONTENT_ENCODING), ContentCoding::GZIP);
     head.headers.remove(CONTENT_LENGTH);
     head.headers.append(CONTENT_ENCODING, header);
@@ -131,17 +389,21 @@ pub fn gzip(
 
 /// Create a wrapping handler that compresses the Body of a [`Response`](hyper::Response)
 /// using deflate, adding `content-encoding: deflate` to the Response's [`HeaderMap`](hyper::HeaderMap)
+///
+/// The body is a zlib stream (RFC 1950) as the HTTP `deflate` coding requires, not a raw deflate one.
 #[cfg(feature = "compression-deflate")]
 #[cfg_attr(docsrs, doc(cfg(feature = "compression-deflate")))]
 pub fn deflate(
     mut head: http::response::Parts,
     body: CompressableBody<Body, hyper::Error>,
+    level: CompressionLevel,
 ) -> Response<Body> {
     tracing::trace!("compressing response body on the fly using deflate");
 
-    let body = Body::wrap_stream(ReaderStream::new(DeflateEncoder::new(StreamReader::new(
-        body,
-    ))));
+    let body = Body::wrap_stream(ReaderStream::new(ZlibEncoder::with_quality(
+        StreamReader::new(body),
+        level.quality(ContentCoding::DEFLATE),
+    )));
     let header = create_encoding_header(
         head.headers.remove(CONTENT_ENCODING),
         ContentCoding::DEFLATE,
@@ -158,12 +420,14 @@ pub fn deflate(
 pub fn brotli(
     mut head: http::response::Parts,
     body: CompressableBody<Body, hyper::Error>,
+    level: CompressionLevel,
 ) -> Response<Body> {
     tracing::trace!("compressing response body on the fly using brotli");
 
-    let body = Body::wrap_stream(ReaderStream::new(BrotliEncoder::new(StreamReader::new(
-        body,
-    ))));
+    let body = Body::wrap_stream(ReaderStream::new(BrotliEncoder::with_quality(
+        StreamReader::new(body),
+        level.quality(ContentCoding::BROTLI),
+    )));
     let header =
         create_encoding_header(head.headers.remove(CONTENT_ENCODING), ContentCoding::BROTLI);
     head.headers.remove(CONTENT_LENGTH);
@@ -178,10 +442,14 @@ pub fn brotli(
 pub fn zstd(
     mut head: http::response::Parts,
     body: CompressableBody<Body, hyper::Error>,
+    level: CompressionLevel,
 ) -> Response<Body> {
     tracing::trace!("compressing response body on the fly using zstd");
 
-    let body = Body::wrap_stream(ReaderStream::new(ZstdEncoder::new(StreamReader::new(body))));
+    let body = Body::wrap_stream(ReaderStream::new(ZstdEncoder::with_quality(
+        StreamReader::new(body),
+        level.quality(ContentCoding::ZSTD),
+    )));
     let header = create_encoding_header(head.headers.remove(CONTENT_ENCODING), ContentCoding::ZSTD);
     head.headers.remove(CONTENT_LENGTH);
     head.headers.append(CONTENT_ENCODING, header);
@@ -207,6 +475,68 @@ pub fn get_prefered_encoding(headers: &HeaderMap<HeaderValue>) -> Option<Content
     None
 }
 
+/// Negotiates the `content-encoding` of a response via the `accept-encoding` header.
+/// The encoding with the highest quality value is chosen, ties are resolved
+/// following the order of the given `encodings` list.
+/// No encoding is chosen if `identity` is preferred over it, see [`is_identity_preferred`].
+pub fn negotiate_encoding(
+    headers: &HeaderMap<HeaderValue>,
+    encodings: &[ContentCoding],
+) -> Option<ContentCoding> {
+    let mut prefered: Option<(ContentCoding, u16)> = None;
+    for coding in encodings {
+        let quality = accept_quality(headers, coding.to_static()).unwrap_or_default();
+        if quality > 0 && prefered.is_none_or(|(_, q)| quality > q) {
+            prefered = Some((*coding, quality));
+        }
+    }
+    prefered
+        .map(|(coding, _)| coding)
+        .filter(|coding| !is_identity_preferred(headers, coding.to_static()))
+}
+
+/// Checks if a response without any content coding (`identity`) is preferred over the given coding.
+/// An explicit `identity` entry is preferred if its quality value is at least the coding one,
+/// e.g. `identity, gzip` or `identity, br;q=0.5`, whereas a `*` wildcard one must be higher.
+pub fn is_identity_preferred(headers: &HeaderMap<HeaderValue>, coding: &str) -> bool {
+    let quality = accept_quality(headers, coding).unwrap_or_default();
+    match accept_qualities(headers, "identity") {
+        (Some(identity), _) => identity >= quality,
+        (None, Some(wildcard)) => wildcard > quality,
+        (None, None) => false,
+    }
+}
+
+/// Checks if a response without any content coding (`identity`) is acceptable.
+/// It's acceptable by default (e.g. without an `accept-encoding` header) unless it's
+/// excluded via `identity;q=0` or via `*;q=0` without a more specific `identity` entry.
+pub fn is_identity_acceptable(headers: &HeaderMap<HeaderValue>) -> bool {
+    accept_quality(headers, "identity").is_none_or(|quality| quality > 0)
+}
+
+/// Returns the quality value (in thousandths) of a coding in the `accept-encoding` header.
+/// The `*` wildcard value applies to the codings not listed explicitly.
+/// Returns `None` if the coding is neither listed nor matched by a wildcard.
+fn accept_quality(headers: &HeaderMap<HeaderValue>, coding: &str) -> Option<u16> {
+    let (explicit, wildcard) = accept_qualities(headers, coding);
+    explicit.or(wildcard)
+}
+
+/// Returns the quality values (in thousandths) of the explicit entry of a coding
+/// or else of the `*` wildcard entry in the `accept-encoding` header.
+fn accept_qualities(headers: &HeaderMap<HeaderValue>, coding: &str) -> (Option<u16>, Option<u16>) {
+    let mut wildcard = None;
+    for (name, quality) in helpers::quality_values(headers, ACCEPT_ENCODING) {
+        if name.eq_ignore_ascii_case(coding) {
+            return (Some(quality), None);
+        }
+        if name == "*" {
+            wildcard = Some(quality);
+        }
+    }
+    (None, wildcard)
+}
+
 /// A wrapper around any type that implements [`Stream`](futures_util::Stream) to be
 /// compatible with async_compression's `Stream` based encoders.
 #[pin_project]
@@ -240,3 +570,22 @@ impl From<Body> for CompressableBody<Body, hyper::Error> {
         CompressableBody { body }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{AdaptiveCompression, CompressionLevel};
+
+    #[test]
+    fn adaptive_compression_level() {
+        assert!(AdaptiveCompression::new(8, 8).is_err());
+        assert!(AdaptiveCompression::new(16, 8).is_err());
+
+        let adaptive = AdaptiveCompression::new(2, 8).unwrap();
+        assert_eq!(adaptive.level(1), CompressionLevel::Best);
+        assert_eq!(adaptive.level(2), CompressionLevel::Best);
+        assert_eq!(adaptive.level(3), CompressionLevel::Default);
+        assert_eq!(adaptive.level(7), CompressionLevel::Default);
+        assert_eq!(adaptive.level(8), CompressionLevel::Fastest);
+        assert_eq!(adaptive.level(100), CompressionLevel::Fastest);
+    }
+}
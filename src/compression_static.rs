@@ -16,43 +16,49 @@ use std::{
 use crate::{compression, static_files::file_metadata};
 
 /// It defines the pre-compressed file variant metadata of a particular file path.
-pub struct CompressedFileVariant<'a> {
+pub struct CompressedFileVariant {
     /// Current file path.
     pub file_path: PathBuf,
     /// The metadata of the current file.
     pub metadata: Metadata,
     /// The file extension.
-    pub extension: &'a str,
+    pub extension: &'static str,
 }
 
-/// Search for the pre-compressed variant of the given file path.
-pub async fn precompressed_variant<'a>(
-    file_path: &Path,
-    headers: &'a HeaderMap<HeaderValue>,
-) -> Option<CompressedFileVariant<'a>> {
-    tracing::trace!(
-        "preparing pre-compressed file variant path of {}",
-        file_path.display()
-    );
-
-    // Determine prefered-encoding extension if available
-    let comp_ext = match compression::get_prefered_encoding(headers) {
+/// Determines the pre-compressed file extension of the preferred encoding if available
+/// unless an uncompressed response (`identity`) is preferred.
+pub fn precompressed_extension(headers: &HeaderMap<HeaderValue>) -> Option<&'static str> {
+    match compression::get_prefered_encoding(headers)
+        .filter(|coding| !compression::is_identity_preferred(headers, coding.to_static()))
+    {
         // https://zlib.net/zlib_faq.html#faq39
         #[cfg(feature = "compression-gzip")]
-        Some(ContentCoding::GZIP | ContentCoding::DEFLATE) => "gz",
+        Some(ContentCoding::GZIP | ContentCoding::DEFLATE) => Some("gz"),
         // https://peazip.github.io/brotli-compressed-file-format.html
         #[cfg(feature = "compression-brotli")]
-        Some(ContentCoding::BROTLI) => "br",
+        Some(ContentCoding::BROTLI) => Some("br"),
         // https://datatracker.ietf.org/doc/html/rfc8878
         #[cfg(feature = "compression-zstd")]
-        Some(ContentCoding::ZSTD) => "zst",
+        Some(ContentCoding::ZSTD) => Some("zst"),
         _ => {
             tracing::trace!(
                 "preferred encoding based on the file extension was not determined, skipping"
             );
-            return None;
+            None
         }
-    };
+    }
+}
+
+/// Search for the pre-compressed variant of the given file path
+/// using the extension returned by `precompressed_extension()`.
+pub fn precompressed_variant(
+    file_path: &Path,
+    comp_ext: &'static str,
+) -> Option<CompressedFileVariant> {
+    tracing::trace!(
+        "preparing pre-compressed file variant path of {}",
+        file_path.display()
+    );
 
     let comp_name = match file_path.file_name().and_then(OsStr::to_str) {
         Some(v) => v,
@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to limit the number of concurrent connections at the accept loop.
+//!
+//! Each accepted connection holds a semaphore permit which is released once the connection
+//! is dropped, no matter whether it was closed normally, by an error or during a graceful shutdown.
+//! The accepted connections and the bytes written to them are also counted if stats are given.
+//!
+
+use clap::ValueEnum;
+use hyper::server::accept::Accept;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::PollSemaphore;
+
+use crate::stats::Stats;
+#[cfg(feature = "http2")]
+use crate::tls::ClientCertSlot;
+use crate::transport::Transport;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+/// Behavior of new connections once the connection limit is reached.
+pub enum ConnLimitMode {
+    /// Delay accepting new connections until a connection is closed.
+    Delay,
+    /// Close new connections immediately.
+    Close,
+}
+
+/// Type to intercept incoming connections and limit the concurrent ones.
+pub struct LimitedIncoming<A> {
+    incoming: A,
+    semaphore: Option<PollSemaphore>,
+    mode: ConnLimitMode,
+    permit: Option<OwnedSemaphorePermit>,
+    stats: Option<Arc<Stats>>,
+}
+
+impl<A> LimitedIncoming<A> {
+    /// Creates a new connection limit interceptor.
+    /// A `max_connections` value of `0` means no limit.
+    pub fn new(incoming: A, max_connections: usize, mode: ConnLimitMode) -> Self {
+        let semaphore = if max_connections > 0 {
+            Some(PollSemaphore::new(Arc::new(Semaphore::new(
+                max_connections,
+            ))))
+        } else {
+            None
+        };
+        Self {
+            incoming,
+            semaphore,
+            mode,
+            permit: None,
+            stats: None,
+        }
+    }
+
+    /// Counts the accepted connections and the bytes written to them using the given stats.
+    pub fn with_stats(mut self, stats: Arc<Stats>) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+}
+
+impl<A> Accept for LimitedIncoming<A>
+where
+    A: Accept + Unpin,
+{
+    type Conn = LimitedConn<A::Conn>;
+    type Error = A::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let pin = self.get_mut();
+
+        let semaphore = match pin.semaphore.as_mut() {
+            Some(semaphore) => semaphore,
+            None => {
+                let conn = ready!(Pin::new(&mut pin.incoming).poll_accept(cx));
+                return Poll::Ready(
+                    conn.map(|res| res.map(|conn| LimitedConn::new(conn, None, &pin.stats))),
+                );
+            }
+        };
+
+        match pin.mode {
+            ConnLimitMode::Delay => {
+                // Wait for an available permit before accepting a new connection
+                if pin.permit.is_none() {
+                    match ready!(semaphore.poll_acquire(cx)) {
+                        Some(permit) => pin.permit = Some(permit),
+                        None => return Poll::Ready(None),
+                    }
+                }
+                let conn = ready!(Pin::new(&mut pin.incoming).poll_accept(cx));
+                Poll::Ready(conn.map(|res| {
+                    res.map(|conn| LimitedConn::new(conn, pin.permit.take(), &pin.stats))
+                }))
+            }
+            ConnLimitMode::Close => loop {
+                let conn = match ready!(Pin::new(&mut pin.incoming).poll_accept(cx)) {
+                    Some(Ok(conn)) => conn,
+                    Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                    None => return Poll::Ready(None),
+                };
+                match semaphore.clone_inner().try_acquire_owned() {
+                    Ok(permit) => {
+                        return Poll::Ready(Some(Ok(LimitedConn::new(
+                            conn,
+                            Some(permit),
+                            &pin.stats,
+                        ))))
+                    }
+                    Err(_) => {
+                        tracing::debug!("connection limit reached, closing the new connection");
+                        drop(conn);
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Connection holding a permit of the connection limit (if any) until it gets dropped.
+pub struct LimitedConn<C> {
+    conn: C,
+    _permit: Option<OwnedSemaphorePermit>,
+    stats: Option<Arc<Stats>>,
+}
+
+impl<C> LimitedConn<C> {
+    fn new(conn: C, permit: Option<OwnedSemaphorePermit>, stats: &Option<Arc<Stats>>) -> Self {
+        if let Some(stats) = stats {
+            stats.add_connection();
+        }
+        Self {
+            conn,
+            _permit: permit,
+            stats: stats.clone(),
+        }
+    }
+}
+
+impl<C: Transport + Unpin> Transport for LimitedConn<C> {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.conn.remote_addr()
+    }
+
+    #[cfg(feature = "http2")]
+    fn client_cert(&self) -> Option<ClientCertSlot> {
+        self.conn.client_cert()
+    }
+}
+
+impl<C: AsyncRead + Unpin> AsyncRead for LimitedConn<C> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().conn).poll_read(cx, buf)
+    }
+}
+
+impl<C: AsyncWrite + Unpin> AsyncWrite for LimitedConn<C> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let pin = self.get_mut();
+        let written = ready!(Pin::new(&mut pin.conn).poll_write(cx, buf))?;
+        if let Some(stats) = &pin.stats {
+            stats.add_bytes_sent(written);
+        }
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().conn).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().conn).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConnLimitMode, LimitedIncoming};
+    use futures_util::future::poll_fn;
+    use hyper::server::{accept::Accept, conn::AddrIncoming};
+    use std::pin::Pin;
+    use std::time::Duration;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::{TcpListener, TcpStream};
+
+    async fn limited_incoming(mode: ConnLimitMode) -> (LimitedIncoming<AddrIncoming>, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let incoming = AddrIncoming::from_listener(listener).unwrap();
+        (LimitedIncoming::new(incoming, 1, mode), addr)
+    }
+
+    #[tokio::test]
+    async fn limit_delay_mode() {
+        let (mut incoming, addr) = limited_incoming(ConnLimitMode::Delay).await;
+
+        let _client1 = TcpStream::connect(&addr).await.unwrap();
+        let conn1 = poll_fn(|cx| Pin::new(&mut incoming).poll_accept(cx))
+            .await
+            .unwrap()
+            .unwrap();
+
+        // The second connection is not accepted until the first one is closed
+        let _client2 = TcpStream::connect(&addr).await.unwrap();
+        let pending = tokio::time::timeout(
+            Duration::from_millis(100),
+            poll_fn(|cx| Pin::new(&mut incoming).poll_accept(cx)),
+        )
+        .await;
+        assert!(pending.is_err());
+
+        drop(conn1);
+        let conn2 = poll_fn(|cx| Pin::new(&mut incoming).poll_accept(cx)).await;
+        assert!(matches!(conn2, Some(Ok(_))));
+    }
+
+    #[tokio::test]
+    async fn limit_close_mode() {
+        let (mut incoming, addr) = limited_incoming(ConnLimitMode::Close).await;
+
+        let _client1 = TcpStream::connect(&addr).await.unwrap();
+        let conn1 = poll_fn(|cx| Pin::new(&mut incoming).poll_accept(cx))
+            .await
+            .unwrap()
+            .unwrap();
+
+        // The second connection is closed right away
+        let mut client2 = TcpStream::connect(&addr).await.unwrap();
+        let pending = tokio::time::timeout(
+            Duration::from_millis(100),
+            poll_fn(|cx| Pin::new(&mut incoming).poll_accept(cx)),
+        )
+        .await;
+        assert!(pending.is_err());
+        let mut buf = [0; 1];
+        assert_eq!(client2.read(&mut buf).await.unwrap_or_default(), 0);
+
+        // A permit is released once a connection is dropped
+        drop(conn1);
+        let _client3 = TcpStream::connect(&addr).await.unwrap();
+        let conn3 = poll_fn(|cx| Pin::new(&mut incoming).poll_accept(cx)).await;
+        assert!(matches!(conn3, Some(Ok(_))));
+    }
+}
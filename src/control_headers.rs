@@ -5,16 +5,34 @@
 
 //! It provides an arbitrary `Cache-Control` headers functionality
 //! for incoming requests based on a set of file types.
+//! It also supports custom `Cache-Control` rules by content type or file extension
+//! as well as rules forcing matching request paths to not be cached at all.
 //!
+//! `Cache-Control` values can be composed from structured directives
+//! (e.g. `max-age` or `stale-while-revalidate`) which are validated at startup.
+//!
+
+use clap::ValueEnum;
+use headers::{ContentType, HeaderMapExt};
+use hyper::{
+    header::{HeaderValue, CACHE_CONTROL, EXPIRES, PRAGMA},
+    Body, Response,
+};
+use mime_guess::Mime;
+use serde::{Deserialize, Serialize};
 
-use headers::{CacheControl, HeaderMapExt};
-use hyper::{Body, Response};
+use crate::path_normalization::lookup_path;
+use crate::settings::{CacheControl as CacheControlRule, CacheControlMatcher, NoStore};
+use crate::{Context, Result};
 
 // Cache-Control `max-age` variants
 const MAX_AGE_ONE_HOUR: u64 = 60 * 60;
 const MAX_AGE_ONE_DAY: u64 = 60 * 60 * 24;
 const MAX_AGE_ONE_YEAR: u64 = 60 * 60 * 24 * 365;
 
+/// Maximum value of the `Cache-Control` delta-seconds directives (RFC 9111).
+const MAX_DELTA_SECONDS: u64 = 2_147_483_648;
+
 // `Cache-Control` list of extensions
 const CACHE_EXT_ONE_HOUR: [&str; 4] = ["atom", "json", "rss", "xml"];
 const CACHE_EXT_ONE_YEAR: [&str; 32] = [
@@ -23,8 +41,98 @@ const CACHE_EXT_ONE_YEAR: [&str; 32] = [
     "weba", "webm", "webp", "woff", "woff2", "zip",
 ];
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+/// Cache visibility of responses.
+pub enum CacheVisibility {
+    /// Responses can be stored by any cache (`public`).
+    Public,
+    /// Responses can only be stored by private caches like browsers (`private`).
+    Private,
+}
+
+/// Structured `Cache-Control` directives.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheDirectives {
+    /// `public` or `private` directive.
+    pub visibility: Option<CacheVisibility>,
+    /// `max-age` directive value in seconds.
+    pub max_age: Option<u64>,
+    /// `s-maxage` directive value in seconds.
+    pub s_maxage: Option<u64>,
+    /// `stale-while-revalidate` directive value in seconds.
+    pub stale_while_revalidate: Option<u64>,
+    /// `must-revalidate` directive.
+    pub must_revalidate: bool,
+}
+
+impl CacheDirectives {
+    /// Checks if no directive is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Validates the directive values.
+    pub fn validate(&self) -> Result {
+        for (name, value) in [
+            ("max-age", self.max_age),
+            ("s-maxage", self.s_maxage),
+            ("stale-while-revalidate", self.stale_while_revalidate),
+        ] {
+            if value.is_some_and(|v| v > MAX_DELTA_SECONDS) {
+                bail!(
+                    "cache control {name} value exceeds the maximum of {MAX_DELTA_SECONDS} seconds"
+                );
+            }
+        }
+        if self.s_maxage.is_some() && self.visibility == Some(CacheVisibility::Private) {
+            bail!("cache control s-maxage can not be used along with private");
+        }
+        Ok(())
+    }
+
+    /// Composes a `Cache-Control` header value from the directives.
+    /// The directives are written in a fixed order
+    /// (`public`/`private`, `max-age`, `s-maxage`, `must-revalidate`, `stale-while-revalidate`).
+    pub fn header_value(&self) -> Result<HeaderValue> {
+        self.validate()?;
+        if self.stale_while_revalidate.is_some()
+            && self.max_age.is_none()
+            && self.s_maxage.is_none()
+        {
+            bail!("cache control stale-while-revalidate requires a max-age or s-maxage value");
+        }
+
+        let mut directives: Vec<String> = Vec::new();
+        match self.visibility {
+            Some(CacheVisibility::Public) => directives.push("public".to_owned()),
+            Some(CacheVisibility::Private) => directives.push("private".to_owned()),
+            None => {}
+        }
+        if let Some(secs) = self.max_age {
+            directives.push(format!("max-age={secs}"));
+        }
+        if let Some(secs) = self.s_maxage {
+            directives.push(format!("s-maxage={secs}"));
+        }
+        if self.must_revalidate {
+            directives.push("must-revalidate".to_owned());
+        }
+        if let Some(secs) = self.stale_while_revalidate {
+            directives.push(format!("stale-while-revalidate={secs}"));
+        }
+        if directives.is_empty() {
+            bail!("cache control directives can not be empty");
+        }
+
+        HeaderValue::from_str(&directives.join(", "))
+            .with_context(|| "invalid cache control directives value")
+    }
+}
+
 /// It appends a `Cache-Control` header to a response if that one is part of a set of file types.
-pub fn append_headers(uri: &str, resp: &mut Response<Body>) {
+/// The given directives take precedence over the default ones (`public` and the file type `max-age`).
+pub fn append_headers(uri: &str, resp: &mut Response<Body>, directives: &CacheDirectives) {
     // Default max-age value in seconds (one day)
     let mut max_age = MAX_AGE_ONE_DAY;
 
@@ -36,15 +144,115 @@ pub fn append_headers(uri: &str, resp: &mut Response<Body>) {
         }
     }
 
-    let cache_control = CacheControl::new()
-        .with_public()
-        .with_max_age(duration_from_secs(max_age));
-    resp.headers_mut().typed_insert(cache_control);
+    let directives = CacheDirectives {
+        visibility: directives.visibility.or(Some(CacheVisibility::Public)),
+        max_age: directives.max_age.or(Some(max_age)),
+        ..directives.to_owned()
+    };
+    match directives.header_value() {
+        Ok(value) => {
+            resp.headers_mut().insert(CACHE_CONTROL, value);
+        }
+        Err(err) => tracing::error!("unable to compose cache control header: {:?}", err),
+    }
+}
+
+/// Returns the long-lived `Cache-Control` value of the site icons for the given `max-age`
+/// or `None` if it's `0` (disabled).
+pub fn icons_header_value(max_age: u64) -> Result<Option<HeaderValue>> {
+    if max_age == 0 {
+        return Ok(None);
+    }
+    if max_age > MAX_DELTA_SECONDS {
+        bail!(
+            "cache control icons max-age value exceeds the maximum of {MAX_DELTA_SECONDS} seconds"
+        );
+    }
+    let value = HeaderValue::from_str(&format!("public, max-age={max_age}, immutable"))
+        .with_context(|| "invalid cache control icons value")?;
+    Ok(Some(value))
 }
 
-/// It caps a duration value at ~136 years.
-fn duration_from_secs(secs: u64) -> std::time::Duration {
-    std::time::Duration::from_secs(std::cmp::min(secs, u32::MAX as u64))
+/// Checks if the request uri is a site icon or manifest path, that is
+/// `/favicon.ico`, `/apple-touch-icon*.png` or `/site.webmanifest`.
+fn is_icon_path(uri: &str) -> bool {
+    uri == "/favicon.ico"
+        || uri == "/site.webmanifest"
+        || (uri.starts_with("/apple-touch-icon")
+            && uri.ends_with(".png")
+            && !uri[1..].contains('/'))
+}
+
+/// It replaces the `Cache-Control` header of a response with the given value
+/// if the request uri is a site icon or manifest path.
+pub fn append_icon_headers(uri: &str, value: &HeaderValue, resp: &mut Response<Body>) {
+    if is_icon_path(uri) {
+        resp.headers_mut().insert(CACHE_CONTROL, value.to_owned());
+    }
+}
+
+/// It appends the `Cache-Control` value of the first rule matching the response content type
+/// or the request file extension. It returns `true` if a rule was applied.
+pub fn append_rule_headers(
+    uri: &str,
+    cache_control_opts_vec: &Option<Vec<CacheControlRule>>,
+    resp: &mut Response<Body>,
+) -> bool {
+    let rules = match cache_control_opts_vec {
+        Some(rules) if !rules.is_empty() => rules,
+        _ => return false,
+    };
+
+    let content_type = resp
+        .headers()
+        .typed_get::<ContentType>()
+        .map(|ct| Mime::from(ct).essence_str().to_lowercase());
+    let extension = uri_file_extension(uri).map(|ext| ext.to_lowercase());
+
+    let rule = rules.iter().find(|rule| match &rule.matcher {
+        CacheControlMatcher::ContentType(glob) => {
+            content_type.as_deref().is_some_and(|ct| glob.is_match(ct))
+        }
+        CacheControlMatcher::Extension(ext) => extension.as_deref() == Some(ext.as_str()),
+    });
+
+    match rule {
+        Some(rule) => {
+            resp.headers_mut()
+                .insert(CACHE_CONTROL, rule.value.to_owned());
+            true
+        }
+        None => false,
+    }
+}
+
+/// It replaces the caching headers of a response with `Cache-Control: no-store, no-cache`
+/// and `Pragma: no-cache` if the request uri matches one of the no-store rules.
+/// Conflicting headers like `Expires` are removed.
+///
+/// The rules are also matched against the decoded lookup path so that
+/// percent-encoded variants of a path get the same headers as the file served.
+pub fn append_no_store_headers(
+    uri: &str,
+    no_store_opts_vec: &Option<Vec<NoStore>>,
+    resp: &mut Response<Body>,
+) {
+    if let Some(no_store_vec) = no_store_opts_vec {
+        let lookup_path = lookup_path(uri);
+        if !no_store_vec.iter().any(|rule| {
+            (rule.source.is_match(uri) || rule.source.is_match(&lookup_path))
+                && !rule.exclude.iter().any(|m| m.is_match(&lookup_path))
+        }) {
+            return;
+        }
+        let headers = resp.headers_mut();
+        headers.remove(EXPIRES);
+        headers.insert(
+            CACHE_CONTROL,
+            HeaderValue::from_static("no-store, no-cache"),
+        );
+        headers.insert(PRAGMA, HeaderValue::from_static("no-cache"));
+    }
 }
 
 /// Gets the file extension for a URI.
@@ -59,8 +267,9 @@ mod tests {
     use hyper::{Body, Response, StatusCode};
 
     use super::{
-        append_headers, uri_file_extension, CACHE_EXT_ONE_HOUR, CACHE_EXT_ONE_YEAR,
-        MAX_AGE_ONE_DAY, MAX_AGE_ONE_HOUR, MAX_AGE_ONE_YEAR,
+        append_headers, append_no_store_headers, append_rule_headers, uri_file_extension,
+        CacheDirectives, CacheVisibility, CACHE_EXT_ONE_HOUR, CACHE_EXT_ONE_YEAR, MAX_AGE_ONE_DAY,
+        MAX_AGE_ONE_HOUR, MAX_AGE_ONE_YEAR,
     };
 
     #[tokio::test]
@@ -69,7 +278,11 @@ mod tests {
         *resp.status_mut() = StatusCode::OK;
 
         for ext in CACHE_EXT_ONE_HOUR.iter() {
-            append_headers(&["/some.", ext].concat(), &mut resp);
+            append_headers(
+                &["/some.", ext].concat(),
+                &mut resp,
+                &CacheDirectives::default(),
+            );
 
             let cache_control = resp.headers().get(http::header::CACHE_CONTROL).unwrap();
             assert_eq!(resp.status(), StatusCode::OK);
@@ -85,7 +298,7 @@ mod tests {
         let mut resp = Response::new(Body::empty());
         *resp.status_mut() = StatusCode::OK;
 
-        append_headers("/", &mut resp);
+        append_headers("/", &mut resp, &CacheDirectives::default());
 
         let cache_control = resp.headers().get(http::header::CACHE_CONTROL).unwrap();
         assert_eq!(resp.status(), StatusCode::OK);
@@ -101,7 +314,11 @@ mod tests {
         *resp.status_mut() = StatusCode::OK;
 
         for ext in CACHE_EXT_ONE_YEAR.iter() {
-            append_headers(&["/some.", ext].concat(), &mut resp);
+            append_headers(
+                &["/some.", ext].concat(),
+                &mut resp,
+                &CacheDirectives::default(),
+            );
 
             let cache_control = resp.headers().get(http::header::CACHE_CONTROL).unwrap();
             assert_eq!(resp.status(), StatusCode::OK);
@@ -112,6 +329,199 @@ mod tests {
         }
     }
 
+    #[test]
+    fn headers_structured_directives() {
+        let directives = CacheDirectives {
+            visibility: Some(CacheVisibility::Public),
+            max_age: Some(60),
+            s_maxage: Some(300),
+            stale_while_revalidate: Some(86400),
+            must_revalidate: true,
+        };
+        assert_eq!(
+            directives.header_value().unwrap(),
+            "public, max-age=60, s-maxage=300, must-revalidate, stale-while-revalidate=86400"
+        );
+
+        // Unset directives fall back to the file type defaults
+        let directives = CacheDirectives {
+            stale_while_revalidate: Some(86400),
+            ..Default::default()
+        };
+        let mut resp = Response::new(Body::empty());
+        append_headers("/index.html", &mut resp, &directives);
+        assert_eq!(
+            resp.headers()[http::header::CACHE_CONTROL],
+            format!("public, max-age={MAX_AGE_ONE_DAY}, stale-while-revalidate=86400")
+        );
+        let directives = CacheDirectives {
+            visibility: Some(CacheVisibility::Private),
+            max_age: Some(0),
+            must_revalidate: true,
+            ..Default::default()
+        };
+        let mut resp = Response::new(Body::empty());
+        append_headers("/main.js", &mut resp, &directives);
+        assert_eq!(
+            resp.headers()[http::header::CACHE_CONTROL],
+            "private, max-age=0, must-revalidate"
+        );
+
+        // Invalid directives
+        assert!(CacheDirectives::default().header_value().is_err());
+        assert!(directives.validate().is_ok());
+        for directives in [
+            CacheDirectives {
+                max_age: Some(u64::MAX),
+                ..Default::default()
+            },
+            CacheDirectives {
+                visibility: Some(CacheVisibility::Private),
+                s_maxage: Some(60),
+                ..Default::default()
+            },
+            CacheDirectives {
+                stale_while_revalidate: Some(60),
+                ..Default::default()
+            },
+        ] {
+            assert!(directives.header_value().is_err(), "{directives:?}");
+        }
+    }
+
+    #[test]
+    fn headers_by_rules() {
+        use crate::settings::{CacheControl, CacheControlMatcher};
+        use headers::{ContentType, HeaderMapExt};
+
+        let rules = Some(vec![
+            CacheControl {
+                matcher: CacheControlMatcher::ContentType(
+                    globset::Glob::new("text/html").unwrap().compile_matcher(),
+                ),
+                value: "no-cache".parse().unwrap(),
+            },
+            CacheControl {
+                matcher: CacheControlMatcher::ContentType(
+                    globset::Glob::new("image/*").unwrap().compile_matcher(),
+                ),
+                value: "max-age=86400".parse().unwrap(),
+            },
+            CacheControl {
+                matcher: CacheControlMatcher::Extension("woff2".to_owned()),
+                value: "max-age=31536000".parse().unwrap(),
+            },
+        ]);
+
+        let cases = [
+            ("/", "text/html; charset=utf-8", Some("no-cache")),
+            ("/logo.png", "image/png", Some("max-age=86400")),
+            (
+                "/font.WOFF2",
+                "application/octet-stream",
+                Some("max-age=31536000"),
+            ),
+            ("/main.css", "text/css", None),
+        ];
+        for (uri, content_type, expected) in cases {
+            let mut resp = Response::new(Body::empty());
+            resp.headers_mut().typed_insert(ContentType::from(
+                content_type.parse::<mime_guess::Mime>().unwrap(),
+            ));
+
+            let applied = append_rule_headers(uri, &rules, &mut resp);
+            assert_eq!(applied, expected.is_some());
+            assert_eq!(
+                resp.headers()
+                    .get(http::header::CACHE_CONTROL)
+                    .map(|v| v.to_str().unwrap()),
+                expected
+            );
+        }
+
+        let mut resp = Response::new(Body::empty());
+        assert!(!append_rule_headers("/logo.png", &None, &mut resp));
+    }
+
+    #[test]
+    fn headers_no_store_rules() {
+        use crate::settings::NoStore;
+        use http::header::{CACHE_CONTROL, EXPIRES, PRAGMA};
+
+        let rules = Some(vec![NoStore {
+            source: globset::Glob::new("/account/**").unwrap().compile_matcher(),
+            exclude: vec![],
+        }]);
+
+        let mut resp = Response::new(Body::empty());
+        append_headers(
+            "/account/index.html",
+            &mut resp,
+            &CacheDirectives::default(),
+        );
+        resp.headers_mut()
+            .insert(EXPIRES, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        append_no_store_headers("/account/index.html", &rules, &mut resp);
+        assert_eq!(resp.headers()[CACHE_CONTROL], "no-store, no-cache");
+        assert_eq!(resp.headers().get_all(CACHE_CONTROL).iter().count(), 1);
+        assert_eq!(resp.headers()[PRAGMA], "no-cache");
+        assert!(resp.headers().get(EXPIRES).is_none());
+
+        // Percent-encoded paths are matched once decoded
+        let mut resp = Response::new(Body::empty());
+        append_headers("/%61ccount/profile", &mut resp, &CacheDirectives::default());
+        append_no_store_headers("/%61ccount/profile", &rules, &mut resp);
+        assert_eq!(resp.headers()[CACHE_CONTROL], "no-store, no-cache");
+
+        // Other paths are left untouched
+        let mut resp = Response::new(Body::empty());
+        append_headers("/index.html", &mut resp, &CacheDirectives::default());
+        append_no_store_headers("/index.html", &rules, &mut resp);
+        assert_eq!(
+            resp.headers()[CACHE_CONTROL],
+            format!("public, max-age={MAX_AGE_ONE_DAY}")
+        );
+        assert!(resp.headers().get(PRAGMA).is_none());
+    }
+
+    #[test]
+    fn headers_icons() {
+        use super::{append_icon_headers, icons_header_value};
+        use http::header::CACHE_CONTROL;
+
+        assert!(icons_header_value(0).unwrap().is_none());
+        assert!(icons_header_value(u64::MAX).is_err());
+        let value = icons_header_value(MAX_AGE_ONE_YEAR).unwrap().unwrap();
+
+        for uri in [
+            "/favicon.ico",
+            "/site.webmanifest",
+            "/apple-touch-icon.png",
+            "/apple-touch-icon-precomposed.png",
+            "/apple-touch-icon-180x180.png",
+        ] {
+            let mut resp = Response::new(Body::empty());
+            append_headers(uri, &mut resp, &CacheDirectives::default());
+            append_icon_headers(uri, &value, &mut resp);
+            assert_eq!(
+                resp.headers()[CACHE_CONTROL],
+                format!("public, max-age={MAX_AGE_ONE_YEAR}, immutable")
+            );
+        }
+
+        for uri in [
+            "/assets/favicon.ico",
+            "/favicon.png",
+            "/apple-touch-icon.jpg",
+            "/apple-touch-icon/x.png",
+            "/site.webmanifest.json",
+        ] {
+            let mut resp = Response::new(Body::empty());
+            append_icon_headers(uri, &value, &mut resp);
+            assert!(resp.headers().get(CACHE_CONTROL).is_none(), "{uri}");
+        }
+    }
+
     #[test]
     fn find_uri_extension() {
         assert_eq!(uri_file_extension("/potato.zip"), Some("zip"));
@@ -230,10 +230,11 @@ pub enum Validated {
     NotCors,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 /// Forbidden errors.
 pub enum Forbidden {
     /// Forbidden error origin.
+    #[default]
     Origin,
     /// Forbidden error method.
     Method,
@@ -241,12 +242,6 @@ pub enum Forbidden {
     Header,
 }
 
-impl Default for Forbidden {
-    fn default() -> Self {
-        Self::Origin
-    }
-}
-
 impl Configured {
     /// Check for the incoming CORS request.
     pub fn check_request(
@@ -282,9 +277,12 @@ impl Configured {
                     }
                 }
 
-                let mut headers = http::HeaderMap::new();
+                let mut headers = self.vary_headers();
                 self.append_preflight_headers(&mut headers);
-                headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.into());
+                headers.insert(
+                    header::ACCESS_CONTROL_ALLOW_ORIGIN,
+                    self.allow_origin(origin),
+                );
 
                 Ok((headers, Validated::Preflight(origin.clone())))
             }
@@ -293,22 +291,46 @@ impl Configured {
                 tracing::trace!("cors origin header: {:?}", origin);
 
                 if self.is_origin_allowed(origin) {
-                    let mut headers = http::HeaderMap::new();
+                    let mut headers = self.vary_headers();
                     self.append_preflight_headers(&mut headers);
-                    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.into());
+                    headers.insert(
+                        header::ACCESS_CONTROL_ALLOW_ORIGIN,
+                        self.allow_origin(origin),
+                    );
 
                     Ok((headers, Validated::Simple(origin.clone())))
                 } else {
-                    Err(Forbidden::Origin)
+                    // Not allowed origins get the response without CORS headers
+                    tracing::debug!("cors: origin not allowed: {:?}", origin);
+                    Ok((self.vary_headers(), Validated::NotCors))
                 }
             }
             (None, _) => {
                 // No `ORIGIN` header means this isn't CORS!
-                Ok((http::HeaderMap::new(), Validated::NotCors))
+                Ok((self.vary_headers(), Validated::NotCors))
             }
         }
     }
 
+    /// Returns the `Access-Control-Allow-Origin` value, that is `*` if any origin is allowed
+    /// or the request origin itself when it's part of the allowed origins list.
+    fn allow_origin(&self, origin: &HeaderValue) -> HeaderValue {
+        match self.cors.origins {
+            Some(_) => origin.to_owned(),
+            None => HeaderValue::from_static("*"),
+        }
+    }
+
+    /// Returns a `Vary: Origin` header if the response depends on the request origin,
+    /// that is when an allowed origins list is used.
+    fn vary_headers(&self) -> http::HeaderMap {
+        let mut headers = http::HeaderMap::new();
+        if self.cors.origins.is_some() {
+            headers.insert(header::VARY, HeaderValue::from_name(header::ORIGIN));
+        }
+        headers
+    }
+
     fn is_method_allowed(&self, header: &HeaderValue) -> bool {
         http::Method::from_bytes(header.as_bytes())
             .map(|method| self.cors.allowed_methods.contains(&method))
@@ -346,7 +368,7 @@ pub trait IntoOrigin {
     fn into_origin(self) -> Origin;
 }
 
-impl<'a> IntoOrigin for &'a str {
+impl IntoOrigin for &str {
     fn into_origin(self) -> Origin {
         let mut parts = self.splitn(2, "://");
         let scheme = parts.next().expect("cors::into_origin: missing url scheme");
@@ -5,11 +5,31 @@
 
 //! Module to append custom HTTP headers via TOML config file.
 //!
+//! Matching header entries are applied in config declaration order.
+//! Single-valued headers (e.g. `Cache-Control`) are replaced by later matching entries,
+//! while list-valued headers (e.g. `Link`) are appended skipping identical values.
+//!
 
-use hyper::{Body, Response};
+use hyper::{
+    header::{
+        HeaderName, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+        ACCESS_CONTROL_EXPOSE_HEADERS, LINK, SET_COOKIE, VARY,
+    },
+    Body, HeaderMap, Response,
+};
 
 use crate::settings::Headers;
 
+/// Headers which can have multiple values, so their custom values are appended.
+const LIST_HEADERS: [HeaderName; 6] = [
+    LINK,
+    VARY,
+    SET_COOKIE,
+    ACCESS_CONTROL_ALLOW_HEADERS,
+    ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_EXPOSE_HEADERS,
+];
+
 /// Append custom HTTP headers to current response.
 pub fn append_headers(
     uri: &str,
@@ -19,12 +39,133 @@ pub fn append_headers(
     if let Some(headers_vec) = headers_opts_vec {
         for headers_entry in headers_vec.iter() {
             // Match header glob pattern against request uri
-            if headers_entry.source.is_match(uri) {
-                // Add/update headers if uri matches
-                for (name, value) in &headers_entry.headers {
-                    resp.headers_mut().insert(name, value.to_owned());
+            if headers_entry.source.is_match(uri)
+                && !headers_entry.exclude.iter().any(|m| m.is_match(uri))
+            {
+                merge_headers(resp.headers_mut(), &headers_entry.headers);
+            }
+        }
+    }
+}
+
+/// Merges the custom headers of a matching entry into the response headers.
+fn merge_headers(dest: &mut HeaderMap, src: &HeaderMap) {
+    for name in src.keys() {
+        if LIST_HEADERS.contains(name) {
+            for value in src.get_all(name) {
+                if !dest.get_all(name).iter().any(|v| v == value) {
+                    dest.append(name, value.to_owned());
                 }
             }
+        } else if let Some(value) = src.get_all(name).iter().next_back() {
+            // The last value wins for single-valued headers
+            dest.insert(name, value.to_owned());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use globset::Glob;
+    use hyper::header::{HeaderName, CACHE_CONTROL, LINK, VARY};
+    use hyper::{Body, HeaderMap, Response};
+
+    use super::append_headers;
+    use crate::settings::Headers;
+
+    fn headers_entry(source: &str, headers: &[(&str, &str)]) -> Headers {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(name.parse::<HeaderName>().unwrap(), value.parse().unwrap());
+        }
+        Headers {
+            source: Glob::new(source).unwrap().compile_matcher(),
+            exclude: vec![],
+            headers: map,
+        }
+    }
+
+    fn values(resp: &Response<Body>, name: HeaderName) -> Vec<&str> {
+        resp.headers()
+            .get_all(name)
+            .iter()
+            .map(|v| v.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn headers_overlapping_globs() {
+        let headers = Some(vec![
+            headers_entry(
+                "**/*",
+                &[
+                    ("cache-control", "no-cache"),
+                    ("link", "</main.css>; rel=preload"),
+                    ("vary", "Origin"),
+                ],
+            ),
+            headers_entry(
+                "**/*.html",
+                &[
+                    ("cache-control", "public, max-age=60"),
+                    ("link", "</main.css>; rel=preload"),
+                    ("link", "</main.js>; rel=preload"),
+                    ("vary", "Origin"),
+                ],
+            ),
+        ]);
+
+        let mut resp = Response::new(Body::empty());
+        resp.headers_mut()
+            .insert(CACHE_CONTROL, "public, max-age=86400".parse().unwrap());
+        resp.headers_mut()
+            .insert(VARY, "accept-encoding".parse().unwrap());
+        append_headers("/index.html", &headers, &mut resp);
+
+        // Later matching entries override single-valued headers
+        assert_eq!(values(&resp, CACHE_CONTROL), ["public, max-age=60"]);
+        // List-valued headers are appended without duplicates
+        assert_eq!(
+            values(&resp, LINK),
+            ["</main.css>; rel=preload", "</main.js>; rel=preload"]
+        );
+        assert_eq!(values(&resp, VARY), ["accept-encoding", "Origin"]);
+
+        // Only the matching entries are applied
+        let mut resp = Response::new(Body::empty());
+        append_headers("/main.css", &headers, &mut resp);
+        assert_eq!(values(&resp, CACHE_CONTROL), ["no-cache"]);
+        assert_eq!(values(&resp, LINK), ["</main.css>; rel=preload"]);
+    }
+
+    #[test]
+    fn headers_single_valued_last_value() {
+        let headers = Some(vec![headers_entry(
+            "**/*",
+            &[("cache-control", "no-cache"), ("cache-control", "no-store")],
+        )]);
+        let mut resp = Response::new(Body::empty());
+        append_headers("/index.html", &headers, &mut resp);
+        assert_eq!(values(&resp, CACHE_CONTROL), ["no-store"]);
+    }
+
+    #[test]
+    fn headers_exclude_patterns() {
+        let mut entry = headers_entry("/assets/**", &[("cache-control", "max-age=31536000")]);
+        entry.exclude = vec![
+            Glob::new("/assets/vendor/**").unwrap().compile_matcher(),
+            Glob::new("**/*.map").unwrap().compile_matcher(),
+        ];
+        let headers = Some(vec![entry]);
+
+        let mut resp = Response::new(Body::empty());
+        append_headers("/assets/main.js", &headers, &mut resp);
+        assert_eq!(values(&resp, CACHE_CONTROL), ["max-age=31536000"]);
+
+        for uri in ["/assets/vendor/lib.js", "/assets/main.js.map"] {
+            let mut resp = Response::new(Body::empty());
+            append_headers(uri, &headers, &mut resp);
+            assert!(values(&resp, CACHE_CONTROL).is_empty(), "{uri}");
         }
     }
 }
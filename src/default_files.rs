@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to serve default `/robots.txt` and `/favicon.ico` files
+//! when they don't exist in the root directory.
+//!
+
+use bytes::Bytes;
+use headers::{ContentLength, ContentType, HeaderMapExt};
+use hyper::{Body, Method, Response};
+use mime_guess::{mime, Mime};
+use std::path::Path;
+
+use crate::{exts::http::MethodExt, helpers, Result};
+
+/// Default `robots.txt` content disallowing all crawlers.
+pub const ROBOTS_TXT_DISALLOW_ALL: &str = "User-agent: *\nDisallow: /\n";
+
+/// Default `robots.txt` content allowing all crawlers.
+pub const ROBOTS_TXT_ALLOW_ALL: &str = "User-agent: *\nDisallow:\n";
+
+/// Default files served when missing in the root directory.
+#[derive(Debug, Default)]
+pub struct DefaultFiles {
+    /// Content of the default `/robots.txt` file.
+    pub robots_txt: Option<Bytes>,
+    /// Content and MIME type of the default `/favicon.ico` file.
+    pub favicon: Option<(Bytes, Mime)>,
+}
+
+impl DefaultFiles {
+    /// Creates the default files from the `robots.txt` option value and the favicon file path.
+    ///
+    /// The `disallow-all` and `allow-all` values result in a `robots.txt` for all crawlers,
+    /// other values are used as its content where `\n` sequences are replaced by new lines.
+    pub fn new(robots_txt: &str, favicon: Option<&Path>) -> Result<Self> {
+        let robots_txt = match robots_txt.trim() {
+            "" => None,
+            "disallow-all" => Some(Bytes::from_static(ROBOTS_TXT_DISALLOW_ALL.as_bytes())),
+            "allow-all" => Some(Bytes::from_static(ROBOTS_TXT_ALLOW_ALL.as_bytes())),
+            content => Some(Bytes::from(content.replace("\\n", "\n"))),
+        };
+        let favicon = match favicon {
+            Some(path) if !path.as_os_str().is_empty() => {
+                let content = helpers::read_bytes(path)?;
+                let mime = mime_guess::from_path(path).first_or(mime::IMAGE_STAR);
+                Some((Bytes::from(content), mime))
+            }
+            _ => None,
+        };
+        Ok(Self {
+            robots_txt,
+            favicon,
+        })
+    }
+
+    /// Returns a default file response for `GET` or `HEAD` requests of
+    /// the `/robots.txt` or `/favicon.ico` paths if configured.
+    pub fn response(&self, uri_path: &str, method: &Method) -> Option<Response<Body>> {
+        if !method.is_get() && !method.is_head() {
+            return None;
+        }
+        let (content, mime) = match uri_path {
+            "/robots.txt" => (self.robots_txt.as_ref()?, mime::TEXT_PLAIN_UTF_8),
+            "/favicon.ico" => {
+                let (content, mime) = self.favicon.as_ref()?;
+                (content, mime.to_owned())
+            }
+            _ => return None,
+        };
+
+        let body = if method.is_head() {
+            Body::empty()
+        } else {
+            Body::from(content.to_owned())
+        };
+        let mut resp = Response::new(body);
+        resp.headers_mut()
+            .typed_insert(ContentLength(content.len() as u64));
+        resp.headers_mut().typed_insert(ContentType::from(mime));
+        Some(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DefaultFiles, ROBOTS_TXT_DISALLOW_ALL};
+    use hyper::Method;
+
+    #[tokio::test]
+    async fn default_robots_txt() {
+        let files = DefaultFiles::new("disallow-all", None).unwrap();
+        let resp = files.response("/robots.txt", &Method::GET).unwrap();
+        assert_eq!(resp.headers()["content-type"], "text/plain; charset=utf-8");
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        assert_eq!(body, ROBOTS_TXT_DISALLOW_ALL);
+
+        let files = DefaultFiles::new("User-agent: *\\nDisallow: /admin", None).unwrap();
+        let resp = files.response("/robots.txt", &Method::HEAD).unwrap();
+        assert_eq!(resp.headers()["content-length"], "30");
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        assert!(body.is_empty());
+
+        assert!(files.response("/robots.txt", &Method::POST).is_none());
+        assert!(files.response("/favicon.ico", &Method::GET).is_none());
+        assert!(DefaultFiles::new("", None)
+            .unwrap()
+            .response("/robots.txt", &Method::GET)
+            .is_none());
+    }
+}
@@ -6,22 +6,28 @@
 //! It provides directory listig and auto-index support.
 //!
 
+use chrono::format::{Item, StrftimeItems};
 use chrono::{DateTime, Local, NaiveDateTime, Utc};
 use clap::ValueEnum;
 use futures_util::future::Either;
 use futures_util::{future, FutureExt};
-use headers::{ContentLength, ContentType, HeaderMapExt};
+use globset::GlobMatcher;
+use headers::{
+    ContentLength, ContentType, ETag, HeaderMap, HeaderMapExt, HeaderValue, IfNoneMatch,
+};
 use humansize::FormatSize;
 use hyper::{Body, Method, Response, StatusCode};
 use mime_guess::mime;
 use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
 use std::cmp::Ordering;
+use std::collections::{hash_map::DefaultHasher, HashSet};
 use std::future::Future;
+use std::hash::{Hash, Hasher};
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::{exts::http::MethodExt, Context, Result};
+use crate::{exts::http::MethodExt, settings::DirectoryListingRule, Context, Result};
 
 #[derive(Debug, Serialize, Deserialize, Clone, ValueEnum)]
 #[serde(rename_all = "lowercase")]
@@ -33,52 +39,95 @@ pub enum DirListFmt {
     Json,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Hash, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+/// Directory listing file size format for HTML entries.
+pub enum DirListSizeFmt {
+    /// Human-readable sizes using binary units like `KiB` or `MiB` (default).
+    Human,
+    /// Raw sizes in bytes.
+    Bytes,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Hash, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+/// Directory listing timezone of the HTML entries dates.
+pub enum DirListTimezone {
+    /// Local timezone of the server (default).
+    Local,
+    /// Coordinated Universal Time.
+    Utc,
+}
+
+/// Checks that the given strftime-style directory listing date format is valid.
+pub fn validate_date_format(format: &str) -> Result {
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        bail!("invalid directory listing date format: {}", format);
+    }
+    Ok(())
+}
+
 /// Directory listing options.
 pub struct DirListOpts<'a> {
     /// Request method.
     pub method: &'a Method,
+    /// Request headers.
+    pub headers: &'a HeaderMap<HeaderValue>,
     /// Current Request path.
     pub current_path: &'a str,
     /// URI Request query
     pub uri_query: Option<&'a str>,
-    /// Request file path.
+    /// Request directory path.
     pub filepath: &'a Path,
     /// Directory listing order.
     pub dir_listing_order: u8,
     /// Directory listing format.
     pub dir_listing_format: &'a DirListFmt,
+    /// Directory listing size format.
+    pub dir_listing_size_format: &'a DirListSizeFmt,
+    /// Directory listing date format.
+    pub dir_listing_date_format: &'a str,
+    /// Directory listing timezone.
+    pub dir_listing_timezone: &'a DirListTimezone,
+    /// Directory listing ignore patterns of entry names.
+    pub dir_listing_ignore: &'a [GlobMatcher],
+    /// Flag the symbolic link entries along with their targets.
+    pub dir_listing_show_symlinks: bool,
+    /// Directory path whose entries are merged into the listing if present.
+    /// Entries of the request directory take precedence over the ones with the same name.
+    pub merge_filepath: Option<PathBuf>,
     /// Ignore hidden files (dotfiles).
     pub ignore_hidden_files: bool,
 }
 
+/// Returns whether the directory listing is enabled (`true`) or disabled (`false`)
+/// by the last directory listing rule matching the given request path.
+pub fn enabled_by_rules(uri_path: &str, rules: &Option<Vec<DirectoryListingRule>>) -> Option<bool> {
+    rules
+        .as_ref()?
+        .iter()
+        .rev()
+        .find(|rule| {
+            rule.source.is_match(uri_path) && !rule.exclude.iter().any(|m| m.is_match(uri_path))
+        })
+        .map(|rule| rule.enabled)
+}
+
 /// Provides directory listing support for the current request.
 /// Note that this function highly depends on `static_files::composed_file_metadata()` function
 /// which must be called first. See `static_files::handle()` for more details.
 pub fn auto_index(
     opts: DirListOpts<'_>,
 ) -> impl Future<Output = Result<Response<Body>, StatusCode>> + Send + '_ {
-    // Note: it's safe to call `parent()` here since `filepath`
-    // value always refer to a path with file ending and under
-    // a root directory boundary.
+    // Note: the `filepath` value always refers to a directory path
+    // under a root directory boundary.
     // See `composed_file_metadata()` function which sanitizes the requested
     // path before to be delegated here.
     let filepath = opts.filepath;
-    let parent = filepath.parent().unwrap_or(filepath);
 
-    tokio::fs::read_dir(parent).then(move |res| match res {
+    tokio::fs::read_dir(filepath).then(move |res| match res {
         Ok(dir_reader) => Either::Left(async move {
-            let is_head = opts.method.is_head();
-            match read_dir_entries(
-                dir_reader,
-                opts.current_path,
-                opts.uri_query,
-                is_head,
-                opts.dir_listing_order,
-                opts.dir_listing_format,
-                opts.ignore_hidden_files,
-            )
-            .await
-            {
+            match read_dir_entries(dir_reader, &opts).await {
                 Ok(resp) => Ok(resp),
                 Err(err) => {
                     tracing::error!("error after try to read directory entries: {:?}", err);
@@ -106,8 +155,8 @@ pub fn auto_index(
                 }
                 _ => {
                     tracing::error!(
-                        "unable to read parent directory (parent={}): {:?}",
-                        parent.display(),
+                        "unable to read directory (path={}): {:?}",
+                        filepath.display(),
                         err
                     );
                     StatusCode::INTERNAL_SERVER_ERROR
@@ -123,18 +172,27 @@ const FOOTER: &str =
     r#"<footer><em>Powered by Static Web Server (SWS) / static-web-server.net</em></footer>"#;
 
 const DATETIME_FORMAT_UTC: &str = "%FT%TZ";
-const DATETIME_FORMAT_LOCAL: &str = "%F %T";
 
 /// Defines a file entry and its properties.
 struct FileEntry {
     name: String,
     name_encoded: String,
-    modified: Option<DateTime<Local>>,
+    modified: Option<DateTime<Utc>>,
     filesize: u64,
+    is_dir: bool,
     uri: Option<String>,
+    symlink: Option<SymlinkTarget>,
+}
+
+/// Defines the target of a symbolic link entry.
+struct SymlinkTarget {
+    /// Target path as stored in the symbolic link.
+    path: String,
+    /// Whether the target can not be resolved.
+    broken: bool,
 }
 
-/// Defines sorting attributes for file entries.
+/// Defines the sorting order (`asc` or `desc`) attributes for file entries.
 struct SortingAttr<'a> {
     name: &'a str,
     last_modified: &'a str,
@@ -144,186 +202,237 @@ struct SortingAttr<'a> {
 /// It reads a list of directory entries and create an index page content.
 /// Otherwise it returns a status error.
 async fn read_dir_entries(
-    mut dir_reader: tokio::fs::ReadDir,
-    base_path: &str,
-    uri_query: Option<&str>,
-    is_head: bool,
-    mut order_code: u8,
-    content_format: &DirListFmt,
-    ignore_hidden_files: bool,
+    dir_reader: tokio::fs::ReadDir,
+    opts: &DirListOpts<'_>,
 ) -> Result<Response<Body>> {
+    let base_path = opts.current_path;
+    let uri_query = opts.uri_query;
+    let is_head = opts.method.is_head();
+    let mut order_code = opts.dir_listing_order;
+    let content_format = opts.dir_listing_format;
+    let ignore_hidden_files = opts.ignore_hidden_files;
     let mut dirs_count: usize = 0;
     let mut files_count: usize = 0;
     let mut file_entries: Vec<FileEntry> = vec![];
-
-    while let Some(dir_entry) = dir_reader
-        .next_entry()
-        .await
-        .with_context(|| "unable to read directory entry")?
-    {
-        let meta = match dir_entry.metadata().await {
-            Ok(m) => m,
-            Err(err) => {
-                tracing::error!(
-                    "unable to resolve metadata for file or directory entry (skipped): {:?}",
-                    err
-                );
-                continue;
-            }
-        };
-
-        let name = match dir_entry
-            .file_name()
-            .into_string()
-            .map_err(|err| anyhow::anyhow!(err.into_string().unwrap_or_default()))
-        {
-            Ok(s) => s,
-            Err(err) => {
-                tracing::error!(
-                    "unable to resolve name for file or directory entry (skipped): {:?}",
-                    err
-                );
-                continue;
-            }
-        };
-
-        // Check and ignore the current hidden file/directory (dotfile) if feature enabled
-        if ignore_hidden_files && name.starts_with('.') {
-            continue;
+    let mut dir_readers = vec![dir_reader];
+
+    // Merge the entries of another directory if any (e.g. the overlay root one)
+    if let Some(merge_filepath) = &opts.merge_filepath {
+        match tokio::fs::read_dir(merge_filepath).await {
+            Ok(merge_reader) => dir_readers.push(merge_reader),
+            Err(err) => tracing::debug!(
+                "unable to read directory to merge (path: {}): {:?}",
+                merge_filepath.display(),
+                err
+            ),
         }
+    }
+    let merging = dir_readers.len() > 1;
+    let mut names = HashSet::new();
 
-        let mut name_encoded = utf8_percent_encode(&name, NON_ALPHANUMERIC).to_string();
-        let mut filesize = 0_u64;
-
-        if meta.is_dir() {
-            name_encoded.push('/');
-            dirs_count += 1;
-        } else if meta.is_file() {
-            filesize = meta.len();
-            files_count += 1;
-        } else if meta.file_type().is_symlink() {
-            // NOTE: we resolve the symlink path below to just know if is a directory or not.
-            // Hwever, we are still showing the symlink name but not the resolved name.
-
-            let symlink = dir_entry.path();
-            let symlink = match symlink.canonicalize() {
-                Ok(v) => v,
+    for mut dir_reader in dir_readers {
+        while let Some(dir_entry) = dir_reader
+            .next_entry()
+            .await
+            .with_context(|| "unable to read directory entry")?
+        {
+            let meta = match dir_entry.metadata().await {
+                Ok(m) => m,
                 Err(err) => {
                     tracing::error!(
-                        "unable to resolve `{}` symlink path (skipped): {:?}",
-                        symlink.display(),
+                        "unable to resolve metadata for file or directory entry (skipped): {:?}",
                         err
                     );
                     continue;
                 }
             };
 
-            let symlink_meta = match tokio::fs::symlink_metadata(&symlink).await {
-                Ok(v) => v,
+            let name = match dir_entry
+                .file_name()
+                .into_string()
+                .map_err(|err| anyhow::anyhow!(err.into_string().unwrap_or_default()))
+            {
+                Ok(s) => s,
                 Err(err) => {
                     tracing::error!(
-                        "unable to resolve metadata for `{}` symlink (skipped): {:?}",
-                        symlink.display(),
+                        "unable to resolve name for file or directory entry (skipped): {:?}",
                         err
                     );
                     continue;
                 }
             };
-            if symlink_meta.is_dir() {
-                name_encoded.push('/');
-                dirs_count += 1;
-            } else {
-                filesize = meta.len();
-                files_count += 1;
+
+            // Check and ignore the current hidden file/directory (dotfile) if feature enabled
+            if ignore_hidden_files && name.starts_with('.') {
+                continue;
             }
-        } else {
-            continue;
-        }
 
-        let mut uri = None;
-        // NOTE: Use relative paths by default independently of
-        // the "redirect trailing slash" feature.
-        // However, when "redirect trailing slash" is disabled
-        // and a request path doesn't contain a trailing slash then
-        // entries should contain the "parent/entry-name" as a link format.
-        // Otherwise, we just use the "entry-name" as a link (default behavior).
-        // Note that in both cases, we add a trailing slash if the entry is a directory.
-        if !base_path.ends_with('/') {
-            let base_path = Path::new(base_path);
-            let parent_dir = base_path.parent().unwrap_or(base_path);
-            let mut base_dir = base_path;
-            if base_path != parent_dir {
-                base_dir = match base_path.strip_prefix(parent_dir) {
-                    Ok(v) => v,
-                    Err(err) => {
-                        tracing::error!(
-                            "unable to strip parent path prefix for `{}` (skipped): {:?}",
-                            base_path.display(),
-                            err
-                        );
-                        continue;
-                    }
-                };
+            // Check and ignore the current entry if it matches an ignore pattern
+            if opts.dir_listing_ignore.iter().any(|m| m.is_match(&name)) {
+                continue;
             }
 
-            let mut base_str = String::new();
-            if !base_dir.starts_with("/") {
-                let base_dir = base_dir.to_str().unwrap_or_default();
-                if !base_dir.is_empty() {
-                    base_str.push_str(base_dir);
-                }
-                base_str.push('/');
+            // Merged entries are shadowed by the ones of the same name listed before
+            if merging && !names.insert(name.clone()) {
+                continue;
             }
 
-            base_str.push_str(&name_encoded);
-            uri = Some(base_str);
-        }
+            let mut name_encoded = utf8_percent_encode(&name, NON_ALPHANUMERIC).to_string();
+            let mut filesize = 0_u64;
+            let mut is_dir = false;
+            let mut symlink = None;
 
-        let modified = match parse_last_modified(meta.modified()?) {
-            Ok(local_dt) => Some(local_dt),
-            Err(err) => {
-                tracing::error!("error determining the file's last modified: {:?}", err);
-                None
+            if meta.is_dir() {
+                is_dir = true;
+            } else if meta.is_file() {
+                filesize = meta.len();
+            } else if meta.file_type().is_symlink() {
+                // NOTE: we resolve the symlink path below to just know if is a directory or not.
+                // Hwever, we are still showing the symlink name but not the resolved name.
+                let symlink_path = dir_entry.path();
+                if opts.dir_listing_show_symlinks {
+                    let target = match tokio::fs::read_link(&symlink_path).await {
+                        Ok(target) => target.to_string_lossy().into_owned(),
+                        Err(err) => {
+                            tracing::error!(
+                                "unable to read `{}` symlink target: {:?}",
+                                symlink_path.display(),
+                                err
+                            );
+                            String::new()
+                        }
+                    };
+                    symlink = Some(SymlinkTarget {
+                        path: target,
+                        broken: false,
+                    });
+                }
+
+                let symlink_meta = match symlink_path.canonicalize() {
+                    Ok(resolved) => tokio::fs::symlink_metadata(&resolved).await,
+                    Err(err) => Err(err),
+                };
+                match symlink_meta {
+                    Ok(symlink_meta) if symlink_meta.is_dir() => is_dir = true,
+                    Ok(_) => filesize = meta.len(),
+                    // Broken symlinks are only listed when flagged
+                    Err(err) => match symlink {
+                        Some(ref mut target) => target.broken = true,
+                        None => {
+                            tracing::error!(
+                                "unable to resolve `{}` symlink path (skipped): {:?}",
+                                symlink_path.display(),
+                                err
+                            );
+                            continue;
+                        }
+                    },
+                }
+            } else {
+                continue;
             }
-        };
-        file_entries.push(FileEntry {
-            name,
-            name_encoded,
-            modified,
-            filesize,
-            uri,
-        });
-    }
 
-    // Check the query request uri for a sorting type. E.g https://blah/?sort=5
-    if let Some(q) = uri_query {
-        let mut parts = form_urlencoded::parse(q.as_bytes());
-        if parts.count() > 0 {
-            // NOTE: we just pick up the first value (pair)
-            if let Some(sort) = parts.next() {
-                if sort.0 == "sort" && !sort.1.trim().is_empty() {
-                    match sort.1.parse::<u8>() {
-                        Ok(code) => order_code = code,
+            if is_dir {
+                name_encoded.push('/');
+                dirs_count += 1;
+            } else {
+                files_count += 1;
+            }
+
+            let mut uri = None;
+            // NOTE: Use relative paths by default independently of
+            // the "redirect trailing slash" feature.
+            // However, when "redirect trailing slash" is disabled
+            // and a request path doesn't contain a trailing slash then
+            // entries should contain the "parent/entry-name" as a link format.
+            // Otherwise, we just use the "entry-name" as a link (default behavior).
+            // Note that in both cases, we add a trailing slash if the entry is a directory.
+            if !base_path.ends_with('/') {
+                let base_path = Path::new(base_path);
+                let parent_dir = base_path.parent().unwrap_or(base_path);
+                let mut base_dir = base_path;
+                if base_path != parent_dir {
+                    base_dir = match base_path.strip_prefix(parent_dir) {
+                        Ok(v) => v,
                         Err(err) => {
                             tracing::error!(
-                                "sorting: query value error when converting to u8: {:?}",
+                                "unable to strip parent path prefix for `{}` (skipped): {:?}",
+                                base_path.display(),
                                 err
                             );
+                            continue;
                         }
+                    };
+                }
+
+                let mut base_str = String::new();
+                if !base_dir.starts_with("/") {
+                    let base_dir = base_dir.to_str().unwrap_or_default();
+                    if !base_dir.is_empty() {
+                        base_str.push_str(base_dir);
                     }
+                    base_str.push('/');
                 }
+
+                base_str.push_str(&name_encoded);
+                uri = Some(base_str);
             }
+
+            let modified = match meta
+                .modified()
+                .map_err(anyhow::Error::from)
+                .and_then(parse_last_modified)
+            {
+                Ok(utc_dt) => Some(utc_dt),
+                Err(err) => {
+                    tracing::error!("error determining the file's last modified: {:?}", err);
+                    None
+                }
+            };
+            file_entries.push(FileEntry {
+                name,
+                name_encoded,
+                modified,
+                filesize,
+                is_dir,
+                uri,
+                symlink,
+            });
+        }
+    }
+
+    // Check the query request uri for a sorting type.
+    // E.g https://blah/?sort=size&order=desc or https://blah/?sort=5
+    if let Some(q) = uri_query {
+        if let Some(code) = sort_order_code(q) {
+            order_code = code
         }
     }
 
     let mut resp = Response::new(Body::empty());
 
+    // Reply `304 Not Modified` if the directory entries are unchanged
+    if let Some(etag) = listing_etag(opts, &file_entries, content_format, order_code) {
+        let not_modified = opts
+            .headers
+            .typed_get::<IfNoneMatch>()
+            .is_some_and(|if_none_match| !if_none_match.precondition_passes(&etag));
+        resp.headers_mut().typed_insert(etag);
+        if not_modified {
+            *resp.status_mut() = StatusCode::NOT_MODIFIED;
+            return Ok(resp);
+        }
+    }
+
     // Handle directory listing content format
     let content = match content_format {
         DirListFmt::Json => {
             // JSON
-            resp.headers_mut()
-                .typed_insert(ContentType::from(mime::APPLICATION_JSON));
+            resp.headers_mut().typed_insert(ContentType::from(
+                "application/json; charset=utf-8"
+                    .parse::<mime::Mime>()
+                    .unwrap_or(mime::APPLICATION_JSON),
+            ));
 
             json_auto_index(&mut file_entries, order_code)?
         }
@@ -332,13 +441,7 @@ async fn read_dir_entries(
             resp.headers_mut()
                 .typed_insert(ContentType::from(mime::TEXT_HTML_UTF_8));
 
-            html_auto_index(
-                base_path,
-                dirs_count,
-                files_count,
-                &mut file_entries,
-                order_code,
-            )?
+            html_auto_index(opts, dirs_count, files_count, &mut file_entries, order_code)?
         }
     };
 
@@ -355,6 +458,74 @@ async fn read_dir_entries(
     Ok(resp)
 }
 
+/// Computes a weak entity tag of a directory listing from its request path, entries
+/// (names, modification times, sizes and types), content format, sorting order
+/// and rendering options (size format, date format and timezone).
+fn listing_etag(
+    opts: &DirListOpts<'_>,
+    entries: &[FileEntry],
+    format: &DirListFmt,
+    order_code: u8,
+) -> Option<ETag> {
+    let mut hasher = DefaultHasher::new();
+    opts.current_path.hash(&mut hasher);
+    matches!(format, DirListFmt::Json).hash(&mut hasher);
+    order_code.hash(&mut hasher);
+    opts.dir_listing_size_format.hash(&mut hasher);
+    opts.dir_listing_date_format.hash(&mut hasher);
+    opts.dir_listing_timezone.hash(&mut hasher);
+    // The local offset may change over time (e.g. daylight saving time)
+    if let DirListTimezone::Local = opts.dir_listing_timezone {
+        Local::now().offset().local_minus_utc().hash(&mut hasher);
+    }
+    for entry in entries {
+        entry.name.hash(&mut hasher);
+        entry.modified.map(|dt| dt.timestamp()).hash(&mut hasher);
+        entry.filesize.hash(&mut hasher);
+        entry.is_dir.hash(&mut hasher);
+        if let Some(symlink) = &entry.symlink {
+            symlink.path.hash(&mut hasher);
+            symlink.broken.hash(&mut hasher);
+        }
+    }
+    format!("W/\"{:x}\"", hasher.finish()).parse().ok()
+}
+
+/// Determine the sorting order code from the `sort` and `order` query parameters.
+/// The `sort` value can be either `name`, `date` or `size` (or a numeric order code)
+/// and `order` either `asc` (default) or `desc`.
+/// It returns `None` if the sort value is missing or invalid.
+fn sort_order_code(uri_query: &str) -> Option<u8> {
+    let mut sort = None;
+    let mut order = None;
+    for (key, value) in form_urlencoded::parse(uri_query.as_bytes()) {
+        match key.as_ref() {
+            "sort" if sort.is_none() => sort = Some(value.trim().to_lowercase()),
+            "order" if order.is_none() => order = Some(value.trim().to_lowercase()),
+            _ => {}
+        }
+    }
+
+    let code = match sort?.as_str() {
+        "name" => 0,
+        "date" => 2,
+        "size" => 4,
+        // Numeric order codes are still supported
+        v => match v.parse::<u8>() {
+            Ok(code) => return Some(code),
+            Err(err) => {
+                tracing::debug!("sorting: invalid sort query value {:?}: {:?}", v, err);
+                return None;
+            }
+        },
+    };
+
+    match order.as_deref() {
+        Some("desc") => Some(code + 1),
+        _ => Some(code),
+    }
+}
+
 /// Create an auto index in JSON format.
 fn json_auto_index(entries: &mut [FileEntry], order_code: u8) -> Result<String> {
     sort_file_entries(entries, order_code);
@@ -364,25 +535,34 @@ fn json_auto_index(entries: &mut [FileEntry], order_code: u8) -> Result<String>
     for entry in entries {
         let file_size = &entry.filesize;
         let file_name = &entry.name;
-        let is_empty = *file_size == 0_u64;
-        let file_type = if is_empty { "directory" } else { "file" };
+        let file_type = if entry.is_dir { "directory" } else { "file" };
         let file_modified = &entry.modified;
 
         json.push('{');
         json.push_str(format!("\"name\":{},", json_quote_str(file_name.as_str())).as_str());
         json.push_str(format!("\"type\":\"{file_type}\",").as_str());
 
-        let file_modified_str = file_modified.map_or("".to_owned(), |local_dt| {
-            local_dt
-                .with_timezone(&Utc)
-                .format(DATETIME_FORMAT_UTC)
-                .to_string()
+        let file_modified_str = file_modified.map_or("".to_owned(), |utc_dt| {
+            utc_dt.format(DATETIME_FORMAT_UTC).to_string()
         });
         json.push_str(format!("\"mtime\":\"{file_modified_str}\"").as_str());
 
-        if !is_empty {
+        if !entry.is_dir {
             json.push_str(format!(",\"size\":{file_size}").as_str());
         }
+
+        if let Some(symlink) = &entry.symlink {
+            json.push_str(
+                format!(
+                    ",\"symlink\":true,\"target\":{}",
+                    json_quote_str(&symlink.path)
+                )
+                .as_str(),
+            );
+            if symlink.broken {
+                json.push_str(",\"broken\":true");
+            }
+        }
         json.push_str("},");
     }
 
@@ -416,19 +596,36 @@ fn json_quote_str(s: &str) -> String {
     r
 }
 
+/// Escapes the characters of a file name or path which have a special meaning in HTML.
+fn html_escape(s: &str) -> String {
+    let mut r = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => r.push_str("&amp;"),
+            '<' => r.push_str("&lt;"),
+            '>' => r.push_str("&gt;"),
+            '"' => r.push_str("&quot;"),
+            '\'' => r.push_str("&#39;"),
+            c => r.push(c),
+        };
+    }
+    r
+}
+
 /// Create an auto index in HTML format.
-fn html_auto_index<'a>(
-    base_path: &'a str,
+fn html_auto_index(
+    opts: &DirListOpts<'_>,
     dirs_count: usize,
     files_count: usize,
-    entries: &'a mut [FileEntry],
+    entries: &mut [FileEntry],
     order_code: u8,
 ) -> Result<String> {
+    let base_path = opts.current_path;
     let sort_attrs = sort_file_entries(entries, order_code);
 
-    // Create the table header specifying every order code column
+    // Create the table header specifying every sorting column
     let table_header = format!(
-        r#"<thead><tr><th><a href="?sort={}">Name</a></th><th style="width:160px;"><a href="?sort={}">Last modified</a></th><th style="width:120px;text-align:right;"><a href="?sort={}">Size</a></th></tr></thead>"#,
+        r#"<thead><tr><th><a href="?sort=name&amp;order={}">Name</a></th><th style="width:160px;"><a href="?sort=date&amp;order={}">Last modified</a></th><th style="width:120px;text-align:right;"><a href="?sort=size&amp;order={}">Size</a></th></tr></thead>"#,
         sort_attrs.name, sort_attrs.last_modified, sort_attrs.size,
     );
 
@@ -442,23 +639,34 @@ fn html_auto_index<'a>(
         let file_name = &entry.name_encoded;
         let file_modified = &entry.modified;
         let file_uri = &entry.uri.clone().unwrap_or_else(|| file_name.to_owned());
-        let file_name_decoded = percent_decode_str(file_name).decode_utf8()?.to_string();
-        let mut filesize_str = entry.filesize.format_size(humansize::DECIMAL);
+        let file_name_decoded = html_escape(&percent_decode_str(file_name).decode_utf8()?);
+        let filesize_str = format_file_size(entry.filesize, opts.dir_listing_size_format);
 
-        if entry.filesize == 0 {
-            filesize_str = String::from("-");
-        }
+        let file_modified_str = format_file_modified(
+            file_modified,
+            opts.dir_listing_date_format,
+            opts.dir_listing_timezone,
+        );
 
-        let file_modified_str = file_modified.map_or("-".to_owned(), |local_dt| {
-            local_dt.format(DATETIME_FORMAT_LOCAL).to_string()
-        });
+        // Symlinks are flagged with an arrow to their target, broken ones are not linked
+        let file_link = match &entry.symlink {
+            Some(symlink) if symlink.broken => format!(
+                "{file_name_decoded} &rarr; {} (broken)",
+                html_escape(&symlink.path)
+            ),
+            Some(symlink) => format!(
+                "<a href=\"{file_uri}\">{file_name_decoded}</a> &rarr; {}",
+                html_escape(&symlink.path)
+            ),
+            None => format!("<a href=\"{file_uri}\">{file_name_decoded}</a>"),
+        };
 
         table_row = format!(
-            "{table_row}<tr><td><a href=\"{file_uri}\">{file_name_decoded}</a></td><td>{file_modified_str}</td><td align=\"right\">{filesize_str}</td></tr>"
+            "{table_row}<tr><td>{file_link}</td><td>{file_modified_str}</td><td align=\"right\">{filesize_str}</td></tr>"
         );
     }
 
-    let current_path = percent_decode_str(base_path).decode_utf8()?.to_string();
+    let current_path = html_escape(&percent_decode_str(base_path).decode_utf8()?);
     let summary = format!(
         "<div>directories: {}, files: {}</div>",
         dirs_count, files_count,
@@ -471,57 +679,72 @@ fn html_auto_index<'a>(
     Ok(html_page)
 }
 
-/// Sort a list of file entries by a specific order code.
-fn sort_file_entries(files: &mut [FileEntry], order_code: u8) -> SortingAttr<'_> {
-    // Default sorting type values
-    let mut name = "0";
-    let mut last_modified = "2";
-    let mut size = "4";
+/// Format a file size for the HTML directory listing.
+/// Note that zero sizes (e.g. directories) are displayed as a dash.
+fn format_file_size(filesize: u64, size_format: &DirListSizeFmt) -> String {
+    if filesize == 0 {
+        return String::from("-");
+    }
+    match size_format {
+        DirListSizeFmt::Human => filesize.format_size(humansize::BINARY.decimal_places(1)),
+        DirListSizeFmt::Bytes => filesize.to_string(),
+    }
+}
+
+/// Format a file last modified date for the HTML directory listing.
+/// Note that unknown dates (e.g. unreadable modification times) are displayed as a dash.
+fn format_file_modified(
+    modified: &Option<DateTime<Utc>>,
+    date_format: &str,
+    timezone: &DirListTimezone,
+) -> String {
+    match (modified, timezone) {
+        (None, _) => String::from("-"),
+        (Some(utc_dt), DirListTimezone::Local) => {
+            utc_dt.with_timezone(&Local).format(date_format).to_string()
+        }
+        (Some(utc_dt), DirListTimezone::Utc) => utc_dt.format(date_format).to_string(),
+    }
+}
 
+/// Sort a list of file entries by a specific order code.
+/// It returns the toggled order (`asc` or `desc`) of every sorting column.
+fn sort_file_entries(files: &mut [FileEntry], order_code: u8) -> SortingAttr<'static> {
     files.sort_by(|a, b| match order_code {
         // Name (asc, desc)
-        0 => {
-            name = "1";
-            a.name.to_lowercase().cmp(&b.name.to_lowercase())
-        }
-        1 => {
-            name = "0";
-            b.name.to_lowercase().cmp(&a.name.to_lowercase())
-        }
+        0 => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        1 => b.name.to_lowercase().cmp(&a.name.to_lowercase()),
 
         // Modified (asc, desc)
-        2 => {
-            last_modified = "3";
-            a.modified.cmp(&b.modified)
-        }
-        3 => {
-            last_modified = "2";
-            b.modified.cmp(&a.modified)
-        }
+        2 => a.modified.cmp(&b.modified),
+        3 => b.modified.cmp(&a.modified),
 
         // File size (asc, desc)
-        4 => {
-            size = "5";
-            a.filesize.cmp(&b.filesize)
-        }
-        5 => {
-            size = "4";
-            b.filesize.cmp(&a.filesize)
-        }
+        4 => a.filesize.cmp(&b.filesize),
+        5 => b.filesize.cmp(&a.filesize),
 
         // Unordered
         _ => Ordering::Equal,
     });
 
+    // Toggle the order of the current sorting column only
+    let toggle = |asc_code: u8| {
+        if order_code == asc_code {
+            "desc"
+        } else {
+            "asc"
+        }
+    };
+
     SortingAttr {
-        name,
-        last_modified,
-        size,
+        name: toggle(0),
+        last_modified: toggle(2),
+        size: toggle(4),
     }
 }
 
-/// Return the last modified `DateTime` in local timescale.
-fn parse_last_modified(modified: SystemTime) -> Result<DateTime<Local>> {
+/// Return the last modified `DateTime` in UTC timescale.
+fn parse_last_modified(modified: SystemTime) -> Result<DateTime<Utc>> {
     let since_epoch = modified.duration_since(UNIX_EPOCH)?;
     // HTTP times don't have nanosecond precision, so we truncate
     // the modification time.
@@ -537,9 +760,138 @@ fn parse_last_modified(modified: SystemTime) -> Result<DateTime<Local>> {
         NaiveDateTime::from_timestamp_opt(since_epoch.as_secs() as i64, since_epoch.subsec_nanos());
 
     match utc_dt {
-        Some(utc_dt) => Ok(DateTime::<Utc>::from_utc(utc_dt, Utc).with_timezone(&Local)),
+        Some(utc_dt) => Ok(DateTime::<Utc>::from_utc(utc_dt, Utc)),
         None => Err(anyhow!(
             "out-of-range number of seconds and/or invalid nanosecond"
         )),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use hyper::{HeaderMap, Method};
+    use std::path::Path;
+
+    use super::{
+        format_file_modified, format_file_size, listing_etag, sort_order_code,
+        validate_date_format, DirListFmt, DirListOpts, DirListSizeFmt, DirListTimezone, FileEntry,
+    };
+
+    #[test]
+    fn sort_query_params() {
+        assert_eq!(sort_order_code("sort=name"), Some(0));
+        assert_eq!(sort_order_code("sort=name&order=desc"), Some(1));
+        assert_eq!(sort_order_code("order=asc&sort=date"), Some(2));
+        assert_eq!(sort_order_code("sort=size&order=desc"), Some(5));
+        assert_eq!(sort_order_code("sort=3"), Some(3));
+        assert_eq!(sort_order_code("sort=unknown&order=desc"), None);
+        assert_eq!(sort_order_code("order=desc"), None);
+        assert_eq!(sort_order_code(""), None);
+    }
+
+    #[test]
+    fn file_size_formats() {
+        assert_eq!(format_file_size(0, &DirListSizeFmt::Human), "-");
+        assert_eq!(format_file_size(0, &DirListSizeFmt::Bytes), "-");
+        assert_eq!(format_file_size(512, &DirListSizeFmt::Human), "512 B");
+        assert_eq!(format_file_size(1536, &DirListSizeFmt::Human), "1.5 KiB");
+        assert_eq!(
+            format_file_size(5_767_168, &DirListSizeFmt::Human),
+            "5.5 MiB"
+        );
+        assert_eq!(format_file_size(1536, &DirListSizeFmt::Bytes), "1536");
+    }
+
+    #[test]
+    fn file_modified_formats() {
+        let modified = Some(Utc.with_ymd_and_hms(2023, 8, 29, 14, 5, 9).unwrap());
+        assert_eq!(
+            format_file_modified(&modified, "%F %T", &DirListTimezone::Utc),
+            "2023-08-29 14:05:09"
+        );
+        assert_eq!(
+            format_file_modified(&modified, "%Y-%m-%dT%H:%M:%SZ", &DirListTimezone::Utc),
+            "2023-08-29T14:05:09Z"
+        );
+        assert_eq!(
+            format_file_modified(&modified, "%d %b %Y", &DirListTimezone::Local).len(),
+            11
+        );
+        assert_eq!(
+            format_file_modified(&None, "%F %T", &DirListTimezone::Utc),
+            "-"
+        );
+
+        assert!(validate_date_format("%F %T").is_ok());
+        assert!(validate_date_format("%Y/%m/%d %H:%M").is_ok());
+        assert!(validate_date_format("%Q").is_err());
+    }
+
+    #[test]
+    fn listing_etag_options() {
+        let headers = HeaderMap::new();
+        let opts = DirListOpts {
+            method: &Method::GET,
+            headers: &headers,
+            current_path: "/assets/",
+            uri_query: None,
+            filepath: Path::new("."),
+            dir_listing_order: 6,
+            dir_listing_format: &DirListFmt::Html,
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            dir_listing_date_format: "%F %T",
+            dir_listing_timezone: &DirListTimezone::Utc,
+            dir_listing_ignore: &[],
+            dir_listing_show_symlinks: false,
+            merge_filepath: None,
+            ignore_hidden_files: false,
+        };
+        let entries = vec![FileEntry {
+            name: String::from("main.js"),
+            name_encoded: String::from("main.js"),
+            modified: Some(Utc.with_ymd_and_hms(2023, 8, 29, 14, 5, 9).unwrap()),
+            filesize: 1536,
+            is_dir: false,
+            uri: None,
+            symlink: None,
+        }];
+        let etag = |opts: &DirListOpts<'_>| listing_etag(opts, &entries, &DirListFmt::Html, 6);
+
+        assert_eq!(etag(&opts), etag(&opts));
+        // Same entries rendered for another path or with other options
+        assert_ne!(
+            etag(&opts),
+            etag(&DirListOpts {
+                current_path: "/assets",
+                merge_filepath: None,
+                ..opts
+            })
+        );
+        assert_ne!(
+            etag(&opts),
+            etag(&DirListOpts {
+                dir_listing_size_format: &DirListSizeFmt::Bytes,
+                merge_filepath: None,
+                ..opts
+            })
+        );
+        assert_ne!(
+            etag(&opts),
+            etag(&DirListOpts {
+                dir_listing_date_format: "%F",
+                merge_filepath: None,
+                ..opts
+            })
+        );
+        assert_ne!(
+            etag(&opts),
+            etag(&DirListOpts {
+                dir_listing_timezone: &DirListTimezone::Local,
+                merge_filepath: None,
+                ..opts
+            })
+        );
+    }
+}
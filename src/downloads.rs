@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to force file downloads via the `Content-Disposition` header
+//! for request paths matching the config file download rules.
+//!
+
+use hyper::{
+    header::{CONTENT_DISPOSITION, CONTENT_ENCODING},
+    Body, Response,
+};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::borrow::Cow;
+
+use crate::{settings::Downloads, static_files::ResolvedFile};
+
+/// File extensions of the pre-compressed file variants.
+const PRECOMPRESSED_EXTS: [&str; 3] = [".gz", ".br", ".zst"];
+
+/// Characters allowed unencoded in an RFC 5987 `ext-value` (`attr-char`).
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+/// Append a `Content-Disposition: attachment` header to the current response
+/// if the request uri matches one of the download rules.
+/// The file name is the one of the file served, e.g. `intro.html` for an `/intro` request.
+pub fn append_headers(
+    uri: &str,
+    downloads_opts_vec: &Option<Vec<Downloads>>,
+    resp: &mut Response<Body>,
+) {
+    if !resp.status().is_success() {
+        return;
+    }
+    if let Some(downloads_vec) = downloads_opts_vec {
+        if !downloads_vec
+            .iter()
+            .any(|d| d.source.is_match(uri) && !d.exclude.iter().any(|m| m.is_match(uri)))
+        {
+            return;
+        }
+        let filename = match served_filename(resp) {
+            Some(name) => name,
+            None => match uri.rsplit('/').next() {
+                Some(name) if !name.is_empty() => percent_decode_str(name).decode_utf8_lossy(),
+                // Directory requests are not downloadable
+                _ => return,
+            },
+        };
+        match content_disposition(&filename).parse() {
+            Ok(val) => {
+                resp.headers_mut().insert(CONTENT_DISPOSITION, val);
+            }
+            Err(err) => {
+                tracing::error!("invalid content-disposition header value: {:?}", err);
+            }
+        }
+    }
+}
+
+/// Returns the name of the file served by the response if any.
+/// Pre-compressed variants are named after their original file.
+fn served_filename(resp: &Response<Body>) -> Option<Cow<'_, str>> {
+    let name = resp
+        .extensions()
+        .get::<ResolvedFile>()?
+        .path
+        .file_name()?
+        .to_string_lossy();
+    if !resp.headers().contains_key(CONTENT_ENCODING) {
+        return Some(name);
+    }
+    let original = PRECOMPRESSED_EXTS
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext))
+        .filter(|original| !original.is_empty())
+        .map(|original| Cow::Owned(original.to_owned()));
+    Some(original.unwrap_or(name))
+}
+
+/// Build an `attachment` content disposition value for the given file name
+/// including an ASCII fallback `filename` and a RFC 5987 encoded `filename*` parameter.
+pub fn content_disposition(filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' => '_',
+            c if c.is_ascii() && !c.is_ascii_control() => c,
+            _ => '_',
+        })
+        .collect();
+
+    if fallback == filename {
+        return format!("attachment; filename=\"{fallback}\"");
+    }
+
+    let encoded = utf8_percent_encode(filename, ATTR_CHAR);
+    format!("attachment; filename=\"{fallback}\"; filename*=UTF-8''{encoded}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{append_headers, content_disposition};
+    use crate::{settings::Downloads, static_files::ResolvedFile};
+    use hyper::{Body, Response};
+    use std::path::PathBuf;
+
+    #[test]
+    fn served_file_name() {
+        let downloads = Some(vec![Downloads {
+            source: globset::Glob::new("/docs/**").unwrap().compile_matcher(),
+            exclude: vec![],
+        }]);
+        let disposition = |path: &str, encoding: Option<&str>| {
+            let mut resp = Response::new(Body::empty());
+            resp.extensions_mut().insert(ResolvedFile {
+                path: PathBuf::from(path),
+                size: 0,
+            });
+            if let Some(encoding) = encoding {
+                resp.headers_mut()
+                    .insert("content-encoding", encoding.parse().unwrap());
+            }
+            append_headers("/docs/intro", &downloads, &mut resp);
+            resp.headers()["content-disposition"].to_owned()
+        };
+        assert_eq!(
+            disposition("public/docs/intro.html", None),
+            "attachment; filename=\"intro.html\""
+        );
+        assert_eq!(
+            disposition("public/docs/intro.html.gz", Some("gzip")),
+            "attachment; filename=\"intro.html\""
+        );
+    }
+
+    #[test]
+    fn ascii_filename() {
+        assert_eq!(
+            content_disposition("report-2023.pdf"),
+            "attachment; filename=\"report-2023.pdf\""
+        );
+    }
+
+    #[test]
+    fn non_ascii_filename() {
+        assert_eq!(
+            content_disposition("résumé final.pdf"),
+            "attachment; filename=\"r_sum_ final.pdf\"; filename*=UTF-8''r%C3%A9sum%C3%A9%20final.pdf"
+        );
+    }
+
+    #[test]
+    fn quoted_filename() {
+        assert_eq!(
+            content_disposition("a\"b.txt"),
+            "attachment; filename=\"a_b.txt\"; filename*=UTF-8''a%22b.txt"
+        );
+    }
+}
@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to append preload `Link` headers (early hints) via TOML config file.
+//!
+//! The links can also be derived from a bundler asset manifest (e.g. Vite's `manifest.json`)
+//! mapping entrypoints to their dependency chunks.
+//!
+//! Note that the underlying HTTP server does not support sending interim (1xx) responses,
+//! so the `Link` headers are sent along with the final response instead of a `103 Early Hints` one.
+//! They can still be used by browsers and reverse proxies or CDNs able to emit early hints on their behalf.
+//!
+
+use globset::GlobMatcher;
+use headers::{ContentType, HeaderMapExt};
+use hyper::{
+    header::{HeaderValue, LINK},
+    Body, Response,
+};
+use mime_guess::Mime;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use crate::{helpers, settings::EarlyHints, Context, Result};
+
+/// A chunk entry of a bundler asset manifest.
+#[derive(Debug, Deserialize)]
+struct ManifestChunk {
+    /// Output file path of the chunk.
+    file: String,
+    /// CSS files imported by the chunk.
+    #[serde(default)]
+    css: Vec<String>,
+    /// Manifest keys of the chunks statically imported by the chunk.
+    #[serde(default)]
+    imports: Vec<String>,
+}
+
+/// Preload `Link` headers derived from a bundler asset manifest.
+/// The manifest links can be reloaded at runtime.
+#[derive(Clone)]
+pub struct PreloadManifest {
+    /// Path of the JSON manifest file.
+    path: PathBuf,
+    /// URL path prefix of the manifest files always ending with a slash.
+    base: String,
+    /// Route glob matchers and their manifest entrypoints.
+    routes: Arc<[(GlobMatcher, String)]>,
+    /// Resolved links of every route.
+    links: Arc<RwLock<Vec<Vec<HeaderValue>>>>,
+}
+
+impl PreloadManifest {
+    /// Creates a new preload manifest reading the manifest file.
+    pub fn new(path: PathBuf, base: &str, routes: Vec<(GlobMatcher, String)>) -> Result<Self> {
+        let base = match base.trim_matches('/') {
+            "" => "/".to_owned(),
+            base => ["/", base, "/"].concat(),
+        };
+        let links = resolve_links(&path, &base, &routes)?;
+        Ok(Self {
+            path,
+            base,
+            routes: routes.into(),
+            links: Arc::new(RwLock::new(links)),
+        })
+    }
+
+    /// Reads the manifest file again.
+    /// The current links are kept if the manifest file is invalid.
+    pub fn reload(&self) {
+        match resolve_links(&self.path, &self.base, &self.routes) {
+            Ok(links) => {
+                if let Ok(mut current) = self.links.write() {
+                    *current = links;
+                }
+                tracing::info!("preload manifest reloaded: {}", self.path.display());
+            }
+            Err(err) => {
+                tracing::error!(
+                    "unable to reload the preload manifest, links unchanged: {:?}",
+                    err
+                );
+            }
+        }
+    }
+
+    /// Returns the links of the routes matching the given request uri.
+    fn links_for(&self, uri: &str) -> Vec<HeaderValue> {
+        let links = match self.links.read() {
+            Ok(links) => links,
+            Err(_) => return vec![],
+        };
+        let mut matched: Vec<HeaderValue> = Vec::new();
+        for ((source, _), links) in self.routes.iter().zip(links.iter()) {
+            if source.is_match(uri) {
+                for link in links {
+                    if !matched.contains(link) {
+                        matched.push(link.to_owned());
+                    }
+                }
+            }
+        }
+        matched
+    }
+}
+
+/// Reads the manifest file resolving the links of every route entrypoint.
+fn resolve_links(
+    path: &Path,
+    base: &str,
+    routes: &[(GlobMatcher, String)],
+) -> Result<Vec<Vec<HeaderValue>>> {
+    let content = helpers::read_file(path)
+        .with_context(|| format!("error reading preload manifest {}", path.display()))?;
+    let manifest: HashMap<String, ManifestChunk> = serde_json::from_str(&content)
+        .with_context(|| format!("error parsing preload manifest {}", path.display()))?;
+
+    routes
+        .iter()
+        .map(|(_, entrypoint)| entrypoint_links(&manifest, base, entrypoint))
+        .collect()
+}
+
+/// Returns the preload links of a manifest entrypoint and its imported chunks.
+fn entrypoint_links(
+    manifest: &HashMap<String, ManifestChunk>,
+    base: &str,
+    entrypoint: &str,
+) -> Result<Vec<HeaderValue>> {
+    if !manifest.contains_key(entrypoint) {
+        bail!("preload manifest entrypoint \"{}\" not found", entrypoint);
+    }
+
+    let mut files = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![entrypoint];
+    while let Some(key) = pending.pop() {
+        if !visited.insert(key) {
+            continue;
+        }
+        let chunk = match manifest.get(key) {
+            Some(chunk) => chunk,
+            None => bail!("preload manifest import \"{}\" not found", key),
+        };
+        files.push(chunk.file.as_str());
+        files.extend(chunk.css.iter().map(|s| s.as_str()));
+        // Keep the declaration order of the imports
+        pending.extend(chunk.imports.iter().rev().map(|s| s.as_str()));
+    }
+
+    let mut links = Vec::new();
+    let mut seen = HashSet::new();
+    for file in files {
+        if !seen.insert(file) {
+            continue;
+        }
+        let rel = match file.rsplit_once('.').map(|(_, ext)| ext) {
+            Some("js" | "mjs") => "rel=modulepreload",
+            Some("css") => "rel=preload; as=style",
+            _ => continue,
+        };
+        let link = format!("<{}{}>; {}", base, file.trim_start_matches('/'), rel);
+        let link = HeaderValue::from_str(&link)
+            .with_context(|| format!("invalid preload manifest link header value: {}", link))?;
+        links.push(link);
+    }
+    Ok(links)
+}
+
+/// Append preload `Link` headers to the current response if the request uri matches.
+pub fn append_headers(
+    uri: &str,
+    early_hints_opts_vec: &Option<Vec<EarlyHints>>,
+    resp: &mut Response<Body>,
+) {
+    if !resp.status().is_success() {
+        return;
+    }
+    if let Some(early_hints_vec) = early_hints_opts_vec {
+        for early_hints_entry in early_hints_vec.iter() {
+            if early_hints_entry.source.is_match(uri)
+                && !early_hints_entry.exclude.iter().any(|m| m.is_match(uri))
+            {
+                for link in &early_hints_entry.links {
+                    resp.headers_mut().append(LINK, link.to_owned());
+                }
+            }
+        }
+    }
+}
+
+/// Append the preload `Link` headers of a manifest to the current HTML response
+/// if the request uri matches one of its routes.
+pub fn append_manifest_headers(
+    uri: &str,
+    preload_manifest: &Option<PreloadManifest>,
+    resp: &mut Response<Body>,
+) {
+    if !resp.status().is_success() {
+        return;
+    }
+    let manifest = match preload_manifest {
+        Some(manifest) => manifest,
+        None => return,
+    };
+    let is_html = resp
+        .headers()
+        .typed_get::<ContentType>()
+        .is_some_and(|ct| Mime::from(ct).essence_str() == "text/html");
+    if !is_html {
+        return;
+    }
+    for link in manifest.links_for(uri) {
+        resp.headers_mut().append(LINK, link);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use globset::Glob;
+    use hyper::{header::LINK, Body, Response};
+
+    use super::{append_manifest_headers, PreloadManifest};
+
+    const MANIFEST: &str = r#"{
+        "index.html": {
+            "file": "assets/index-4f1a.js",
+            "isEntry": true,
+            "css": ["assets/index-9c2b.css"],
+            "imports": ["_vendor-77ad.js", "_shared-11ef.js"]
+        },
+        "admin.html": {
+            "file": "assets/admin-5e3c.js",
+            "isEntry": true,
+            "imports": ["_vendor-77ad.js"]
+        },
+        "_vendor-77ad.js": {
+            "file": "assets/vendor-77ad.js",
+            "imports": ["_shared-11ef.js"]
+        },
+        "_shared-11ef.js": {
+            "file": "assets/shared-11ef.js",
+            "css": ["assets/shared-0d8e.css"]
+        },
+        "logo.svg": { "file": "assets/logo-3b1f.svg" }
+    }"#;
+
+    fn manifest(dir: &str, entrypoint: &str) -> super::Result<PreloadManifest> {
+        let dir = std::env::temp_dir().join(dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("manifest.json");
+        std::fs::write(&path, MANIFEST).unwrap();
+        let routes = vec![
+            (
+                Glob::new("/admin/**").unwrap().compile_matcher(),
+                "admin.html".to_owned(),
+            ),
+            (
+                Glob::new("/**").unwrap().compile_matcher(),
+                entrypoint.to_owned(),
+            ),
+        ];
+        PreloadManifest::new(path, "static", routes)
+    }
+
+    fn links(manifest: &PreloadManifest, uri: &str, content_type: &str) -> Vec<String> {
+        let mut resp = Response::new(Body::empty());
+        resp.headers_mut()
+            .insert("content-type", content_type.parse().unwrap());
+        append_manifest_headers(uri, &Some(manifest.clone()), &mut resp);
+        resp.headers()
+            .get_all(LINK)
+            .iter()
+            .map(|v| v.to_str().unwrap().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn manifest_entrypoint_links() {
+        let manifest = manifest("sws-preload-manifest-test", "index.html").unwrap();
+
+        assert_eq!(
+            links(&manifest, "/index.html", "text/html; charset=utf-8"),
+            [
+                "</static/assets/index-4f1a.js>; rel=modulepreload",
+                "</static/assets/index-9c2b.css>; rel=preload; as=style",
+                "</static/assets/vendor-77ad.js>; rel=modulepreload",
+                "</static/assets/shared-11ef.js>; rel=modulepreload",
+                "</static/assets/shared-0d8e.css>; rel=preload; as=style",
+            ]
+        );
+        // Links of overlapping routes are not repeated
+        let admin_links = links(&manifest, "/admin/", "text/html");
+        assert_eq!(
+            admin_links[..2],
+            [
+                "</static/assets/admin-5e3c.js>; rel=modulepreload",
+                "</static/assets/vendor-77ad.js>; rel=modulepreload",
+            ]
+        );
+        assert_eq!(admin_links.len(), 6);
+
+        // Only HTML responses get the links
+        assert!(links(&manifest, "/main.css", "text/css").is_empty());
+    }
+
+    #[test]
+    fn manifest_entrypoint_not_found() {
+        let err = manifest("sws-preload-manifest-invalid-test", "main.ts")
+            .err()
+            .unwrap();
+        assert!(format!("{err:?}").contains("entrypoint \"main.ts\" not found"));
+    }
+}
@@ -5,17 +5,29 @@
 
 //! Error page module to compose an HTML page response.
 //!
+//! Clients preferring JSON over HTML via the `Accept` header
+//! get a small JSON error body instead of the HTML page.
+//!
 
 use headers::{AcceptRanges, ContentLength, ContentType, HeaderMapExt};
-use hyper::{Body, Method, Response, StatusCode, Uri};
+use hyper::{
+    header::{HeaderValue, ACCEPT, VARY},
+    Body, HeaderMap, Method, Response, StatusCode, Uri,
+};
 use mime_guess::mime;
+use serde::Serialize;
 
-use crate::{exts::http::MethodExt, Result};
+use crate::{exts::http::MethodExt, helpers, request_id::REQUEST_ID_HEADER, Result};
 
 /// It returns a HTTP error response which also handles available `404` or `50x` HTML content.
+/// A JSON error body is returned instead if the request `Accept` header prefers `application/json`.
+///
+/// The HTML content can contain `{{status}}`, `{{status_text}}` and `{{request_id}}` placeholders
+/// which are replaced with the response status code, its reason phrase and the request ID (if any).
 pub fn error_response(
     uri: &Uri,
     method: &Method,
+    headers: &HeaderMap,
     status_code: &StatusCode,
     page404: &[u8],
     page50x: &[u8],
@@ -73,7 +85,13 @@ pub fn error_response(
         _ => status_code,
     };
 
-    if error_page_content.is_empty() {
+    let json = prefers_json(headers);
+    if json {
+        error_page_content = serde_json::to_string(&JsonError {
+            error: status_code.canonical_reason().unwrap_or_default(),
+            status: status_code.as_u16(),
+        })?;
+    } else if error_page_content.is_empty() {
         error_page_content = [
             "<html><head><title>",
             status_code.as_str(),
@@ -86,6 +104,8 @@ pub fn error_response(
             "</h1></center></body></html>",
         ]
         .concat();
+    } else if error_page_content.contains("{{") {
+        error_page_content = render_placeholders(&error_page_content, status_code, headers);
     }
 
     let mut body = Body::empty();
@@ -97,10 +117,153 @@ pub fn error_response(
 
     let mut resp = Response::new(body);
     *resp.status_mut() = *status_code;
-    resp.headers_mut()
-        .typed_insert(ContentType::from(mime::TEXT_HTML_UTF_8));
+    if json {
+        resp.headers_mut().typed_insert(ContentType::json());
+    } else {
+        resp.headers_mut()
+            .typed_insert(ContentType::from(mime::TEXT_HTML_UTF_8));
+    }
     resp.headers_mut().typed_insert(ContentLength(len));
     resp.headers_mut().typed_insert(AcceptRanges::bytes());
+    resp.headers_mut()
+        .append(VARY, HeaderValue::from_static("accept"));
 
     Ok(resp)
 }
+
+#[derive(Serialize)]
+struct JsonError<'a> {
+    error: &'a str,
+    status: u16,
+}
+
+/// Checks if the `Accept` header of a request lists `application/json` with a higher
+/// quality than `text/html`. Wildcards like `*/*` are not considered, so clients
+/// not asking for JSON explicitly (E.g. browsers) still get the HTML content.
+fn prefers_json(headers: &HeaderMap) -> bool {
+    let mut json = 0;
+    let mut html = 0;
+    for (name, quality) in helpers::quality_values(headers, ACCEPT) {
+        if name.eq_ignore_ascii_case("application/json") {
+            json = json.max(quality);
+        } else if name.eq_ignore_ascii_case("text/html") {
+            html = html.max(quality);
+        }
+    }
+    json > html
+}
+
+/// Replaces the placeholders of an error page content.
+fn render_placeholders(content: &str, status_code: &StatusCode, headers: &HeaderMap) -> String {
+    // Only a safe subset of characters is kept since the value comes from the client
+    let request_id = headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+        .take(128)
+        .collect::<String>();
+
+    content
+        .replace("{{status}}", status_code.as_str())
+        .replace(
+            "{{status_text}}",
+            status_code.canonical_reason().unwrap_or_default(),
+        )
+        .replace("{{request_id}}", &request_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{error_response, prefers_json};
+    use hyper::{HeaderMap, Method, StatusCode, Uri};
+
+    #[tokio::test]
+    async fn error_page_placeholders() {
+        let page50x = b"<h1>{{status}} {{status_text}}</h1><p>{{request_id}}</p>";
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", "abc-123<script>".parse().unwrap());
+
+        let resp = error_response(
+            &Uri::from_static("/"),
+            &Method::GET,
+            &headers,
+            &StatusCode::BAD_GATEWAY,
+            &[],
+            page50x,
+        )
+        .unwrap();
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        assert_eq!(body, "<h1>502 Bad Gateway</h1><p>abc-123script</p>");
+
+        // Contents without placeholders are sent unchanged
+        let page404 = b"<h1>Not Found {{</h1>";
+        let resp = error_response(
+            &Uri::from_static("/"),
+            &Method::GET,
+            &HeaderMap::new(),
+            &StatusCode::NOT_FOUND,
+            page404,
+            &[],
+        )
+        .unwrap();
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        assert_eq!(body, &page404[..]);
+    }
+
+    #[tokio::test]
+    async fn error_page_json() {
+        let mut headers = HeaderMap::new();
+        headers.insert("accept", "application/json".parse().unwrap());
+
+        let resp = error_response(
+            &Uri::from_static("/api/missing"),
+            &Method::GET,
+            &headers,
+            &StatusCode::NOT_FOUND,
+            b"<h1>Not Found</h1>",
+            &[],
+        )
+        .unwrap();
+        assert_eq!(resp.status(), 404);
+        assert_eq!(resp.headers()["content-type"], "application/json");
+        assert_eq!(resp.headers()["content-length"], "34");
+        assert_eq!(resp.headers()["vary"], "accept");
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        assert_eq!(body, r#"{"error":"Not Found","status":404}"#);
+
+        let resp = error_response(
+            &Uri::from_static("/"),
+            &Method::HEAD,
+            &headers,
+            &StatusCode::SERVICE_UNAVAILABLE,
+            &[],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(resp.headers()["content-type"], "application/json");
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn error_page_accept_negotiation() {
+        let prefers = |accept: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("accept", accept.parse().unwrap());
+            prefers_json(&headers)
+        };
+        assert!(prefers("application/json"));
+        assert!(prefers("application/json, text/plain, */*"));
+        assert!(prefers("text/html;q=0.5, Application/JSON"));
+
+        assert!(!prefers(
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"
+        ));
+        assert!(!prefers("*/*"));
+        assert!(!prefers("text/html, application/json"));
+        assert!(!prefers("application/json;q=0"));
+        assert!(!prefers_json(&HeaderMap::new()));
+    }
+}
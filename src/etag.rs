@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to compute the entity tags (`ETag`) of the files.
+//!
+//! Entity tags are either derived from the file metadata (modification time and size)
+//! or from a 64-bit [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) hash of the file content,
+//! which is stable across builds and platforms. Content hashes are cached by file path,
+//! modification time and size so files are only hashed again once they change.
+//!
+
+use clap::ValueEnum;
+use headers::ETag;
+use std::collections::HashMap;
+use std::fs::{File, Metadata};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Maximum number of file content hashes kept in memory.
+const CACHE_MAX_ENTRIES: usize = 4_096;
+
+/// FNV-1a 64-bit offset basis.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+/// FNV-1a 64-bit prime.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+/// Entity tags generation mode of the files.
+pub enum EtagMode {
+    /// Strong entity tags derived from the file modification time and size (default).
+    #[default]
+    Metadata,
+    /// Weak entity tags (`W/` prefixed) derived from the file modification time and size.
+    Weak,
+    /// Strong entity tags derived from a hash of the file content.
+    Strong,
+}
+
+#[derive(Debug)]
+struct CachedHash {
+    modified: SystemTime,
+    len: u64,
+    hash: u64,
+}
+
+/// Cache of previously computed file content hashes.
+fn cache() -> &'static Mutex<HashMap<PathBuf, CachedHash>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, CachedHash>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Computes the entity tag of a file according to the given mode.
+///
+/// The `meta` param value should correspond to the file of the `path` param.
+/// Content hashes are computed on cache misses only, which costs reading the whole file
+/// on a blocking thread.
+pub async fn file_etag(path: &Path, meta: &Metadata, mode: EtagMode) -> Option<ETag> {
+    let modified = meta.modified().ok()?;
+    let tag = match mode {
+        EtagMode::Metadata | EtagMode::Weak => {
+            let secs = modified.duration_since(UNIX_EPOCH).ok()?.as_secs();
+            format!("\"{:x}-{:x}\"", secs, meta.len())
+        }
+        EtagMode::Strong => {
+            let hash = content_hash(path, modified, meta.len()).await?;
+            format!("\"{:016x}-{:x}\"", hash, meta.len())
+        }
+    };
+    let tag = match mode {
+        EtagMode::Weak => ["W/", &tag].concat(),
+        _ => tag,
+    };
+    tag.parse().ok()
+}
+
+/// Returns the content hash of a file from the cache if its modification time and size
+/// didn't change, otherwise it hashes the file content and caches it.
+async fn content_hash(path: &Path, modified: SystemTime, len: u64) -> Option<u64> {
+    if let Ok(cache) = cache().lock() {
+        if let Some(cached) = cache.get(path) {
+            if cached.modified == modified && cached.len == len {
+                tracing::trace!("file content hash found in cache: {:?}", path);
+                return Some(cached.hash);
+            }
+        }
+    }
+
+    let file_path = path.to_path_buf();
+    let hashed = tokio::task::spawn_blocking(move || hash_file(&file_path))
+        .await
+        .unwrap_or_else(|err| Err(err.into()));
+    let hash = match hashed {
+        Ok(hash) => hash,
+        Err(err) => {
+            tracing::error!(
+                "file content hash error (path={:?}): {}",
+                path.display(),
+                err
+            );
+            return None;
+        }
+    };
+    tracing::trace!("file content hash computed: {:?}", path);
+
+    if let Ok(mut cache) = cache().lock() {
+        if cache.len() >= CACHE_MAX_ENTRIES {
+            cache.clear();
+        }
+        cache.insert(
+            path.to_path_buf(),
+            CachedHash {
+                modified,
+                len,
+                hash,
+            },
+        );
+    }
+
+    Some(hash)
+}
+
+/// Hashes the file content using the 64-bit FNV-1a algorithm.
+fn hash_file(path: &Path) -> io::Result<u64> {
+    let mut file = File::open(path)?;
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        match file.read(&mut buf)? {
+            0 => return Ok(hash),
+            n => hash = fnv1a(hash, &buf[..n]),
+        }
+    }
+}
+
+/// Continues a 64-bit FNV-1a hash with the given bytes.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{file_etag, fnv1a, EtagMode, FNV_OFFSET_BASIS};
+
+    #[test]
+    fn fnv1a_test_vectors() {
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"foobar"), 0x8594_4171_f739_67e8);
+        // Hashing in chunks gives the same result
+        assert_eq!(
+            fnv1a(fnv1a(FNV_OFFSET_BASIS, b"foo"), b"bar"),
+            fnv1a(FNV_OFFSET_BASIS, b"foobar")
+        );
+    }
+
+    #[tokio::test]
+    async fn file_etag_modes() {
+        let dir = std::env::temp_dir().join("sws-etag-unit-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let (a, b) = (dir.join("a.txt"), dir.join("b.txt"));
+        std::fs::write(&a, "same content").unwrap();
+        std::fs::write(&b, "same content").unwrap();
+        async fn etag(path: &Path, mode: EtagMode) -> String {
+            let meta = std::fs::metadata(path).unwrap();
+            format!("{:?}", file_etag(path, &meta, mode).await.unwrap())
+        }
+
+        assert!(!etag(&a, EtagMode::Metadata).await.contains("W/"));
+        assert!(etag(&a, EtagMode::Weak).await.contains("W/"));
+        assert!(!etag(&a, EtagMode::Strong).await.contains("W/"));
+
+        // Identical contents get the same strong entity tag
+        assert_eq!(
+            etag(&a, EtagMode::Strong).await,
+            etag(&b, EtagMode::Strong).await
+        );
+
+        // Modified contents are hashed again
+        std::fs::write(&b, "other content").unwrap();
+        assert_ne!(
+            etag(&a, EtagMode::Strong).await,
+            etag(&b, EtagMode::Strong).await
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -6,19 +6,21 @@
 //! Fallback page module useful for a custom page default.
 //!
 
-use headers::{AcceptRanges, ContentLength, ContentType, HeaderMapExt};
-use hyper::{Body, Response, StatusCode};
+use headers::{AcceptRanges, ContentLength, ContentType, HeaderMap, HeaderMapExt};
+use hyper::{header::ACCEPT, Body, Response, StatusCode};
 use mime_guess::mime;
 
+use crate::helpers;
+
 /// Checks if a fallback response can be generated, i.e. if it is a `GET` request
 /// that would result in a `404` error and a fallback page is configured.
 /// If a response can be generated then is returned otherwise `None`.
-pub fn fallback_response(page_fallback: &[u8]) -> Response<Body> {
+pub fn fallback_response(page_fallback: &[u8], status: StatusCode) -> Response<Body> {
     let body = Body::from(page_fallback.to_owned());
     let len = page_fallback.len() as u64;
 
     let mut resp = Response::new(body);
-    *resp.status_mut() = StatusCode::OK;
+    *resp.status_mut() = status;
 
     resp.headers_mut().typed_insert(ContentLength(len));
     resp.headers_mut()
@@ -27,3 +29,80 @@ pub fn fallback_response(page_fallback: &[u8]) -> Response<Body> {
 
     resp
 }
+
+/// Checks if the incoming request is a navigation request, that is,
+/// a request whose `Accept` header includes the `text/html` media type with a non-zero quality.
+/// Requests for missing assets (E.g `/app.js`) are not considered navigation requests.
+pub fn is_navigation_request(headers: &HeaderMap) -> bool {
+    helpers::quality_values(headers, ACCEPT)
+        .into_iter()
+        .any(|(value, quality)| quality > 0 && value.eq_ignore_ascii_case("text/html"))
+}
+
+/// Returns the index file paths of the parent directories of the given request path,
+/// from the nearest directory up to the root one.
+///
+/// For example, `/t/42/settings` results in `/t/42/index.html`, `/t/index.html` and `/index.html`
+/// for the `index.html` index file.
+pub fn nested_index_paths(uri_path: &str, index_files: &[String]) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut dir = uri_path.trim_end_matches('/');
+    while let Some(pos) = dir.rfind('/') {
+        dir = &dir[..pos];
+        for index in index_files {
+            paths.push([dir, "/", index].concat());
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_navigation_request, nested_index_paths};
+    use headers::HeaderMap;
+
+    #[test]
+    fn navigation_request() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "accept",
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"
+                .parse()
+                .unwrap(),
+        );
+        assert!(is_navigation_request(&headers));
+
+        headers.insert("accept", "Text/HTML;q=0.5".parse().unwrap());
+        assert!(is_navigation_request(&headers));
+    }
+
+    #[test]
+    fn non_navigation_request() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_navigation_request(&headers));
+
+        headers.insert("accept", "*/*".parse().unwrap());
+        assert!(!is_navigation_request(&headers));
+
+        headers.insert("accept", "application/javascript".parse().unwrap());
+        assert!(!is_navigation_request(&headers));
+
+        headers.insert("accept", "text/html;q=0, */*".parse().unwrap());
+        assert!(!is_navigation_request(&headers));
+    }
+
+    #[test]
+    fn nested_index_file_paths() {
+        let index_files = ["index.html".to_owned(), "index.htm".to_owned()];
+        assert_eq!(
+            nested_index_paths("/t/42/settings", &index_files[..1]),
+            ["/t/42/index.html", "/t/index.html", "/index.html"]
+        );
+        assert_eq!(
+            nested_index_paths("/t/42/", &index_files),
+            ["/t/index.html", "/t/index.htm", "/index.html", "/index.htm"]
+        );
+        assert!(nested_index_paths("/", &index_files).is_empty());
+        assert!(nested_index_paths("/t/42", &[]).is_empty());
+    }
+}
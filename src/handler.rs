@@ -7,8 +7,8 @@
 //!
 
 use headers::{ContentType, HeaderMapExt, HeaderValue};
-use hyper::{Body, Request, Response, StatusCode};
-use std::{future::Future, net::IpAddr, net::SocketAddr, path::PathBuf, sync::Arc};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use std::{future::Future, net::IpAddr, net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
 
 #[cfg(feature = "compression")]
 use crate::compression;
@@ -20,26 +20,91 @@ use {crate::basic_auth, hyper::header::WWW_AUTHENTICATE};
 use crate::fallback_page;
 
 use crate::{
-    control_headers, cors, custom_headers, error_page,
+    access_log,
+    allowed_hosts::AllowedHosts,
+    allowed_methods,
+    canonical_host::{self, CanonicalHost},
+    canonical_link::CanonicalLink,
+    control_headers::{self, CacheDirectives},
+    cors, custom_headers,
+    default_files::DefaultFiles,
+    downloads, early_hints, error_page,
+    etag::EtagMode,
     exts::http::MethodExt,
-    redirects, rewrites, security_headers,
-    settings::{file::RedirectsKind, Advanced},
+    internal_redirect::InternalRedirectOpts,
+    maintenance_mode::{self, MaintenanceOpts},
+    not_found_cache::NotFoundCache,
+    path_normalization,
+    readiness::{self, Readiness},
+    redirects,
+    request_id::RequestId,
+    rewrites, security_headers,
+    settings::{
+        file::{RedirectsKind, TrailingSlashPolicy},
+        Advanced,
+    },
+    sitemap::{self, Sitemap},
     static_files::{self, HandleOpts},
+    stats::Stats,
+    trailing_slash, url_prefix,
+    version_endpoint::VersionEndpoint,
     virtual_hosts, Error, Result,
 };
 
 #[cfg(feature = "directory-listing")]
-use crate::directory_listing::DirListFmt;
+use crate::directory_listing::{self, DirListFmt, DirListSizeFmt, DirListTimezone};
+
+#[cfg(feature = "http2")]
+use crate::tls::ClientCert;
+
+#[cfg(any(
+    feature = "compression",
+    feature = "directory-listing",
+    feature = "fallback-page"
+))]
+use globset::GlobMatcher;
+#[cfg(feature = "compression")]
+use headers::ContentCoding;
 
 /// It defines options for a request handler.
 pub struct RequestHandlerOpts {
     // General options
     /// Root directory of static files.
     pub root_dir: PathBuf,
+    /// Overlay directory whose files take precedence over the root directory ones.
+    pub overlay_root: Option<PathBuf>,
     /// Compression feature.
     pub compression: bool,
     /// Compression static feature.
     pub compression_static: bool,
+    /// Compression buffer size feature.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub compression_buffer_size: u64,
+    /// Compression minimum size in bytes.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub compression_min_size: u64,
+    /// Auto-compression of HTTP/1.0 requests.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub compression_http10: bool,
+    /// Compression MIME type patterns feature.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub compression_types: Vec<GlobMatcher>,
+    /// Auto-compression encodings in order of preference.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub compression_encodings: Vec<ContentCoding>,
+    /// `User-Agent` patterns of clients denied compressed responses.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub compression_user_agent_denylist: Vec<GlobMatcher>,
+    /// Adaptive compression level feature.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub adaptive_compression: Option<compression::AdaptiveCompression>,
     /// Directory listing feature.
     #[cfg(feature = "directory-listing")]
     #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
@@ -52,12 +117,47 @@ pub struct RequestHandlerOpts {
     #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
     /// Directory listing format feature.
     pub dir_listing_format: DirListFmt,
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    /// Directory listing size format feature.
+    pub dir_listing_size_format: DirListSizeFmt,
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    /// Directory listing date format feature.
+    pub dir_listing_date_format: String,
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    /// Directory listing timezone feature.
+    pub dir_listing_timezone: DirListTimezone,
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    /// Directory listing ignore patterns feature.
+    pub dir_listing_ignore: Vec<GlobMatcher>,
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    /// Directory listing symbolic links flagging feature.
+    pub dir_listing_show_symlinks: bool,
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    /// Directory listing merge of the overlay root entries.
+    pub dir_listing_merge_overlay: bool,
     /// CORS feature.
     pub cors: Option<cors::Configured>,
     /// Security headers feature.
     pub security_headers: bool,
+    /// Strict-Transport-Security (HSTS) header of the security headers.
+    /// It's disabled when the server falls back to plain HTTP.
+    pub security_headers_hsts: bool,
     /// Cache control headers feature.
     pub cache_control_headers: bool,
+    /// Cache control directives taking precedence over the default ones.
+    pub cache_control_directives: CacheDirectives,
+    /// Long-lived `Cache-Control` value of the site icons if enabled.
+    pub cache_control_icons: Option<HeaderValue>,
+    /// Default files served when missing.
+    pub default_files: DefaultFiles,
+    /// Version endpoint feature.
+    pub version_endpoint: Option<VersionEndpoint>,
     /// Page for 404 errors.
     pub page404: Vec<u8>,
     /// Page for 50x errors.
@@ -66,23 +166,110 @@ pub struct RequestHandlerOpts {
     #[cfg(feature = "fallback-page")]
     #[cfg_attr(docsrs, doc(cfg(feature = "fallback-page")))]
     pub page_fallback: Vec<u8>,
+    /// Page fallback status code feature.
+    #[cfg(feature = "fallback-page")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "fallback-page")))]
+    pub page_fallback_status: StatusCode,
+    /// Request path patterns falling back to the nearest index file.
+    #[cfg(feature = "fallback-page")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "fallback-page")))]
+    pub page_fallback_nested: Vec<GlobMatcher>,
     /// Basic auth feature.
     #[cfg(feature = "basic-auth")]
     #[cfg_attr(docsrs, doc(cfg(feature = "basic-auth")))]
     pub basic_auth: String,
+    /// Basic auth `WWW-Authenticate` challenge value.
+    #[cfg(feature = "basic-auth")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "basic-auth")))]
+    pub basic_auth_www_authenticate: HeaderValue,
     /// Log remote address feature.
     pub log_remote_address: bool,
+    /// Index file names of directories in order of preference.
+    pub index_files: Vec<String>,
+    /// Root page feature.
+    pub root_page: Option<String>,
+    /// Extensionless HTML paths feature.
+    pub extensionless_html: bool,
+    /// Allowed request methods.
+    pub allowed_methods: Vec<Method>,
     /// Redirect trailing slash feature.
     pub redirect_trailing_slash: bool,
     /// Ignore hidden files feature.
     pub ignore_hidden_files: bool,
     /// Health endpoint feature.
     pub health: bool,
+    /// Health liveness endpoint path.
+    pub health_liveness_path: String,
+    /// Health readiness endpoint path.
+    pub health_readiness_path: String,
+    /// Server readiness state used by the readiness endpoint and the startup gate.
+    pub ready: Arc<Readiness>,
+    /// Server counters logged on shutdown.
+    pub stats: Arc<Stats>,
+    /// Case-insensitive paths feature.
+    pub case_insensitive_paths: bool,
+    /// Disable range requests feature.
+    pub disable_range_requests: bool,
+    /// Entity tags generation mode.
+    pub etag_mode: EtagMode,
+    /// Disable keep-alive feature.
+    pub disable_keep_alive: bool,
+    /// Maximum number of requests per HTTP/1 keep-alive connection.
+    pub keep_alive_max_requests: u64,
+    /// Generated sitemap feature.
+    pub sitemap: Option<Sitemap>,
+    /// Canonical host feature.
+    pub canonical_host: Option<CanonicalHost>,
+    /// Canonical link header feature.
+    pub canonical_link: Option<CanonicalLink>,
+    /// Allowed hosts feature.
+    pub allowed_hosts: Option<AllowedHosts>,
+    /// Server header feature.
+    pub server_header: Option<HeaderValue>,
+    /// Alt-Svc header feature.
+    pub alt_svc: Option<HeaderValue>,
+    /// Upgrade header feature.
+    pub upgrade_protocols: Option<HeaderValue>,
+    /// Maintenance mode feature.
+    pub maintenance: MaintenanceOpts,
+    /// URL prefix feature.
+    pub url_prefix: String,
+    /// Request ID feature.
+    pub request_id: bool,
+    /// Trusted proxies of the inbound request ID.
+    pub request_id_trusted_proxies: Vec<IpAddr>,
+    /// Response timeout feature.
+    pub response_timeout: Option<Duration>,
+    /// Apply the response timeout to the response body too.
+    pub response_timeout_body: bool,
+    /// Threshold above which requests are logged as slow.
+    pub slow_request_threshold: Option<Duration>,
+    /// Not found responses cache and rate limit feature.
+    pub not_found_cache: Option<NotFoundCache>,
+    /// Internal paths served via the `X-Accel-Redirect` header of trusted proxies.
+    pub internal_redirect: Option<InternalRedirectOpts>,
+    /// Maximum body size of the `GET`, `HEAD` and `OPTIONS` requests.
+    pub request_body_max_size: u64,
 
     /// Advanced options from the config file.
     pub advanced_opts: Option<Advanced>,
 }
 
+/// Compression settings of a request resolved from the options and the compression rules.
+#[cfg(feature = "compression")]
+struct RequestCompression<'a> {
+    /// Auto compression of the response body.
+    auto: bool,
+    /// Precompressed files variants.
+    is_static: bool,
+    /// Minimum body size to compress.
+    min_size: u64,
+    /// Content types to compress.
+    types: &'a [GlobMatcher],
+    /// Compression level.
+    level: compression::CompressionLevel,
+}
+
 /// It defines the main request handler used by the Hyper service request.
 pub struct RequestHandler {
     /// Request handler options.
@@ -90,6 +277,36 @@ pub struct RequestHandler {
 }
 
 impl RequestHandler {
+    /// Checks if the request targets the health endpoints.
+    fn is_health_request(&self, method: &Method, uri_path: &str) -> bool {
+        self.opts.health
+            && (uri_path == "/health"
+                || uri_path == self.opts.health_liveness_path
+                || uri_path == self.opts.health_readiness_path)
+            && (method.is_get() || method.is_head())
+    }
+
+    /// Checks if the request targets the version endpoint.
+    fn is_version_request(&self, method: &Method, uri_path: &str) -> bool {
+        // The version endpoint path is relative to the URL prefix
+        self.opts.version_endpoint.as_ref().is_some_and(|endpoint| {
+            let path = if self.opts.url_prefix.is_empty() {
+                Some(uri_path)
+            } else {
+                url_prefix::strip(&self.opts.url_prefix, uri_path)
+            };
+            path.is_some_and(|path| endpoint.is_match(path, method))
+        })
+    }
+
+    /// Checks if the request is a health or version endpoint probe,
+    /// whose requests are only logged at the debug level.
+    pub fn is_probe_request(&self, req: &Request<Body>) -> bool {
+        let uri_path = path_normalization::normalize(req.uri().path());
+        self.is_health_request(req.method(), &uri_path)
+            || self.is_version_request(req.method(), &uri_path)
+    }
+
     /// Main entry point for incoming requests.
     pub fn handle<'a>(
         &'a self,
@@ -99,9 +316,13 @@ impl RequestHandler {
         let method = req.method();
         let headers = req.headers();
         let uri = req.uri();
+        #[cfg(feature = "compression")]
+        let version = req.version();
 
         let mut base_path = &self.opts.root_dir;
-        let mut uri_path = uri.path().to_owned();
+        let mut overlay_root = self.opts.overlay_root.as_ref();
+        // Collapse duplicate slashes and resolve dot segments before any matching
+        let mut uri_path = path_normalization::normalize(uri.path());
         let uri_query = uri.query();
         #[cfg(feature = "directory-listing")]
         let dir_listing = self.opts.dir_listing;
@@ -109,16 +330,17 @@ impl RequestHandler {
         let dir_listing_order = self.opts.dir_listing_order;
         #[cfg(feature = "directory-listing")]
         let dir_listing_format = &self.opts.dir_listing_format;
+        #[cfg(feature = "directory-listing")]
+        let dir_listing_size_format = &self.opts.dir_listing_size_format;
         let log_remote_addr = self.opts.log_remote_address;
         let redirect_trailing_slash = self.opts.redirect_trailing_slash;
         let compression_static = self.opts.compression_static;
         let ignore_hidden_files = self.opts.ignore_hidden_files;
-        let health = self.opts.health;
 
         let mut cors_headers: Option<http::HeaderMap> = None;
 
-        let health_request =
-            health && uri_path == "/health" && (method.is_get() || method.is_head());
+        let health_request = self.is_health_request(method, &uri_path);
+        let version_request = self.is_version_request(method, &uri_path);
 
         // Log request information with its remote address if available
         let mut remote_addr_str = String::new();
@@ -126,56 +348,164 @@ impl RequestHandler {
             remote_addr_str.push_str(" remote_addr=");
             remote_addr_str.push_str(&remote_addr.map_or("".to_owned(), |v| v.to_string()));
 
-            if let Some(client_ip_address) = headers
-                .get("X-Forwarded-For")
-                .and_then(|v| v.to_str().ok())
-                .and_then(|s| s.split(',').next())
-                .and_then(|s| s.trim().parse::<IpAddr>().ok())
-            {
+            if let Some(client_ip_address) = access_log::real_remote_ip(headers) {
                 remote_addr_str.push_str(" real_remote_ip=");
                 remote_addr_str.push_str(&client_ip_address.to_string())
             }
         }
 
-        if health_request {
-            tracing::debug!(
-                "incoming request: method={} uri={}{}",
-                method,
-                uri,
-                remote_addr_str,
-            );
-        } else {
-            tracing::info!(
-                "incoming request: method={} uri={}{}",
-                method,
-                uri,
-                remote_addr_str,
-            );
+        // Log the request ID if available
+        let mut request_id_str = String::new();
+        if let Some(request_id) = req.extensions().get::<RequestId>() {
+            request_id_str.push_str(" request_id=");
+            request_id_str.push_str(request_id.as_str());
         }
 
+        // Log the TLS client certificate common name if available
+        #[cfg(feature = "http2")]
+        let client_cn_str = req
+            .extensions()
+            .get::<ClientCert>()
+            .and_then(|cert| cert.common_name.as_deref())
+            .map_or(String::new(), |cn| format!(" client_cn={cn}"));
+        #[cfg(not(feature = "http2"))]
+        let client_cn_str = "";
+
+        // Requests are logged at the info level once completed (see `access_log`)
+        tracing::debug!(
+            "incoming request: method={} uri={}{}{}{}",
+            method,
+            uri,
+            remote_addr_str,
+            request_id_str,
+            client_cn_str,
+        );
+
         async move {
             if health_request {
-                let body = if method.is_get() {
+                // The readiness endpoint is unhealthy during startup, shutdown (draining)
+                // or maintenance while the liveness one stays healthy
+                let ready = uri_path != self.opts.health_readiness_path
+                    || (self.opts.ready.is_ready() && !self.opts.maintenance.is_enabled());
+                let body = if !method.is_get() {
+                    Body::empty()
+                } else if ready {
                     Body::from("OK")
                 } else {
-                    Body::empty()
+                    Body::from("NOT READY")
                 };
                 let mut resp = Response::new(body);
+                if !ready {
+                    *resp.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+                }
                 resp.headers_mut().typed_insert(ContentType::html());
                 return Ok(resp);
             }
 
-            // Reject in case of incoming HTTP request method is not allowed
-            if !method.is_allowed() {
-                return error_page::error_response(
+            // Requests arriving before the server initialization completes
+            if self.opts.ready.is_starting() {
+                return readiness::starting_response(
                     uri,
                     method,
-                    &StatusCode::METHOD_NOT_ALLOWED,
+                    headers,
                     &self.opts.page404,
                     &self.opts.page50x,
                 );
             }
 
+            // Reject requests whose host is not allowed
+            if let Some(allowed_hosts) = &self.opts.allowed_hosts {
+                if !allowed_hosts.is_allowed(uri, headers) {
+                    tracing::debug!("request host is not allowed");
+                    return error_page::error_response(
+                        uri,
+                        method,
+                        headers,
+                        &allowed_hosts.status,
+                        &self.opts.page404,
+                        &self.opts.page50x,
+                    );
+                }
+            }
+
+            // Strip the URL prefix and reject requests outside of it
+            // except for the server-wide `OPTIONS *` one
+            if !self.opts.url_prefix.is_empty() && uri_path != "*" {
+                match url_prefix::strip(&self.opts.url_prefix, &uri_path) {
+                    // The prefix alone is redirected to its trailing slash form if enabled
+                    Some("") if !redirect_trailing_slash => uri_path = "/".to_owned(),
+                    Some(path) => uri_path = path.to_owned(),
+                    None => {
+                        return error_page::error_response(
+                            uri,
+                            method,
+                            headers,
+                            &StatusCode::NOT_FOUND,
+                            &self.opts.page404,
+                            &self.opts.page50x,
+                        );
+                    }
+                }
+            }
+
+            // Maintenance mode
+            if self.opts.maintenance.is_active_for(uri_path.as_str()) {
+                return maintenance_mode::maintenance_response(
+                    uri,
+                    method,
+                    headers,
+                    &self.opts.maintenance,
+                    &self.opts.page404,
+                    &self.opts.page50x,
+                );
+            }
+
+            // Redirect to the canonical host if the request host does not match it
+            if let Some(canonical) = &self.opts.canonical_host {
+                if let Some(resp) = canonical_host::redirect(canonical, uri, headers) {
+                    return Ok(resp);
+                }
+            }
+
+            // The allowed methods rules override the global allowed methods for their paths
+            let allowed_methods = self
+                .opts
+                .advanced_opts
+                .as_ref()
+                .and_then(|advanced| {
+                    allowed_methods::find_methods(&uri_path, &advanced.allowed_methods)
+                })
+                .unwrap_or(&self.opts.allowed_methods);
+
+            // Reject in case of incoming HTTP request method is not allowed
+            // or it's allowed by a rule but not supported by the server
+            if !allowed_methods.contains(method) || !method.is_allowed() {
+                let status = if allowed_methods.contains(method) {
+                    StatusCode::NOT_IMPLEMENTED
+                } else {
+                    StatusCode::METHOD_NOT_ALLOWED
+                };
+                let mut resp = error_page::error_response(
+                    uri,
+                    method,
+                    headers,
+                    &status,
+                    &self.opts.page404,
+                    &self.opts.page50x,
+                )?;
+                resp.headers_mut()
+                    .typed_insert(static_files::allow_header(allowed_methods));
+                return Ok(resp);
+            }
+
+            // Server-wide `OPTIONS *` request
+            if method.is_options() && uri_path == "*" {
+                return Ok(static_files::options_response(
+                    false,
+                    &self.opts.allowed_methods,
+                ));
+            }
+
             // CORS
             if let Some(cors) = &self.opts.cors {
                 match cors.check_request(method, headers) {
@@ -188,6 +518,7 @@ impl RequestHandler {
                         return error_page::error_response(
                             uri,
                             method,
+                            headers,
                             &StatusCode::FORBIDDEN,
                             &self.opts.page404,
                             &self.opts.page50x,
@@ -198,37 +529,86 @@ impl RequestHandler {
 
             #[cfg(feature = "basic-auth")]
             // `Basic` HTTP Authorization Schema
-            if !self.opts.basic_auth.is_empty() {
-                if let Some((user_id, password)) = self.opts.basic_auth.split_once(':') {
+            {
+                // Path-based credentials take precedence over the global ones
+                let rule = self
+                    .opts
+                    .advanced_opts
+                    .as_ref()
+                    .and_then(|advanced| advanced.basic_auth.as_ref())
+                    .and_then(|rules| basic_auth::find_rule(rules, &uri_path));
+                let credentials = match rule {
+                    Some(rule) => Some((
+                        rule.user_id.as_str(),
+                        rule.password.as_str(),
+                        &rule.www_authenticate,
+                    )),
+                    None if !self.opts.basic_auth.is_empty() => {
+                        match self.opts.basic_auth.split_once(':') {
+                            Some((user_id, password)) => {
+                                Some((user_id, password, &self.opts.basic_auth_www_authenticate))
+                            }
+                            None => {
+                                tracing::error!(
+                                    "invalid basic authentication `user_id:password` pairs"
+                                );
+                                return error_page::error_response(
+                                    uri,
+                                    method,
+                                    headers,
+                                    &StatusCode::INTERNAL_SERVER_ERROR,
+                                    &self.opts.page404,
+                                    &self.opts.page50x,
+                                );
+                            }
+                        }
+                    }
+                    None => None,
+                };
+                if let Some((user_id, password, www_authenticate)) = credentials {
                     if let Err(err) = basic_auth::check_request(headers, user_id, password) {
                         tracing::warn!("basic authentication failed {:?}", err);
                         let mut resp = error_page::error_response(
                             uri,
                             method,
+                            headers,
                             &StatusCode::UNAUTHORIZED,
                             &self.opts.page404,
                             &self.opts.page50x,
                         )?;
-                        resp.headers_mut().insert(
-                            WWW_AUTHENTICATE,
-                            "Basic realm=\"Static Web Server\", charset=\"UTF-8\""
-                                .parse()
-                                .unwrap(),
-                        );
+                        resp.headers_mut()
+                            .insert(WWW_AUTHENTICATE, www_authenticate.to_owned());
                         return Ok(resp);
                     }
-                } else {
-                    tracing::error!("invalid basic authentication `user_id:password` pairs");
-                    return error_page::error_response(
-                        uri,
-                        method,
-                        &StatusCode::INTERNAL_SERVER_ERROR,
-                        &self.opts.page404,
-                        &self.opts.page50x,
-                    );
                 }
             }
 
+            // Version endpoint
+            if let Some(endpoint) = self
+                .opts
+                .version_endpoint
+                .as_ref()
+                .filter(|_| version_request)
+            {
+                return endpoint.response(method);
+            }
+
+            // Trailing slash rules override the global trailing slash redirection
+            let trailing_slash_policy = self.opts.advanced_opts.as_ref().and_then(|advanced| {
+                trailing_slash::find_policy(&uri_path, &advanced.trailing_slash)
+            });
+            // The `add` policy is applied after the file lookup since only directories
+            // get their trailing slash added
+            if trailing_slash_policy == Some(TrailingSlashPolicy::Remove) {
+                if let Some(resp) =
+                    trailing_slash::redirect(TrailingSlashPolicy::Remove, &uri_path, uri.query())
+                {
+                    return Ok(resp);
+                }
+            }
+            let redirect_trailing_slash =
+                trailing_slash::redirects_to_slash(trailing_slash_policy, redirect_trailing_slash);
+
             // Advanced options
             if let Some(advanced) = &self.opts.advanced_opts {
                 // Redirects
@@ -281,6 +661,7 @@ impl RequestHandler {
                             return error_page::error_response(
                                 uri,
                                 method,
+                                headers,
                                 &StatusCode::INTERNAL_SERVER_ERROR,
                                 &self.opts.page404,
                                 &self.opts.page50x,
@@ -332,6 +713,7 @@ impl RequestHandler {
                                 return error_page::error_response(
                                     uri,
                                     method,
+                                    headers,
                                     &StatusCode::INTERNAL_SERVER_ERROR,
                                     &self.opts.page404,
                                     &self.opts.page50x,
@@ -349,18 +731,173 @@ impl RequestHandler {
                 }
 
                 // If the "Host" header matches any virtual_host, change the root dir
+                // (the overlay directory only applies to the default root)
                 if let Some(root) = virtual_hosts::get_real_root(&advanced.virtual_hosts, headers) {
                     base_path = root;
+                    overlay_root = None;
+                }
+            }
+
+            // The root page is served for the root path only, bypassing the index files
+            if let Some(root_page) = &self.opts.root_page {
+                if uri_path == "/" {
+                    uri_path = root_page.to_owned();
+                }
+            }
+
+            // Internal paths are only served when named by a trusted proxy,
+            // the requested path is replaced by the `X-Accel-Redirect` one
+            if let Some(internal) = &self.opts.internal_redirect {
+                if let Some(path) = internal.redirect_path(headers, remote_addr) {
+                    uri_path = path;
+                } else if internal.is_internal(&uri_path) {
+                    return error_page::error_response(
+                        uri,
+                        method,
+                        headers,
+                        &StatusCode::NOT_FOUND,
+                        &self.opts.page404,
+                        &self.opts.page50x,
+                    );
+                }
+            }
+
+            // The generated sitemap of the default root is served unless a real file exists
+            if let Some(sitemap) = &self.opts.sitemap {
+                if uri_path == sitemap::SITEMAP_PATH
+                    && (method.is_get() || method.is_head())
+                    && base_path == &self.opts.root_dir
+                    && !overlay_root
+                        .into_iter()
+                        .chain([base_path])
+                        .any(|root| root.join("sitemap.xml").is_file())
+                {
+                    // Internal and path-based protected locations are not listed
+                    let opts = self.opts.clone();
+                    let excluded = move |path: &str| {
+                        let internal = opts
+                            .internal_redirect
+                            .as_ref()
+                            .is_some_and(|internal| internal.is_internal(path));
+                        #[cfg(feature = "basic-auth")]
+                        let protected = opts
+                            .advanced_opts
+                            .as_ref()
+                            .and_then(|advanced| advanced.basic_auth.as_ref())
+                            .is_some_and(|rules| basic_auth::find_rule(rules, path).is_some());
+                        #[cfg(not(feature = "basic-auth"))]
+                        let protected = false;
+                        internal || protected
+                    };
+                    return match sitemap
+                        .response(
+                            base_path.to_owned(),
+                            self.opts.index_files.clone(),
+                            method.is_head(),
+                            excluded,
+                        )
+                        .await
+                    {
+                        Ok(resp) => Ok(resp),
+                        Err(status) => error_page::error_response(
+                            uri,
+                            method,
+                            headers,
+                            &status,
+                            &self.opts.page404,
+                            &self.opts.page50x,
+                        ),
+                    };
                 }
             }
 
             let uri_path = &uri_path;
 
+            // Compression rules override the auto-compression and the precompressed files
+            #[cfg(feature = "compression")]
+            let compression_forced =
+                self.opts.advanced_opts.as_ref().and_then(|advanced| {
+                    compression::forced_by_rules(uri_path, &advanced.compression)
+                });
+            #[cfg(feature = "compression")]
+            let compression_static = compression_static && compression_forced != Some(false);
+            #[cfg(feature = "compression")]
+            let auto_compression = compression_forced.unwrap_or(self.opts.compression);
+            // Legacy HTTP/1.0 clients get identity responses if their compression is disabled
+            #[cfg(feature = "compression")]
+            let auto_compression = auto_compression
+                && (self.opts.compression_http10 || version != hyper::Version::HTTP_10);
+            // Denied user agents always get identity responses, regardless of the compression rules
+            #[cfg(feature = "compression")]
+            let compression_denied = compression::is_user_agent_denied(
+                headers,
+                &self.opts.compression_user_agent_denylist,
+            );
+            #[cfg(feature = "compression")]
+            let compression_static = compression_static && !compression_denied;
+            #[cfg(feature = "compression")]
+            let auto_compression = auto_compression && !compression_denied;
+            #[cfg(feature = "compression")]
+            let (compression_min_size, compression_types) = match compression_forced {
+                Some(true) => (0, compression::any_types()),
+                _ => (
+                    self.opts.compression_min_size,
+                    self.opts.compression_types.as_slice(),
+                ),
+            };
+            // The compression level follows the server load if the adaptive compression is enabled
+            #[cfg(feature = "compression")]
+            let compression_level = self
+                .opts
+                .adaptive_compression
+                .map_or(compression::CompressionLevel::Default, |adaptive| {
+                    adaptive.level(self.opts.stats.in_flight_requests())
+                });
+            #[cfg(feature = "compression")]
+            let request_compression = RequestCompression {
+                auto: auto_compression,
+                is_static: compression_static,
+                min_size: compression_min_size,
+                types: compression_types,
+                level: compression_level,
+            };
+
+            // Directory listing rules override the global directory listing option
+            #[cfg(feature = "directory-listing")]
+            let dir_listing = self
+                .opts
+                .advanced_opts
+                .as_ref()
+                .and_then(|advanced| {
+                    directory_listing::enabled_by_rules(uri_path, &advanced.directory_listing)
+                })
+                .unwrap_or(dir_listing);
+
+            // Clients requesting too many missing paths are throttled
+            let not_found_cache = self.opts.not_found_cache.as_ref();
+            let client_ip = remote_addr.map(|addr| addr.ip());
+            if let (Some(cache), Some(ip)) = (not_found_cache, client_ip) {
+                if cache.is_throttled(ip) {
+                    let mut resp = error_page::error_response(
+                        uri,
+                        method,
+                        headers,
+                        &StatusCode::TOO_MANY_REQUESTS,
+                        &self.opts.page404,
+                        &self.opts.page50x,
+                    )?;
+                    resp.headers_mut()
+                        .insert(hyper::header::RETRY_AFTER, HeaderValue::from_static("1"));
+                    return Ok(resp);
+                }
+            }
+
             // Static files
-            match static_files::handle(&HandleOpts {
+            let handle_opts = HandleOpts {
                 method,
                 headers,
                 base_path,
+                overlay_root,
                 uri_path,
                 uri_query,
                 #[cfg(feature = "directory-listing")]
@@ -369,138 +906,193 @@ impl RequestHandler {
                 dir_listing_order,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: &self.opts.dir_listing_date_format,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &self.opts.dir_listing_timezone,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &self.opts.dir_listing_ignore,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: self.opts.dir_listing_show_symlinks,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: self.opts.dir_listing_merge_overlay,
+                index_files: &self.opts.index_files,
+                allowed_methods,
+                extensionless_html: self.opts.extensionless_html,
                 redirect_trailing_slash,
+                trailing_slash_policy,
                 compression_static,
                 ignore_hidden_files,
-            })
-            .await
+                case_insensitive_paths: self.opts.case_insensitive_paths,
+                disable_range_requests: self.opts.disable_range_requests,
+                etag_mode: self.opts.etag_mode,
+            };
+            // Recently missing paths skip the file lookup
+            let not_found_roots: Vec<&std::path::Path> = match not_found_cache {
+                Some(_) if method.is_get() || method.is_head() => overlay_root
+                    .into_iter()
+                    .chain([base_path])
+                    .map(|p| p.as_path())
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let not_found_cached = not_found_cache.is_some_and(|cache| {
+                !not_found_roots.is_empty() && cache.contains(&not_found_roots, uri_path)
+            });
+            let result = if not_found_cached {
+                tracing::debug!("not found cache hit: {}", uri_path);
+                Err(StatusCode::NOT_FOUND)
+            } else {
+                static_files::handle(&handle_opts).await
+            };
+
+            // A missing root directory (e.g. removed and recreated during a deploy)
+            // is a temporary condition so the request can be retried shortly
+            if matches!(result, Err(status) if status == StatusCode::NOT_FOUND || status.is_server_error())
+                && !base_path.is_dir()
             {
-                Ok((mut resp, _is_precompressed)) => {
-                    // Append CORS headers if they are present
-                    if let Some(cors_headers) = cors_headers {
-                        if !cors_headers.is_empty() {
-                            for (k, v) in cors_headers.iter() {
-                                resp.headers_mut().insert(k, v.to_owned());
+                tracing::warn!("root directory is missing: {}", base_path.display());
+                let mut resp = error_page::error_response(
+                    uri,
+                    method,
+                    headers,
+                    &StatusCode::SERVICE_UNAVAILABLE,
+                    &self.opts.page404,
+                    &self.opts.page50x,
+                )?;
+                resp.headers_mut()
+                    .insert(hyper::header::RETRY_AFTER, HeaderValue::from_static("1"));
+                return Ok(resp);
+            }
+
+            if let (Some(cache), Err(StatusCode::NOT_FOUND)) = (not_found_cache, &result) {
+                if !not_found_cached && !not_found_roots.is_empty() {
+                    cache.insert(&not_found_roots, uri_path);
+                }
+            }
+            if let (Some(cache), Some(ip), Err(StatusCode::NOT_FOUND)) =
+                (not_found_cache, client_ip, &result)
+            {
+                cache.add_client_not_found(ip);
+            }
+
+            // Nested fallback pages: serve the nearest index file of the parent directories
+            #[cfg(feature = "fallback-page")]
+            let result = match result {
+                Err(status)
+                    if status == StatusCode::NOT_FOUND
+                        && method.is_get()
+                        && self
+                            .opts
+                            .page_fallback_nested
+                            .iter()
+                            .any(|m| m.is_match(uri_path))
+                        && fallback_page::is_navigation_request(headers) =>
+                {
+                    let mut result = Err(status);
+                    for index_path in
+                        fallback_page::nested_index_paths(uri_path, &self.opts.index_files)
+                    {
+                        match static_files::handle(&HandleOpts {
+                            uri_path: &index_path,
+                            uri_query: None,
+                            ..handle_opts
+                        })
+                        .await
+                        {
+                            Err(status) if status == StatusCode::NOT_FOUND => continue,
+                            res => {
+                                tracing::debug!("nested fallback page served: {}", index_path);
+                                result = res;
+                                break;
                             }
-                            resp.headers_mut().remove(http::header::ALLOW);
                         }
                     }
+                    result
+                }
+                result => result,
+            };
 
-                    // Compression content encoding varies so use a `Vary` header
-                    #[cfg(feature = "compression")]
-                    if self.opts.compression || compression_static {
-                        resp.headers_mut().append(
-                            hyper::header::VARY,
-                            hyper::header::HeaderValue::from_name(hyper::header::ACCEPT_ENCODING),
-                        );
-                    }
-
-                    // Auto compression based on the `Accept-Encoding` header
-                    #[cfg(feature = "compression")]
-                    if self.opts.compression && !_is_precompressed {
-                        resp = match compression::auto(method, headers, resp) {
-                            Ok(res) => res,
-                            Err(err) => {
-                                tracing::error!("error during body compression: {:?}", err);
-                                return error_page::error_response(
-                                    uri,
-                                    method,
-                                    &StatusCode::INTERNAL_SERVER_ERROR,
-                                    &self.opts.page404,
-                                    &self.opts.page50x,
+            match result {
+                Ok((resp, _is_precompressed)) => {
+                    self.decorate_response(
+                        resp,
+                        uri,
+                        uri_path,
+                        method,
+                        headers,
+                        cors_headers.as_ref(),
+                        #[cfg(feature = "compression")]
+                        &request_compression,
+                        _is_precompressed,
+                    )
+                    .await
+                }
+                Err(status) => {
+                    // Serve a default `robots.txt` or `favicon.ico` file if missing
+                    if status == StatusCode::NOT_FOUND {
+                        if let Some(mut resp) = self.opts.default_files.response(uri_path, method) {
+                            if self.opts.cache_control_headers {
+                                control_headers::append_headers(
+                                    uri_path,
+                                    &mut resp,
+                                    &self.opts.cache_control_directives,
                                 );
                             }
-                        };
-                    }
-
-                    // Append `Cache-Control` headers for web assets
-                    if self.opts.cache_control_headers {
-                        control_headers::append_headers(uri_path, &mut resp);
-                    }
-
-                    // Append security headers
-                    if self.opts.security_headers {
-                        security_headers::append_headers(&mut resp);
-                    }
-
-                    // Add/update custom headers
-                    if let Some(advanced) = &self.opts.advanced_opts {
-                        custom_headers::append_headers(uri_path, &advanced.headers, &mut resp)
+                            if let Some(icons) = &self.opts.cache_control_icons {
+                                control_headers::append_icon_headers(uri_path, icons, &mut resp);
+                            }
+                            if self.opts.security_headers {
+                                security_headers::append_headers(
+                                    &mut resp,
+                                    self.opts.security_headers_hsts,
+                                );
+                            }
+                            if let Some(advanced) = &self.opts.advanced_opts {
+                                custom_headers::append_headers(
+                                    uri_path,
+                                    &advanced.headers,
+                                    &mut resp,
+                                );
+                            }
+                            return Ok(resp);
+                        }
                     }
 
-                    Ok(resp)
-                }
-                Err(status) => {
                     // Check for a fallback response
                     #[cfg(feature = "fallback-page")]
                     if method.is_get()
                         && status == StatusCode::NOT_FOUND
                         && !self.opts.page_fallback.is_empty()
+                        && fallback_page::is_navigation_request(headers)
                     {
                         // We use all modules as usual when the `page-fallback` feature is enabled
-                        let mut resp = fallback_page::fallback_response(&self.opts.page_fallback);
-
-                        // Append CORS headers if they are present
-                        if let Some(cors_headers) = cors_headers {
-                            if !cors_headers.is_empty() {
-                                for (k, v) in cors_headers.iter() {
-                                    resp.headers_mut().insert(k, v.to_owned());
-                                }
-                                resp.headers_mut().remove(http::header::ALLOW);
-                            }
-                        }
-
-                        // Compression content encoding varies so use a `Vary` header
-                        #[cfg(feature = "compression")]
-                        if self.opts.compression || compression_static {
-                            resp.headers_mut().append(
-                                hyper::header::VARY,
-                                hyper::header::HeaderValue::from_name(
-                                    hyper::header::ACCEPT_ENCODING,
-                                ),
-                            );
-                        }
-
-                        // Auto compression based on the `Accept-Encoding` header
-                        #[cfg(feature = "compression")]
-                        if self.opts.compression {
-                            resp = match compression::auto(method, headers, resp) {
-                                Ok(res) => res,
-                                Err(err) => {
-                                    tracing::error!("error during body compression: {:?}", err);
-                                    return error_page::error_response(
-                                        uri,
-                                        method,
-                                        &StatusCode::INTERNAL_SERVER_ERROR,
-                                        &self.opts.page404,
-                                        &self.opts.page50x,
-                                    );
-                                }
-                            };
-                        }
-
-                        // Append `Cache-Control` headers for web assets
-                        if self.opts.cache_control_headers {
-                            control_headers::append_headers(uri_path, &mut resp);
-                        }
-
-                        // Append security headers
-                        if self.opts.security_headers {
-                            security_headers::append_headers(&mut resp);
-                        }
-
-                        // Add/update custom headers
-                        if let Some(advanced) = &self.opts.advanced_opts {
-                            custom_headers::append_headers(uri_path, &advanced.headers, &mut resp)
-                        }
-
-                        return Ok(resp);
+                        let resp = fallback_page::fallback_response(
+                            &self.opts.page_fallback,
+                            self.opts.page_fallback_status,
+                        );
+                        return self
+                            .decorate_response(
+                                resp,
+                                uri,
+                                uri_path,
+                                method,
+                                headers,
+                                cors_headers.as_ref(),
+                                #[cfg(feature = "compression")]
+                                &request_compression,
+                                false,
+                            )
+                            .await;
                     }
 
                     // Otherwise return an error response
                     error_page::error_response(
                         uri,
                         method,
+                        headers,
                         &status,
                         &self.opts.page404,
                         &self.opts.page50x,
@@ -509,4 +1101,130 @@ impl RequestHandler {
             }
         }
     }
+
+    /// Appends the response headers shared by the files served and the fallback page,
+    /// compresses the body if needed and checks that the client accepts the response encoding.
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(not(feature = "compression"), allow(unused_variables))]
+    async fn decorate_response(
+        &self,
+        mut resp: Response<Body>,
+        uri: &hyper::Uri,
+        uri_path: &str,
+        method: &Method,
+        headers: &http::HeaderMap,
+        cors_headers: Option<&http::HeaderMap>,
+        #[cfg(feature = "compression")] compression: &RequestCompression<'_>,
+        _is_precompressed: bool,
+    ) -> Result<Response<Body>, Error> {
+        // Append CORS headers if they are present
+        if let Some(cors_headers) = cors_headers {
+            for (k, v) in cors_headers.iter() {
+                resp.headers_mut().append(k, v.to_owned());
+            }
+            if cors_headers.contains_key(http::header::ACCESS_CONTROL_ALLOW_ORIGIN) {
+                resp.headers_mut().remove(http::header::ALLOW);
+            }
+        }
+
+        // Compression content encoding varies so use a `Vary` header
+        #[cfg(feature = "compression")]
+        if compression.auto || compression.is_static {
+            resp.headers_mut().append(
+                hyper::header::VARY,
+                hyper::header::HeaderValue::from_name(hyper::header::ACCEPT_ENCODING),
+            );
+        }
+
+        // Append `Cache-Control` headers for web assets
+        let has_rule = self.opts.advanced_opts.as_ref().is_some_and(|advanced| {
+            control_headers::append_rule_headers(uri_path, &advanced.cache_control, &mut resp)
+        });
+        if !has_rule && self.opts.cache_control_headers {
+            control_headers::append_headers(
+                uri_path,
+                &mut resp,
+                &self.opts.cache_control_directives,
+            );
+        }
+        // Long-lived caching of the site icons unless a rule applies
+        match &self.opts.cache_control_icons {
+            Some(icons) if !has_rule => {
+                control_headers::append_icon_headers(uri_path, icons, &mut resp)
+            }
+            _ => {}
+        }
+
+        // Append security headers
+        if self.opts.security_headers {
+            security_headers::append_headers(&mut resp, self.opts.security_headers_hsts);
+        }
+
+        // Append the canonical link of HTML pages
+        if let Some(canonical_link) = &self.opts.canonical_link {
+            canonical_link.append_header(uri, &mut resp);
+        }
+
+        if let Some(advanced) = &self.opts.advanced_opts {
+            // Force file downloads
+            downloads::append_headers(uri_path, &advanced.downloads, &mut resp);
+            // Preload links (early hints)
+            early_hints::append_headers(uri_path, &advanced.early_hints, &mut resp);
+            early_hints::append_manifest_headers(uri_path, &advanced.preload_manifest, &mut resp);
+            // Add/update custom headers
+            custom_headers::append_headers(uri_path, &advanced.headers, &mut resp);
+            // Prevent caching of no-store paths
+            control_headers::append_no_store_headers(uri_path, &advanced.no_store, &mut resp);
+        }
+
+        // Auto compression based on the `Accept-Encoding` header
+        // NOTE: it comes last in order to respect the `Cache-Control: no-transform` header
+        #[cfg(feature = "compression")]
+        if compression.auto && !_is_precompressed {
+            resp = match compression::auto_sized(
+                method,
+                headers,
+                resp,
+                self.opts.compression_buffer_size,
+                compression.min_size,
+                compression.types,
+                &self.opts.compression_encodings,
+                compression.level,
+            )
+            .await
+            {
+                Ok(res) => res,
+                Err(err) => {
+                    tracing::error!("error during body compression: {:?}", err);
+                    return error_page::error_response(
+                        uri,
+                        method,
+                        headers,
+                        &StatusCode::INTERNAL_SERVER_ERROR,
+                        &self.opts.page404,
+                        &self.opts.page50x,
+                    );
+                }
+            };
+        }
+
+        // Identity responses explicitly refused by the client are not acceptable
+        #[cfg(feature = "compression")]
+        if resp.status().is_success()
+            && !method.is_options()
+            && !resp.headers().contains_key(hyper::header::CONTENT_ENCODING)
+            && !compression::is_identity_acceptable(headers)
+        {
+            return error_page::error_response(
+                uri,
+                method,
+                headers,
+                &StatusCode::NOT_ACCEPTABLE,
+                &self.opts.page404,
+                &self.opts.page50x,
+            );
+        }
+
+        Ok(resp)
+    }
 }
@@ -3,6 +3,8 @@
 // See https://static-web-server.net/ for more information
 // Copyright (C) 2019-present Jose Quintana <joseluisq.net>
 
+use globset::{Glob, GlobMatcher};
+use hyper::header::{HeaderMap, HeaderName};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -20,6 +22,33 @@ where
     }
 }
 
+/// Returns the `(value, quality)` items of a comma-separated header list with quality values
+/// like `Accept` or `Accept-Encoding`. Qualities are in thousandths and default to `1000`,
+/// while invalid ones count as `0`.
+pub fn quality_values(headers: &HeaderMap, name: HeaderName) -> Vec<(&str, u16)> {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|item| {
+            let mut params = item.split(';');
+            let value = params.next().unwrap_or_default().trim();
+            let quality = params
+                .find_map(|p| {
+                    let p = p.trim();
+                    p.strip_prefix("q=").or_else(|| p.strip_prefix("Q="))
+                })
+                .map_or(1000, |q| {
+                    q.trim()
+                        .parse::<f32>()
+                        .map_or(0, |q| (q.clamp(0.0, 1.0) * 1000.0) as u16)
+                });
+            (value, quality)
+        })
+        .collect()
+}
+
 /// Read the entire contents of a file into a bytes vector.
 pub fn read_bytes(path: &Path) -> Result<Vec<u8>> {
     fs::read(path).with_context(|| format!("failed to read file `{}`", path.display()))
@@ -38,6 +67,20 @@ pub fn read_file(path: &Path) -> Result<String> {
     }
 }
 
+/// Compile a comma-separated list of glob patterns into glob matchers.
+pub fn parse_glob_list(globs: &str) -> Result<Vec<GlobMatcher>> {
+    globs
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            Glob::new(s)
+                .with_context(|| format!("can not compile glob pattern: {s}"))
+                .map(|g| g.compile_matcher())
+        })
+        .collect()
+}
+
 pub fn stringify(dst: &mut String, path: &serde_ignored::Path<'_>) {
     use serde_ignored::Path;
 
@@ -8,10 +8,17 @@
 
 use headers::{HeaderMapExt, Host};
 use hyper::{header::LOCATION, Body, Request, Response, StatusCode};
+use std::path::PathBuf;
 use std::sync::Arc;
 
+use crate::etag::EtagMode;
+use crate::exts::http::HTTP_SUPPORTED_METHODS;
+use crate::static_files::{self, HandleOpts};
 use crate::Result;
 
+#[cfg(feature = "directory-listing")]
+use crate::directory_listing::{DirListFmt, DirListSizeFmt, DirListTimezone};
+
 /// HTTPS redirect options.
 pub struct RedirectOpts {
     /// HTTPS hostname to redirect to.
@@ -20,6 +27,92 @@ pub struct RedirectOpts {
     pub https_port: u16,
     /// Hostnames or IPS to redirect from.
     pub allowed_hosts: Vec<String>,
+    /// ACME challenge files served over HTTP (if any).
+    pub acme_challenge: Option<AcmeChallengeOpts>,
+}
+
+/// ACME challenge options.
+pub struct AcmeChallengeOpts {
+    /// URL path of the challenge files ending with a slash.
+    pub path: String,
+    /// Directory to look up the challenge files by name.
+    /// If `None`, they are looked up by their full request path in the root directory.
+    pub dir: Option<PathBuf>,
+    /// Root directory of static files.
+    pub root_dir: PathBuf,
+}
+
+impl AcmeChallengeOpts {
+    /// Creates new ACME challenge options if the given URL path is not empty.
+    pub fn new(path: &str, dir: PathBuf, root_dir: PathBuf) -> Option<Self> {
+        let path = path.trim().trim_matches('/');
+        if path.is_empty() {
+            return None;
+        }
+        Some(Self {
+            path: ["/", path, "/"].concat(),
+            dir: if dir.as_os_str().is_empty() {
+                None
+            } else {
+                Some(dir)
+            },
+            root_dir,
+        })
+    }
+}
+
+/// It serves an ACME challenge file if the request path matches the challenge path.
+/// Otherwise it returns `None`.
+pub async fn acme_challenge(
+    req: &Request<Body>,
+    opts: &AcmeChallengeOpts,
+) -> Option<Result<Response<Body>, StatusCode>> {
+    let uri_path = req.uri().path();
+    let token = uri_path.strip_prefix(&opts.path)?;
+    let (base_path, uri_path) = match &opts.dir {
+        Some(dir) => (dir, token),
+        None => (&opts.root_dir, uri_path),
+    };
+    tracing::debug!("serving acme challenge file {}", uri_path);
+
+    let resp = static_files::handle(&HandleOpts {
+        method: req.method(),
+        headers: req.headers(),
+        base_path,
+        overlay_root: None,
+        uri_path,
+        uri_query: None,
+        #[cfg(feature = "directory-listing")]
+        dir_listing: false,
+        #[cfg(feature = "directory-listing")]
+        dir_listing_order: 6,
+        #[cfg(feature = "directory-listing")]
+        dir_listing_format: &DirListFmt::Html,
+        #[cfg(feature = "directory-listing")]
+        dir_listing_size_format: &DirListSizeFmt::Human,
+        #[cfg(feature = "directory-listing")]
+        dir_listing_date_format: "%F %T",
+        #[cfg(feature = "directory-listing")]
+        dir_listing_timezone: &DirListTimezone::Local,
+        #[cfg(feature = "directory-listing")]
+        dir_listing_ignore: &[],
+        #[cfg(feature = "directory-listing")]
+        dir_listing_show_symlinks: false,
+        #[cfg(feature = "directory-listing")]
+        dir_listing_merge_overlay: false,
+        index_files: &[],
+        allowed_methods: HTTP_SUPPORTED_METHODS,
+        extensionless_html: false,
+        redirect_trailing_slash: false,
+        trailing_slash_policy: None,
+        compression_static: false,
+        ignore_hidden_files: false,
+        case_insensitive_paths: false,
+        disable_range_requests: true,
+        etag_mode: EtagMode::default(),
+    })
+    .await;
+    Some(resp.map(|(resp, _)| resp))
 }
 
 /// It redirects all requests from HTTP to HTTPS.
@@ -27,6 +120,13 @@ pub async fn redirect_to_https(
     req: &Request<Body>,
     opts: Arc<RedirectOpts>,
 ) -> Result<Response<Body>, StatusCode> {
+    // ACME challenge files are served over HTTP regardless of the request host
+    if let Some(acme_challenge_opts) = &opts.acme_challenge {
+        if let Some(resp) = acme_challenge(req, acme_challenge_opts).await {
+            return resp;
+        }
+    }
+
     if let Some(ref host) = req.headers().typed_get::<Host>() {
         let from_hostname = host.hostname();
         if !opts
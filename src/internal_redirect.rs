@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to serve internal-only files delegated by a trusted upstream (e.g. an app server
+//! in charge of the authorization) via the `X-Accel-Redirect` request header.
+//!
+//! The paths under the internal prefix are never served to direct client requests.
+//! They are only served when a trusted proxy names one of them via the `X-Accel-Redirect`
+//! header, in which case the requested path is replaced by the header one.
+//!
+
+use hyper::HeaderMap;
+use std::net::{IpAddr, SocketAddr};
+
+use crate::{path_normalization, request_id, url_prefix, Result};
+
+/// Request header naming the internal path to serve.
+pub const INTERNAL_REDIRECT_HEADER: &str = "x-accel-redirect";
+
+/// Internal paths options.
+#[derive(Debug)]
+pub struct InternalRedirectOpts {
+    /// Internal path prefix like `/internal`.
+    pub prefix: String,
+    /// Proxy IP addresses whose `X-Accel-Redirect` header is trusted.
+    pub trusted_proxies: Vec<IpAddr>,
+    /// Whether the prefix is matched case-insensitively like the file lookup.
+    pub case_insensitive: bool,
+}
+
+impl InternalRedirectOpts {
+    /// Creates the internal paths options from the internal path prefix and a comma-separated
+    /// list of trusted proxy IP addresses. It returns `None` if the prefix is empty.
+    /// The prefix is matched case-insensitively if the case-insensitive paths are enabled.
+    pub fn new(
+        prefix: &str,
+        trusted_proxies: &str,
+        case_insensitive: bool,
+    ) -> Result<Option<Self>> {
+        let prefix = url_prefix::parse(prefix)?;
+        if prefix.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            prefix,
+            trusted_proxies: request_id::parse_trusted_proxies(trusted_proxies)?,
+            case_insensitive,
+        }))
+    }
+
+    /// Checks if the request path is under the internal path prefix.
+    ///
+    /// The path is matched in the percent-decoded form used by the file lookup,
+    /// so encoded variants (e.g. `/%69nternal`) of an internal path are internal too.
+    pub fn is_internal(&self, uri_path: &str) -> bool {
+        let path = path_normalization::lookup_path(uri_path);
+        if self.case_insensitive {
+            url_prefix::strip(&self.prefix.to_lowercase(), &path.to_lowercase()).is_some()
+        } else {
+            url_prefix::strip(&self.prefix, &path).is_some()
+        }
+    }
+
+    /// Returns the normalized internal path named by the `X-Accel-Redirect` header
+    /// if the remote address is a trusted proxy, otherwise `None`.
+    ///
+    /// The query string of the header value is ignored and paths outside
+    /// the internal prefix (e.g. `/internal/../secret`) are rejected.
+    pub fn redirect_path(
+        &self,
+        headers: &HeaderMap,
+        remote_addr: Option<SocketAddr>,
+    ) -> Option<String> {
+        let value = headers.get(INTERNAL_REDIRECT_HEADER)?.to_str().ok()?;
+        if !remote_addr.is_some_and(|addr| self.trusted_proxies.contains(&addr.ip())) {
+            tracing::debug!("internal redirect header ignored from an untrusted remote address");
+            return None;
+        }
+        let path = value.split(['?', '#']).next().unwrap_or_default();
+        let path = path_normalization::normalize(path);
+        if !path.starts_with('/') || !self.is_internal(&path) {
+            tracing::debug!("internal redirect path ignored: {}", value);
+            return None;
+        }
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InternalRedirectOpts;
+    use hyper::HeaderMap;
+
+    #[test]
+    fn internal_redirect_path() {
+        assert!(InternalRedirectOpts::new("", "10.0.0.1", false)
+            .unwrap()
+            .is_none());
+        assert!(InternalRedirectOpts::new("/internal", "10.0.0.x", false).is_err());
+
+        let opts = InternalRedirectOpts::new("internal/", "10.0.0.1", false)
+            .unwrap()
+            .unwrap();
+        assert!(opts.is_internal("/internal"));
+        assert!(opts.is_internal("/internal/file.pdf"));
+        assert!(!opts.is_internal("/internals/file.pdf"));
+        // Encoded and non-canonical variants resolving to an internal file
+        assert!(opts.is_internal("/%69nternal/file.pdf"));
+        assert!(opts.is_internal("//internal/file.pdf"));
+        assert!(!opts.is_internal("/INTERNAL/file.pdf"));
+
+        let insensitive = InternalRedirectOpts::new("/Internal", "10.0.0.1", true)
+            .unwrap()
+            .unwrap();
+        assert!(insensitive.is_internal("/INTERNAL/file.pdf"));
+        assert!(insensitive.is_internal("/%49nternal/file.pdf"));
+
+        let trusted = Some("10.0.0.1:8080".parse().unwrap());
+        let untrusted = Some("10.0.0.2:8080".parse().unwrap());
+        let redirect = |value: &str, remote_addr| {
+            let mut headers = HeaderMap::new();
+            headers.insert("x-accel-redirect", value.parse().unwrap());
+            opts.redirect_path(&headers, remote_addr)
+        };
+
+        assert_eq!(
+            redirect("/internal/a.pdf?x=1", trusted).as_deref(),
+            Some("/internal/a.pdf")
+        );
+        assert_eq!(redirect("/internal/a.pdf", untrusted), None);
+        assert_eq!(redirect("/internal/a.pdf", None), None);
+        assert_eq!(redirect("/public/a.pdf", trusted), None);
+        assert_eq!(redirect("/internal/../secret.txt", trusted), None);
+        assert_eq!(opts.redirect_path(&HeaderMap::new(), trusted), None);
+    }
+}
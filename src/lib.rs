@@ -90,6 +90,10 @@
 //! `basic-auth` | Activates the Basic HTTP Authorization Schema feature.
 //! [**Fallback Page**](./features/error-pages.md#fallback-page-for-use-with-client-routers) |
 //! `fallback-page` | Activates the Fallback Page feature.
+//! [**Archive Root**](https://static-web-server.net/features/archive-root/) |
+//! `archive` | Activates serving files from a `.tar` or `.tar.gz` archive root.
+//! [**ACME Certificates**](https://static-web-server.net/features/acme/) |
+//! `acme` | Activates obtaining and renewing TLS certificates automatically via ACME (e.g. Let's Encrypt).
 //!
 
 #![deny(missing_docs)]
@@ -106,22 +110,39 @@ extern crate anyhow;
 extern crate serde;
 
 // Public modules
+pub mod access_log;
+#[cfg(feature = "acme")]
+#[cfg_attr(docsrs, doc(cfg(feature = "acme")))]
+pub mod acme;
+pub mod allowed_hosts;
+pub mod allowed_methods;
+#[cfg(feature = "archive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "archive")))]
+pub mod archive;
 #[cfg(feature = "basic-auth")]
 #[cfg_attr(docsrs, doc(cfg(feature = "basic-auth")))]
 pub mod basic_auth;
+pub mod canonical_host;
+pub mod canonical_link;
+pub mod case_insensitive;
 #[cfg(feature = "compression")]
 #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
 pub mod compression;
 #[cfg(feature = "compression")]
 #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
 pub mod compression_static;
+pub mod connection_limit;
 pub mod control_headers;
 pub mod cors;
 pub mod custom_headers;
+pub mod default_files;
 #[cfg(feature = "directory-listing")]
 #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
 pub mod directory_listing;
+pub mod downloads;
+pub mod early_hints;
 pub mod error_page;
+pub mod etag;
 pub mod exts;
 #[cfg(feature = "fallback-page")]
 #[cfg_attr(docsrs, doc(cfg(feature = "fallback-page")))]
@@ -130,22 +151,37 @@ pub mod handler;
 #[cfg(feature = "http2")]
 #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
 pub mod https_redirect;
+pub mod internal_redirect;
 #[macro_use]
 pub mod logger;
+pub mod maintenance_mode;
+pub mod not_found_cache;
+pub mod path_normalization;
+pub mod protocol_hints;
+pub mod readiness;
 pub mod redirects;
+pub mod request_body;
+pub mod request_id;
+pub mod response_timeout;
 pub mod rewrites;
 pub mod security_headers;
 pub mod server;
+pub mod server_header;
 pub mod service;
 pub mod settings;
 #[cfg(any(unix, windows))]
 #[cfg_attr(docsrs, doc(cfg(any(unix, windows))))]
 pub mod signals;
+pub mod sitemap;
 pub mod static_files;
+pub mod stats;
 #[cfg(feature = "http2")]
 #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
 pub mod tls;
+pub mod trailing_slash;
 pub mod transport;
+pub mod url_prefix;
+pub mod version_endpoint;
 pub mod virtual_hosts;
 #[cfg(windows)]
 #[cfg_attr(docsrs, doc(cfg(windows)))]
@@ -6,33 +6,180 @@
 //! Provides logging initialization for the web server.
 //!
 
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::OnceLock;
 use tracing::Level;
-use tracing_subscriber::{filter::Targets, fmt::format::FmtSpan, prelude::*};
+use tracing_subscriber::{
+    filter::Targets,
+    fmt::{format::FmtSpan, writer::BoxMakeWriter},
+    prelude::*,
+};
 
 use crate::{Context, Result};
 
+/// Maximum number of log entries waiting to be written to the log file.
+/// Logging only blocks when the log file writer falls behind this limit.
+const LOG_FILE_QUEUE_SIZE: usize = 128_000;
+
+/// Log file used as the logging output when configured.
+static LOG_FILE: OnceLock<LogFile> = OnceLock::new();
+
+/// Commands processed by the log file writer thread.
+enum LogFileCommand {
+    /// Write a formatted log entry.
+    Write(Vec<u8>),
+    /// Re-open the log file, replying with the result.
+    Reopen(SyncSender<io::Result<()>>),
+    /// Flush the buffered log entries, replying once done.
+    Flush(SyncSender<()>),
+}
+
+/// Log file which is written by a background thread
+/// and can be re-opened at runtime (e.g. after a rotation).
+struct LogFile {
+    path: PathBuf,
+    sender: SyncSender<LogFileCommand>,
+}
+
+impl LogFile {
+    /// Opens the log file and spawns its buffered writer thread.
+    fn open(path: &Path) -> io::Result<Self> {
+        let file = open_log_file(path)?;
+        let (sender, receiver) = mpsc::sync_channel(LOG_FILE_QUEUE_SIZE);
+        let thread_path = path.to_owned();
+        std::thread::Builder::new()
+            .name("static-web-server-logger".into())
+            .spawn(move || write_log_file(&thread_path, file, receiver))?;
+        Ok(Self {
+            path: path.to_owned(),
+            sender,
+        })
+    }
+
+    /// Sends a command to the writer thread waiting for its reply.
+    fn request<T>(&self, command: impl FnOnce(SyncSender<T>) -> LogFileCommand) -> Option<T> {
+        let (reply, replied) = mpsc::sync_channel(1);
+        self.sender.send(command(reply)).ok()?;
+        replied.recv().ok()
+    }
+}
+
+/// Writes the received log entries into a buffered log file.
+/// The buffer is flushed once there are no more pending entries.
+fn write_log_file(path: &Path, file: File, receiver: Receiver<LogFileCommand>) {
+    let mut writer = BufWriter::new(file);
+    while let Ok(mut command) = receiver.recv() {
+        loop {
+            match command {
+                LogFileCommand::Write(entry) => {
+                    let _ = writer.write_all(&entry);
+                }
+                LogFileCommand::Reopen(reply) => {
+                    let _ = writer.flush();
+                    let result = open_log_file(path).map(|file| writer = BufWriter::new(file));
+                    let _ = reply.send(result);
+                }
+                LogFileCommand::Flush(reply) => {
+                    let _ = writer.flush();
+                    let _ = reply.send(());
+                }
+            }
+            match receiver.try_recv() {
+                Ok(next) => command = next,
+                Err(_) => break,
+            }
+        }
+        let _ = writer.flush();
+    }
+}
+
+/// Writer sending the log entries to the log file writer thread.
+struct LogFileWriter(&'static LogFile);
+
+impl Write for LogFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .sender
+            .send(LogFileCommand::Write(buf.to_vec()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "log file writer stopped"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Opens a log file for appending, creating it if it doesn't exist.
+fn open_log_file(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
 /// Logging system initialization
-pub fn init(log_level: &str) -> Result {
+pub fn init(log_level: &str, log_file: &Path) -> Result {
     let log_level = log_level.to_lowercase();
 
-    configure(&log_level).with_context(|| "failed to initialize logging")?;
+    configure(&log_level, log_file).with_context(|| "failed to initialize logging")?;
 
     Ok(())
 }
 
+/// Re-opens the current log file if any, returning its path.
+/// Useful when the log file was moved or removed by a rotation tool.
+pub fn reopen() -> Result<Option<PathBuf>> {
+    let log_file = match LOG_FILE.get() {
+        Some(log_file) => log_file,
+        None => return Ok(None),
+    };
+    if let Some(Err(err)) = log_file.request(LogFileCommand::Reopen) {
+        return Err(err)
+            .with_context(|| format!("failed to re-open log file: {}", log_file.path.display()));
+    }
+    Ok(Some(log_file.path.clone()))
+}
+
+/// Flushes the buffered log entries of the current log file if any.
+/// It should be called before the server process exits.
+pub fn flush() {
+    if let Some(log_file) = LOG_FILE.get() {
+        log_file.request(LogFileCommand::Flush);
+    }
+}
+
 /// Initialize logging builder with its levels.
-fn configure(level: &str) -> Result {
+fn configure(level: &str, log_file: &Path) -> Result {
     let level = level
         .parse::<Level>()
         .with_context(|| "failed to parse log level")?;
 
     #[cfg(not(windows))]
-    let enable_ansi = true;
+    let mut enable_ansi = true;
     #[cfg(windows)]
-    let enable_ansi = false;
+    let mut enable_ansi = false;
+
+    let writer = match log_file.to_str() {
+        Some("") | Some("stderr") => BoxMakeWriter::new(std::io::stderr),
+        Some("stdout") => BoxMakeWriter::new(std::io::stdout),
+        _ => {
+            let log_file = match LOG_FILE.get() {
+                Some(current) => current,
+                None => {
+                    let opened = LogFile::open(log_file).with_context(|| {
+                        format!("failed to open log file: {}", log_file.display())
+                    })?;
+                    LOG_FILE.get_or_init(|| opened)
+                }
+            };
+            enable_ansi = false;
+            BoxMakeWriter::new(move || LogFileWriter(log_file))
+        }
+    };
 
     let filtered_layer = tracing_subscriber::fmt::layer()
-        .with_writer(std::io::stderr)
+        .with_writer(writer)
         .with_span_events(FmtSpan::CLOSE)
         .with_ansi(enable_ansi)
         .with_filter(
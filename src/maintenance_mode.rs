@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Maintenance mode module to respond with a `503 Service Unavailable` page globally.
+//!
+
+use globset::GlobMatcher;
+use hyper::{header::RETRY_AFTER, Body, HeaderMap, Method, Response, StatusCode, Uri};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::{error_page, settings::file::Settings as FileSettings, Result};
+
+/// Maintenance mode options.
+pub struct MaintenanceOpts {
+    /// Whether the maintenance mode is active. It can be toggled at runtime.
+    pub enabled: Arc<AtomicBool>,
+    /// HTML page content for the maintenance mode.
+    pub page: Vec<u8>,
+    /// Value in seconds of the `Retry-After` header.
+    pub retry_after: u64,
+    /// Request paths (glob matchers) still served during maintenance.
+    pub allowed_paths: Vec<GlobMatcher>,
+}
+
+impl MaintenanceOpts {
+    /// Checks if the maintenance mode is currently enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Checks if the given request path should get a maintenance response.
+    pub fn is_active_for(&self, uri_path: &str) -> bool {
+        self.is_enabled() && !self.allowed_paths.iter().any(|m| m.is_match(uri_path))
+    }
+}
+
+/// It returns a `503 Service Unavailable` maintenance response with a `Retry-After` header.
+/// The `page50x` content is used if no maintenance page is available.
+pub fn maintenance_response(
+    uri: &Uri,
+    method: &Method,
+    headers: &HeaderMap,
+    opts: &MaintenanceOpts,
+    page404: &[u8],
+    page50x: &[u8],
+) -> Result<Response<Body>> {
+    let page = if opts.page.is_empty() {
+        page50x
+    } else {
+        &opts.page
+    };
+    let mut resp = error_page::error_response(
+        uri,
+        method,
+        headers,
+        &StatusCode::SERVICE_UNAVAILABLE,
+        page404,
+        page,
+    )?;
+    if opts.retry_after > 0 {
+        resp.headers_mut()
+            .insert(RETRY_AFTER, opts.retry_after.to_string().parse()?);
+    }
+    Ok(resp)
+}
+
+/// Reloads the maintenance mode state from the given configuration file.
+/// Note that the maintenance mode gets disabled if the `maintenance-mode` option is not present.
+pub fn reload(config_file: &Path, enabled: &AtomicBool) {
+    match FileSettings::read(config_file) {
+        Ok(settings) => {
+            let value = settings
+                .general
+                .and_then(|g| g.maintenance_mode)
+                .unwrap_or_default();
+            enabled.store(value, Ordering::Relaxed);
+            tracing::info!("maintenance mode reloaded: enabled={}", value);
+        }
+        Err(err) => {
+            tracing::error!(
+                "unable to reload maintenance mode from config file, state unchanged: {:?}",
+                err
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MaintenanceOpts;
+    use crate::helpers::parse_glob_list;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn maintenance_allowed_paths() {
+        let opts = MaintenanceOpts {
+            enabled: Arc::new(AtomicBool::new(true)),
+            page: vec![],
+            retry_after: 60,
+            allowed_paths: parse_glob_list("/assets/**, /maintenance.css").unwrap(),
+        };
+        assert!(opts.is_active_for("/index.html"));
+        assert!(!opts.is_active_for("/assets/main.css"));
+        assert!(!opts.is_active_for("/maintenance.css"));
+
+        opts.enabled.store(false, Ordering::Relaxed);
+        assert!(!opts.is_active_for("/index.html"));
+    }
+}
@@ -0,0 +1,262 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to mitigate the load of requests for missing paths (e.g. vulnerability scanners).
+//!
+//! The paths not found are remembered for a short time in a bounded cache,
+//! so their repeated requests skip the whole file lookup (index files, pre-compressed variants, etc.)
+//! and only check whether something appeared at their location in the meantime.
+//! The client IP addresses requesting too many missing paths per second are also throttled.
+//!
+
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::static_files::sanitize_path;
+
+/// Time window of the not found responses rate limit.
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(1);
+/// Number of tracked clients above which the expired time windows are pruned.
+const RATE_LIMIT_PRUNE_LEN: usize = 4096;
+
+/// Cached paths along with their insertion time.
+///
+/// The `order` queue keeps the paths in insertion order, so the oldest ones
+/// (the first to expire) are evicted from its front. Its entries whose insertion time
+/// doesn't match the `times` one anymore are stale and skipped.
+#[derive(Debug, Default)]
+struct CachedPaths {
+    times: HashMap<PathBuf, Instant>,
+    order: VecDeque<(PathBuf, Instant)>,
+}
+
+impl CachedPaths {
+    /// Removes the expired paths from the front of the queue.
+    fn prune(&mut self, ttl: Duration) {
+        while let Some((path, inserted)) = self.order.pop_front() {
+            if self.times.get(&path) == Some(&inserted) {
+                if inserted.elapsed() < ttl {
+                    self.order.push_front((path, inserted));
+                    return;
+                }
+                self.times.remove(&path);
+            }
+        }
+    }
+
+    /// Removes the oldest path of the queue.
+    fn evict_oldest(&mut self) {
+        while let Some((path, inserted)) = self.order.pop_front() {
+            if self.times.get(&path) == Some(&inserted) {
+                self.times.remove(&path);
+                return;
+            }
+        }
+    }
+
+    fn insert(&mut self, path: PathBuf, max_len: usize) {
+        let now = Instant::now();
+        self.times.insert(path.clone(), now);
+        self.order.push_back((path, now));
+
+        // Drop the stale entries once they outnumber the cached paths
+        if self.order.len() > max_len.saturating_mul(2) {
+            let times = &self.times;
+            self.order
+                .retain(|(path, inserted)| times.get(path) == Some(inserted));
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ClientWindow {
+    started: Instant,
+    count: u64,
+}
+
+/// Cache of the paths not found along with the not found responses rate limit of the clients.
+#[derive(Debug)]
+pub struct NotFoundCache {
+    ttl: Duration,
+    size: usize,
+    rate_limit: u64,
+    paths: Mutex<CachedPaths>,
+    clients: Mutex<HashMap<IpAddr, ClientWindow>>,
+}
+
+impl NotFoundCache {
+    /// Creates a new cache remembering up to `size` paths during `ttl_secs` seconds
+    /// and limiting the not found responses per second of a client to `rate_limit`.
+    /// A `ttl_secs` or `rate_limit` value of `0` disables the corresponding feature
+    /// and `None` is returned if both are disabled.
+    pub fn new(ttl_secs: u64, size: usize, rate_limit: u64) -> Option<Self> {
+        if (ttl_secs == 0 || size == 0) && rate_limit == 0 {
+            return None;
+        }
+        Some(Self {
+            ttl: Duration::from_secs(ttl_secs),
+            size,
+            rate_limit,
+            paths: Mutex::new(CachedPaths::default()),
+            clients: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn caches_paths(&self) -> bool {
+        !self.ttl.is_zero() && self.size > 0
+    }
+
+    /// Checks if the request path was recently not found in the given root directories.
+    ///
+    /// Expired paths and paths where a file or directory appeared are evicted.
+    pub fn contains(&self, roots: &[&Path], uri_path: &str) -> bool {
+        if !self.caches_paths() {
+            return false;
+        }
+        let Some((key, file_paths)) = file_paths(roots, uri_path) else {
+            return false;
+        };
+
+        let mut paths = self.paths.lock().unwrap_or_else(|err| err.into_inner());
+        let Some(inserted) = paths.times.get(&key) else {
+            return false;
+        };
+        if inserted.elapsed() >= self.ttl || file_paths.iter().any(|p| exists(p)) {
+            paths.times.remove(&key);
+            return false;
+        }
+        true
+    }
+
+    /// Remembers a request path not found in the given root directories.
+    ///
+    /// Existing paths (e.g. ignored hidden files) are not cached since their eviction is immediate.
+    /// Expired paths are pruned and the oldest path is evicted when the cache is full.
+    pub fn insert(&self, roots: &[&Path], uri_path: &str) {
+        if !self.caches_paths() {
+            return;
+        }
+        let Some((key, file_paths)) = file_paths(roots, uri_path) else {
+            return;
+        };
+        if file_paths.iter().any(|p| exists(p)) {
+            return;
+        }
+
+        let mut paths = self.paths.lock().unwrap_or_else(|err| err.into_inner());
+        paths.prune(self.ttl);
+        if paths.times.len() >= self.size && !paths.times.contains_key(&key) {
+            paths.evict_oldest();
+        }
+        paths.insert(key, self.size);
+    }
+
+    /// Checks if the client exceeded its not found responses rate limit in the current time window.
+    pub fn is_throttled(&self, ip: IpAddr) -> bool {
+        if self.rate_limit == 0 {
+            return false;
+        }
+        let clients = self.clients.lock().unwrap_or_else(|err| err.into_inner());
+        clients.get(&ip).is_some_and(|window| {
+            window.started.elapsed() < RATE_LIMIT_WINDOW && window.count >= self.rate_limit
+        })
+    }
+
+    /// Counts a not found response of the client in its current time window.
+    pub fn add_client_not_found(&self, ip: IpAddr) {
+        if self.rate_limit == 0 {
+            return;
+        }
+        let mut clients = self.clients.lock().unwrap_or_else(|err| err.into_inner());
+        if clients.len() >= RATE_LIMIT_PRUNE_LEN {
+            clients.retain(|_, window| window.started.elapsed() < RATE_LIMIT_WINDOW);
+        }
+        let window = clients.entry(ip).or_insert(ClientWindow {
+            started: Instant::now(),
+            count: 0,
+        });
+        if window.started.elapsed() >= RATE_LIMIT_WINDOW {
+            window.started = Instant::now();
+            window.count = 0;
+        }
+        window.count += 1;
+    }
+}
+
+/// Returns the cache key of the request path (its file path in the first root directory)
+/// along with its file paths in all the root directories.
+fn file_paths(roots: &[&Path], uri_path: &str) -> Option<(PathBuf, Vec<PathBuf>)> {
+    let file_paths = roots
+        .iter()
+        .map(|root| sanitize_path(root, uri_path).ok())
+        .collect::<Option<Vec<_>>>()?;
+    Some((file_paths.first()?.clone(), file_paths))
+}
+
+fn exists(path: &Path) -> bool {
+    std::fs::symlink_metadata(path).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NotFoundCache;
+    use std::path::Path;
+
+    #[test]
+    fn not_found_cache_paths() {
+        assert!(NotFoundCache::new(0, 1024, 0).is_none());
+
+        let root = std::env::temp_dir().join("sws-not-found-cache-unit-test");
+        std::fs::create_dir_all(&root).unwrap();
+        let roots: [&Path; 1] = [&root];
+        let cache = NotFoundCache::new(60, 2, 0).unwrap();
+
+        assert!(!cache.contains(&roots, "/a.txt"));
+        cache.insert(&roots, "/a.txt");
+        cache.insert(&roots, "/b.txt");
+        assert!(cache.contains(&roots, "/a.txt"));
+        assert!(cache.contains(&roots, "/b.txt"));
+
+        // The oldest path is evicted once full
+        cache.insert(&roots, "/c.txt");
+        assert!(!cache.contains(&roots, "/a.txt"));
+        assert!(cache.contains(&roots, "/b.txt"));
+        assert!(cache.contains(&roots, "/c.txt"));
+
+        // Paths are evicted once a file appears
+        std::fs::write(root.join("b.txt"), "b").unwrap();
+        assert!(!cache.contains(&roots, "/b.txt"));
+        cache.insert(&roots, "/b.txt");
+        assert!(!cache.contains(&roots, "/b.txt"));
+
+        // Re-inserted paths are not evicted by their stale queue entries
+        cache.insert(&roots, "/a.txt");
+        cache.insert(&roots, "/c.txt");
+        cache.insert(&roots, "/d.txt");
+        assert!(!cache.contains(&roots, "/a.txt"));
+        assert!(cache.contains(&roots, "/c.txt"));
+        assert!(cache.contains(&roots, "/d.txt"));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn not_found_rate_limit() {
+        let cache = NotFoundCache::new(0, 0, 2).unwrap();
+        let ip = "10.0.0.1".parse().unwrap();
+        let other = "10.0.0.2".parse().unwrap();
+
+        assert!(!cache.is_throttled(ip));
+        cache.add_client_not_found(ip);
+        assert!(!cache.is_throttled(ip));
+        cache.add_client_not_found(ip);
+        assert!(cache.is_throttled(ip));
+        assert!(!cache.is_throttled(other));
+        assert!(!cache.contains(&[Path::new(".")], "/missing"));
+    }
+}
@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to normalize request paths before any rule matching or file lookup.
+//!
+//! Duplicate slashes are collapsed and `.`/`..` segments are resolved without ever
+//! going above the root, also when they are percent-encoded (e.g. `%2e%2e` or `%2f`).
+//! The normalized path is served directly, there is no redirect to it.
+//!
+//! The lookup path is the fully percent-decoded form used by the file lookup,
+//! so the rules protecting files (e.g. basic auth or internal paths) are matched against it.
+//!
+
+use percent_encoding::percent_decode_str;
+
+/// Normalizes a request path like `/a//b/../c` into `/c`.
+///
+/// A trailing slash is kept, and added if the last segment is `.` or `..`.
+/// Other percent-encoded characters are left untouched. Paths not starting
+/// with a slash (e.g. the `*` of an `OPTIONS *` request) are returned as is.
+pub fn normalize(uri_path: &str) -> String {
+    if !uri_path.starts_with('/') {
+        return uri_path.to_owned();
+    }
+
+    let path = decode_dots_and_slashes(uri_path);
+    let mut segments: Vec<&str> = Vec::new();
+    let mut trailing_slash = false;
+    for segment in path.split('/') {
+        match segment {
+            "" => {}
+            "." => trailing_slash = true,
+            ".." => {
+                segments.pop();
+                trailing_slash = true;
+            }
+            _ => {
+                segments.push(segment);
+                trailing_slash = false;
+            }
+        }
+    }
+    trailing_slash |= path.ends_with('/');
+
+    let mut normalized = String::with_capacity(path.len());
+    for segment in &segments {
+        normalized.push('/');
+        normalized.push_str(segment);
+    }
+    if trailing_slash || normalized.is_empty() {
+        normalized.push('/');
+    }
+    normalized
+}
+
+/// Returns the percent-decoded request path without empty, `.` or `..` segments
+/// which are skipped by the file lookup.
+pub fn lookup_path(uri_path: &str) -> String {
+    let decoded = percent_decode_str(uri_path).decode_utf8_lossy();
+    let mut path = String::new();
+    for segment in decoded.split(['/', '\\']) {
+        if !matches!(segment, "" | "." | "..") {
+            path.push('/');
+            path.push_str(segment);
+        }
+    }
+    if path.is_empty() || decoded.ends_with('/') {
+        path.push('/');
+    }
+    path
+}
+
+/// Decodes the percent-encoded dot (`%2e`) and slash (`%2f`) characters only.
+fn decode_dots_and_slashes(path: &str) -> String {
+    let mut decoded = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(pos) = rest.find('%') {
+        decoded.push_str(&rest[..pos]);
+        let escape = rest.get(pos..pos + 3).unwrap_or("%");
+        if escape.eq_ignore_ascii_case("%2e") {
+            decoded.push('.');
+        } else if escape.eq_ignore_ascii_case("%2f") {
+            decoded.push('/');
+        } else {
+            decoded.push('%');
+            rest = &rest[pos + 1..];
+            continue;
+        }
+        rest = &rest[pos + 3..];
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lookup_path, normalize};
+
+    #[test]
+    fn normalize_paths() {
+        let cases = [
+            ("/", "/"),
+            ("/index.html", "/index.html"),
+            ("/assets/", "/assets/"),
+            ("//", "/"),
+            ("/a//b/../c", "/a/c"),
+            ("/a/b/../../c", "/c"),
+            ("/a/./b/", "/a/b/"),
+            ("/a/b/..", "/a/"),
+            ("/a/.", "/a/"),
+            ("/../../etc/passwd", "/etc/passwd"),
+            ("/a/..//b", "/b"),
+            ("*", "*"),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(normalize(path), expected, "{path}");
+        }
+    }
+
+    #[test]
+    fn normalize_encoded_paths() {
+        let cases = [
+            ("/a/%2e%2e/c", "/c"),
+            ("/a/%2E%2E/c", "/c"),
+            ("/a/.%2e/c", "/c"),
+            ("/a%2f..%2fc", "/c"),
+            ("/a/%2e/b", "/a/b"),
+            ("/%2e%2e/%2e%2e/etc/passwd", "/etc/passwd"),
+            // Other escapes are left untouched
+            ("/a%20b/%25/c", "/a%20b/%25/c"),
+            ("/a/%252e%252e/c", "/a/%252e%252e/c"),
+            ("/a/%2", "/a/%2"),
+            ("/a/%", "/a/%"),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(normalize(path), expected, "{path}");
+        }
+    }
+
+    #[test]
+    fn lookup_paths() {
+        assert_eq!(lookup_path("/"), "/");
+        assert_eq!(lookup_path("/a//b/"), "/a/b/");
+        assert_eq!(lookup_path("/%69nternal/a%20b.txt"), "/internal/a b.txt");
+        assert_eq!(lookup_path("/a/../b\\c"), "/a/b/c");
+    }
+}
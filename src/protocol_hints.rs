@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! The module provides the `Alt-Svc` and `Upgrade` HTTP headers support
+//! to advertise alternative services (e.g. HTTP/3 on an edge) and protocol upgrades.
+//!
+
+use hyper::{
+    header::{HeaderValue, ALT_SVC, CONNECTION, UPGRADE},
+    Body, Response, Version,
+};
+
+use crate::Result;
+
+/// It parses an `Alt-Svc` header option value (RFC 7838),
+/// e.g. `h3=":443"; ma=86400, h3-29=":443"` or `clear`. An empty value omits the header.
+pub fn parse_alt_svc(value: &str) -> Result<Option<HeaderValue>> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    if value != "clear" {
+        for alt in value.split(',') {
+            if !is_valid_alt_value(alt) {
+                bail!("invalid alt-svc header value {:?}: {:?}", value, alt.trim());
+            }
+        }
+    }
+    match HeaderValue::from_str(value) {
+        Ok(val) => Ok(Some(val)),
+        Err(err) => bail!("invalid alt-svc header value {:?}: {}", value, err),
+    }
+}
+
+/// It parses a comma-separated list of protocols advertised via the `Upgrade` header,
+/// e.g. `h2c, websocket/13`. An empty value omits the header.
+pub fn parse_upgrade(value: &str) -> Result<Option<HeaderValue>> {
+    let protocols = value
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|protocol| {
+            let (name, version) = match protocol.split_once('/') {
+                Some((name, version)) => (name, Some(version)),
+                None => (protocol, None),
+            };
+            if !is_token(name) || version.is_some_and(|version| !is_token(version)) {
+                bail!("invalid upgrade protocol: {:?}", protocol);
+            }
+            Ok(protocol)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if protocols.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(HeaderValue::from_str(&protocols.join(", "))?))
+}
+
+/// It appends the `Alt-Svc` and `Upgrade` headers to the current response if present.
+///
+/// Headers already set (e.g. by custom headers) are preserved. The `Upgrade` header
+/// is connection-specific, so it's only sent on HTTP/1.1 along with `Connection: upgrade`.
+pub fn append_headers(
+    alt_svc: &Option<HeaderValue>,
+    upgrade: &Option<HeaderValue>,
+    version: Version,
+    resp: &mut Response<Body>,
+) {
+    if let Some(val) = alt_svc {
+        resp.headers_mut()
+            .entry(ALT_SVC)
+            .or_insert_with(|| val.to_owned());
+    }
+    if let Some(val) = upgrade {
+        if version != Version::HTTP_11 || resp.headers().contains_key(UPGRADE) {
+            return;
+        }
+        resp.headers_mut().insert(UPGRADE, val.to_owned());
+        let connection = match resp.headers().get(CONNECTION) {
+            Some(connection) => match connection.to_str() {
+                Ok(connection) => HeaderValue::from_str(&format!("{connection}, upgrade")).ok(),
+                Err(_) => None,
+            },
+            None => Some(HeaderValue::from_static("upgrade")),
+        };
+        if let Some(connection) = connection {
+            resp.headers_mut().insert(CONNECTION, connection);
+        }
+    }
+}
+
+/// Checks an `alt-value`: `protocol-id="alt-authority"` followed by optional parameters.
+fn is_valid_alt_value(alt: &str) -> bool {
+    let mut parts = alt.split(';').map(|s| s.trim());
+    let alternative = parts
+        .next()
+        .and_then(|alternative| alternative.split_once('='));
+    let is_valid_alternative = alternative.is_some_and(|(protocol, authority)| {
+        let port = authority
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .and_then(|s| s.rsplit_once(':'))
+            .map(|(_host, port)| port);
+        is_token(protocol) && port.is_some_and(|port| port.parse::<u16>().is_ok_and(|p| p > 0))
+    });
+    is_valid_alternative
+        && parts.all(|param| match param.split_once('=') {
+            Some(("ma", value)) => value.parse::<u64>().is_ok(),
+            Some(("persist", value)) => value == "1",
+            Some((name, value)) => {
+                is_token(name)
+                    && (is_token(value)
+                        || (value.len() >= 2
+                            && value.starts_with('"')
+                            && value.ends_with('"')
+                            && !value[1..value.len() - 1].contains('"')))
+            }
+            None => false,
+        })
+}
+
+/// Checks a `token` as defined by RFC 9110.
+fn is_token(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_alt_svc, parse_upgrade};
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(parse_alt_svc("").unwrap(), None);
+        assert_eq!(parse_alt_svc("clear").unwrap().unwrap(), "clear");
+        for value in [
+            "h3=\":443\"",
+            "h3=\":443\"; ma=86400",
+            "h3=\"edge.example.com:443\"; ma=3600; persist=1, h3-29=\":443\"",
+        ] {
+            assert_eq!(parse_alt_svc(value).unwrap().unwrap(), value);
+        }
+        for value in [
+            "h3",
+            "h3=:443",
+            "h3=\":0\"",
+            "h3=\"example.com\"",
+            "h3=\":443\"; ma=-1",
+            "h3=\":443\"; persist=yes",
+            "h3=\":443\",",
+            "h 3=\":443\"",
+        ] {
+            assert!(parse_alt_svc(value).is_err(), "{value}");
+        }
+
+        assert_eq!(parse_upgrade(" ").unwrap(), None);
+        assert_eq!(
+            parse_upgrade("h2c,websocket/13").unwrap().unwrap(),
+            "h2c, websocket/13"
+        );
+        assert!(parse_upgrade("h2c, web socket").is_err());
+        assert!(parse_upgrade("websocket/").is_err());
+    }
+}
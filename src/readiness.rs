@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to track the server readiness state.
+//!
+//! The server starts in the `starting` state until its initialization completes
+//! (e.g. the listener is bound and the ACME certificate is available), then it's `ready`
+//! until a termination signal is caught and the server is `draining`.
+//! Requests arriving while starting get a `503 Service Unavailable` response.
+//!
+
+use hyper::{header::RETRY_AFTER, Body, HeaderMap, Method, Response, StatusCode, Uri};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::{error_page, Result};
+
+const STARTING: u8 = 0;
+const READY: u8 = 1;
+const DRAINING: u8 = 2;
+
+/// `Retry-After` value in seconds of the responses sent while the server is starting.
+pub const STARTUP_RETRY_AFTER_SECS: u64 = 1;
+
+/// Server readiness state shared by the handler and the signal listeners.
+#[derive(Debug, Default)]
+pub struct Readiness(AtomicU8);
+
+impl Readiness {
+    /// Marks the server as ready unless it's already draining.
+    pub fn set_ready(&self) {
+        let _ = self
+            .0
+            .compare_exchange(STARTING, READY, Ordering::Relaxed, Ordering::Relaxed);
+    }
+
+    /// Marks the server as draining, it's never ready again afterwards.
+    pub fn set_draining(&self) {
+        self.0.store(DRAINING, Ordering::Relaxed);
+    }
+
+    /// Checks if the server is ready to accept requests.
+    pub fn is_ready(&self) -> bool {
+        self.0.load(Ordering::Relaxed) == READY
+    }
+
+    /// Checks if the server initialization is still in progress.
+    pub fn is_starting(&self) -> bool {
+        self.0.load(Ordering::Relaxed) == STARTING
+    }
+}
+
+/// Returns the `503 Service Unavailable` response sent while the server is starting.
+pub fn starting_response(
+    uri: &Uri,
+    method: &Method,
+    headers: &HeaderMap,
+    page404: &[u8],
+    page50x: &[u8],
+) -> Result<Response<Body>> {
+    let mut resp = error_page::error_response(
+        uri,
+        method,
+        headers,
+        &StatusCode::SERVICE_UNAVAILABLE,
+        page404,
+        page50x,
+    )?;
+    resp.headers_mut()
+        .insert(RETRY_AFTER, STARTUP_RETRY_AFTER_SECS.into());
+    Ok(resp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Readiness;
+
+    #[test]
+    fn readiness_transitions() {
+        let readiness = Readiness::default();
+        assert!(readiness.is_starting());
+        assert!(!readiness.is_ready());
+
+        readiness.set_ready();
+        assert!(readiness.is_ready());
+        assert!(!readiness.is_starting());
+
+        readiness.set_draining();
+        assert!(!readiness.is_ready());
+        assert!(!readiness.is_starting());
+
+        // A draining server never gets ready again
+        readiness.set_ready();
+        assert!(!readiness.is_ready());
+
+        let readiness = Readiness::default();
+        readiness.set_draining();
+        readiness.set_ready();
+        assert!(!readiness.is_ready());
+        assert!(!readiness.is_starting());
+    }
+}
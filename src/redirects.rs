@@ -17,7 +17,9 @@ pub fn get_redirection<'a>(
     if let Some(redirects_vec) = redirects_opts_vec {
         for redirect_entry in redirects_vec.iter() {
             // Match source glob pattern against the request uri path
-            if redirect_entry.source.is_match(uri_path) {
+            if redirect_entry.source.is_match(uri_path)
+                && !redirect_entry.exclude.iter().any(|m| m.is_match(uri_path))
+            {
                 return Some(redirect_entry);
             }
         }
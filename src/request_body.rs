@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to discard the request bodies sent along with the `GET`, `HEAD` and `OPTIONS` methods.
+//!
+//! Those bodies have no meaning for the server, so they are read and discarded before
+//! responding in order to keep the connection in a clean state for the next requests.
+//! Bodies exceeding a maximum size are rejected instead of being read.
+//!
+
+use hyper::{body::HttpBody, Body, Method, Request};
+
+/// Reads and discards the body of a `GET`, `HEAD` or `OPTIONS` request.
+///
+/// It returns `false` if the body exceeds the given maximum size (declared by
+/// its `Content-Length` header or actually read) or can't be read.
+/// Bodies of other methods are left untouched.
+pub async fn discard(req: &mut Request<Body>, max_size: u64) -> bool {
+    if !matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return true;
+    }
+    let body = req.body_mut();
+    if body.is_end_stream() {
+        return true;
+    }
+    if body.size_hint().lower() > max_size {
+        tracing::debug!(
+            "request body of {} bytes exceeds the maximum size of {} bytes",
+            body.size_hint().lower(),
+            max_size
+        );
+        return false;
+    }
+
+    let mut size = 0;
+    while let Some(chunk) = body.data().await {
+        match chunk {
+            Ok(chunk) => {
+                size += chunk.len() as u64;
+                if size > max_size {
+                    tracing::debug!(
+                        "request body exceeds the maximum size of {} bytes",
+                        max_size
+                    );
+                    return false;
+                }
+            }
+            Err(err) => {
+                tracing::debug!("unable to read the request body: {:?}", err);
+                return false;
+            }
+        }
+    }
+    tracing::trace!("request body of {} bytes discarded", size);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::discard;
+    use hyper::{Body, Method, Request};
+
+    fn request(method: Method, body: &'static str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn discard_request_body() {
+        let mut req = request(Method::GET, "hello");
+        assert!(discard(&mut req, 5).await);
+        assert!(hyper::body::to_bytes(req.into_body())
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert!(discard(&mut request(Method::GET, ""), 0).await);
+        assert!(!discard(&mut request(Method::GET, "hello"), 4).await);
+        assert!(!discard(&mut request(Method::HEAD, "hello"), 0).await);
+
+        // Other methods are left untouched
+        let mut req = request(Method::POST, "hello");
+        assert!(discard(&mut req, 0).await);
+        assert_eq!(
+            hyper::body::to_bytes(req.into_body()).await.unwrap(),
+            "hello"
+        );
+    }
+}
@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to attach a request ID to every request and its response via the `X-Request-Id` header.
+//!
+//! A random UUID (v4) is generated for every request unless an inbound `X-Request-Id`
+//! header is sent by a trusted proxy, in which case its value is used instead.
+//!
+
+use hyper::{header::HeaderValue, HeaderMap};
+use std::net::{IpAddr, SocketAddr};
+
+use crate::{Context, Result};
+
+/// Request and response header containing the request ID.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Maximum length of an inbound request ID.
+const REQUEST_ID_MAX_LEN: usize = 128;
+
+/// Request ID stored in the request extensions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub HeaderValue);
+
+impl RequestId {
+    /// Returns the request ID as a string slice.
+    pub fn as_str(&self) -> &str {
+        self.0.to_str().unwrap_or_default()
+    }
+}
+
+/// Parses a comma-separated list of trusted proxy IP addresses.
+pub fn parse_trusted_proxies(proxies: &str) -> Result<Vec<IpAddr>> {
+    proxies
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.parse::<IpAddr>()
+                .with_context(|| format!("invalid trusted proxy ip address: {s}"))
+        })
+        .collect()
+}
+
+/// Returns the request ID of the current request.
+/// The inbound `X-Request-Id` header is only used if the remote address is a trusted proxy
+/// and its value is valid, otherwise a new ID is generated.
+pub fn resolve(
+    headers: &HeaderMap,
+    remote_addr: Option<SocketAddr>,
+    trusted_proxies: &[IpAddr],
+) -> RequestId {
+    let trusted = remote_addr.is_some_and(|addr| trusted_proxies.contains(&addr.ip()));
+    if trusted {
+        if let Some(val) = headers.get(REQUEST_ID_HEADER).filter(|v| is_valid(v)) {
+            return RequestId(val.clone());
+        }
+    }
+    RequestId(HeaderValue::from_str(&generate()).expect("invalid generated request id"))
+}
+
+/// Generates a random UUID (v4) string.
+pub fn generate() -> String {
+    let mut b = [0u8; 16];
+    if let Err(err) = getrandom::getrandom(&mut b) {
+        tracing::error!("unable to get random bytes for the request id: {:?}", err);
+    }
+    // Version 4 and RFC 4122 variant bits
+    b[6] = (b[6] & 0x0f) | 0x40;
+    b[8] = (b[8] & 0x3f) | 0x80;
+    let hex = format!("{:032x}", u128::from_be_bytes(b));
+    [
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..],
+    ]
+    .join("-")
+}
+
+/// Checks if an inbound request ID only contains a safe subset of characters.
+fn is_valid(val: &HeaderValue) -> bool {
+    let bytes = val.as_bytes();
+    !bytes.is_empty()
+        && bytes.len() <= REQUEST_ID_MAX_LEN
+        && bytes
+            .iter()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, b'-' | b'_' | b'.' | b':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate, parse_trusted_proxies, resolve, REQUEST_ID_HEADER};
+    use hyper::HeaderMap;
+
+    #[test]
+    fn generate_uuid_v4() {
+        let id = generate();
+        assert_eq!(id.len(), 36);
+        assert_eq!(&id[14..15], "4");
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(id, generate());
+    }
+
+    #[test]
+    fn inbound_request_id_from_trusted_proxies() {
+        let trusted = parse_trusted_proxies("10.0.0.1, ::1").unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_ID_HEADER, "abc-123".parse().unwrap());
+
+        let id = resolve(&headers, Some("10.0.0.1:8080".parse().unwrap()), &trusted);
+        assert_eq!(id.as_str(), "abc-123");
+
+        // Untrusted clients get a new request id
+        let id = resolve(&headers, Some("10.0.0.2:8080".parse().unwrap()), &trusted);
+        assert_ne!(id.as_str(), "abc-123");
+        let id = resolve(&headers, None, &trusted);
+        assert_ne!(id.as_str(), "abc-123");
+
+        // Invalid values are replaced too
+        headers.insert(REQUEST_ID_HEADER, "<script>".parse().unwrap());
+        let id = resolve(&headers, Some("10.0.0.1:8080".parse().unwrap()), &trusted);
+        assert_eq!(id.as_str().len(), 36);
+
+        assert!(parse_trusted_proxies("10.0.0.1, localhost").is_err());
+    }
+}
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to limit the time spent on producing a response.
+//!
+//! Requests exceeding the timeout get a `504 Gateway Timeout` response
+//! and their pending handling is cancelled.
+//! Note that a file operation already running on the blocking thread pool can not be interrupted,
+//! so its thread is only released once the operation returns.
+//! The response body streaming can be optionally limited too, in which case
+//! the body is aborted once the deadline is reached.
+//!
+
+use bytes::Bytes;
+use futures_util::Stream;
+use hyper::{body::HttpBody, Body, Request, Response, StatusCode};
+use pin_project::pin_project;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{Instant, Sleep};
+
+use crate::{error_page, handler::RequestHandler, Error};
+
+/// Handles a request returning a `504 Gateway Timeout` response if it is not produced in time.
+///
+/// The blocking file operations of the handler run on the blocking thread pool,
+/// so the timeout can be reached even if the handling is stalled by the file system.
+/// The pending handling is dropped (cancelled) once the timeout is reached.
+pub async fn handle(
+    handler: Arc<RequestHandler>,
+    mut req: Request<Body>,
+    remote_addr: Option<SocketAddr>,
+    timeout: Duration,
+) -> Result<Response<Body>, Error> {
+    let deadline = Instant::now() + timeout;
+    let (method, uri, headers) = (
+        req.method().clone(),
+        req.uri().clone(),
+        req.headers().clone(),
+    );
+
+    match tokio::time::timeout_at(deadline, handler.handle(&mut req, remote_addr)).await {
+        Ok(resp) => {
+            let resp = resp?;
+            if !handler.opts.response_timeout_body {
+                return Ok(resp);
+            }
+            let (head, body) = resp.into_parts();
+            Ok(Response::from_parts(
+                head,
+                body_with_deadline(body, deadline),
+            ))
+        }
+        Err(_) => {
+            tracing::warn!("response timeout exceeded: method={} uri={}", method, uri);
+            error_page::error_response(
+                &uri,
+                &method,
+                &headers,
+                &StatusCode::GATEWAY_TIMEOUT,
+                &handler.opts.page404,
+                &handler.opts.page50x,
+            )
+        }
+    }
+}
+
+/// Wraps a response body aborting it once the given deadline is reached.
+pub fn body_with_deadline(body: Body, deadline: Instant) -> Body {
+    Body::wrap_stream(DeadlineBody {
+        body,
+        sleep: tokio::time::sleep_until(deadline),
+    })
+}
+
+/// A body stream which fails once its deadline is reached.
+#[pin_project]
+struct DeadlineBody {
+    #[pin]
+    body: Body,
+    #[pin]
+    sleep: Sleep,
+}
+
+impl Stream for DeadlineBody {
+    type Item = Result<Bytes, io::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        if this.sleep.poll(cx).is_ready() {
+            tracing::warn!("response timeout exceeded, aborting the response body");
+            return Poll::Ready(Some(Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "response timeout exceeded",
+            ))));
+        }
+        match this.body.poll_data(cx) {
+            Poll::Ready(Some(Ok(data))) => Poll::Ready(Some(Ok(data))),
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(io::Error::other(err)))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::body_with_deadline;
+    use hyper::Body;
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    #[tokio::test]
+    async fn body_before_deadline() {
+        let body = body_with_deadline(Body::from("hello"), Instant::now() + Duration::from_secs(5));
+        let buf = hyper::body::to_bytes(body).await.unwrap();
+        assert_eq!(buf, "hello");
+    }
+
+    #[tokio::test]
+    async fn body_after_deadline() {
+        let (_sender, body) = Body::channel();
+        let body = body_with_deadline(body, Instant::now() + Duration::from_millis(50));
+        assert!(hyper::body::to_bytes(body).await.is_err());
+    }
+}
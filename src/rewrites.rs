@@ -17,7 +17,9 @@ pub fn rewrite_uri_path<'a>(
     if let Some(rewrites_vec) = rewrites_opts_vec {
         for rewrites_entry in rewrites_vec.iter() {
             // Match source glob pattern against request uri path
-            if rewrites_entry.source.is_match(uri_path) {
+            if rewrites_entry.source.is_match(uri_path)
+                && !rewrites_entry.exclude.iter().any(|m| m.is_match(uri_path))
+            {
                 return Some(rewrites_entry);
             }
         }
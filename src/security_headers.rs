@@ -13,14 +13,17 @@ use hyper::{Body, Response};
 
 /// It appends security headers like `Strict-Transport-Security: max-age=63072000; includeSubDomains; preload` (2 years max-age),
 ///`X-Frame-Options: DENY` and `Content-Security-Policy: frame-ancestors 'self'`.
-pub fn append_headers(resp: &mut Response<Body>) {
+/// The `Strict-Transport-Security` header is only appended if `hsts` is `true`.
+pub fn append_headers(resp: &mut Response<Body>, hsts: bool) {
     // Strict-Transport-Security (HSTS)
-    resp.headers_mut().insert(
-        STRICT_TRANSPORT_SECURITY,
-        "max-age=63072000; includeSubDomains; preload"
-            .parse()
-            .unwrap(),
-    );
+    if hsts {
+        resp.headers_mut().insert(
+            STRICT_TRANSPORT_SECURITY,
+            "max-age=63072000; includeSubDomains; preload"
+                .parse()
+                .unwrap(),
+        );
+    }
 
     // X-Frame-Options
     resp.headers_mut()
@@ -6,25 +6,62 @@
 //! Server module intended to construct a multi-thread HTTP or HTTP/2 web server.
 //!
 
-use hyper::server::Server as HyperServer;
+use hyper::server::{conn::AddrIncoming, Builder, Server as HyperServer};
+#[cfg(feature = "fallback-page")]
+use hyper::StatusCode;
 use listenfd::ListenFd;
+use socket2::{Domain, Protocol, Socket, Type};
 use std::net::{IpAddr, SocketAddr, TcpListener};
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::watch::Receiver;
 
 use crate::handler::{RequestHandler, RequestHandlerOpts};
+use crate::logger;
+use crate::readiness::Readiness;
 #[cfg(any(unix, windows))]
 use crate::signals;
+use crate::stats::Stats;
+
+#[cfg(feature = "acme")]
+use crate::acme;
+#[cfg(feature = "archive")]
+use crate::archive::{self, ArchiveRoot};
+#[cfg(feature = "basic-auth")]
+use crate::basic_auth;
+#[cfg(feature = "compression")]
+use crate::compression;
+#[cfg(feature = "directory-listing")]
+use crate::directory_listing;
 
 #[cfg(feature = "http2")]
 use {
-    crate::tls::{TlsAcceptor, TlsConfigBuilder},
-    crate::{error, error_page, https_redirect},
-    hyper::server::conn::{AddrIncoming, AddrStream},
+    crate::tls::{self, PlainHttpRedirectOpts, TlsAcceptor, TlsClientAuthMode, TlsConfigBuilder},
+    crate::{connection_limit::LimitedConn, error, error_page, https_redirect},
+    hyper::server::conn::AddrStream,
     hyper::service::{make_service_fn, service_fn},
+    std::path::PathBuf,
 };
 
-use crate::{cors, helpers, Settings};
+use crate::{
+    allowed_hosts::AllowedHosts,
+    canonical_host::CanonicalHost,
+    canonical_link::CanonicalLink,
+    connection_limit::LimitedIncoming,
+    control_headers::{self, CacheDirectives},
+    cors,
+    default_files::DefaultFiles,
+    helpers,
+    internal_redirect::InternalRedirectOpts,
+    maintenance_mode::{self, MaintenanceOpts},
+    not_found_cache::NotFoundCache,
+    protocol_hints, request_id, server_header,
+    sitemap::Sitemap,
+    static_files, url_prefix,
+    version_endpoint::VersionEndpoint,
+    Settings,
+};
 use crate::{service::RouterService, Context, Result};
 
 /// Define a multi-thread HTTP or HTTP/2 web server.
@@ -38,11 +75,7 @@ impl Server {
     /// Create new multi-thread server instance.
     pub fn new(opts: Settings) -> Result<Server> {
         // Configure number of worker threads
-        let cpus = num_cpus::get();
-        let worker_threads = match opts.general.threads_multiplier {
-            0 | 1 => cpus,
-            n => cpus * n,
-        };
+        let worker_threads = worker_threads(opts.general.threads, opts.general.threads_multiplier);
         let max_blocking_threads = opts.general.max_blocking_threads;
 
         Ok(Server {
@@ -85,10 +118,14 @@ impl Server {
                 tracing::trace!("tokio runtime initialized");
                 if let Err(err) = self.start_server(cancel_recv, cancel_fn).await {
                     tracing::error!("server failed to start up: {:?}", err);
+                    logger::flush();
                     std::process::exit(1)
                 }
             });
 
+        // Make sure buffered log entries are written before exiting
+        logger::flush();
+
         Ok(())
     }
 
@@ -102,20 +139,71 @@ impl Server {
         server_info!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
 
         // Config "general" options
+        #[cfg(feature = "http2")]
+        let mut general = self.opts.general;
+        #[cfg(not(feature = "http2"))]
         let general = self.opts.general;
         // Config-file "advanced" options
         let advanced_opts = self.opts.advanced;
 
-        server_info!("log level: {}", general.log_level);
+        server_info!(
+            "log level: {}",
+            general.log_level.as_deref().unwrap_or_default()
+        );
+        server_info!("log file: {}", general.log_file.display());
 
         // Config file option
-        if let Some(config_file) = general.config_file {
+        if let Some(config_file) = &general.config_file {
             server_info!("config file: {}", config_file.display());
         }
 
+        // Check the TLS certificate and key files before binding the listener
+        #[cfg(feature = "http2")]
+        let tls_fallback = {
+            #[cfg(feature = "acme")]
+            let acme = general.acme;
+            #[cfg(not(feature = "acme"))]
+            let acme = false;
+            let mut tls_fallback = false;
+            if general.http2 && !acme {
+                if let Err(err) = tls::validate_cert_key_files(
+                    general.http2_tls_cert.as_deref(),
+                    general.http2_tls_key.as_deref(),
+                ) {
+                    if !general.http2_tls_fallback {
+                        return Err(err);
+                    }
+                    // Plain HTTP would silently skip the client certificate authentication
+                    if general.http2_tls_client_ca.is_some() {
+                        bail!(
+                            "{}, tls fallback refused since the client certificate authentication is configured",
+                            err
+                        );
+                    }
+                    tracing::warn!("{}, falling back to plain http (http2 disabled)", err);
+                    general.http2 = false;
+                    tls_fallback = true;
+                }
+            }
+            server_info!("http2 tls fallback: enabled={}", general.http2_tls_fallback);
+            tls_fallback
+        };
+        #[cfg(not(feature = "http2"))]
+        let tls_fallback = false;
+
         // Determine TCP listener either file descriptor or TCP socket
         let (tcp_listener, addr_str);
         match general.fd {
+            None if general.socket_activation => {
+                tcp_listener = systemd_tcp_listener()?;
+                addr_str = tcp_listener
+                    .local_addr()
+                    .map_or_else(|_| "@FD(systemd)".to_owned(), |addr| addr.to_string());
+                server_info!(
+                    "converted systemd socket activation file descriptor to a 'tcp' listener {}",
+                    addr_str
+                );
+            }
             Some(fd) => {
                 addr_str = format!("@FD({fd})");
                 tcp_listener = ListenFd::from_env()
@@ -132,13 +220,18 @@ impl Server {
                     .parse::<IpAddr>()
                     .with_context(|| format!("failed to parse {} address", general.host))?;
                 let addr = SocketAddr::from((ip, general.port));
-                tcp_listener = TcpListener::bind(addr)
+                tcp_listener = bind_tcp_listener(addr, general.reuse_port)
                     .with_context(|| format!("failed to bind to {addr} address"))?;
                 addr_str = addr.to_string();
                 server_info!("server bound to tcp socket {}", addr_str);
             }
         }
 
+        // TCP socket options
+        let tcp_nodelay = general.tcp_nodelay;
+        server_info!("tcp nodelay: enabled={}", tcp_nodelay);
+        server_info!("tcp reuse port: enabled={}", general.reuse_port);
+
         // Number of worker threads option
         let threads = self.worker_threads;
         server_info!("runtime worker threads: {}", threads);
@@ -149,10 +242,81 @@ impl Server {
             general.max_blocking_threads
         );
 
+        // Unpack the root archive if any
+        #[cfg(feature = "archive")]
+        let archive_root = if archive::is_archive(&general.root) {
+            let archive_root = ArchiveRoot::unpack(&general.root)?;
+            server_info!(
+                "root archive {} unpacked into {}",
+                general.root.display(),
+                archive_root.path().display()
+            );
+            Some(archive_root)
+        } else {
+            None
+        };
+        #[cfg(feature = "archive")]
+        let root = archive_root
+            .as_ref()
+            .map_or(general.root.as_path(), |a| a.path());
+        #[cfg(not(feature = "archive"))]
+        let root = general.root.as_path();
+
         // Check for a valid root directory
-        let root_dir = helpers::get_valid_dirpath(&general.root)
+        let root_dir = helpers::get_valid_dirpath(root)
             .with_context(|| "root directory was not found or inaccessible")?;
 
+        // Overlay root directory option
+        let overlay_root = if general.overlay_root.as_os_str().is_empty() {
+            None
+        } else {
+            let overlay_root = helpers::get_valid_dirpath(&general.overlay_root)
+                .with_context(|| "overlay root directory was not found or inaccessible")?;
+            Some(overlay_root)
+        };
+        server_info!(
+            "overlay root: enabled={}, path=\"{}\"",
+            overlay_root.is_some(),
+            general.overlay_root.display()
+        );
+
+        // ACME certificates
+        #[cfg(feature = "acme")]
+        let acme_opts = if general.acme {
+            if !general.http2 || !general.https_redirect {
+                bail!("acme requires the http2 and https-redirect options to be enabled")
+            }
+            if !general.acme_tos_agreed {
+                bail!("acme requires agreeing to the terms of service of the acme server via the acme-tos-agreed option")
+            }
+            Some(acme::AcmeOpts::new(
+                &general.acme_email,
+                &general.acme_domains,
+                general.acme_cache_dir.clone(),
+                &general.acme_directory_url,
+                general.acme_tos_agreed,
+            )?)
+        } else {
+            None
+        };
+
+        // ACME challenge files served by the HTTP to HTTPS redirect server
+        #[cfg(feature = "http2")]
+        let acme_challenge_opts = https_redirect::AcmeChallengeOpts::new(
+            &general.https_redirect_acme_challenge_path,
+            general.https_redirect_acme_challenge_dir.clone(),
+            root_dir.clone(),
+        );
+        #[cfg(feature = "acme")]
+        let acme_challenge_opts = match &acme_opts {
+            Some(opts) => https_redirect::AcmeChallengeOpts::new(
+                acme::ACME_CHALLENGE_PATH,
+                opts.challenge_dir(),
+                root_dir.clone(),
+            ),
+            None => acme_challenge_opts,
+        };
+
         // Custom error pages content
         let page404 = helpers::read_bytes_default(&general.page404);
         let page50x = helpers::read_bytes_default(&general.page50x);
@@ -176,10 +340,40 @@ impl Server {
                 page_fallback_opt
             );
         }
+        // Fallback page status code option
+        #[cfg(feature = "fallback-page")]
+        let page_fallback_status = match general.page_fallback_status {
+            200 => StatusCode::OK,
+            404 => StatusCode::NOT_FOUND,
+            n => bail!("fallback page status code {n} is not supported, use either 200 or 404"),
+        };
+        #[cfg(feature = "fallback-page")]
+        server_info!("fallback page status code: {}", page_fallback_status);
+
+        // Nested fallback pages option
+        #[cfg(feature = "fallback-page")]
+        let page_fallback_nested = helpers::parse_glob_list(&general.page_fallback_nested)?;
+        #[cfg(feature = "fallback-page")]
+        server_info!(
+            "nested fallback pages: enabled={}, patterns={}",
+            !page_fallback_nested.is_empty(),
+            page_fallback_nested
+                .iter()
+                .map(|m| m.glob().glob())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
 
         // Security Headers option
+        // NOTE: the HSTS header is left out once fallen back to plain http since the
+        // security headers may only be enabled by default along with http2.
         let security_headers = general.security_headers;
-        server_info!("security headers: enabled={}", security_headers);
+        let security_headers_hsts = security_headers && !tls_fallback;
+        server_info!(
+            "security headers: enabled={}, hsts={}",
+            security_headers,
+            security_headers_hsts
+        );
 
         // Auto compression based on the `Accept-Encoding` header
         #[cfg(not(feature = "compression"))]
@@ -197,6 +391,82 @@ impl Server {
         #[cfg(feature = "compression")]
         server_info!("compression static: enabled={}", compression_static);
 
+        // Buffer size threshold for auto-compressed responses
+        #[cfg(feature = "compression")]
+        let compression_buffer_size = general.compression_buffer_size;
+        #[cfg(feature = "compression")]
+        server_info!("compression buffer size: {} bytes", compression_buffer_size);
+
+        // Minimum size of auto-compressed responses
+        #[cfg(feature = "compression")]
+        let compression_min_size = general.compression_min_size;
+        #[cfg(feature = "compression")]
+        server_info!("compression min size: {} bytes", compression_min_size);
+
+        // Auto-compression of HTTP/1.0 requests
+        #[cfg(feature = "compression")]
+        let compression_http10 = general.compression_http10;
+        #[cfg(feature = "compression")]
+        server_info!("compression http/1.0: enabled={}", compression_http10);
+
+        // Compression MIME types
+        #[cfg(feature = "compression")]
+        let compression_types = compression::parse_types(&general.compression_types)?;
+        #[cfg(feature = "compression")]
+        server_info!(
+            "compression types: {}",
+            compression_types
+                .iter()
+                .map(|m| m.glob().glob())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+
+        // Compression encodings
+        #[cfg(feature = "compression")]
+        let compression_encodings = compression::parse_encodings(&general.compression_encodings)?;
+        #[cfg(feature = "compression")]
+        server_info!(
+            "compression encodings: {}",
+            compression_encodings
+                .iter()
+                .map(|c| c.to_static())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+
+        // Compression user agents denylist
+        #[cfg(feature = "compression")]
+        let compression_user_agent_denylist =
+            compression::parse_user_agents(&general.compression_user_agent_denylist)?;
+        #[cfg(feature = "compression")]
+        server_info!(
+            "compression user agent denylist: {}",
+            compression_user_agent_denylist
+                .iter()
+                .map(|m| m.glob().glob())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+
+        // Adaptive compression level
+        #[cfg(feature = "compression")]
+        let adaptive_compression = if general.adaptive_compression {
+            Some(compression::AdaptiveCompression::new(
+                general.adaptive_compression_idle_requests,
+                general.adaptive_compression_busy_requests,
+            )?)
+        } else {
+            None
+        };
+        #[cfg(feature = "compression")]
+        server_info!(
+            "adaptive compression: enabled={}, idle_requests={}, busy_requests={}",
+            general.adaptive_compression,
+            general.adaptive_compression_idle_requests,
+            general.adaptive_compression_busy_requests
+        );
+
         // Directory listing options
         #[cfg(feature = "directory-listing")]
         let dir_listing = general.directory_listing;
@@ -212,11 +482,98 @@ impl Server {
         let dir_listing_format = general.directory_listing_format;
         #[cfg(feature = "directory-listing")]
         server_info!("directory listing format: {:?}", dir_listing_format);
+        // Directory listing size format
+        #[cfg(feature = "directory-listing")]
+        let dir_listing_size_format = general.directory_listing_size_format;
+        #[cfg(feature = "directory-listing")]
+        server_info!(
+            "directory listing size format: {:?}",
+            dir_listing_size_format
+        );
+        // Directory listing date format and timezone
+        #[cfg(feature = "directory-listing")]
+        let dir_listing_date_format = general.directory_listing_date_format;
+        #[cfg(feature = "directory-listing")]
+        directory_listing::validate_date_format(&dir_listing_date_format)?;
+        #[cfg(feature = "directory-listing")]
+        server_info!("directory listing date format: {}", dir_listing_date_format);
+        #[cfg(feature = "directory-listing")]
+        let dir_listing_timezone = general.directory_listing_timezone;
+        #[cfg(feature = "directory-listing")]
+        server_info!("directory listing timezone: {:?}", dir_listing_timezone);
+        // Directory listing ignore patterns
+        #[cfg(feature = "directory-listing")]
+        let dir_listing_ignore = helpers::parse_glob_list(&general.directory_listing_ignore)?;
+        #[cfg(feature = "directory-listing")]
+        server_info!(
+            "directory listing ignore patterns: {}",
+            general.directory_listing_ignore
+        );
+        // Directory listing symbolic links
+        #[cfg(feature = "directory-listing")]
+        let dir_listing_show_symlinks = general.directory_listing_show_symlinks;
+        #[cfg(feature = "directory-listing")]
+        server_info!(
+            "directory listing show symlinks: enabled={}",
+            dir_listing_show_symlinks
+        );
+        // Directory listing merge of the overlay root entries
+        #[cfg(feature = "directory-listing")]
+        let dir_listing_merge_overlay = general.directory_listing_merge_overlay;
+        #[cfg(feature = "directory-listing")]
+        server_info!(
+            "directory listing merge overlay: enabled={}",
+            dir_listing_merge_overlay
+        );
 
         // Cache control headers option
         let cache_control_headers = general.cache_control_headers;
         server_info!("cache control headers: enabled={}", cache_control_headers);
 
+        // Default files options
+        let default_files = DefaultFiles::new(
+            &general.default_robots_txt,
+            general.default_favicon.as_deref(),
+        )
+        .with_context(|| "failed to load the default favicon file")?;
+        server_info!(
+            "default files: robots.txt={}, favicon.ico={}",
+            default_files.robots_txt.is_some(),
+            default_files.favicon.is_some()
+        );
+
+        // Version endpoint option
+        let version_endpoint = VersionEndpoint::new(&general.version_endpoint)?;
+        server_info!(
+            "version endpoint: enabled={}, path={}",
+            version_endpoint.is_some(),
+            general.version_endpoint
+        );
+
+        // Cache control directives options
+        let cache_control_directives = CacheDirectives {
+            visibility: general.cache_control_visibility,
+            max_age: general.cache_control_max_age,
+            s_maxage: general.cache_control_s_maxage,
+            stale_while_revalidate: general.cache_control_stale_while_revalidate,
+            must_revalidate: general.cache_control_must_revalidate,
+        };
+        cache_control_directives
+            .validate()
+            .with_context(|| "invalid cache control directives")?;
+        if !cache_control_directives.is_empty() {
+            server_info!("cache control directives: {:?}", cache_control_directives);
+        }
+
+        // Cache control of the site icons option
+        let cache_control_icons =
+            control_headers::icons_header_value(general.cache_control_icons_max_age)?;
+        server_info!(
+            "cache control icons: enabled={}, max-age={}",
+            cache_control_icons.is_some(),
+            general.cache_control_icons_max_age
+        );
+
         // CORS option
         let cors = cors::new(
             general.cors_allow_origins.trim(),
@@ -228,15 +585,50 @@ impl Server {
         // `Basic` HTTP Authentication Schema option
         let basic_auth = general.basic_auth.trim().to_owned();
         #[cfg(feature = "basic-auth")]
+        let basic_auth_www_authenticate = basic_auth::www_authenticate(&general.basic_auth_realm)?;
+        #[cfg(feature = "basic-auth")]
         server_info!(
-            "basic authentication: enabled={}",
-            !general.basic_auth.is_empty()
+            "basic authentication: enabled={}, realm={:?}",
+            !general.basic_auth.is_empty(),
+            general.basic_auth_realm
         );
 
         // Log remote address option
         let log_remote_address = general.log_remote_address;
         server_info!("log remote address: enabled={}", log_remote_address);
 
+        // Directory index files option
+        let index_files = static_files::parse_index_files(&general.index_files)?;
+        server_info!("index files: {}", index_files.join(","));
+
+        // Disable index option
+        let disable_index = general.disable_index;
+        server_info!("disable index: enabled={}", disable_index);
+        let index_files = if disable_index { vec![] } else { index_files };
+
+        // Root page option
+        let root_page = static_files::parse_root_page(&general.root_page)?;
+        server_info!(
+            "root page: enabled={}, path={}",
+            root_page.is_some(),
+            root_page.as_deref().unwrap_or_default()
+        );
+
+        // Extensionless HTML paths option
+        let extensionless_html = general.extensionless_html;
+        server_info!("extensionless html: enabled={}", extensionless_html);
+
+        // Allowed request methods option
+        let allowed_methods = static_files::parse_allowed_methods(&general.allowed_methods)?;
+        server_info!(
+            "allowed methods: {}",
+            allowed_methods
+                .iter()
+                .map(|m| m.as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+
         // Log redirect trailing slash option
         let redirect_trailing_slash = general.redirect_trailing_slash;
         server_info!(
@@ -256,31 +648,357 @@ impl Server {
         let health = general.health;
         server_info!("health endpoint: enabled={}", health);
 
+        // Liveness and readiness health endpoints option
+        let health_liveness_path = general.health_liveness_path.trim().to_owned();
+        let health_readiness_path = general.health_readiness_path.trim().to_owned();
+        if !health_liveness_path.starts_with('/') || !health_readiness_path.starts_with('/') {
+            bail!("health liveness and readiness paths should start with a slash");
+        }
+        if health {
+            server_info!(
+                "health liveness endpoint: {}, readiness endpoint: {}",
+                health_liveness_path,
+                health_readiness_path
+            );
+        }
+        // Server readiness state, it's only ready once initialized and until a termination signal is caught
+        let ready = Arc::new(Readiness::default());
+        // Server counters summarized once the server is shut down
+        let stats = Arc::new(Stats::default());
+
+        // Case-insensitive paths option
+        let case_insensitive_paths = general.case_insensitive_paths;
+        server_info!("case-insensitive paths: enabled={}", case_insensitive_paths);
+
+        // Disable range requests option
+        let disable_range_requests = general.disable_range_requests;
+        server_info!("range requests: enabled={}", !disable_range_requests);
+
+        // Entity tags mode option
+        let etag_mode = general.etag_mode;
+        server_info!("etag mode: {:?}", etag_mode);
+
+        // Max connections option
+        let max_connections = general.max_connections;
+        let max_connections_mode = general.max_connections_mode;
+        server_info!(
+            "max connections: enabled={}, limit={}, mode={:?}",
+            max_connections > 0,
+            max_connections,
+            max_connections_mode
+        );
+
+        // Header read timeout option
+        let header_read_timeout = general.header_read_timeout;
+        server_info!(
+            "header read timeout: enabled={}, secs={}",
+            header_read_timeout > 0,
+            header_read_timeout
+        );
+
+        // Request body maximum size option
+        let request_body_max_size = general.request_body_max_size;
+        server_info!("request body max size: bytes={}", request_body_max_size);
+
+        // Response timeout option
+        let response_timeout =
+            (general.response_timeout > 0).then(|| Duration::from_secs(general.response_timeout));
+        let response_timeout_body = general.response_timeout_body;
+        server_info!(
+            "response timeout: enabled={}, secs={}, body={}",
+            response_timeout.is_some(),
+            general.response_timeout,
+            response_timeout_body
+        );
+
+        // Slow requests threshold option
+        let slow_request_threshold = (general.slow_request_threshold_ms > 0)
+            .then(|| Duration::from_millis(general.slow_request_threshold_ms));
+        server_info!(
+            "slow requests log: enabled={}, threshold_ms={}",
+            slow_request_threshold.is_some(),
+            general.slow_request_threshold_ms
+        );
+
+        // Not found responses cache and rate limit options
+        let not_found_cache = NotFoundCache::new(
+            general.not_found_cache_ttl,
+            general.not_found_cache_size,
+            general.not_found_rate_limit,
+        );
+        server_info!(
+            "not found cache: enabled={}, ttl_secs={}, size={}",
+            general.not_found_cache_ttl > 0 && general.not_found_cache_size > 0,
+            general.not_found_cache_ttl,
+            general.not_found_cache_size
+        );
+        server_info!(
+            "not found rate limit: enabled={}, per_sec={}",
+            general.not_found_rate_limit > 0,
+            general.not_found_rate_limit
+        );
+
+        // Internal paths options
+        let internal_redirect = InternalRedirectOpts::new(
+            &general.internal_path_prefix,
+            &general.internal_path_trusted_proxies,
+            general.case_insensitive_paths,
+        )?;
+        server_info!(
+            "internal paths: enabled={}, prefix={}, trusted_proxies={}",
+            internal_redirect.is_some(),
+            general.internal_path_prefix,
+            general.internal_path_trusted_proxies
+        );
+
+        // Disable keep-alive option
+        let disable_keep_alive = general.disable_keep_alive;
+        server_info!("keep-alive: enabled={}", !disable_keep_alive);
+
+        // Keep-alive max requests option
+        let keep_alive_max_requests = general.keep_alive_max_requests;
+        server_info!(
+            "keep-alive max requests: enabled={}, requests={}",
+            keep_alive_max_requests > 0,
+            keep_alive_max_requests
+        );
+
+        // Canonical host option
+        #[cfg(feature = "http2")]
+        let secure = general.http2;
+        #[cfg(not(feature = "http2"))]
+        let secure = false;
+        let canonical_host = match general.canonical_host.trim() {
+            "" => None,
+            host => Some(CanonicalHost::new(host, secure)?),
+        };
+        server_info!(
+            "canonical host: enabled={}, host={}",
+            canonical_host.is_some(),
+            general.canonical_host.trim()
+        );
+
+        // Canonical link option
+        let canonical_link =
+            CanonicalLink::new(&general.canonical_base_url, general.canonical_skip_query)?;
+        server_info!(
+            "canonical link: enabled={}, base_url={}, skip_query={}",
+            canonical_link.is_some(),
+            general.canonical_base_url.trim(),
+            general.canonical_skip_query
+        );
+
+        // Allowed hosts option
+        let allowed_hosts = AllowedHosts::new(
+            &general.allowed_hosts,
+            general.allowed_hosts_status,
+            advanced_opts
+                .as_ref()
+                .and_then(|advanced| advanced.virtual_hosts.as_ref()),
+        )?;
+        server_info!(
+            "allowed hosts: enabled={}, hosts={}, status={}",
+            allowed_hosts.is_some(),
+            general.allowed_hosts,
+            general.allowed_hosts_status
+        );
+
+        // Server header option
+        let server_header = server_header::parse(&general.server_header)?;
+        server_info!("server header: {}", general.server_header.trim());
+
+        // Alt-Svc and Upgrade headers options
+        let alt_svc = protocol_hints::parse_alt_svc(&general.alt_svc)?;
+        server_info!("alt-svc header: {}", general.alt_svc.trim());
+        let upgrade_protocols = protocol_hints::parse_upgrade(&general.upgrade_protocols)?;
+        server_info!(
+            "upgrade protocols: {}",
+            upgrade_protocols
+                .as_ref()
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+        );
+
+        // URL prefix option
+        let url_prefix = url_prefix::parse(&general.url_prefix)?;
+        server_info!(
+            "url prefix: enabled={}, prefix={}",
+            !url_prefix.is_empty(),
+            url_prefix
+        );
+
+        // Request ID options
+        let request_id = general.request_id;
+        let request_id_trusted_proxies =
+            request_id::parse_trusted_proxies(&general.request_id_trusted_proxies)?;
+        server_info!(
+            "request id: enabled={}, trusted_proxies={:?}",
+            request_id,
+            request_id_trusted_proxies
+        );
+
+        // Maintenance mode options
+        let maintenance_enabled = Arc::new(AtomicBool::new(general.maintenance_mode));
+        server_info!("maintenance mode: enabled={}", general.maintenance_mode);
+        let maintenance = MaintenanceOpts {
+            enabled: maintenance_enabled.clone(),
+            page: helpers::read_bytes_default(&general.maintenance_page),
+            retry_after: general.maintenance_retry_after,
+            allowed_paths: helpers::parse_glob_list(&general.maintenance_allowed_paths)?,
+        };
+
+        // Preload manifest options
+        let preload_manifest = advanced_opts
+            .as_ref()
+            .and_then(|advanced| advanced.preload_manifest.clone());
+        server_info!("preload manifest: enabled={}", preload_manifest.is_some());
+
+        // Sitemap options
+        let sitemap = Sitemap::new(
+            &general.sitemap,
+            general.sitemap_ttl,
+            &general.sitemap_ignore,
+        )?;
+        server_info!(
+            "sitemap: enabled={}, base_url={}, ttl_secs={}, ignore={}",
+            sitemap.is_some(),
+            general.sitemap,
+            general.sitemap_ttl,
+            general.sitemap_ignore
+        );
+
+        // Maintenance mode, preload manifest (config file only) and sitemap reloading via SIGHUP
+        #[cfg(unix)]
+        if general.config_file.is_some() || sitemap.is_some() {
+            let config_file = general.config_file.clone();
+            let sitemap = sitemap.clone();
+            tokio::spawn(async move {
+                let reload_fn = || {
+                    if let Some(config_file) = &config_file {
+                        maintenance_mode::reload(config_file, maintenance_enabled.as_ref());
+                    }
+                    if let Some(preload_manifest) = &preload_manifest {
+                        preload_manifest.reload();
+                    }
+                    if let Some(sitemap) = &sitemap {
+                        sitemap.reload();
+                    }
+                };
+                if let Err(err) = signals::wait_for_sighup(reload_fn).await {
+                    tracing::error!("unable to register the SIGHUP signal: {:?}", err);
+                }
+            });
+        }
+
+        // Log file re-opening via SIGUSR1 (e.g. for logrotate)
+        // NOTE: the signal is registered right away so it never terminates the process
+        #[cfg(unix)]
+        if !general.log_file.as_os_str().is_empty() {
+            let signals = signals::create_sigusr1_signals()
+                .with_context(|| "failed to register the SIGUSR1 signal")?;
+            tokio::spawn(signals::wait_for_sigusr1(
+                signals,
+                || match logger::reopen() {
+                    Ok(Some(path)) => server_info!("log file re-opened: {}", path.display()),
+                    Ok(None) => {}
+                    Err(err) => tracing::error!("{:?}", err),
+                },
+            ));
+        }
+
         // Create a service router for Hyper
         let router_service = RouterService::new(RequestHandler {
             opts: Arc::from(RequestHandlerOpts {
                 root_dir,
+                overlay_root,
                 compression,
                 compression_static,
+                #[cfg(feature = "compression")]
+                compression_buffer_size,
+                #[cfg(feature = "compression")]
+                compression_min_size,
+                #[cfg(feature = "compression")]
+                compression_http10,
+                #[cfg(feature = "compression")]
+                compression_types,
+                #[cfg(feature = "compression")]
+                compression_encodings,
+                #[cfg(feature = "compression")]
+                compression_user_agent_denylist,
+                #[cfg(feature = "compression")]
+                adaptive_compression,
                 #[cfg(feature = "directory-listing")]
                 dir_listing,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_order,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay,
                 cors,
                 security_headers,
+                security_headers_hsts,
                 cache_control_headers,
+                cache_control_directives,
+                cache_control_icons,
+                default_files,
+                version_endpoint,
                 page404: page404.clone(),
                 page50x: page50x.clone(),
                 #[cfg(feature = "fallback-page")]
                 page_fallback,
+                #[cfg(feature = "fallback-page")]
+                page_fallback_status,
+                #[cfg(feature = "fallback-page")]
+                page_fallback_nested,
                 #[cfg(feature = "basic-auth")]
                 basic_auth,
+                #[cfg(feature = "basic-auth")]
+                basic_auth_www_authenticate,
                 log_remote_address,
+                index_files,
+                root_page,
+                extensionless_html,
+                allowed_methods,
                 redirect_trailing_slash,
                 ignore_hidden_files,
                 health,
+                health_liveness_path,
+                health_readiness_path,
+                ready: ready.clone(),
+                stats: stats.clone(),
+                case_insensitive_paths,
+                disable_range_requests,
+                etag_mode,
+                disable_keep_alive,
+                keep_alive_max_requests,
+                sitemap,
+                canonical_host,
+                canonical_link,
+                allowed_hosts,
+                server_header,
+                alt_svc,
+                upgrade_protocols,
+                maintenance,
+                url_prefix,
+                request_id,
+                request_id_trusted_proxies,
+                response_timeout,
+                response_timeout_body,
+                slow_request_threshold,
+                not_found_cache,
+                internal_redirect,
+                request_body_max_size,
                 advanced_opts,
             }),
         });
@@ -318,6 +1036,24 @@ impl Server {
                 "http to https redirect from hosts: {}",
                 general.https_redirect_from_hosts
             );
+            server_info!(
+                "http to https redirect acme challenge path: {}",
+                acme_challenge_opts
+                    .as_ref()
+                    .map_or("", |opts| opts.path.as_str())
+            );
+
+            server_info!(
+                "http2 plain http requests: mode={:?}",
+                general.http2_plain_http
+            );
+
+            server_info!(
+                "http2 keep-alive pings: enabled={}, interval_secs={}, timeout_secs={}",
+                general.http2_keep_alive_interval > 0,
+                general.http2_keep_alive_interval,
+                general.http2_keep_alive_timeout
+            );
 
             // HTTP/2 + TLS
             tcp_listener
@@ -328,24 +1064,71 @@ impl Server {
             let mut incoming = AddrIncoming::from_listener(listener).with_context(|| {
                 "failed to create an AddrIncoming from the current tokio::net::TcpListener"
             })?;
-            incoming.set_nodelay(true);
+            incoming.set_nodelay(tcp_nodelay);
+
+            // ACME certificates
+            #[cfg(feature = "acme")]
+            server_info!("acme: enabled={}", acme_opts.is_some());
+            #[cfg(feature = "acme")]
+            let acme = acme_opts.map(|opts| {
+                server_info!("acme domains: {}", opts.domains.join(","));
+                server_info!("acme cache dir: {}", opts.cache_dir.display());
+                server_info!("acme directory url: {}", opts.directory_url);
+                let resolver = Arc::new(acme::CertResolver::default());
+                let cert = acme::load_cached(&opts);
+                if let Some(ref cert) = cert {
+                    resolver.set(cert.certified_key.clone());
+                }
+                server_info!("acme cached certificate: loaded={}", cert.is_some());
+                (opts, resolver, cert)
+            });
 
-            let http2_tls_cert = match general.http2_tls_cert {
-                Some(v) => v,
-                _ => bail!("failed to initialize TLS because cert file missing"),
-            };
-            let http2_tls_key = match general.http2_tls_key {
-                Some(v) => v,
-                _ => bail!("failed to initialize TLS because key file missing"),
+            #[cfg(feature = "acme")]
+            let tls = match acme {
+                Some((_, ref resolver, _)) => {
+                    TlsConfigBuilder::new().cert_resolver(resolver.clone())
+                }
+                None => tls_files(general.http2_tls_cert, general.http2_tls_key)?,
             };
+            #[cfg(not(feature = "acme"))]
+            let tls = tls_files(general.http2_tls_cert, general.http2_tls_key)?;
+            server_info!("http2 tls min version: {:?}", general.http2_tls_min_version);
+            let cipher_suites = tls::parse_cipher_suites(&general.http2_tls_ciphers)?;
+            server_info!(
+                "http2 tls cipher suites: {}",
+                if cipher_suites.is_empty() {
+                    String::from("default")
+                } else {
+                    general.http2_tls_ciphers.to_owned()
+                }
+            );
+            let tls = tls
+                .min_version(general.http2_tls_min_version)
+                .cipher_suites(&cipher_suites);
 
-            let tls = TlsConfigBuilder::new()
-                .cert_path(&http2_tls_cert)
-                .key_path(&http2_tls_key)
-                .build()
-                .with_context(|| {
-                    "failed to initialize TLS probably because invalid cert or key file"
-                })?;
+            // TLS client certificate authentication (mTLS)
+            server_info!(
+                "http2 tls client auth: enabled={}, mode={:?}, ca={}",
+                general.http2_tls_client_ca.is_some(),
+                general.http2_tls_client_auth,
+                general
+                    .http2_tls_client_ca
+                    .as_ref()
+                    .map_or(String::new(), |ca| ca.display().to_string())
+            );
+            let tls = match general.http2_tls_client_ca {
+                Some(ref ca) if !ca.is_file() => {
+                    bail!("tls client ca file not found: {}", ca.display())
+                }
+                Some(ref ca) => match general.http2_tls_client_auth {
+                    TlsClientAuthMode::Required => tls.client_auth_required_path(ca),
+                    TlsClientAuthMode::Optional => tls.client_auth_optional_path(ca),
+                },
+                None => tls,
+            };
+            let tls = tls.build().with_context(|| {
+                "failed to initialize TLS probably because invalid cert, key or client ca file"
+            })?;
 
             #[cfg(unix)]
             let signals = signals::create_signals()
@@ -353,12 +1136,41 @@ impl Server {
             #[cfg(unix)]
             let handle = signals.handle();
 
-            let http2_server =
-                HyperServer::builder(TlsAcceptor::new(tls, incoming)).serve(router_service);
+            // Plain HTTP requests are redirected following the https redirect host options
+            let plain_http_redirect = PlainHttpRedirectOpts {
+                https_hostname: general.https_redirect_host.clone(),
+                https_port: general.port,
+                allowed_hosts: general
+                    .https_redirect_from_hosts
+                    .split(',')
+                    .map(|s| s.trim().to_owned())
+                    .collect(),
+            };
+
+            let http2_server = HyperServer::builder(
+                LimitedIncoming::new(
+                    TlsAcceptor::new(tls, incoming)
+                        .plain_http(general.http2_plain_http)
+                        .plain_http_redirect(plain_http_redirect),
+                    max_connections,
+                    max_connections_mode,
+                )
+                .with_stats(stats.clone()),
+            );
+            let http2_server = http1_opts(http2_server, header_read_timeout, !disable_keep_alive);
+            let http2_server = http2_opts(
+                http2_server,
+                general.http2_keep_alive_interval,
+                general.http2_keep_alive_timeout,
+            )
+            .serve(router_service);
 
             #[cfg(unix)]
-            let http2_server = http2_server
-                .with_graceful_shutdown(signals::wait_for_signals(signals, grace_period));
+            let http2_server = http2_server.with_graceful_shutdown(signals::wait_for_signals(
+                signals,
+                grace_period,
+                ready.clone(),
+            ));
 
             #[cfg(windows)]
             let http2_cancel_recv = Arc::new(tokio::sync::Mutex::new(_cancel_recv));
@@ -370,12 +1182,14 @@ impl Server {
             #[cfg(windows)]
             let redirect_ctrlc_recv = http2_ctrlc_recv.clone();
 
+            #[cfg(windows)]
+            let http2_ready = ready.clone();
             #[cfg(windows)]
             let http2_server = http2_server.with_graceful_shutdown(async move {
                 if general.windows_service {
-                    signals::wait_for_ctrl_c(http2_cancel_recv, grace_period).await;
+                    signals::wait_for_ctrl_c(http2_cancel_recv, grace_period, http2_ready).await;
                 } else {
-                    signals::wait_for_ctrl_c(http2_ctrlc_recv, grace_period).await;
+                    signals::wait_for_ctrl_c(http2_ctrlc_recv, grace_period, http2_ready).await;
                 }
             });
 
@@ -392,7 +1206,7 @@ impl Server {
                     .parse::<IpAddr>()
                     .with_context(|| format!("failed to parse {} address", general.host))?;
                 let addr = SocketAddr::from((ip, general.https_redirect_from_port));
-                let tcp_listener = TcpListener::bind(addr)
+                let tcp_listener = bind_tcp_listener(addr, general.reuse_port)
                     .with_context(|| format!("failed to bind to {addr} address"))?;
                 server_info!(
                     parent: tracing::info_span!("Server::start_server", ?addr, ?threads),
@@ -423,53 +1237,93 @@ impl Server {
                     https_hostname: general.https_redirect_host,
                     https_port: general.port,
                     allowed_hosts: redirect_allowed_hosts,
+                    acme_challenge: acme_challenge_opts,
                 });
 
-                let server_redirect = HyperServer::from_tcp(tcp_listener)
-                    .unwrap()
-                    .tcp_nodelay(true)
-                    .serve(make_service_fn(move |_: &AddrStream| {
-                        let redirect_opts = redirect_opts.clone();
-                        let page404 = page404.clone();
-                        let page50x = page50x.clone();
-                        async move {
-                            Ok::<_, error::Error>(service_fn(move |req| {
-                                let redirect_opts = redirect_opts.clone();
-                                let page404 = page404.clone();
-                                let page50x = page50x.clone();
-                                async move {
-                                    let uri = req.uri();
-                                    let method = req.method();
-                                    match https_redirect::redirect_to_https(&req, redirect_opts)
-                                        .await
-                                    {
-                                        Ok(resp) => Ok(resp),
-                                        Err(status) => error_page::error_response(
-                                            uri, method, &status, &page404, &page50x,
-                                        ),
-                                    }
+                // The redirect server gets its own connection limit as well
+                let listener = tokio::net::TcpListener::from_std(tcp_listener)
+                    .with_context(|| "failed to create tokio::net::TcpListener")?;
+                let mut incoming = AddrIncoming::from_listener(listener).with_context(|| {
+                    "failed to create an AddrIncoming from the current tokio::net::TcpListener"
+                })?;
+                incoming.set_nodelay(true);
+                let server_redirect = HyperServer::builder(LimitedIncoming::new(
+                    incoming,
+                    max_connections,
+                    max_connections_mode,
+                ));
+                let server_redirect = http1_opts(
+                    server_redirect,
+                    header_read_timeout,
+                    !disable_keep_alive,
+                )
+                .serve(make_service_fn(move |_: &LimitedConn<AddrStream>| {
+                    let redirect_opts = redirect_opts.clone();
+                    let page404 = page404.clone();
+                    let page50x = page50x.clone();
+                    async move {
+                        Ok::<_, error::Error>(service_fn(move |req| {
+                            let redirect_opts = redirect_opts.clone();
+                            let page404 = page404.clone();
+                            let page50x = page50x.clone();
+                            async move {
+                                let uri = req.uri();
+                                let method = req.method();
+                                match https_redirect::redirect_to_https(&req, redirect_opts).await {
+                                    Ok(resp) => Ok(resp),
+                                    Err(status) => error_page::error_response(
+                                        uri,
+                                        method,
+                                        req.headers(),
+                                        &status,
+                                        &page404,
+                                        &page50x,
+                                    ),
                                 }
-                            }))
-                        }
-                    }));
+                            }
+                        }))
+                    }
+                }));
 
                 #[cfg(unix)]
                 let server_redirect = server_redirect.with_graceful_shutdown(
-                    signals::wait_for_signals(redirect_signals, grace_period),
+                    signals::wait_for_signals(redirect_signals, grace_period, ready.clone()),
                 );
                 #[cfg(windows)]
+                let redirect_ready = ready.clone();
+                #[cfg(windows)]
                 let server_redirect = server_redirect.with_graceful_shutdown(async move {
                     if general.windows_service {
-                        signals::wait_for_ctrl_c(redirect_cancel_recv, grace_period).await;
+                        signals::wait_for_ctrl_c(
+                            redirect_cancel_recv,
+                            grace_period,
+                            redirect_ready,
+                        )
+                        .await;
                     } else {
-                        signals::wait_for_ctrl_c(redirect_ctrlc_recv, grace_period).await;
+                        signals::wait_for_ctrl_c(redirect_ctrlc_recv, grace_period, redirect_ready)
+                            .await;
                     }
                 });
 
+                // The server is only ready once an ACME certificate is available
+                #[cfg(feature = "acme")]
+                let acme_pending = acme.as_ref().is_some_and(|(_, _, cert)| cert.is_none());
+                #[cfg(not(feature = "acme"))]
+                let acme_pending = false;
+
+                // ACME certificate renewal task
+                // which starts once the redirect server serves the challenge files
+                #[cfg(feature = "acme")]
+                if let Some((opts, resolver, cert)) = acme {
+                    tokio::spawn(acme::run(opts, resolver, cert, ready.clone()));
+                }
+
                 // HTTP/2 server task
                 let server_task = tokio::spawn(async move {
                     if let Err(err) = http2_server.await {
                         tracing::error!("http2 server failed to start up: {:?}", err);
+                        logger::flush();
                         std::process::exit(1)
                     }
                 });
@@ -478,10 +1332,14 @@ impl Server {
                 let redirect_server_task = tokio::spawn(async move {
                     if let Err(err) = server_redirect.await {
                         tracing::error!("http1 redirect server failed to start up: {:?}", err);
+                        logger::flush();
                         std::process::exit(1)
                     }
                 });
 
+                if !acme_pending {
+                    ready.set_ready();
+                }
                 server_info!("press ctrl+c to shut down the servers");
 
                 #[cfg(windows)]
@@ -492,6 +1350,7 @@ impl Server {
                 #[cfg(unix)]
                 redirect_handle.close();
             } else {
+                ready.set_ready();
                 server_info!("press ctrl+c to shut down the server");
                 http2_server.await?;
             }
@@ -503,6 +1362,7 @@ impl Server {
             _cancel_fn();
 
             server_warn!("termination signal caught, shutting down the server execution");
+            server_info!("server stats: {}", stats.summary());
             return Ok(());
         }
 
@@ -518,26 +1378,40 @@ impl Server {
             .set_nonblocking(true)
             .with_context(|| "failed to set TCP non-blocking mode")?;
 
-        let http1_server = HyperServer::from_tcp(tcp_listener)
-            .unwrap()
-            .tcp_nodelay(true)
+        let listener = tokio::net::TcpListener::from_std(tcp_listener)
+            .with_context(|| "failed to create tokio::net::TcpListener")?;
+        let mut incoming = AddrIncoming::from_listener(listener).with_context(|| {
+            "failed to create an AddrIncoming from the current tokio::net::TcpListener"
+        })?;
+        incoming.set_nodelay(tcp_nodelay);
+
+        let http1_server = HyperServer::builder(
+            LimitedIncoming::new(incoming, max_connections, max_connections_mode)
+                .with_stats(stats.clone()),
+        );
+        let http1_server = http1_opts(http1_server, header_read_timeout, !disable_keep_alive)
             .serve(router_service);
 
         #[cfg(unix)]
-        let http1_server =
-            http1_server.with_graceful_shutdown(signals::wait_for_signals(signals, grace_period));
+        let http1_server = http1_server.with_graceful_shutdown(signals::wait_for_signals(
+            signals,
+            grace_period,
+            ready.clone(),
+        ));
 
         #[cfg(windows)]
         let http1_cancel_recv = Arc::new(tokio::sync::Mutex::new(_cancel_recv));
         #[cfg(windows)]
         let http1_ctrlc_recv = Arc::new(tokio::sync::Mutex::new(Some(receiver)));
 
+        #[cfg(windows)]
+        let http1_ready = ready.clone();
         #[cfg(windows)]
         let http1_server = http1_server.with_graceful_shutdown(async move {
             if general.windows_service {
-                signals::wait_for_ctrl_c(http1_cancel_recv, grace_period).await;
+                signals::wait_for_ctrl_c(http1_cancel_recv, grace_period, http1_ready).await;
             } else {
-                signals::wait_for_ctrl_c(http1_ctrlc_recv, grace_period).await;
+                signals::wait_for_ctrl_c(http1_ctrlc_recv, grace_period, http1_ready).await;
             }
         });
 
@@ -547,6 +1421,7 @@ impl Server {
             addr_str
         );
 
+        ready.set_ready();
         server_info!("press ctrl+c to shut down the server");
 
         http1_server.await?;
@@ -558,6 +1433,146 @@ impl Server {
         handle.close();
 
         server_warn!("termination signal caught, shutting down the server execution");
+        server_info!("server stats: {}", stats.summary());
         Ok(())
     }
 }
+
+/// It applies the HTTP/1 connection options to a server builder.
+/// The header read timeout is only applied if enabled (greater than zero).
+fn http1_opts<I>(builder: Builder<I>, header_read_timeout: u64, keep_alive: bool) -> Builder<I> {
+    let builder = builder.http1_keepalive(keep_alive);
+    if header_read_timeout > 0 {
+        builder.http1_header_read_timeout(Duration::from_secs(header_read_timeout))
+    } else {
+        builder
+    }
+}
+
+/// It applies the HTTP/2 keep-alive pings options to a server builder.
+/// The pings are only sent if their interval is enabled (greater than zero).
+#[cfg(feature = "http2")]
+fn http2_opts<I>(builder: Builder<I>, interval: u64, timeout: u64) -> Builder<I> {
+    if interval > 0 {
+        builder
+            .http2_keep_alive_interval(Duration::from_secs(interval))
+            .http2_keep_alive_timeout(Duration::from_secs(timeout))
+    } else {
+        builder.http2_keep_alive_interval(None)
+    }
+}
+
+/// It returns a TLS configuration builder using the given certificate and key files.
+#[cfg(feature = "http2")]
+fn tls_files(cert: Option<PathBuf>, key: Option<PathBuf>) -> Result<TlsConfigBuilder> {
+    let cert = match cert {
+        Some(v) => v,
+        _ => bail!("failed to initialize TLS because cert file missing"),
+    };
+    let key = match key {
+        Some(v) => v,
+        _ => bail!("failed to initialize TLS because key file missing"),
+    };
+    Ok(TlsConfigBuilder::new().cert_path(cert).key_path(key))
+}
+
+/// Binds a TCP socket listener to the given address.
+/// The `SO_REUSEPORT` option is enabled on the socket if `reuse_port` is set.
+fn bind_tcp_listener(addr: SocketAddr, reuse_port: bool) -> Result<TcpListener> {
+    if !reuse_port {
+        return Ok(TcpListener::bind(addr)?);
+    }
+
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    // Keep the `SO_REUSEADDR` default of the standard library listener
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?;
+    set_reuse_port(&socket)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    Ok(socket.into())
+}
+
+/// Enables the `SO_REUSEPORT` option on the given socket.
+#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+fn set_reuse_port(socket: &Socket) -> Result {
+    socket
+        .set_reuse_port(true)
+        .with_context(|| "failed to enable the SO_REUSEPORT socket option")
+}
+
+/// Enables the `SO_REUSEPORT` option on the given socket.
+#[cfg(not(all(unix, not(any(target_os = "solaris", target_os = "illumos")))))]
+fn set_reuse_port(_socket: &Socket) -> Result {
+    bail!("the reuse-port option is not supported on this platform")
+}
+
+/// Takes the first TCP socket listener passed by systemd via socket activation.
+fn systemd_tcp_listener() -> Result<TcpListener> {
+    let listen_pid = std::env::var("LISTEN_PID").with_context(|| {
+        "socket activation requires the `LISTEN_PID` environment variable set by systemd"
+    })?;
+    let listen_fds = std::env::var("LISTEN_FDS").with_context(|| {
+        "socket activation requires the `LISTEN_FDS` environment variable set by systemd"
+    })?;
+    if listen_pid.trim().parse::<u32>().ok() != Some(std::process::id()) {
+        bail!(
+            "socket activation `LISTEN_PID` value {} does not match the current process id {}",
+            listen_pid,
+            std::process::id()
+        );
+    }
+    if !matches!(listen_fds.trim().parse::<usize>(), Ok(n) if n > 0) {
+        bail!(
+            "socket activation `LISTEN_FDS` value {:?} is not a valid number of file descriptors",
+            listen_fds
+        );
+    }
+
+    // Descriptors start at fd 3, so iterate over them until a TCP one is found
+    let mut listenfd = ListenFd::from_env();
+    for idx in 0..listenfd.len() {
+        match listenfd.take_tcp_listener(idx) {
+            Ok(Some(listener)) => {
+                if listenfd.len() > 1 {
+                    tracing::warn!(
+                        "{} sockets passed via socket activation, only fd {} is used",
+                        listenfd.len(),
+                        idx + 3
+                    );
+                }
+                return Ok(listener);
+            }
+            Ok(None) => {}
+            Err(err) => tracing::warn!("skipping socket activation fd {}: {:?}", idx + 3, err),
+        }
+    }
+    bail!("no 'tcp' socket listener was passed via socket activation")
+}
+
+/// Returns the number of worker threads to use.
+/// The precedence is: `threads` > `threads_multiplier` > number of available CPUs.
+fn worker_threads(threads: usize, threads_multiplier: usize) -> usize {
+    if threads > 0 {
+        return threads;
+    }
+    // Note that the available parallelism respects the cgroup CPU quotas (e.g. in containers)
+    let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+    match threads_multiplier {
+        0 | 1 => cpus,
+        n => cpus * n,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::worker_threads;
+
+    #[test]
+    fn worker_threads_precedence() {
+        let cpus = std::thread::available_parallelism().unwrap().get();
+        assert_eq!(worker_threads(3, 8), 3);
+        assert_eq!(worker_threads(0, 8), cpus * 8);
+        assert_eq!(worker_threads(0, 0), cpus);
+    }
+}
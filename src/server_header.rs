@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! The module provides the `Server` HTTP header support.
+//!
+
+use hyper::{
+    header::{HeaderValue, SERVER},
+    Body, Response,
+};
+
+use crate::Result;
+
+/// It parses a `Server` header option value.
+/// `default` means `static-web-server/<version>`, `off` (or empty) omits the header
+/// and any other value is used as a custom header value.
+pub fn parse(value: &str) -> Result<Option<HeaderValue>> {
+    let value = value.trim();
+    match value {
+        "" | "off" => Ok(None),
+        "default" => Ok(Some(HeaderValue::from_static(concat!(
+            env!("CARGO_PKG_NAME"),
+            "/",
+            env!("CARGO_PKG_VERSION")
+        )))),
+        custom => match HeaderValue::from_str(custom) {
+            Ok(val) => Ok(Some(val)),
+            Err(err) => bail!("invalid server header value {:?}: {}", custom, err),
+        },
+    }
+}
+
+/// It appends the `Server` header to the current response if present.
+pub fn append_headers(server_header: &Option<HeaderValue>, resp: &mut Response<Body>) {
+    if let Some(val) = server_header {
+        resp.headers_mut().insert(SERVER, val.to_owned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(parse("off").unwrap(), None);
+        assert_eq!(parse("").unwrap(), None);
+        assert_eq!(
+            parse("default").unwrap().unwrap(),
+            concat!("static-web-server/", env!("CARGO_PKG_VERSION"))
+        );
+        assert_eq!(parse("my-server").unwrap().unwrap(), "my-server");
+        assert!(parse("bad\nvalue").is_err());
+    }
+}
@@ -6,15 +6,31 @@
 //! The module provides a custom [Hyper service](hyper::service::Service).
 //!
 
-use hyper::{service::Service, Body, Request, Response};
+use hyper::{
+    header::{HeaderValue, CONNECTION},
+    service::Service,
+    Body, Request, Response, StatusCode, Version,
+};
 use std::convert::Infallible;
 use std::future::{ready, Future, Ready};
 use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Instant;
 
-use crate::{handler::RequestHandler, transport::Transport, Error};
+use crate::{
+    access_log::{AccessLog, LoggedBody},
+    error_page,
+    handler::RequestHandler,
+    protocol_hints, request_body, request_id, response_timeout, server_header,
+    static_files::ResolvedFile,
+    transport::Transport,
+    url_prefix, Error,
+};
+
+#[cfg(feature = "http2")]
+use crate::tls::ClientCertSlot;
 
 /// It defines the router service which is the main entry point for Hyper Server.
 pub struct RouterService {
@@ -40,7 +56,11 @@ impl<T: Transport + Send + 'static> Service<&T> for RouterService {
     }
 
     fn call(&mut self, conn: &T) -> Self::Future {
-        ready(Ok(self.builder.build(conn.remote_addr())))
+        ready(Ok(RequestService {
+            #[cfg(feature = "http2")]
+            client_cert: conn.client_cert(),
+            ..self.builder.build(conn.remote_addr())
+        }))
     }
 }
 
@@ -48,12 +68,18 @@ impl<T: Transport + Send + 'static> Service<&T> for RouterService {
 pub struct RequestService {
     handler: Arc<RequestHandler>,
     remote_addr: Option<SocketAddr>,
+    /// Number of requests received by the connection.
+    requests: u64,
+    /// Verified client certificate of the TLS connection.
+    #[cfg(feature = "http2")]
+    client_cert: Option<ClientCertSlot>,
 }
 
 impl Service<Request<Body>> for RequestService {
-    type Response = Response<Body>;
+    type Response = Response<LoggedBody>;
     type Error = Error;
-    type Future = Pin<Box<dyn Future<Output = Result<Response<Body>, Error>> + Send + 'static>>;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<Response<LoggedBody>, Error>> + Send + 'static>>;
 
     fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Error>> {
         Poll::Ready(Ok(()))
@@ -62,7 +88,117 @@ impl Service<Request<Body>> for RequestService {
     fn call(&mut self, mut req: Request<Body>) -> Self::Future {
         let handler = self.handler.clone();
         let remote_addr = self.remote_addr;
-        Box::pin(async move { handler.handle(&mut req, remote_addr).await })
+        // The connection is closed once it reaches the keep-alive max requests
+        self.requests += 1;
+        let max_requests = handler.opts.keep_alive_max_requests;
+        let last_request = max_requests > 0 && self.requests >= max_requests;
+        // Expose the verified client certificate to the request handler
+        #[cfg(feature = "http2")]
+        if let Some(cert) = self.client_cert.as_ref().and_then(|slot| slot.get()) {
+            req.extensions_mut().insert(cert.clone());
+        }
+        let started = Instant::now();
+
+        // Attach the request ID to the request so it can be logged and used by error pages
+        let request_id = if handler.opts.request_id {
+            let request_id = request_id::resolve(
+                req.headers(),
+                remote_addr,
+                &handler.opts.request_id_trusted_proxies,
+            );
+            req.headers_mut()
+                .insert(request_id::REQUEST_ID_HEADER, request_id.0.clone());
+            req.extensions_mut().insert(request_id.clone());
+            Some(request_id)
+        } else {
+            None
+        };
+
+        // Every request is logged once completed, even if it fails or gets aborted
+        let access_log = AccessLog::new(
+            &req,
+            remote_addr.filter(|_| handler.opts.log_remote_address),
+            handler.is_probe_request(&req),
+        )
+        .with_in_flight(handler.opts.stats.track_request());
+
+        let respond = async move {
+            let version = req.version();
+            let slow_request = handler
+                .opts
+                .slow_request_threshold
+                .map(|threshold| (threshold, req.method().clone(), req.uri().clone()));
+            // Bodies left unread would corrupt the next requests of the connection
+            let body_discarded =
+                request_body::discard(&mut req, handler.opts.request_body_max_size).await;
+            let mut resp = if !body_discarded {
+                let mut resp = error_page::error_response(
+                    req.uri(),
+                    req.method(),
+                    req.headers(),
+                    &StatusCode::BAD_REQUEST,
+                    &handler.opts.page404,
+                    &handler.opts.page50x,
+                )?;
+                // The rest of the body is never read so the connection can't be reused
+                if version < Version::HTTP_2 {
+                    resp.headers_mut()
+                        .insert(CONNECTION, HeaderValue::from_static("close"));
+                }
+                resp
+            } else {
+                match handler.opts.response_timeout {
+                    Some(timeout) => {
+                        response_timeout::handle(handler.clone(), req, remote_addr, timeout).await?
+                    }
+                    None => handler.handle(&mut req, remote_addr).await?,
+                }
+            };
+            // Log requests whose response took longer than the threshold
+            if let Some((threshold, method, uri)) = slow_request {
+                let duration = started.elapsed();
+                if duration > threshold {
+                    let (file, size) = resp
+                        .extensions()
+                        .get::<ResolvedFile>()
+                        .map_or((String::from("-"), String::from("-")), |file| {
+                            (file.path.display().to_string(), file.size.to_string())
+                        });
+                    tracing::warn!(
+                        "slow request: method={} uri={} status={} duration_ms={} file={} size={}{}",
+                        method,
+                        uri,
+                        resp.status().as_u16(),
+                        duration.as_millis(),
+                        file,
+                        size,
+                        request_id
+                            .as_ref()
+                            .map_or(String::new(), |id| format!(" request_id={}", id.as_str())),
+                    );
+                }
+            }
+            if let Some(request_id) = request_id {
+                resp.headers_mut()
+                    .insert(request_id::REQUEST_ID_HEADER, request_id.0);
+            }
+            handler.opts.stats.add_request(resp.status());
+            server_header::append_headers(&handler.opts.server_header, &mut resp);
+            url_prefix::prepend_location(&handler.opts.url_prefix, &mut resp);
+            // Connection-specific headers are not allowed on HTTP/2
+            if (handler.opts.disable_keep_alive || last_request) && version < Version::HTTP_2 {
+                resp.headers_mut()
+                    .insert(CONNECTION, HeaderValue::from_static("close"));
+            }
+            protocol_hints::append_headers(
+                &handler.opts.alt_svc,
+                &handler.opts.upgrade_protocols,
+                version,
+                &mut resp,
+            );
+            Ok(resp)
+        };
+        Box::pin(async move { access_log.respond(respond.await) })
     }
 }
 
@@ -84,6 +220,9 @@ impl RequestServiceBuilder {
         RequestService {
             handler: self.handler.clone(),
             remote_addr,
+            requests: 0,
+            #[cfg(feature = "http2")]
+            client_cert: None,
         }
     }
 }
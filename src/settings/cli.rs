@@ -5,15 +5,33 @@
 
 //! The server CLI options
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
 use std::path::PathBuf;
 
+use crate::connection_limit::ConnLimitMode;
+use crate::control_headers::CacheVisibility;
+use crate::etag::EtagMode;
+
+#[cfg(feature = "http2")]
+use crate::tls::{PlainHttpMode, TlsClientAuthMode, TlsMinVersion};
+
 #[cfg(feature = "directory-listing")]
-use crate::directory_listing::DirListFmt;
+use crate::directory_listing::{DirListFmt, DirListSizeFmt, DirListTimezone};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+/// Output format of the effective configuration.
+pub enum PrintConfigFmt {
+    /// TOML format (default).
+    Toml,
+    /// JSON format.
+    Json,
+}
 
 /// General server configuration available in CLI and config file options.
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Serialize)]
 #[command(author, version, about, long_about = None)]
+#[serde(rename_all = "kebab-case")]
 pub struct General {
     #[arg(long, short = 'a', default_value = "::", env = "SERVER_HOST")]
     /// Host address (E.g 127.0.0.1 or ::1)
@@ -50,6 +68,21 @@ pub struct General {
     /// static-web-server to be sandboxed more completely.
     pub fd: Option<usize>,
 
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        conflicts_with_all(&["host", "port", "fd"]),
+        env = "SERVER_SOCKET_ACTIVATION"
+    )]
+    /// Accept incoming connections to the TCP socket listener passed by systemd via socket activation
+    /// (`LISTEN_FDS` and `LISTEN_PID` environment variables). If several sockets are passed then the first TCP one is used.
+    /// The server fails to start if the variables are not present or not meant for the current process.
+    pub socket_activation: bool,
+
     #[cfg_attr(
         not(wasm),
         arg(
@@ -74,6 +107,11 @@ pub struct General {
     /// Number of worker threads result should be a number between 1 and 32,768 though it is advised to keep this value on the smaller side.
     pub threads_multiplier: usize,
 
+    #[arg(long, default_value = "0", env = "SERVER_THREADS")]
+    /// Exact number of worker threads. When set to a value greater than 0, it takes precedence over the `threads-multiplier` option.
+    /// Default 0 (worker threads are derived from the number of available CPUs using the `threads-multiplier` option).
+    pub threads: usize,
+
     #[cfg_attr(
         not(wasm),
         arg(
@@ -92,13 +130,18 @@ pub struct General {
             env = "SERVER_MAX_BLOCKING_THREADS"
         )
     )] // We use 20 in Wasm, 512 in Native (default for tokio)
-    /// Maximum number of blocking threads
+    /// Maximum number of blocking threads used for asynchronous filesystem operations like reading directory entries for the directory listing.
+    /// It is independent of the number of worker threads.
     pub max_blocking_threads: usize,
 
     #[arg(long, short = 'd', default_value = "./public", env = "SERVER_ROOT")]
-    /// Root directory path of static files.
+    /// Root directory path of static files. It can also be a `.tar` or `.tar.gz` (`.tgz`) archive file path whose entries are unpacked at startup (requires the `archive` feature).
     pub root: PathBuf,
 
+    #[arg(long, default_value = "", value_parser = value_parser_pathbuf, env = "SERVER_OVERLAY_ROOT")]
+    /// Overlay directory path whose files take precedence over the ones of the root directory. Requested paths are looked up in the overlay directory first and then in the root directory if not found, so only the overridden files have to be present in it. Default empty (disabled).
+    pub overlay_root: PathBuf,
+
     #[arg(
         long,
         default_value = "./public/50x.html",
@@ -118,12 +161,39 @@ pub struct General {
     #[cfg(feature = "fallback-page")]
     #[cfg_attr(docsrs, doc(cfg(feature = "fallback-page")))]
     #[arg(long, default_value = "", value_parser = value_parser_pathbuf, env = "SERVER_FALLBACK_PAGE")]
-    /// HTML file path that is used for GET requests when the requested path doesn't exist. The fallback page is served with a 200 status code, useful when using client routers. If the path is not specified or simply doesn't exist then this feature will not be active.
+    /// HTML file path that is used for GET requests when the requested path doesn't exist. The fallback page is served with the status code of the fallback page status option (200 by default), useful when using client routers, and only for navigation requests. If the path is not specified or simply doesn't exist then this feature will not be active.
     pub page_fallback: PathBuf,
 
-    #[arg(long, short = 'g', default_value = "error", env = "SERVER_LOG_LEVEL")]
-    /// Specify a logging level in lower case. Values: error, warn, info, debug or trace
-    pub log_level: String,
+    #[cfg(feature = "fallback-page")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "fallback-page")))]
+    #[arg(long, default_value = "200", env = "SERVER_FALLBACK_PAGE_STATUS")]
+    /// HTTP status code used when the fallback page is served. Values: 200 or 404. Default 200.
+    /// Note that the fallback page is only served for navigation requests, that is, requests whose `Accept` header includes `text/html`.
+    pub page_fallback_status: u16,
+
+    #[cfg(feature = "fallback-page")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "fallback-page")))]
+    #[arg(long, default_value = "", env = "SERVER_FALLBACK_PAGE_NESTED")]
+    /// Comma-separated list of glob patterns (e.g. `/t/*/**`) of request paths falling back to the nearest `index.html` file (or the configured index files) found by walking up the requested path within the root directory, useful for nested single-page applications. It takes precedence over the fallback page and only applies to navigation requests. Default empty (disabled).
+    pub page_fallback_nested: String,
+
+    #[arg(long, short = 'g', env = "SERVER_LOG_LEVEL")]
+    /// Specify a logging level in lower case. Values: error, warn, info, debug or trace. It takes precedence over the `--verbose` and `--quiet` flags. Default `error`.
+    pub log_level: Option<String>,
+
+    #[arg(long, short = 'v', action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    #[serde(skip)]
+    /// Increase the logging level to `debug` (`-v`) or `trace` (`-vv`) unless a logging level is specified via `--log-level`.
+    pub verbose: u8,
+
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    #[serde(skip)]
+    /// Log errors only (`error` logging level) unless a logging level is specified via `--log-level`. It has no short form since `-q` is the short form of `--grace-period`.
+    pub quiet: bool,
+
+    #[arg(long, default_value = "", value_parser = value_parser_pathbuf, env = "SERVER_LOG_FILE")]
+    /// File path to write the logs to via a buffered writer instead of the standard error. The file is created if it doesn't exist and logs are appended to it. Use `stdout` or `stderr` to write the logs to the standard output or the standard error respectively. On Unix-like systems, the file is re-opened when a `SIGUSR1` signal is caught, so it can be rotated (e.g. via logrotate).
+    pub log_file: PathBuf,
 
     #[arg(
         long,
@@ -166,18 +236,93 @@ pub struct General {
     /// Enable HTTP/2 with TLS support.
     pub http2: bool,
 
-    #[arg(long, required_if_eq("http2", "true"), env = "SERVER_HTTP2_TLS_CERT")]
+    #[arg(long, env = "SERVER_HTTP2_TLS_CERT")]
+    #[cfg_attr(not(feature = "acme"), arg(required_if_eq("http2", "true")))]
     #[cfg(feature = "http2")]
     #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
     /// Specify the file path to read the certificate.
     pub http2_tls_cert: Option<PathBuf>,
 
-    #[arg(long, required_if_eq("http2", "true"), env = "SERVER_HTTP2_TLS_KEY")]
+    #[arg(long, env = "SERVER_HTTP2_TLS_KEY")]
+    #[cfg_attr(not(feature = "acme"), arg(required_if_eq("http2", "true")))]
     #[cfg(feature = "http2")]
     #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
     /// Specify the file path to read the private key.
     pub http2_tls_key: Option<PathBuf>,
 
+    #[arg(
+        long,
+        value_enum,
+        default_value = "1.2",
+        env = "SERVER_HTTP2_TLS_MIN_VERSION"
+    )]
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    /// Minimum TLS protocol version accepted. Values supported: "1.2" or "1.3". Default "1.2". It depends on "http2" to be enabled.
+    pub http2_tls_min_version: TlsMinVersion,
+
+    #[arg(long, default_value = "", env = "SERVER_HTTP2_TLS_CIPHERS")]
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    /// Comma-separated list of allowed TLS cipher suites in order of preference. E.g. "TLS13_AES_256_GCM_SHA384,TLS13_CHACHA20_POLY1305_SHA256". Default empty (Rustls safe defaults). It depends on "http2" to be enabled.
+    pub http2_tls_ciphers: String,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "bad-request",
+        ignore_case(true),
+        env = "SERVER_HTTP2_PLAIN_HTTP"
+    )]
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    /// How to handle plain HTTP requests sent to the HTTPS server port. Use "close" to drop the connection, "bad-request" to reply with a "400 Bad Request" explaining HTTPS is required or "redirect" to redirect the request to the "https_redirect_host" if its "Host" header matches the "https_redirect_from_hosts" list. It depends on "http2" to be enabled.
+    pub http2_plain_http: PlainHttpMode,
+
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_HTTP2_TLS_FALLBACK",
+    )]
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    /// Fall back to plain HTTP with a warning instead of failing at startup when the TLS certificate or key file is missing or invalid. Intended for development only and refused if the client certificate authentication is configured. It depends on "http2" to be enabled.
+    pub http2_tls_fallback: bool,
+
+    #[arg(long, env = "SERVER_HTTP2_TLS_CLIENT_CA")]
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    /// Specify the file path of the PEM-encoded CA certificates (trust anchors) used to verify the TLS client certificates, enabling the client certificate authentication (mTLS). It depends on "http2" to be enabled.
+    pub http2_tls_client_ca: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "required",
+        ignore_case(true),
+        env = "SERVER_HTTP2_TLS_CLIENT_AUTH"
+    )]
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    /// TLS client certificate authentication mode. Use "required" to reject the clients without a valid certificate during the TLS handshake or "optional" to accept anonymous clients too. It only applies if "--http2-tls-client-ca" is set.
+    pub http2_tls_client_auth: TlsClientAuthMode,
+
+    #[arg(long, default_value = "60", env = "SERVER_HTTP2_KEEP_ALIVE_INTERVAL")]
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    /// Interval in seconds between the HTTP/2 keep-alive pings sent to idle connections, so the clients vanishing without closing their connections are detected. Use `0` to disable them. Default `60`. It depends on "http2" to be enabled.
+    pub http2_keep_alive_interval: u64,
+
+    #[arg(long, default_value = "20", env = "SERVER_HTTP2_KEEP_ALIVE_TIMEOUT")]
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    /// Timeout in seconds to receive the acknowledgement of an HTTP/2 keep-alive ping, otherwise the connection is closed. Default `20`. It depends on "http2" and "http2_keep_alive_interval" to be enabled.
+    pub http2_keep_alive_timeout: u64,
+
     #[arg(
         long,
         default_value = "false",
@@ -226,6 +371,95 @@ pub struct General {
     /// List of host names or IPs allowed to redirect from. HTTP requests must contain the HTTP 'Host' header and match against this list. It depends on "https_redirect" to be enabled.
     pub https_redirect_from_hosts: String,
 
+    #[arg(
+        long,
+        requires_if("true", "https_redirect"),
+        default_value = "",
+        env = "SERVER_HTTPS_REDIRECT_ACME_CHALLENGE_PATH"
+    )]
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    /// URL path of ACME challenge files (e.g. "/.well-known/acme-challenge/") served by the redirect server over HTTP instead of being redirected to HTTPS. Useful for certificate renewals via the webroot method. Default empty (disabled). It depends on "https_redirect" to be enabled.
+    pub https_redirect_acme_challenge_path: String,
+
+    #[arg(
+        long,
+        default_value = "",
+        value_parser = value_parser_pathbuf,
+        env = "SERVER_HTTPS_REDIRECT_ACME_CHALLENGE_DIR"
+    )]
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    /// Directory containing the ACME challenge files which are looked up by name. If not specified then the challenge files are looked up by their full request path in the root directory.
+    pub https_redirect_acme_challenge_dir: PathBuf,
+
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        requires_if("true", "http2"),
+        env = "SERVER_ACME"
+    )]
+    #[cfg(feature = "acme")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "acme")))]
+    /// Obtain and renew the TLS certificate automatically via ACME (e.g. Let's Encrypt) using the "http-01" challenge. It depends on "http2", "https_redirect" and "acme_tos_agreed" to be enabled and overrides the "http2_tls_cert" and "http2_tls_key" options.
+    pub acme: bool,
+
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_ACME_TOS_AGREED"
+    )]
+    #[cfg(feature = "acme")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "acme")))]
+    /// Agree to the terms of service of the ACME server, required to create the ACME account. Default false (disagreed).
+    pub acme_tos_agreed: bool,
+
+    #[arg(long, default_value = "", env = "SERVER_ACME_EMAIL")]
+    #[cfg(feature = "acme")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "acme")))]
+    /// Contact email of the ACME account used for expiration notices. Default empty.
+    pub acme_email: String,
+
+    #[arg(
+        long,
+        required_if_eq("acme", "true"),
+        default_value = "",
+        env = "SERVER_ACME_DOMAINS"
+    )]
+    #[cfg(feature = "acme")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "acme")))]
+    /// List of domain names separated by commas to obtain the certificate for. Wildcard domains are not supported.
+    pub acme_domains: String,
+
+    #[arg(
+        long,
+        default_value = "./acme",
+        value_parser = value_parser_pathbuf,
+        env = "SERVER_ACME_CACHE_DIR"
+    )]
+    #[cfg(feature = "acme")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "acme")))]
+    /// Directory to store the ACME account, the obtained certificate and its private key.
+    pub acme_cache_dir: PathBuf,
+
+    #[arg(
+        long,
+        default_value = "https://acme-v02.api.letsencrypt.org/directory",
+        env = "SERVER_ACME_DIRECTORY_URL"
+    )]
+    #[cfg(feature = "acme")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "acme")))]
+    /// Directory URL of the ACME server. Use "https://acme-staging-v02.api.letsencrypt.org/directory" for testing.
+    pub acme_directory_url: String,
+
     #[cfg(feature = "compression")]
     #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
     #[arg(
@@ -256,6 +490,82 @@ pub struct General {
     /// The compression type is determined by the `Accept-Encoding` header.
     pub compression_static: bool,
 
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[arg(long, default_value = "65536", env = "SERVER_COMPRESSION_BUFFER_SIZE")]
+    /// Size threshold in bytes below which auto-compressed responses are compressed in memory entirely in order to send a `Content-Length` header instead of using a chunked transfer encoding. Larger responses are streamed. Use 0 to always stream.
+    pub compression_buffer_size: u64,
+
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[arg(long, default_value = "1024", env = "SERVER_COMPRESSION_MIN_SIZE")]
+    /// Minimum size in bytes of a response body to be auto-compressed. Smaller responses are sent uncompressed while responses without a known size are always compressed. Use 0 to compress all responses.
+    pub compression_min_size: u64,
+
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[arg(
+        long,
+        default_value = "true",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_COMPRESSION_HTTP10",
+    )]
+    /// Auto-compress the responses of HTTP/1.0 requests. Disable it to serve them uncompressed (identity) for legacy HTTP/1.0 clients or proxies which can't handle compressed responses. Pre-compressed files are not affected.
+    pub compression_http10: bool,
+
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[arg(long, default_value = "", env = "SERVER_COMPRESSION_TYPES")]
+    /// Comma-separated list of MIME type patterns (e.g. `text/*,application/json`) of responses to be auto-compressed. Other responses are sent uncompressed. It defaults to common text-based types like `text/*`, JSON, JavaScript, SVG or WebAssembly when empty.
+    pub compression_types: String,
+
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[arg(long, default_value = "", env = "SERVER_COMPRESSION_ENCODINGS")]
+    /// Comma-separated list of auto-compression encodings (`zstd`, `br`, `gzip` or `deflate`) in order of preference. The order resolves ties between encodings with the same `Accept-Encoding` quality value and encodings not listed are not used. It defaults to `zstd,br,gzip,deflate` when empty.
+    pub compression_encodings: String,
+
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[arg(
+        long,
+        default_value = "",
+        env = "SERVER_COMPRESSION_USER_AGENT_DENYLIST"
+    )]
+    /// Comma-separated list of case-insensitive glob patterns (e.g. `*MSIE [1-6].*`) matched against the `User-Agent` request header. Matching clients always get uncompressed (identity) responses, including pre-compressed files. Useful to work around legacy clients mishandling compressed responses. Empty by default.
+    pub compression_user_agent_denylist: String,
+
+    #[arg(long, default_value = "index.html", env = "SERVER_INDEX_FILES")]
+    /// Comma-separated list of index file names tried in order for directory requests. E.g. "index.html,index.htm". If none exists, the directory listing is served if enabled, otherwise the fallback page if set or a 404 error. An empty value disables the index files.
+    pub index_files: String,
+
+    #[arg(long, default_value = "", env = "SERVER_ROOT_PAGE")]
+    /// File path relative to the root directory (e.g. "home.html") served for the root path ("/") requests only, regardless of the index files and the directory listing. Deeper paths are not affected. Default empty (disabled).
+    pub root_page: String,
+
+    #[arg(
+        long,
+        default_value = "true",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_EXTENSIONLESS_HTML",
+    )]
+    /// Serve the `.html` suffixed file (e.g. `/guide/intro.html`) for a request path without file extension (e.g. `/guide/intro`) when no file or directory matches it. Real directories always take precedence.
+    pub extensionless_html: bool,
+
+    #[arg(
+        long,
+        default_value = "GET,HEAD,OPTIONS",
+        env = "SERVER_ALLOWED_METHODS"
+    )]
+    /// Comma-separated list of allowed HTTP methods. Values: GET, HEAD or OPTIONS. Requests with other methods are rejected early with a `405 Method Not Allowed` response including an `Allow` header. Default "GET,HEAD,OPTIONS".
+    pub allowed_methods: String,
+
     #[cfg(feature = "directory-listing")]
     #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
     #[arg(
@@ -295,6 +605,79 @@ pub struct General {
     /// Specify a content format for directory listing entries. Formats supported: "html" or "json". Default "html".
     pub directory_listing_format: DirListFmt,
 
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    #[arg(
+        long,
+        value_enum,
+        requires_if("true", "directory_listing"),
+        default_value = "human",
+        env = "SERVER_DIRECTORY_LISTING_SIZE_FORMAT",
+        ignore_case(true)
+    )]
+    /// Specify a file size format for the HTML directory listing entries. Formats supported: "human" (e.g. 1.5 KiB) or "bytes". Default "human". Note that the JSON format always uses bytes.
+    pub directory_listing_size_format: DirListSizeFmt,
+
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    #[arg(long, default_value = "", env = "SERVER_DIRECTORY_LISTING_IGNORE")]
+    /// Comma-separated list of glob patterns of file or directory names to omit from the directory listing entries. E.g. "*.bak,node_modules". Note that matching entries are only hidden from the listing but they can still be requested.
+    pub directory_listing_ignore: String,
+
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        requires_if("true", "directory_listing"),
+        env = "SERVER_DIRECTORY_LISTING_SHOW_SYMLINKS"
+    )]
+    /// Flag the symbolic link entries of the directory listing along with their targets. Broken symbolic links are also listed instead of being omitted. It depends on "directory_listing" to be enabled.
+    pub directory_listing_show_symlinks: bool,
+
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        requires_if("true", "directory_listing"),
+        env = "SERVER_DIRECTORY_LISTING_MERGE_OVERLAY"
+    )]
+    /// Merge the entries of the same directory of the root directory into the directory listing of the overlay directory. Otherwise, only the entries of the first directory found (overlay first) are listed. It depends on "directory_listing" and "overlay_root" to be enabled.
+    pub directory_listing_merge_overlay: bool,
+
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    #[arg(
+        long,
+        requires_if("true", "directory_listing"),
+        default_value = "%F %T",
+        env = "SERVER_DIRECTORY_LISTING_DATE_FORMAT"
+    )]
+    /// Specify a strftime-style format (e.g. "%Y-%m-%dT%H:%M:%SZ") of the last modified dates of the HTML directory listing entries. Default "%F %T". Note that the JSON format always uses RFC 3339 UTC dates.
+    pub directory_listing_date_format: String,
+
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    #[arg(
+        long,
+        value_enum,
+        requires_if("true", "directory_listing"),
+        default_value = "local",
+        env = "SERVER_DIRECTORY_LISTING_TIMEZONE",
+        ignore_case(true)
+    )]
+    /// Specify the timezone of the last modified dates of the HTML directory listing entries. Values supported: "local" or "utc". Default "local". Note that the JSON format always uses UTC.
+    pub directory_listing_timezone: DirListTimezone,
+
     #[arg(
         long,
         default_value = "false",
@@ -332,6 +715,7 @@ pub struct General {
     #[cfg(feature = "basic-auth")]
     /// It provides The "Basic" HTTP Authentication scheme using credentials as "user-id:password" pairs. Password must be encoded using the "BCrypt" password-hashing function.
     #[arg(long, default_value = "", env = "SERVER_BASIC_AUTH")]
+    #[serde(serialize_with = "redact")]
     pub basic_auth: String,
 
     #[arg(long, short = 'q', default_value = "0", env = "SERVER_GRACE_PERIOD")]
@@ -342,6 +726,18 @@ pub struct General {
     /// Server TOML configuration file path.
     pub config_file: Option<PathBuf>,
 
+    #[arg(
+        long,
+        value_enum,
+        num_args(0..=1),
+        require_equals(true),
+        default_missing_value("toml"),
+        ignore_case(true)
+    )]
+    #[serde(skip)]
+    /// Print the effective configuration after merging the CLI, environment and config file options, then exit. Formats supported: "toml" (default) or "json". Note that secrets like the basic auth credentials are redacted.
+    pub print_config: Option<PrintConfigFmt>,
+
     #[arg(
         long,
         default_value = "false",
@@ -363,7 +759,7 @@ pub struct General {
         action = clap::ArgAction::Set,
         env = "SERVER_REDIRECT_TRAILING_SLASH",
     )]
-    /// Check for a trailing slash in the requested directory URI and redirect permanently (308) to the same path with a trailing slash suffix if it is missing.
+    /// Check for a trailing slash in the requested directory URI and redirect permanently (308) to the same path with a trailing slash suffix if it is missing. File paths with a trailing slash respond with a 404 instead.
     pub redirect_trailing_slash: bool,
 
     #[arg(
@@ -391,6 +787,347 @@ pub struct General {
     /// This is especially useful with Kubernetes liveness and readiness probes.
     pub health: bool,
 
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_CASE_INSENSITIVE_PATHS",
+    )]
+    /// Fall back to a case-insensitive lookup of the requested path when an exact-case lookup misses.
+    /// The first matching file or directory is served unless the match is ambiguous, in which case a 404 is returned.
+    pub case_insensitive_paths: bool,
+
+    #[arg(long, default_value = "", env = "SERVER_CANONICAL_HOST")]
+    /// Canonical host name (and optional port) to permanently redirect (301) requests to when their `Host` does not match it. E.g. `example.com` or `www.example.com:8080`. Path, query and scheme are preserved. Disabled by default.
+    pub canonical_host: String,
+
+    #[arg(long, default_value = "off", env = "SERVER_SERVER_HEADER")]
+    /// Value of the `Server` response header. Use `default` to send `static-web-server/<version>`, `off` to omit the header or any other value as a custom one. Default `off`.
+    pub server_header: String,
+
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_DISABLE_RANGE_REQUESTS",
+    )]
+    /// Disable range requests. If enabled, the full content is always served (200) and the `Accept-Ranges` header is omitted.
+    pub disable_range_requests: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "metadata",
+        env = "SERVER_ETAG_MODE",
+        ignore_case(true)
+    )]
+    /// Generation mode of the files entity tags (`ETag`). Use "metadata" for strong tags derived from the file modification time and size, "weak" for the same tags marked as weak (`W/`) or "strong" for strong tags derived from a hash of the file content, which costs reading the whole file once per modification. Default "metadata".
+    pub etag_mode: EtagMode,
+
+    #[arg(long, default_value = "/livez", env = "SERVER_HEALTH_LIVENESS_PATH")]
+    /// Path of the liveness health endpoint which always returns a 200 status code while the server is running. It depends on "health" to be enabled.
+    pub health_liveness_path: String,
+
+    #[arg(long, default_value = "/readyz", env = "SERVER_HEALTH_READINESS_PATH")]
+    /// Path of the readiness health endpoint which returns a 200 status code when the server is ready to accept requests or a 503 status code during startup, shutdown (draining) or maintenance mode. It depends on "health" to be enabled.
+    pub health_readiness_path: String,
+
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_MAINTENANCE_MODE",
+    )]
+    /// Enable the maintenance mode which responds with a 503 status code and a maintenance page to all requests except the allowed paths. When a config file is used, the mode can be toggled at runtime via a SIGHUP signal (Unix only).
+    pub maintenance_mode: bool,
+
+    #[arg(long, default_value = "", value_parser = value_parser_pathbuf, env = "SERVER_MAINTENANCE_PAGE")]
+    /// HTML file path for the maintenance mode page. If the path is not specified or simply doesn't exist then the 50x page is used instead.
+    pub maintenance_page: PathBuf,
+
+    #[arg(long, default_value = "60", env = "SERVER_MAINTENANCE_RETRY_AFTER")]
+    /// Number of seconds of the `Retry-After` header sent during the maintenance mode. Use 0 to omit the header.
+    pub maintenance_retry_after: u64,
+
+    #[arg(long, default_value = "", env = "SERVER_MAINTENANCE_ALLOWED_PATHS")]
+    /// Comma-separated list of glob patterns of request paths still served during the maintenance mode. E.g. "/assets/**,/favicon.ico".
+    pub maintenance_allowed_paths: String,
+
+    #[arg(long, default_value = "0", env = "SERVER_MAX_CONNECTIONS")]
+    /// Maximum number of concurrent connections. It applies to every listener, so the HTTP to HTTPS redirect server gets its own limit. Use `0` for no limit. Default `0`.
+    pub max_connections: usize,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "delay",
+        env = "SERVER_MAX_CONNECTIONS_MODE",
+        ignore_case(true)
+    )]
+    /// Behavior of new connections once the `--max-connections` limit is reached: "delay" (wait until a connection is closed before accepting new ones) or "close" (close new connections immediately). Default "delay".
+    pub max_connections_mode: ConnLimitMode,
+
+    #[arg(long, default_value = "5", env = "SERVER_HEADER_READ_TIMEOUT")]
+    /// Timeout in seconds for receiving the complete HTTP/1 request head (method, path and headers). Connections exceeding it are closed with no response, mitigating slow-loris attacks. Use `0` to disable it. Default `5`.
+    pub header_read_timeout: u64,
+
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_DISABLE_KEEP_ALIVE"
+    )]
+    /// Disable HTTP/1 keep-alive connections. When enabled, connections are closed after each request and every response includes a `Connection: close` header.
+    pub disable_keep_alive: bool,
+
+    #[arg(long, default_value = "", env = "SERVER_URL_PREFIX")]
+    /// Base URL prefix (subpath) to serve the files under. E.g. `/myapp`. The prefix is stripped from request paths before the file lookup and prepended to redirect locations. Requests outside the prefix respond with a 404. Disabled by default.
+    pub url_prefix: String,
+
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_REQUEST_ID"
+    )]
+    /// Attach a request ID to every request via the `X-Request-Id` response header and the request log entries. A random UUID is generated unless a trusted proxy sends one.
+    pub request_id: bool,
+
+    #[arg(long, default_value = "", env = "SERVER_REQUEST_ID_TRUSTED_PROXIES")]
+    /// Comma-separated list of proxy IP addresses whose inbound `X-Request-Id` header is trusted and used as the request ID. E.g. "10.0.0.1,::1". Default empty (never trusted).
+    pub request_id_trusted_proxies: String,
+
+    #[cfg(feature = "basic-auth")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "basic-auth")))]
+    #[arg(
+        long,
+        default_value = "Static Web Server",
+        env = "SERVER_BASIC_AUTH_REALM"
+    )]
+    /// Realm of the "Basic" HTTP Authentication challenge shown by browsers in their credentials prompt. Double quotes, backslashes and non-ASCII characters are not allowed.
+    pub basic_auth_realm: String,
+
+    #[arg(long, default_value = "0", env = "SERVER_RESPONSE_TIMEOUT")]
+    /// Timeout in seconds for producing a response, like opening a file and reading its metadata. Requests exceeding it get a `504 Gateway Timeout` response and their pending file operations are aborted. The response body streaming is not limited unless `--response-timeout-body` is enabled. Use `0` to disable it. Default `0`.
+    pub response_timeout: u64,
+
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_RESPONSE_TIMEOUT_BODY",
+    )]
+    /// Apply the response timeout to the whole response including its body streaming. Responses whose body is not fully sent in time are aborted. Note that large file or range downloads over slow connections may be aborted as well.
+    pub response_timeout_body: bool,
+
+    #[arg(long, value_enum, env = "SERVER_CACHE_CONTROL_VISIBILITY")]
+    /// Visibility directive (`public` or `private`) of the cache control headers. Default `public`. It only applies if `--cache-control-headers` is enabled.
+    pub cache_control_visibility: Option<CacheVisibility>,
+
+    #[arg(long, env = "SERVER_CACHE_CONTROL_MAX_AGE")]
+    /// Value in seconds of the `max-age` directive of the cache control headers. It replaces the default value based on the file type. It only applies if `--cache-control-headers` is enabled.
+    pub cache_control_max_age: Option<u64>,
+
+    #[arg(long, env = "SERVER_CACHE_CONTROL_S_MAXAGE")]
+    /// Value in seconds of the `s-maxage` directive (shared caches like CDNs) of the cache control headers. It can not be used along with the `private` visibility. It only applies if `--cache-control-headers` is enabled.
+    pub cache_control_s_maxage: Option<u64>,
+
+    #[arg(long, env = "SERVER_CACHE_CONTROL_STALE_WHILE_REVALIDATE")]
+    /// Value in seconds of the `stale-while-revalidate` directive of the cache control headers, allowing caches to serve stale responses while they revalidate them in the background. It only applies if `--cache-control-headers` is enabled.
+    pub cache_control_stale_while_revalidate: Option<u64>,
+
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_CACHE_CONTROL_MUST_REVALIDATE",
+    )]
+    /// Append the `must-revalidate` directive to the cache control headers, so stale responses are not served without a successful revalidation. It only applies if `--cache-control-headers` is enabled.
+    pub cache_control_must_revalidate: bool,
+
+    #[arg(long, default_value = "0", env = "SERVER_CACHE_CONTROL_ICONS_MAX_AGE")]
+    /// Value in seconds of the `max-age` directive of a long-lived `public, max-age=<seconds>, immutable` cache control header for the site icons and manifest: `/favicon.ico`, `/apple-touch-icon*.png` and `/site.webmanifest`. Only the headers are affected and cache control rules take precedence. Use `0` to disable it. Default `0`.
+    pub cache_control_icons_max_age: u64,
+
+    #[arg(
+        long,
+        default_value = "true",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_TCP_NODELAY",
+    )]
+    /// Enable the `TCP_NODELAY` option on accepted connections, disabling the Nagle algorithm so small responses are sent without delay.
+    pub tcp_nodelay: bool,
+
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_REUSE_PORT",
+    )]
+    /// Enable the `SO_REUSEPORT` option on the listening sockets, so multiple server instances can bind the same address and port while the kernel balances incoming connections between them. It is not supported on Windows, Solaris or illumos. It does not apply to inherited file descriptors or socket activation.
+    pub reuse_port: bool,
+
+    #[arg(long, default_value = "", env = "SERVER_DEFAULT_ROBOTS_TXT")]
+    /// Content of a default `/robots.txt` file served when it doesn't exist in the root directory. Use `disallow-all` or `allow-all` for all crawlers or an inline content where `\n` sequences are replaced by new lines. Real files always take precedence. Default empty (disabled).
+    pub default_robots_txt: String,
+
+    #[arg(long, env = "SERVER_DEFAULT_FAVICON")]
+    /// Icon file path of a default `/favicon.ico` file served when it doesn't exist in the root directory. Real files always take precedence. Default empty (disabled).
+    pub default_favicon: Option<PathBuf>,
+
+    #[arg(long, default_value = "", env = "SERVER_VERSION_ENDPOINT")]
+    /// Request path (e.g. `/version`) of an endpoint returning the server version, build Git commit hash, enabled features and uptime as JSON. Its requests don't generate info log entries but they are subject to the basic authentication. Default empty (disabled).
+    pub version_endpoint: String,
+
+    #[arg(long, default_value = "0", env = "SERVER_SLOW_REQUEST_THRESHOLD_MS")]
+    /// Threshold in milliseconds above which requests are logged as slow with a warning including their path, status, duration and resolved file. It's independent of the log level as long as warnings are logged. Use `0` to disable it. Default `0`.
+    pub slow_request_threshold_ms: u64,
+
+    #[arg(long, default_value = "0", env = "SERVER_NOT_FOUND_CACHE_TTL")]
+    /// Time in seconds to remember the request paths not found in the root directory, so their repeated requests get a `404 Not Found` response without looking up the filesystem again. A cached path is evicted as soon as a file appears at it or its time expires. Use `0` to disable it. Default `0`.
+    pub not_found_cache_ttl: u64,
+
+    #[arg(long, default_value = "1024", env = "SERVER_NOT_FOUND_CACHE_SIZE")]
+    /// Maximum number of request paths remembered by the not found responses cache. The oldest cached paths are evicted once it's full. It depends on "not_found_cache_ttl" to be enabled. Default `1024`.
+    pub not_found_cache_size: usize,
+
+    #[arg(long, default_value = "0", env = "SERVER_NOT_FOUND_RATE_LIMIT")]
+    /// Maximum number of requests per second of a client IP address for paths not found in the root directory. Clients exceeding it get a `429 Too Many Requests` response to all their requests until the next second. Use `0` to disable it. Default `0`.
+    pub not_found_rate_limit: u64,
+
+    #[arg(long, default_value = "", env = "SERVER_INTERNAL_PATH_PREFIX")]
+    /// Request path prefix (e.g. `/internal`) of internal-only files. Direct requests to them get a 404 response, they are only served when a trusted proxy names them via the `X-Accel-Redirect` request header. Default empty (disabled).
+    pub internal_path_prefix: String,
+
+    #[arg(long, default_value = "", env = "SERVER_INTERNAL_PATH_TRUSTED_PROXIES")]
+    /// Comma-separated list of proxy IP addresses whose `X-Accel-Redirect` request header is trusted to serve internal-only files. The header is ignored from any other address. Default empty (none).
+    pub internal_path_trusted_proxies: String,
+
+    #[arg(long, default_value = "8192", env = "SERVER_REQUEST_BODY_MAX_SIZE")]
+    /// Maximum body size in bytes of the `GET`, `HEAD` and `OPTIONS` requests. Their bodies are read and discarded before responding so they don't corrupt keep-alive connections, larger ones get a `400 Bad Request` response and the connection is closed. Use `0` to reject any body. Default `8192`.
+    pub request_body_max_size: u64,
+
+    #[arg(long, default_value = "", env = "SERVER_ALLOWED_HOSTS")]
+    /// Comma-separated list of allowed request hosts, either exact hostnames (e.g. `example.com`) or wildcard patterns matching any subdomain (e.g. `*.example.com`). Requests with a non-matching or missing host are rejected, the virtual hosts are allowed implicitly. Default empty (any host allowed).
+    pub allowed_hosts: String,
+
+    #[arg(long, default_value = "421", env = "SERVER_ALLOWED_HOSTS_STATUS")]
+    /// HTTP status code (`4xx` or `5xx`) of the requests whose host is not allowed by the `--allowed-hosts` option. Default `421` (Misdirected Request).
+    pub allowed_hosts_status: u16,
+
+    #[arg(long, default_value = "0", env = "SERVER_KEEP_ALIVE_MAX_REQUESTS")]
+    /// Maximum number of requests served per HTTP/1 keep-alive connection. The response of the last one carries a `Connection: close` header and the connection is closed once it's sent. Use `0` for unlimited requests. Default `0` (unlimited).
+    pub keep_alive_max_requests: u64,
+
+    #[arg(long, default_value = "", env = "SERVER_SITEMAP")]
+    /// Base URL (e.g. `https://example.com`) of the locations of a `/sitemap.xml` file generated from the HTML files of the root directory. A real `sitemap.xml` file takes precedence. Default empty (disabled).
+    pub sitemap: String,
+
+    #[arg(long, default_value = "300", env = "SERVER_SITEMAP_TTL")]
+    /// Time to live in seconds of the generated sitemap before it's generated again. It's also discarded once a `SIGHUP` signal is caught. Use `0` to keep it until then. Default `300`.
+    pub sitemap_ttl: u64,
+
+    #[arg(long, default_value = "", env = "SERVER_SITEMAP_IGNORE")]
+    /// Comma-separated list of glob patterns (e.g. `/drafts/**`) of request paths excluded from the generated sitemap. Default empty.
+    pub sitemap_ignore: String,
+
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_DISABLE_INDEX",
+    )]
+    /// Disable the index files resolution entirely, so directory requests are never served with an index file (see `--index-files`) but the directory listing if enabled, otherwise the fallback page if set or a 404 error.
+    pub disable_index: bool,
+
+    #[arg(long, default_value = "", env = "SERVER_ALT_SVC")]
+    /// Value of the `Alt-Svc` response header advertising alternative services, e.g. `h3=":443"; ma=86400` for an HTTP/3 capable edge in front of the server, or `clear`. The value is validated at startup. Default empty (disabled).
+    pub alt_svc: String,
+
+    #[arg(long, default_value = "", env = "SERVER_UPGRADE_PROTOCOLS")]
+    /// Comma-separated list of protocols advertised via the `Upgrade` response header on HTTP/1.1 connections, e.g. `h2c`. Default empty (disabled).
+    pub upgrade_protocols: String,
+
+    #[arg(long, default_value = "", env = "SERVER_CANONICAL_BASE_URL")]
+    /// Base URL (e.g. `https://example.com`) of the canonical `Link` header appended to the HTML responses along with the normalized request path, e.g. `Link: <https://example.com/docs/>; rel="canonical"`. Default empty (disabled).
+    pub canonical_base_url: String,
+
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_CANONICAL_SKIP_QUERY",
+    )]
+    /// Omit the canonical `Link` header for requests with a query string instead of pointing them to the URL without it. It only applies if `--canonical-base-url` is set.
+    pub canonical_skip_query: bool,
+
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[arg(
+        long,
+        default_value = "false",
+        default_missing_value("true"),
+        num_args(0..=1),
+        require_equals(true),
+        action = clap::ArgAction::Set,
+        env = "SERVER_ADAPTIVE_COMPRESSION",
+    )]
+    /// Adapt the auto-compression level to the server load, measured as the number of in-flight requests. Bodies are compressed using the best level while the server is idle, the fastest level while it's busy and the default level otherwise. Pre-compressed files are not affected. Disabled by default (default level only).
+    pub adaptive_compression: bool,
+
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[arg(
+        long,
+        default_value = "4",
+        env = "SERVER_ADAPTIVE_COMPRESSION_IDLE_REQUESTS"
+    )]
+    /// Maximum number of in-flight requests (including the current one) at which the server is considered idle by the adaptive compression, so the best compression level is used. It must be lower than the busy requests threshold.
+    pub adaptive_compression_idle_requests: u64,
+
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[arg(
+        long,
+        default_value = "64",
+        env = "SERVER_ADAPTIVE_COMPRESSION_BUSY_REQUESTS"
+    )]
+    /// Minimum number of in-flight requests (including the current one) at which the server is considered busy by the adaptive compression, so the fastest compression level is used.
+    pub adaptive_compression_busy_requests: u64,
+
     //
     // Windows specific arguments and commands
     //
@@ -411,6 +1148,7 @@ pub struct General {
     // Windows commands
     #[cfg(windows)]
     #[command(subcommand)]
+    #[serde(skip)]
     /// Subcommands to install or uninstall the SWS Windows Service.
     pub commands: Option<Commands>,
 }
@@ -428,7 +1166,16 @@ pub enum Commands {
     Uninstall {},
 }
 
-#[cfg(feature = "fallback-page")]
 fn value_parser_pathbuf(s: &str) -> crate::Result<PathBuf, String> {
     Ok(PathBuf::from(s))
 }
+
+/// Redacts a secret value unless it's empty.
+#[cfg(feature = "basic-auth")]
+fn redact<S: serde::Serializer>(value: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    if value.is_empty() {
+        serializer.serialize_str("")
+    } else {
+        serializer.serialize_str("<redacted>")
+    }
+}
@@ -11,10 +11,16 @@ use serde_repr::{Deserialize_repr, Serialize_repr};
 use std::path::Path;
 use std::{collections::BTreeSet, path::PathBuf};
 
+#[cfg(feature = "http2")]
+use crate::tls::{PlainHttpMode, TlsClientAuthMode, TlsMinVersion};
+
 #[cfg(feature = "directory-listing")]
-use crate::directory_listing::DirListFmt;
+use crate::directory_listing::{DirListFmt, DirListSizeFmt, DirListTimezone};
 
-use crate::{helpers, Context, Result};
+use crate::{
+    connection_limit::ConnLimitMode, control_headers::CacheVisibility, etag::EtagMode, helpers,
+    Context, Result,
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "kebab-case")]
@@ -51,11 +57,33 @@ impl LogLevel {
 pub struct Headers {
     /// Header source.
     pub source: String,
+    /// Glob pattern(s) excluded from the source.
+    pub exclude: Option<Exclude>,
     #[serde(rename(deserialize = "headers"), with = "http_serde::header_map")]
     /// headers list.
     pub headers: HeaderMap,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+/// Represents one or more glob patterns excluded from the source of an advanced rule.
+pub enum Exclude {
+    /// A single glob pattern.
+    Pattern(String),
+    /// A list of glob patterns.
+    Patterns(Vec<String>),
+}
+
+impl Exclude {
+    /// Returns the exclude glob patterns.
+    pub fn patterns(&self) -> &[String] {
+        match self {
+            Exclude::Pattern(pattern) => std::slice::from_ref(pattern),
+            Exclude::Patterns(patterns) => patterns,
+        }
+    }
+}
+
 #[derive(Debug, Serialize_repr, Deserialize_repr, Clone)]
 #[repr(u16)]
 /// Represents redirects types.
@@ -72,10 +100,14 @@ pub enum RedirectsKind {
 pub struct Redirects {
     /// Source of the redirect.
     pub source: String,
+    /// Glob pattern(s) excluded from the source.
+    pub exclude: Option<Exclude>,
     /// Redirect destination.
     pub destination: String,
     /// Redirect type either 301 (Moved Permanently) or 302 (Found).
     pub kind: RedirectsKind,
+    /// Optional evaluation priority, higher values are evaluated first. Default `0`.
+    pub priority: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -84,10 +116,14 @@ pub struct Redirects {
 pub struct Rewrites {
     /// Source of the rewrite.
     pub source: String,
+    /// Glob pattern(s) excluded from the source.
+    pub exclude: Option<Exclude>,
     /// Rewrite destination.
     pub destination: String,
     /// Optional redirect type either 301 (Moved Permanently) or 302 (Found).
     pub redirect: Option<RedirectsKind>,
+    /// Optional evaluation priority, higher values are evaluated first. Default `0`.
+    pub priority: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -100,6 +136,176 @@ pub struct VirtualHosts {
     pub root: Option<PathBuf>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+/// Represents file download rules.
+pub struct Downloads {
+    /// Source of the download rule.
+    pub source: String,
+    /// Glob pattern(s) excluded from the source.
+    pub exclude: Option<Exclude>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+/// Represents early hints (preload `Link` headers) rules.
+pub struct EarlyHints {
+    /// Source of the early hints rule.
+    pub source: String,
+    /// Glob pattern(s) excluded from the source.
+    pub exclude: Option<Exclude>,
+    /// List of `Link` header values.
+    pub links: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+/// Represents `Cache-Control` rules by content type or file extension.
+pub struct CacheControl {
+    /// Content type (e.g. `image/*`) or file extension (e.g. `woff2`) pattern.
+    pub pattern: String,
+    /// `Cache-Control` header value. It can not be used along with the structured directives.
+    pub value: Option<String>,
+    /// `public` or `private` directive.
+    pub visibility: Option<CacheVisibility>,
+    /// `max-age` directive value in seconds.
+    pub max_age: Option<u64>,
+    /// `s-maxage` directive value in seconds.
+    pub s_maxage: Option<u64>,
+    /// `stale-while-revalidate` directive value in seconds.
+    pub stale_while_revalidate: Option<u64>,
+    /// `must-revalidate` directive.
+    pub must_revalidate: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+/// Represents a bundler asset manifest used to derive preload `Link` headers.
+pub struct PreloadManifest {
+    /// Path of the JSON manifest file.
+    pub path: PathBuf,
+    /// URL path prefix of the manifest asset files. It defaults to `/`.
+    pub base: Option<String>,
+    /// Routes mapped to manifest entrypoints.
+    pub routes: Vec<PreloadManifestRoute>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+/// Represents a route of the preload manifest.
+pub struct PreloadManifestRoute {
+    /// Source of the route.
+    pub source: String,
+    /// Manifest entrypoint key (e.g. `src/main.ts`).
+    pub entrypoint: String,
+}
+
+#[cfg(feature = "basic-auth")]
+#[cfg_attr(docsrs, doc(cfg(feature = "basic-auth")))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+/// Represents path-based Basic HTTP Authentication rules.
+pub struct BasicAuth {
+    /// Source of the basic auth rule.
+    pub source: String,
+    /// Glob pattern(s) excluded from the source.
+    pub exclude: Option<Exclude>,
+    /// Credentials as a `user-id:password` pair with a BCrypt encoded password.
+    pub credentials: String,
+    /// Realm of the authentication challenge.
+    pub realm: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+/// Represents rules forcing responses to not be cached.
+pub struct NoStore {
+    /// Source of the no-store rule.
+    pub source: String,
+    /// Glob pattern(s) excluded from the source.
+    pub exclude: Option<Exclude>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+/// Represents trailing slash policies.
+pub enum TrailingSlashPolicy {
+    /// Redirect paths without a trailing slash to the ones with it.
+    Add,
+    /// Redirect paths with a trailing slash to the ones without it.
+    Remove,
+    /// Serve paths with or without a trailing slash as is.
+    Ignore,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+/// Represents rules overriding the trailing slash redirection.
+pub struct TrailingSlashRule {
+    /// Source of the trailing slash rule.
+    pub source: String,
+    /// Glob pattern(s) excluded from the source.
+    pub exclude: Option<Exclude>,
+    /// Trailing slash policy of the matching paths.
+    pub policy: TrailingSlashPolicy,
+    /// Optional evaluation priority, higher values are evaluated first. Default `0`.
+    pub priority: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+/// Represents rules overriding the allowed HTTP methods.
+pub struct AllowedMethodsRule {
+    /// Source of the allowed methods rule.
+    pub source: String,
+    /// Glob pattern(s) excluded from the source.
+    pub exclude: Option<Exclude>,
+    /// Allowed HTTP methods of the matching paths (e.g. `["GET", "POST"]`).
+    pub methods: Vec<String>,
+}
+
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+/// Represents rules forcing the auto-compression on or off.
+pub struct CompressionRule {
+    /// Source of the compression rule.
+    pub source: String,
+    /// Glob pattern(s) excluded from the source.
+    pub exclude: Option<Exclude>,
+    /// Whether the matching responses are always (`true`) or never (`false`) compressed.
+    pub enabled: bool,
+}
+
+#[cfg(feature = "directory-listing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+/// Represents rules enabling or disabling the directory listing.
+pub struct DirectoryListingRule {
+    /// Source of the directory listing rule.
+    pub source: String,
+    /// Glob pattern(s) excluded from the source.
+    pub exclude: Option<Exclude>,
+    /// Whether the matching directories are listed (`true`) or not (`false`).
+    pub enabled: bool,
+}
+
+/// Compression options only available in configuration file mode.
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct Compression {
+    /// MIME type patterns (e.g. `text/*`) of responses to be auto-compressed.
+    pub types: Option<Vec<String>>,
+    /// Auto-compression encodings (e.g. `zstd`) in order of preference.
+    pub encodings: Option<Vec<String>>,
+    /// `User-Agent` patterns of clients always getting uncompressed responses.
+    pub user_agent_denylist: Option<Vec<String>>,
+}
+
 /// Advanced server options only available in configuration file mode.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "kebab-case")]
@@ -112,6 +318,32 @@ pub struct Advanced {
     pub redirects: Option<Vec<Redirects>>,
     /// Name-based virtual hosting
     pub virtual_hosts: Option<Vec<VirtualHosts>>,
+    /// Downloads
+    pub downloads: Option<Vec<Downloads>>,
+    /// Early hints
+    pub early_hints: Option<Vec<EarlyHints>>,
+    /// Preload links derived from an asset manifest
+    pub preload_manifest: Option<PreloadManifest>,
+    /// Cache control rules
+    pub cache_control: Option<Vec<CacheControl>>,
+    /// No-store rules
+    pub no_store: Option<Vec<NoStore>>,
+    /// Trailing slash rules
+    pub trailing_slash: Option<Vec<TrailingSlashRule>>,
+    /// Allowed methods rules
+    pub allowed_methods: Option<Vec<AllowedMethodsRule>>,
+    /// Compression rules
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub compression: Option<Vec<CompressionRule>>,
+    /// Basic auth rules
+    #[cfg(feature = "basic-auth")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "basic-auth")))]
+    pub basic_auth: Option<Vec<BasicAuth>>,
+    /// Directory listing rules
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    pub directory_listing: Option<Vec<DirectoryListingRule>>,
 }
 
 /// General server options available in configuration file mode.
@@ -126,9 +358,15 @@ pub struct General {
     /// Root directory path.
     pub root: Option<PathBuf>,
 
+    /// Overlay root directory path.
+    pub overlay_root: Option<PathBuf>,
+
     /// Logging.
     pub log_level: Option<LogLevel>,
 
+    /// Log file path.
+    pub log_file: Option<PathBuf>,
+
     /// Cache Control headers.
     pub cache_control_headers: Option<bool>,
 
@@ -142,6 +380,21 @@ pub struct General {
     #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
     pub compression_static: Option<bool>,
 
+    /// Compression buffer size threshold in bytes.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub compression_buffer_size: Option<u64>,
+
+    /// Compression minimum size in bytes.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub compression_min_size: Option<u64>,
+
+    /// Auto-compression of HTTP/1.0 requests.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub compression_http10: Option<bool>,
+
     /// Error 404 pages.
     pub page404: Option<PathBuf>,
     /// Error 50x pages.
@@ -160,6 +413,46 @@ pub struct General {
     #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
     pub http2_tls_key: Option<PathBuf>,
 
+    /// Minimum TLS protocol version accepted.
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    pub http2_tls_min_version: Option<TlsMinVersion>,
+
+    /// Comma-separated list of allowed TLS cipher suites in order of preference.
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    pub http2_tls_ciphers: Option<String>,
+
+    /// How to handle plain HTTP requests sent to the HTTPS server port.
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    pub http2_plain_http: Option<PlainHttpMode>,
+
+    /// Fall back to plain HTTP when the TLS certificate or key file is missing or invalid.
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    pub http2_tls_fallback: Option<bool>,
+
+    /// CA certificates file path to verify the TLS client certificates.
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    pub http2_tls_client_ca: Option<PathBuf>,
+
+    /// TLS client certificate authentication mode.
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    pub http2_tls_client_auth: Option<TlsClientAuthMode>,
+
+    /// HTTP/2 keep-alive pings interval in seconds.
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    pub http2_keep_alive_interval: Option<u64>,
+
+    /// HTTP/2 keep-alive pings acknowledgement timeout in seconds.
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    pub http2_keep_alive_timeout: Option<u64>,
+
     /// Redirect all HTTP requests to HTTPS.
     #[cfg(feature = "http2")]
     #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
@@ -177,9 +470,23 @@ pub struct General {
     #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
     pub https_redirect_from_hosts: Option<String>,
 
+    /// URL path of ACME challenge files served over HTTP.
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    pub https_redirect_acme_challenge_path: Option<String>,
+
+    /// Directory containing the ACME challenge files.
+    #[cfg(feature = "http2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "http2")))]
+    pub https_redirect_acme_challenge_dir: Option<PathBuf>,
+
     /// Security headers.
     pub security_headers: Option<bool>,
 
+    /// Custom HTTP headers appended to all responses.
+    #[serde(default, with = "optional_header_map")]
+    pub response_headers: Option<HeaderMap>,
+
     /// Cors allow origins feature.
     pub cors_allow_origins: Option<String>,
     /// Cors allow headers feature.
@@ -187,6 +494,18 @@ pub struct General {
     /// Cors expose headers feature.
     pub cors_expose_headers: Option<String>,
 
+    /// Index files of directories.
+    pub index_files: Option<String>,
+
+    /// File served for the root path requests.
+    pub root_page: Option<String>,
+
+    /// Extensionless HTML paths feature.
+    pub extensionless_html: Option<bool>,
+
+    /// Allowed HTTP methods.
+    pub allowed_methods: Option<String>,
+
     /// Directory listing feature.
     #[cfg(feature = "directory-listing")]
     #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
@@ -199,6 +518,34 @@ pub struct General {
     #[cfg(feature = "directory-listing")]
     #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
     pub directory_listing_format: Option<DirListFmt>,
+    /// Directory listing size format feature.
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    pub directory_listing_size_format: Option<DirListSizeFmt>,
+    /// Directory listing ignore patterns feature.
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    pub directory_listing_ignore: Option<String>,
+
+    /// Flag the symbolic link entries of the directory listing along with their targets.
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    pub directory_listing_show_symlinks: Option<bool>,
+
+    /// Directory listing merge of the overlay and root directories entries.
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    pub directory_listing_merge_overlay: Option<bool>,
+
+    /// Directory listing date format.
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    pub directory_listing_date_format: Option<String>,
+
+    /// Directory listing timezone.
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    pub directory_listing_timezone: Option<DirListTimezone>,
 
     /// Basic Authentication feature.
     #[cfg(feature = "basic-auth")]
@@ -208,9 +555,15 @@ pub struct General {
     /// File descriptor binding feature.
     pub fd: Option<usize>,
 
+    /// Systemd socket activation feature.
+    pub socket_activation: Option<bool>,
+
     /// Worker threads.
     pub threads_multiplier: Option<usize>,
 
+    /// Exact number of worker threads.
+    pub threads: Option<usize>,
+
     /// Max blocking threads feature.
     pub max_blocking_threads: Option<usize>,
 
@@ -222,6 +575,16 @@ pub struct General {
     #[cfg_attr(docsrs, doc(cfg(feature = "fallback-page")))]
     pub page_fallback: Option<PathBuf>,
 
+    /// Page fallback status code feature.
+    #[cfg(feature = "fallback-page")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "fallback-page")))]
+    pub page_fallback_status: Option<u16>,
+
+    /// Glob patterns of request paths falling back to the nearest index file.
+    #[cfg(feature = "fallback-page")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "fallback-page")))]
+    pub page_fallback_nested: Option<String>,
+
     /// Log remote address feature.
     pub log_remote_address: Option<bool>,
 
@@ -234,6 +597,203 @@ pub struct General {
     /// Health endpoint feature.
     pub health: Option<bool>,
 
+    /// Case-insensitive paths feature.
+    pub case_insensitive_paths: Option<bool>,
+
+    /// Canonical host feature.
+    pub canonical_host: Option<String>,
+
+    /// Server header feature.
+    pub server_header: Option<String>,
+
+    /// Disable range requests feature.
+    pub disable_range_requests: Option<bool>,
+
+    /// Entity tags generation mode.
+    pub etag_mode: Option<EtagMode>,
+
+    /// Health liveness endpoint path.
+    pub health_liveness_path: Option<String>,
+
+    /// Health readiness endpoint path.
+    pub health_readiness_path: Option<String>,
+
+    /// Maintenance mode feature.
+    pub maintenance_mode: Option<bool>,
+
+    /// Maintenance mode page.
+    pub maintenance_page: Option<PathBuf>,
+
+    /// Maintenance mode `Retry-After` seconds.
+    pub maintenance_retry_after: Option<u64>,
+
+    /// Maintenance mode allowed paths.
+    pub maintenance_allowed_paths: Option<String>,
+
+    /// Maximum number of concurrent connections.
+    pub max_connections: Option<usize>,
+
+    /// Behavior of new connections once the connection limit is reached.
+    pub max_connections_mode: Option<ConnLimitMode>,
+
+    /// Timeout in seconds for receiving the complete HTTP/1 request head.
+    pub header_read_timeout: Option<u64>,
+
+    /// Disable HTTP/1 keep-alive connections.
+    pub disable_keep_alive: Option<bool>,
+
+    /// URL prefix (subpath) to serve the files under.
+    pub url_prefix: Option<String>,
+
+    /// Request ID feature.
+    pub request_id: Option<bool>,
+
+    /// Trusted proxies of the inbound request ID.
+    pub request_id_trusted_proxies: Option<String>,
+
+    /// ACME (Let's Encrypt) certificates feature.
+    #[cfg(feature = "acme")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "acme")))]
+    pub acme: Option<bool>,
+
+    /// ACME server terms of service agreement.
+    #[cfg(feature = "acme")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "acme")))]
+    pub acme_tos_agreed: Option<bool>,
+
+    /// ACME account contact email.
+    #[cfg(feature = "acme")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "acme")))]
+    pub acme_email: Option<String>,
+
+    /// ACME certificate domain names.
+    #[cfg(feature = "acme")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "acme")))]
+    pub acme_domains: Option<String>,
+
+    /// ACME cache directory.
+    #[cfg(feature = "acme")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "acme")))]
+    pub acme_cache_dir: Option<PathBuf>,
+
+    /// ACME server directory URL.
+    #[cfg(feature = "acme")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "acme")))]
+    pub acme_directory_url: Option<String>,
+
+    /// Basic HTTP Authentication realm.
+    #[cfg(feature = "basic-auth")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "basic-auth")))]
+    pub basic_auth_realm: Option<String>,
+
+    /// Response timeout in seconds.
+    pub response_timeout: Option<u64>,
+
+    /// Apply the response timeout to the response body streaming too.
+    pub response_timeout_body: Option<bool>,
+
+    /// Cache control visibility directive.
+    pub cache_control_visibility: Option<CacheVisibility>,
+
+    /// Cache control max-age directive.
+    pub cache_control_max_age: Option<u64>,
+
+    /// Cache control s-maxage directive.
+    pub cache_control_s_maxage: Option<u64>,
+
+    /// Cache control stale-while-revalidate directive.
+    pub cache_control_stale_while_revalidate: Option<u64>,
+
+    /// Cache control must-revalidate directive.
+    pub cache_control_must_revalidate: Option<bool>,
+
+    /// Cache control max-age of the site icons.
+    pub cache_control_icons_max_age: Option<u64>,
+
+    /// TCP_NODELAY option of accepted connections.
+    pub tcp_nodelay: Option<bool>,
+
+    /// SO_REUSEPORT option of the listening socket.
+    pub reuse_port: Option<bool>,
+
+    /// Default robots.txt content.
+    pub default_robots_txt: Option<String>,
+
+    /// Default favicon file path.
+    pub default_favicon: Option<PathBuf>,
+
+    /// Version endpoint path.
+    pub version_endpoint: Option<String>,
+
+    /// Slow requests threshold in milliseconds.
+    pub slow_request_threshold_ms: Option<u64>,
+
+    /// Not found responses cache time in seconds.
+    pub not_found_cache_ttl: Option<u64>,
+
+    /// Not found responses cache maximum number of paths.
+    pub not_found_cache_size: Option<usize>,
+
+    /// Not found responses per second limit of a client IP address.
+    pub not_found_rate_limit: Option<u64>,
+
+    /// Internal-only path prefix served via the `X-Accel-Redirect` header of trusted proxies.
+    pub internal_path_prefix: Option<String>,
+
+    /// Comma-separated IP addresses whose `X-Accel-Redirect` header is trusted.
+    pub internal_path_trusted_proxies: Option<String>,
+
+    /// Maximum body size in bytes of the `GET`, `HEAD` and `OPTIONS` requests.
+    pub request_body_max_size: Option<u64>,
+
+    /// Comma-separated list of allowed hosts.
+    pub allowed_hosts: Option<String>,
+
+    /// Status code of the requests whose host is not allowed.
+    pub allowed_hosts_status: Option<u16>,
+
+    /// Maximum number of requests served per HTTP/1 keep-alive connection.
+    pub keep_alive_max_requests: Option<u64>,
+
+    /// Base URL of the generated `/sitemap.xml` file.
+    pub sitemap: Option<String>,
+
+    /// Time to live in seconds of the generated sitemap.
+    pub sitemap_ttl: Option<u64>,
+
+    /// Comma-separated list of request path globs excluded from the generated sitemap.
+    pub sitemap_ignore: Option<String>,
+
+    /// Disable the directory index files resolution.
+    pub disable_index: Option<bool>,
+
+    /// Alt-Svc header value.
+    pub alt_svc: Option<String>,
+
+    /// Protocols advertised via the Upgrade header.
+    pub upgrade_protocols: Option<String>,
+
+    /// Canonical link base URL.
+    pub canonical_base_url: Option<String>,
+
+    /// Skip the canonical link of requests with a query string.
+    pub canonical_skip_query: Option<bool>,
+
+    /// Adaptive auto-compression level based on the in-flight requests.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub adaptive_compression: Option<bool>,
+
+    /// Maximum number of in-flight requests considered as idle by the adaptive compression.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub adaptive_compression_idle_requests: Option<u64>,
+
+    /// Minimum number of in-flight requests considered as busy by the adaptive compression.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub adaptive_compression_busy_requests: Option<u64>,
+
     #[cfg(windows)]
     /// windows service feature.
     pub windows_service: Option<bool>,
@@ -247,20 +807,37 @@ pub struct Settings {
     pub general: Option<General>,
     /// Advanced settings.
     pub advanced: Option<Advanced>,
+    /// Compression settings.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub compression: Option<Compression>,
 }
 
+/// Merge behavior of the `advanced` rule lists of included configuration files.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum IncludeMerge {
+    /// Rule lists of the including file replace the included ones.
+    #[default]
+    Replace,
+    /// Rule lists of the including file are appended to the included ones.
+    Append,
+}
+
+/// Top-level TOML key containing a list of configuration files to include.
+const INCLUDE_KEY: &str = "include";
+/// Top-level TOML key defining how rule lists of included files are merged.
+const INCLUDE_MERGE_KEY: &str = "include-merge";
+
 impl Settings {
     /// Read and deserialize the server TOML configuration file by path.
+    ///
+    /// Configuration files listed in a top-level `include` key (relative to the including file)
+    /// are merged in order first, then the including file options override them.
     pub fn read(config_file: &Path) -> Result<Settings> {
-        // Validate TOML file extension
-        let ext = config_file.extension();
-        if ext.is_none() || ext.unwrap().is_empty() || ext.unwrap().ne("toml") {
-            bail!("configuration file should be in toml format. E.g `config.toml`");
-        }
-
         // TODO: validate minimal TOML file structure needed
-        let toml =
-            read_toml_file(config_file).with_context(|| "error reading toml configuration file")?;
+        let toml = read_toml_file_with_includes(config_file, &mut Vec::new())
+            .with_context(|| "error reading toml configuration file")?;
         let mut unused = BTreeSet::new();
         let manifest: Settings = serde_ignored::deserialize(toml, |path| {
             let mut key = String::new();
@@ -277,6 +854,118 @@ impl Settings {
     }
 }
 
+/// (De)serializes an optional header map validating its names and values.
+mod optional_header_map {
+    use headers::HeaderMap;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        headers: &Option<HeaderMap>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match headers {
+            Some(headers) => http_serde::header_map::serialize(headers, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<HeaderMap>, D::Error> {
+        http_serde::header_map::deserialize(deserializer).map(Some)
+    }
+}
+
+/// Read a TOML file and merge its included files recursively.
+/// The `stack` contains the files being currently read in order to detect circular includes.
+fn read_toml_file_with_includes(
+    config_file: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<toml::Value> {
+    // Validate TOML file extension
+    let ext = config_file.extension();
+    if ext.is_none() || ext.unwrap().is_empty() || ext.unwrap().ne("toml") {
+        bail!("configuration file should be in toml format. E.g `config.toml`");
+    }
+
+    let config_file = config_file.canonicalize().with_context(|| {
+        format!(
+            "error resolving toml configuration file path \"{}\"",
+            config_file.display()
+        )
+    })?;
+    if stack.contains(&config_file) {
+        bail!(
+            "circular configuration file include detected at \"{}\"",
+            config_file.display()
+        );
+    }
+
+    let mut toml = read_toml_file(&config_file)?;
+    let (includes, merge) = match toml.as_table_mut() {
+        Some(table) => {
+            let includes = match table.remove(INCLUDE_KEY) {
+                Some(value) => value
+                    .try_into::<Vec<PathBuf>>()
+                    .with_context(|| "the `include` key should be a list of file paths")?,
+                None => vec![],
+            };
+            let merge = match table.remove(INCLUDE_MERGE_KEY) {
+                Some(value) => value.try_into::<IncludeMerge>().with_context(|| {
+                    "the `include-merge` key should be either \"replace\" or \"append\""
+                })?,
+                None => IncludeMerge::default(),
+            };
+            (includes, merge)
+        }
+        None => return Ok(toml),
+    };
+    if includes.is_empty() {
+        return Ok(toml);
+    }
+
+    stack.push(config_file.clone());
+    let base_dir = config_file.parent().unwrap_or_else(|| Path::new(""));
+    let mut merged: Option<toml::Value> = None;
+    for include in includes {
+        let value = read_toml_file_with_includes(&base_dir.join(include), stack)?;
+        merged = Some(match merged {
+            Some(base) => merge_toml(base, value, merge),
+            None => value,
+        });
+    }
+    stack.pop();
+
+    Ok(match merged {
+        Some(base) => merge_toml(base, toml, merge),
+        None => toml,
+    })
+}
+
+/// Merge two TOML values where the `other` value takes precedence.
+/// Tables are merged recursively, arrays are either replaced or appended and any other value is replaced.
+fn merge_toml(base: toml::Value, other: toml::Value, merge: IncludeMerge) -> toml::Value {
+    match (base, other) {
+        (toml::Value::Table(mut base), toml::Value::Table(other)) => {
+            for (key, value) in other {
+                let value = match base.remove(&key) {
+                    Some(base_value) => merge_toml(base_value, value, merge),
+                    None => value,
+                };
+                base.insert(key, value);
+            }
+            toml::Value::Table(base)
+        }
+        (toml::Value::Array(mut base), toml::Value::Array(other))
+            if merge == IncludeMerge::Append =>
+        {
+            base.extend(other);
+            toml::Value::Array(base)
+        }
+        (_, other) => other,
+    }
+}
+
 /// Read and parse a TOML file from an specific path.
 fn read_toml_file(path: &Path) -> Result<toml::Value> {
     let toml_str = helpers::read_file(path).with_context(|| {
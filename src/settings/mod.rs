@@ -8,27 +8,37 @@
 
 use clap::Parser;
 use globset::{Glob, GlobMatcher};
-use headers::HeaderMap;
-use hyper::StatusCode;
+use headers::{HeaderMap, HeaderValue};
+use hyper::{Method, StatusCode};
 use regex::Regex;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-use crate::{helpers, logger, Context, Result};
+#[cfg(feature = "basic-auth")]
+use crate::basic_auth;
+use crate::{
+    allowed_methods, control_headers::CacheDirectives, early_hints::PreloadManifest, helpers,
+    logger, Context, Result,
+};
 
 pub mod cli;
 pub mod file;
 
 #[cfg(windows)]
 pub use cli::Commands;
+pub use cli::PrintConfigFmt;
 
 use cli::General;
 
-use self::file::{RedirectsKind, Settings as FileSettings};
+use self::file::{Exclude, RedirectsKind, Settings as FileSettings, TrailingSlashPolicy};
 
 /// The `headers` file options.
 pub struct Headers {
     /// Source pattern glob matcher
     pub source: GlobMatcher,
+    /// Exclude patterns glob matchers
+    pub exclude: Vec<GlobMatcher>,
     /// Map of custom HTTP headers
     pub headers: HeaderMap,
 }
@@ -37,6 +47,8 @@ pub struct Headers {
 pub struct Rewrites {
     /// Source pattern Regex matcher
     pub source: Regex,
+    /// Exclude patterns glob matchers
+    pub exclude: Vec<GlobMatcher>,
     /// A local file that must exist
     pub destination: String,
     /// Optional redirect type either 301 (Moved Permanently) or 302 (Found).
@@ -47,6 +59,8 @@ pub struct Rewrites {
 pub struct Redirects {
     /// Source pattern Regex matcher
     pub source: Regex,
+    /// Exclude patterns glob matchers
+    pub exclude: Vec<GlobMatcher>,
     /// A local file that must exist
     pub destination: String,
     /// Redirection type either 301 (Moved Permanently) or 302 (Found)
@@ -61,7 +75,110 @@ pub struct VirtualHosts {
     pub root: PathBuf,
 }
 
+/// The `downloads` file options.
+pub struct Downloads {
+    /// Source pattern glob matcher
+    pub source: GlobMatcher,
+    /// Exclude patterns glob matchers
+    pub exclude: Vec<GlobMatcher>,
+}
+
+/// The `early_hints` file options.
+pub struct EarlyHints {
+    /// Source pattern glob matcher
+    pub source: GlobMatcher,
+    /// Exclude patterns glob matchers
+    pub exclude: Vec<GlobMatcher>,
+    /// List of `Link` header values
+    pub links: Vec<HeaderValue>,
+}
+
+/// The `cache_control` pattern matcher variants.
+pub enum CacheControlMatcher {
+    /// Content type glob matcher (e.g. `image/*`).
+    ContentType(GlobMatcher),
+    /// Lowercase file extension without the leading dot.
+    Extension(String),
+}
+
+/// The `cache_control` file options.
+pub struct CacheControl {
+    /// Content type or file extension matcher
+    pub matcher: CacheControlMatcher,
+    /// `Cache-Control` header value
+    pub value: HeaderValue,
+}
+
+/// The `no_store` file options.
+pub struct NoStore {
+    /// Source pattern glob matcher
+    pub source: GlobMatcher,
+    /// Exclude patterns glob matchers
+    pub exclude: Vec<GlobMatcher>,
+}
+
+/// The `trailing_slash` file options.
+pub struct TrailingSlashRule {
+    /// Source pattern glob matcher
+    pub source: GlobMatcher,
+    /// Exclude patterns glob matchers
+    pub exclude: Vec<GlobMatcher>,
+    /// Trailing slash policy of the matching paths
+    pub policy: TrailingSlashPolicy,
+}
+
+/// The `allowed_methods` file options.
+pub struct AllowedMethodsRule {
+    /// Source pattern glob matcher
+    pub source: GlobMatcher,
+    /// Exclude patterns glob matchers
+    pub exclude: Vec<GlobMatcher>,
+    /// Allowed HTTP methods of the matching paths
+    pub methods: Vec<Method>,
+}
+
+/// The `compression` file options.
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub struct CompressionRule {
+    /// Source pattern glob matcher
+    pub source: GlobMatcher,
+    /// Exclude patterns glob matchers
+    pub exclude: Vec<GlobMatcher>,
+    /// Whether the matching responses are always or never compressed
+    pub enabled: bool,
+}
+
+/// The `directory_listing` file options.
+#[cfg(feature = "directory-listing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+pub struct DirectoryListingRule {
+    /// Source pattern glob matcher
+    pub source: GlobMatcher,
+    /// Exclude patterns glob matchers
+    pub exclude: Vec<GlobMatcher>,
+    /// Whether the matching directories are listed or not
+    pub enabled: bool,
+}
+
+/// The `basic_auth` file options.
+#[cfg(feature = "basic-auth")]
+#[cfg_attr(docsrs, doc(cfg(feature = "basic-auth")))]
+pub struct BasicAuth {
+    /// Source pattern glob matcher
+    pub source: GlobMatcher,
+    /// Exclude patterns glob matchers
+    pub exclude: Vec<GlobMatcher>,
+    /// User ID
+    pub user_id: String,
+    /// BCrypt encoded password
+    pub password: String,
+    /// `WWW-Authenticate` challenge header value
+    pub www_authenticate: HeaderValue,
+}
+
 /// The `advanced` file options.
+#[derive(Default)]
 pub struct Advanced {
     /// Headers list.
     pub headers: Option<Vec<Headers>>,
@@ -71,6 +188,32 @@ pub struct Advanced {
     pub redirects: Option<Vec<Redirects>>,
     /// Name-based virtual hosting
     pub virtual_hosts: Option<Vec<VirtualHosts>>,
+    /// Downloads list.
+    pub downloads: Option<Vec<Downloads>>,
+    /// Early hints list.
+    pub early_hints: Option<Vec<EarlyHints>>,
+    /// Preload links derived from an asset manifest.
+    pub preload_manifest: Option<PreloadManifest>,
+    /// Cache control rules list.
+    pub cache_control: Option<Vec<CacheControl>>,
+    /// No-store rules list.
+    pub no_store: Option<Vec<NoStore>>,
+    /// Trailing slash rules list.
+    pub trailing_slash: Option<Vec<TrailingSlashRule>>,
+    /// Allowed methods rules list.
+    pub allowed_methods: Option<Vec<AllowedMethodsRule>>,
+    /// Compression rules list.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub compression: Option<Vec<CompressionRule>>,
+    /// Basic auth rules list.
+    #[cfg(feature = "basic-auth")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "basic-auth")))]
+    pub basic_auth: Option<Vec<BasicAuth>>,
+    /// Directory listing rules list.
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    pub directory_listing: Option<Vec<DirectoryListingRule>>,
 }
 
 /// The full server CLI and File options.
@@ -92,7 +235,13 @@ impl Settings {
         let mut host = opts.host;
         let mut port = opts.port;
         let mut root = opts.root;
-        let mut log_level = opts.log_level;
+        // An explicit log level takes precedence over the verbosity flags
+        let mut log_level = match (&opts.log_level, opts.verbose) {
+            (Some(level), _) => level.to_owned(),
+            (None, 0) => String::from("error"),
+            (None, 1) => String::from("debug"),
+            (None, _) => String::from("trace"),
+        };
         let mut config_file = opts.config_file.clone();
         let mut cache_control_headers = opts.cache_control_headers;
 
@@ -100,6 +249,16 @@ impl Settings {
         let mut compression = opts.compression;
         #[cfg(feature = "compression")]
         let mut compression_static = opts.compression_static;
+        #[cfg(feature = "compression")]
+        let mut compression_buffer_size = opts.compression_buffer_size;
+        #[cfg(feature = "compression")]
+        let mut compression_min_size = opts.compression_min_size;
+        #[cfg(feature = "compression")]
+        let mut compression_types = opts.compression_types;
+        #[cfg(feature = "compression")]
+        let mut compression_encodings = opts.compression_encodings;
+        #[cfg(feature = "compression")]
+        let mut compression_user_agent_denylist = opts.compression_user_agent_denylist;
 
         let mut page404 = opts.page404;
         let mut page50x = opts.page50x;
@@ -118,6 +277,10 @@ impl Settings {
         let mut https_redirect_from_port = opts.https_redirect_from_port;
         #[cfg(feature = "http2")]
         let mut https_redirect_from_hosts = opts.https_redirect_from_hosts;
+        #[cfg(feature = "http2")]
+        let mut https_redirect_acme_challenge_path = opts.https_redirect_acme_challenge_path;
+        #[cfg(feature = "http2")]
+        let mut https_redirect_acme_challenge_dir = opts.https_redirect_acme_challenge_dir;
 
         let mut security_headers = opts.security_headers;
         let mut cors_allow_origins = opts.cors_allow_origins;
@@ -130,22 +293,133 @@ impl Settings {
         let mut directory_listing_order = opts.directory_listing_order;
         #[cfg(feature = "directory-listing")]
         let mut directory_listing_format = opts.directory_listing_format;
+        #[cfg(feature = "directory-listing")]
+        let mut directory_listing_size_format = opts.directory_listing_size_format;
+        #[cfg(feature = "directory-listing")]
+        let mut directory_listing_ignore = opts.directory_listing_ignore;
 
         #[cfg(feature = "basic-auth")]
         let mut basic_auth = opts.basic_auth;
 
         let mut fd = opts.fd;
+        let mut socket_activation = opts.socket_activation;
         let mut threads_multiplier = opts.threads_multiplier;
+        let mut threads = opts.threads;
         let mut max_blocking_threads = opts.max_blocking_threads;
         let mut grace_period = opts.grace_period;
 
         #[cfg(feature = "fallback-page")]
         let mut page_fallback = opts.page_fallback;
+        #[cfg(feature = "fallback-page")]
+        let mut page_fallback_status = opts.page_fallback_status;
 
         let mut log_remote_address = opts.log_remote_address;
         let mut redirect_trailing_slash = opts.redirect_trailing_slash;
         let mut ignore_hidden_files = opts.ignore_hidden_files;
         let mut health = opts.health;
+        let mut case_insensitive_paths = opts.case_insensitive_paths;
+        let mut canonical_host = opts.canonical_host;
+        let mut server_header = opts.server_header;
+        let mut disable_range_requests = opts.disable_range_requests;
+        let mut etag_mode = opts.etag_mode;
+        let mut health_liveness_path = opts.health_liveness_path;
+        let mut health_readiness_path = opts.health_readiness_path;
+        let mut maintenance_mode = opts.maintenance_mode;
+        let mut maintenance_page = opts.maintenance_page;
+        let mut maintenance_retry_after = opts.maintenance_retry_after;
+        let mut maintenance_allowed_paths = opts.maintenance_allowed_paths;
+        let mut max_connections = opts.max_connections;
+        let mut max_connections_mode = opts.max_connections_mode;
+        let mut header_read_timeout = opts.header_read_timeout;
+        let mut disable_keep_alive = opts.disable_keep_alive;
+        let mut url_prefix = opts.url_prefix;
+        let mut request_id = opts.request_id;
+        let mut request_id_trusted_proxies = opts.request_id_trusted_proxies;
+        #[cfg(feature = "acme")]
+        let mut acme = opts.acme;
+        #[cfg(feature = "acme")]
+        let mut acme_tos_agreed = opts.acme_tos_agreed;
+        #[cfg(feature = "acme")]
+        let mut acme_email = opts.acme_email;
+        #[cfg(feature = "acme")]
+        let mut acme_domains = opts.acme_domains;
+        #[cfg(feature = "acme")]
+        let mut acme_cache_dir = opts.acme_cache_dir;
+        #[cfg(feature = "acme")]
+        let mut acme_directory_url = opts.acme_directory_url;
+        #[cfg(feature = "basic-auth")]
+        let mut basic_auth_realm = opts.basic_auth_realm;
+        let mut response_timeout = opts.response_timeout;
+        let mut response_timeout_body = opts.response_timeout_body;
+        let mut cache_control_visibility = opts.cache_control_visibility;
+        let mut cache_control_max_age = opts.cache_control_max_age;
+        let mut cache_control_s_maxage = opts.cache_control_s_maxage;
+        let mut cache_control_stale_while_revalidate = opts.cache_control_stale_while_revalidate;
+        let mut cache_control_must_revalidate = opts.cache_control_must_revalidate;
+        let mut tcp_nodelay = opts.tcp_nodelay;
+        let mut reuse_port = opts.reuse_port;
+        let mut default_robots_txt = opts.default_robots_txt;
+        let mut default_favicon = opts.default_favicon;
+        let mut version_endpoint = opts.version_endpoint;
+        #[cfg(feature = "directory-listing")]
+        let mut directory_listing_date_format = opts.directory_listing_date_format;
+        #[cfg(feature = "directory-listing")]
+        let mut directory_listing_timezone = opts.directory_listing_timezone;
+        let mut index_files = opts.index_files;
+        let mut slow_request_threshold_ms = opts.slow_request_threshold_ms;
+        let mut extensionless_html = opts.extensionless_html;
+        let mut log_file = opts.log_file;
+        let mut allowed_methods = opts.allowed_methods;
+        let mut cache_control_icons_max_age = opts.cache_control_icons_max_age;
+        #[cfg(feature = "compression")]
+        let mut compression_http10 = opts.compression_http10;
+        #[cfg(feature = "http2")]
+        let mut http2_plain_http = opts.http2_plain_http;
+        #[cfg(feature = "http2")]
+        let mut http2_tls_fallback = opts.http2_tls_fallback;
+        #[cfg(feature = "http2")]
+        let mut http2_tls_client_ca = opts.http2_tls_client_ca;
+        #[cfg(feature = "http2")]
+        let mut http2_tls_client_auth = opts.http2_tls_client_auth;
+        #[cfg(feature = "directory-listing")]
+        let mut directory_listing_show_symlinks = opts.directory_listing_show_symlinks;
+        #[cfg(feature = "http2")]
+        let mut http2_tls_min_version = opts.http2_tls_min_version;
+        #[cfg(feature = "http2")]
+        let mut http2_tls_ciphers = opts.http2_tls_ciphers;
+        #[cfg(feature = "fallback-page")]
+        let mut page_fallback_nested = opts.page_fallback_nested;
+        let mut overlay_root = opts.overlay_root;
+        #[cfg(feature = "directory-listing")]
+        let mut directory_listing_merge_overlay = opts.directory_listing_merge_overlay;
+        let mut not_found_cache_ttl = opts.not_found_cache_ttl;
+        let mut not_found_cache_size = opts.not_found_cache_size;
+        let mut not_found_rate_limit = opts.not_found_rate_limit;
+        #[cfg(feature = "http2")]
+        let mut http2_keep_alive_interval = opts.http2_keep_alive_interval;
+        #[cfg(feature = "http2")]
+        let mut http2_keep_alive_timeout = opts.http2_keep_alive_timeout;
+        let mut root_page = opts.root_page;
+        let mut internal_path_prefix = opts.internal_path_prefix;
+        let mut internal_path_trusted_proxies = opts.internal_path_trusted_proxies;
+        let mut request_body_max_size = opts.request_body_max_size;
+        let mut allowed_hosts = opts.allowed_hosts;
+        let mut allowed_hosts_status = opts.allowed_hosts_status;
+        let mut keep_alive_max_requests = opts.keep_alive_max_requests;
+        let mut sitemap = opts.sitemap;
+        let mut sitemap_ttl = opts.sitemap_ttl;
+        let mut sitemap_ignore = opts.sitemap_ignore;
+        let mut disable_index = opts.disable_index;
+        let mut alt_svc = opts.alt_svc;
+        let mut upgrade_protocols = opts.upgrade_protocols;
+        let mut canonical_base_url = opts.canonical_base_url;
+        let mut canonical_skip_query = opts.canonical_skip_query;
+        #[cfg(feature = "compression")]
+        let mut adaptive_compression = opts.adaptive_compression;
+        #[cfg(feature = "compression")]
+        let mut adaptive_compression_idle_requests = opts.adaptive_compression_idle_requests;
+        #[cfg(feature = "compression")]
+        let mut adaptive_compression_busy_requests = opts.adaptive_compression_busy_requests;
 
         // Windows-only options
         #[cfg(windows)]
@@ -153,6 +427,7 @@ impl Settings {
 
         // Define the advanced file options
         let mut settings_advanced: Option<Advanced> = None;
+        let mut response_headers: Option<HeaderMap> = None;
 
         // Handle "config file options" and set them when available
         // NOTE: All config file based options shouldn't be mandatory, therefore `Some()` wrapped
@@ -161,6 +436,7 @@ impl Settings {
 
             // File-based "general" options
             if let Some(general) = settings.general {
+                response_headers = general.response_headers;
                 if let Some(v) = general.host {
                     host = v
                 }
@@ -184,6 +460,14 @@ impl Settings {
                 if let Some(v) = general.compression_static {
                     compression_static = v
                 }
+                #[cfg(feature = "compression")]
+                if let Some(v) = general.compression_buffer_size {
+                    compression_buffer_size = v
+                }
+                #[cfg(feature = "compression")]
+                if let Some(v) = general.compression_min_size {
+                    compression_min_size = v
+                }
                 if let Some(v) = general.page404 {
                     page404 = v
                 }
@@ -219,6 +503,14 @@ impl Settings {
                     https_redirect_from_hosts = v
                 }
                 #[cfg(feature = "http2")]
+                if let Some(v) = general.https_redirect_acme_challenge_path {
+                    https_redirect_acme_challenge_path = v
+                }
+                #[cfg(feature = "http2")]
+                if let Some(v) = general.https_redirect_acme_challenge_dir {
+                    https_redirect_acme_challenge_dir = v
+                }
+                #[cfg(feature = "http2")]
                 match general.security_headers {
                     Some(v) => security_headers = v,
                     _ => {
@@ -252,6 +544,14 @@ impl Settings {
                 if let Some(v) = general.directory_listing_format {
                     directory_listing_format = v
                 }
+                #[cfg(feature = "directory-listing")]
+                if let Some(v) = general.directory_listing_size_format {
+                    directory_listing_size_format = v
+                }
+                #[cfg(feature = "directory-listing")]
+                if let Some(ref v) = general.directory_listing_ignore {
+                    directory_listing_ignore = v.to_owned()
+                }
                 #[cfg(feature = "basic-auth")]
                 if let Some(ref v) = general.basic_auth {
                     basic_auth = v.to_owned()
@@ -259,9 +559,15 @@ impl Settings {
                 if let Some(v) = general.fd {
                     fd = Some(v)
                 }
+                if let Some(v) = general.socket_activation {
+                    socket_activation = v
+                }
                 if let Some(v) = general.threads_multiplier {
                     threads_multiplier = v
                 }
+                if let Some(v) = general.threads {
+                    threads = v
+                }
                 if let Some(v) = general.max_blocking_threads {
                     max_blocking_threads = v
                 }
@@ -272,6 +578,10 @@ impl Settings {
                 if let Some(v) = general.page_fallback {
                     page_fallback = v
                 }
+                #[cfg(feature = "fallback-page")]
+                if let Some(v) = general.page_fallback_status {
+                    page_fallback_status = v
+                }
                 if let Some(v) = general.log_remote_address {
                     log_remote_address = v
                 }
@@ -284,6 +594,267 @@ impl Settings {
                 if let Some(v) = general.health {
                     health = v
                 }
+                if let Some(v) = general.case_insensitive_paths {
+                    case_insensitive_paths = v
+                }
+                if let Some(ref v) = general.canonical_host {
+                    canonical_host = v.to_owned()
+                }
+                if let Some(ref v) = general.server_header {
+                    server_header = v.to_owned()
+                }
+                if let Some(v) = general.disable_range_requests {
+                    disable_range_requests = v
+                }
+                if let Some(v) = general.etag_mode {
+                    etag_mode = v
+                }
+                if let Some(ref v) = general.health_liveness_path {
+                    health_liveness_path = v.to_owned()
+                }
+                if let Some(ref v) = general.health_readiness_path {
+                    health_readiness_path = v.to_owned()
+                }
+                if let Some(v) = general.maintenance_mode {
+                    maintenance_mode = v
+                }
+                if let Some(v) = general.maintenance_page {
+                    maintenance_page = v
+                }
+                if let Some(v) = general.maintenance_retry_after {
+                    maintenance_retry_after = v
+                }
+                if let Some(ref v) = general.maintenance_allowed_paths {
+                    maintenance_allowed_paths = v.to_owned()
+                }
+                if let Some(v) = general.max_connections {
+                    max_connections = v
+                }
+                if let Some(v) = general.max_connections_mode {
+                    max_connections_mode = v
+                }
+                if let Some(v) = general.header_read_timeout {
+                    header_read_timeout = v
+                }
+                if let Some(v) = general.disable_keep_alive {
+                    disable_keep_alive = v
+                }
+                if let Some(ref v) = general.url_prefix {
+                    url_prefix = v.to_owned()
+                }
+                if let Some(v) = general.request_id {
+                    request_id = v
+                }
+                if let Some(ref v) = general.request_id_trusted_proxies {
+                    request_id_trusted_proxies = v.to_owned()
+                }
+                #[cfg(feature = "acme")]
+                if let Some(v) = general.acme {
+                    acme = v
+                }
+                #[cfg(feature = "acme")]
+                if let Some(v) = general.acme_tos_agreed {
+                    acme_tos_agreed = v
+                }
+                #[cfg(feature = "acme")]
+                if let Some(ref v) = general.acme_email {
+                    acme_email = v.to_owned()
+                }
+                #[cfg(feature = "acme")]
+                if let Some(ref v) = general.acme_domains {
+                    acme_domains = v.to_owned()
+                }
+                #[cfg(feature = "acme")]
+                if let Some(v) = general.acme_cache_dir {
+                    acme_cache_dir = v
+                }
+                #[cfg(feature = "acme")]
+                if let Some(ref v) = general.acme_directory_url {
+                    acme_directory_url = v.to_owned()
+                }
+                #[cfg(feature = "basic-auth")]
+                if let Some(ref v) = general.basic_auth_realm {
+                    basic_auth_realm = v.to_owned()
+                }
+                if let Some(v) = general.response_timeout {
+                    response_timeout = v
+                }
+                if let Some(v) = general.response_timeout_body {
+                    response_timeout_body = v
+                }
+                if let Some(v) = general.cache_control_visibility {
+                    cache_control_visibility = Some(v)
+                }
+                if let Some(v) = general.cache_control_max_age {
+                    cache_control_max_age = Some(v)
+                }
+                if let Some(v) = general.cache_control_s_maxage {
+                    cache_control_s_maxage = Some(v)
+                }
+                if let Some(v) = general.cache_control_stale_while_revalidate {
+                    cache_control_stale_while_revalidate = Some(v)
+                }
+                if let Some(v) = general.cache_control_must_revalidate {
+                    cache_control_must_revalidate = v
+                }
+                if let Some(v) = general.tcp_nodelay {
+                    tcp_nodelay = v
+                }
+                if let Some(v) = general.reuse_port {
+                    reuse_port = v
+                }
+                if let Some(ref v) = general.default_robots_txt {
+                    default_robots_txt = v.to_owned()
+                }
+                if let Some(v) = general.default_favicon {
+                    default_favicon = Some(v)
+                }
+                if let Some(ref v) = general.version_endpoint {
+                    version_endpoint = v.to_owned()
+                }
+                #[cfg(feature = "directory-listing")]
+                if let Some(ref v) = general.directory_listing_date_format {
+                    directory_listing_date_format = v.to_owned()
+                }
+                #[cfg(feature = "directory-listing")]
+                if let Some(v) = general.directory_listing_timezone {
+                    directory_listing_timezone = v
+                }
+                if let Some(ref v) = general.index_files {
+                    index_files = v.to_owned()
+                }
+                if let Some(v) = general.slow_request_threshold_ms {
+                    slow_request_threshold_ms = v
+                }
+                if let Some(v) = general.extensionless_html {
+                    extensionless_html = v
+                }
+                if let Some(v) = general.log_file {
+                    log_file = v
+                }
+                if let Some(ref v) = general.allowed_methods {
+                    allowed_methods = v.to_owned()
+                }
+                if let Some(v) = general.cache_control_icons_max_age {
+                    cache_control_icons_max_age = v
+                }
+                #[cfg(feature = "compression")]
+                if let Some(v) = general.compression_http10 {
+                    compression_http10 = v
+                }
+                #[cfg(feature = "http2")]
+                if let Some(v) = general.http2_plain_http {
+                    http2_plain_http = v
+                }
+                #[cfg(feature = "http2")]
+                if let Some(v) = general.http2_tls_fallback {
+                    http2_tls_fallback = v
+                }
+                #[cfg(feature = "http2")]
+                if let Some(v) = general.http2_tls_client_ca {
+                    http2_tls_client_ca = Some(v)
+                }
+                #[cfg(feature = "http2")]
+                if let Some(v) = general.http2_tls_client_auth {
+                    http2_tls_client_auth = v
+                }
+                #[cfg(feature = "directory-listing")]
+                if let Some(v) = general.directory_listing_show_symlinks {
+                    directory_listing_show_symlinks = v
+                }
+                #[cfg(feature = "http2")]
+                if let Some(v) = general.http2_tls_min_version {
+                    http2_tls_min_version = v
+                }
+                #[cfg(feature = "http2")]
+                if let Some(ref v) = general.http2_tls_ciphers {
+                    http2_tls_ciphers = v.to_owned()
+                }
+                #[cfg(feature = "fallback-page")]
+                if let Some(ref v) = general.page_fallback_nested {
+                    page_fallback_nested = v.to_owned()
+                }
+                if let Some(ref v) = general.overlay_root {
+                    overlay_root = v.to_owned()
+                }
+                #[cfg(feature = "directory-listing")]
+                if let Some(v) = general.directory_listing_merge_overlay {
+                    directory_listing_merge_overlay = v
+                }
+                if let Some(v) = general.not_found_cache_ttl {
+                    not_found_cache_ttl = v
+                }
+                if let Some(v) = general.not_found_cache_size {
+                    not_found_cache_size = v
+                }
+                if let Some(v) = general.not_found_rate_limit {
+                    not_found_rate_limit = v
+                }
+                #[cfg(feature = "http2")]
+                if let Some(v) = general.http2_keep_alive_interval {
+                    http2_keep_alive_interval = v
+                }
+                #[cfg(feature = "http2")]
+                if let Some(v) = general.http2_keep_alive_timeout {
+                    http2_keep_alive_timeout = v
+                }
+                if let Some(ref v) = general.root_page {
+                    root_page = v.to_owned()
+                }
+                if let Some(ref v) = general.internal_path_prefix {
+                    internal_path_prefix = v.to_owned()
+                }
+                if let Some(ref v) = general.internal_path_trusted_proxies {
+                    internal_path_trusted_proxies = v.to_owned()
+                }
+                if let Some(v) = general.request_body_max_size {
+                    request_body_max_size = v
+                }
+                if let Some(ref v) = general.allowed_hosts {
+                    allowed_hosts = v.to_owned()
+                }
+                if let Some(v) = general.allowed_hosts_status {
+                    allowed_hosts_status = v
+                }
+                if let Some(v) = general.keep_alive_max_requests {
+                    keep_alive_max_requests = v
+                }
+                if let Some(ref v) = general.sitemap {
+                    sitemap = v.to_owned()
+                }
+                if let Some(v) = general.sitemap_ttl {
+                    sitemap_ttl = v
+                }
+                if let Some(ref v) = general.sitemap_ignore {
+                    sitemap_ignore = v.to_owned()
+                }
+                if let Some(v) = general.disable_index {
+                    disable_index = v
+                }
+                if let Some(ref v) = general.alt_svc {
+                    alt_svc = v.to_owned()
+                }
+                if let Some(ref v) = general.upgrade_protocols {
+                    upgrade_protocols = v.to_owned()
+                }
+                if let Some(ref v) = general.canonical_base_url {
+                    canonical_base_url = v.to_owned()
+                }
+                if let Some(v) = general.canonical_skip_query {
+                    canonical_skip_query = v
+                }
+                #[cfg(feature = "compression")]
+                if let Some(v) = general.adaptive_compression {
+                    adaptive_compression = v
+                }
+                #[cfg(feature = "compression")]
+                if let Some(v) = general.adaptive_compression_idle_requests {
+                    adaptive_compression_idle_requests = v
+                }
+                #[cfg(feature = "compression")]
+                if let Some(v) = general.adaptive_compression_busy_requests {
+                    adaptive_compression_busy_requests = v
+                }
 
                 // Windows-only options
                 #[cfg(windows)]
@@ -294,10 +865,24 @@ impl Settings {
 
             // Logging system initialization
             if log_init {
-                logger::init(log_level.as_str())?;
+                logger::init(log_level.as_str(), &log_file)?;
             }
             tracing::debug!("toml configuration file read successfully");
 
+            // File-based "compression" options
+            #[cfg(feature = "compression")]
+            if let Some(compression) = settings.compression {
+                if let Some(types) = compression.types {
+                    compression_types = types.join(",");
+                }
+                if let Some(encodings) = compression.encodings {
+                    compression_encodings = encodings.join(",");
+                }
+                if let Some(user_agents) = compression.user_agent_denylist {
+                    compression_user_agent_denylist = user_agents.join(",");
+                }
+            }
+
             // File-based "advanced" options
             if let Some(advanced) = settings.advanced {
                 // 1. Custom HTTP headers assignment
@@ -316,8 +901,12 @@ impl Settings {
                                 })?
                                 .compile_matcher();
 
+                            let exclude =
+                                exclude_matchers(&headers_entry.exclude, "header", false)?;
+
                             headers_vec.push(Headers {
                                 source,
+                                exclude,
                                 headers: headers_entry.headers.to_owned(),
                             });
                         }
@@ -328,7 +917,12 @@ impl Settings {
 
                 // 2. Rewrites assignment
                 let rewrites_entries = match advanced.rewrites {
-                    Some(rewrites_entries) => {
+                    Some(mut rewrites_entries) => {
+                        // First matching entry wins so sort them by priority
+                        // keeping the declaration order of equal priorities
+                        rewrites_entries
+                            .sort_by_key(|entry| Reverse(entry.priority.unwrap_or_default()));
+
                         let mut rewrites_vec: Vec<Rewrites> = Vec::new();
 
                         // Compile a glob pattern for each rewrite sources entry
@@ -357,8 +951,12 @@ impl Settings {
                                     )
                                 })?;
 
+                            let exclude =
+                                exclude_matchers(&rewrites_entry.exclude, "rewrite", false)?;
+
                             rewrites_vec.push(Rewrites {
                                 source,
+                                exclude,
                                 destination: rewrites_entry.destination.to_owned(),
                                 redirect: rewrites_entry.redirect.to_owned(),
                             });
@@ -370,7 +968,12 @@ impl Settings {
 
                 // 3. Redirects assignment
                 let redirects_entries = match advanced.redirects {
-                    Some(redirects_entries) => {
+                    Some(mut redirects_entries) => {
+                        // First matching entry wins so sort them by priority
+                        // keeping the declaration order of equal priorities
+                        redirects_entries
+                            .sort_by_key(|entry| Reverse(entry.priority.unwrap_or_default()));
+
                         let mut redirects_vec: Vec<Redirects> = Vec::new();
 
                         // Compile a glob pattern for each redirect sources entry
@@ -399,9 +1002,13 @@ impl Settings {
                                     )
                                 })?;
 
+                            let exclude =
+                                exclude_matchers(&redirects_entry.exclude, "redirect", false)?;
+
                             let status_code = redirects_entry.kind.to_owned() as u16;
                             redirects_vec.push(Redirects {
                                 source,
+                                exclude,
                                 destination: redirects_entry.destination.to_owned(),
                                 kind: StatusCode::from_u16(status_code).with_context(|| {
                                     format!("invalid redirect status code: {status_code}")
@@ -439,16 +1046,422 @@ impl Settings {
                     _ => None,
                 };
 
+                // 4. Downloads assignment
+                let downloads_entries = match advanced.downloads {
+                    Some(downloads_entries) => {
+                        let mut downloads_vec: Vec<Downloads> = Vec::new();
+
+                        // Compile a glob pattern for each download sources entry
+                        for downloads_entry in downloads_entries.iter() {
+                            let source = Glob::new(&downloads_entry.source)
+                                .with_context(|| {
+                                    format!(
+                                        "can not compile glob pattern for download source: {}",
+                                        &downloads_entry.source
+                                    )
+                                })?
+                                .compile_matcher();
+
+                            let exclude =
+                                exclude_matchers(&downloads_entry.exclude, "download", false)?;
+
+                            downloads_vec.push(Downloads { source, exclude });
+                        }
+                        Some(downloads_vec)
+                    }
+                    _ => None,
+                };
+
+                // 5. Early hints assignment
+                let early_hints_entries = match advanced.early_hints {
+                    Some(early_hints_entries) => {
+                        let mut early_hints_vec: Vec<EarlyHints> = Vec::new();
+
+                        // Compile a glob pattern for each early hints sources entry
+                        for early_hints_entry in early_hints_entries.iter() {
+                            let source = Glob::new(&early_hints_entry.source)
+                                .with_context(|| {
+                                    format!(
+                                        "can not compile glob pattern for early hints source: {}",
+                                        &early_hints_entry.source
+                                    )
+                                })?
+                                .compile_matcher();
+
+                            let mut links = Vec::new();
+                            for link in early_hints_entry.links.iter() {
+                                let link = HeaderValue::from_str(link).with_context(|| {
+                                    format!("invalid early hints link header value: {}", link)
+                                })?;
+                                links.push(link);
+                            }
+
+                            let exclude =
+                                exclude_matchers(&early_hints_entry.exclude, "early hints", false)?;
+
+                            early_hints_vec.push(EarlyHints {
+                                source,
+                                exclude,
+                                links,
+                            });
+                        }
+                        Some(early_hints_vec)
+                    }
+                    _ => None,
+                };
+
+                // 6. Preload manifest assignment
+                let preload_manifest = match advanced.preload_manifest {
+                    Some(manifest) => {
+                        let mut routes = Vec::new();
+                        for route in manifest.routes {
+                            let source = Glob::new(&route.source)
+                                .with_context(|| {
+                                    format!(
+                                        "can not compile glob pattern for preload manifest source: {}",
+                                        &route.source
+                                    )
+                                })?
+                                .compile_matcher();
+                            routes.push((source, route.entrypoint));
+                        }
+                        let base = manifest.base.unwrap_or_default();
+                        Some(PreloadManifest::new(manifest.path, &base, routes)?)
+                    }
+                    _ => None,
+                };
+
+                // 7. Cache control assignment
+                let cache_control_entries = match advanced.cache_control {
+                    Some(cache_control_entries) => {
+                        let mut cache_control_vec: Vec<CacheControl> = Vec::new();
+
+                        // Compile a content type glob pattern or an extension for each entry
+                        for cache_control_entry in cache_control_entries.iter() {
+                            let pattern = cache_control_entry.pattern.trim();
+                            let matcher = if pattern.contains('/') {
+                                let glob = Glob::new(&pattern.to_lowercase()).with_context(|| {
+                                    format!(
+                                        "can not compile glob pattern for cache control content type: {}",
+                                        pattern
+                                    )
+                                })?;
+                                CacheControlMatcher::ContentType(glob.compile_matcher())
+                            } else {
+                                let ext = pattern.trim_start_matches('.').to_lowercase();
+                                if ext.is_empty() {
+                                    bail!("cache control pattern can not be empty");
+                                }
+                                CacheControlMatcher::Extension(ext)
+                            };
+
+                            // Compose the header value from the structured directives if any
+                            let directives = CacheDirectives {
+                                visibility: cache_control_entry.visibility,
+                                max_age: cache_control_entry.max_age,
+                                s_maxage: cache_control_entry.s_maxage,
+                                stale_while_revalidate: cache_control_entry.stale_while_revalidate,
+                                must_revalidate: cache_control_entry
+                                    .must_revalidate
+                                    .unwrap_or_default(),
+                            };
+                            let value = match &cache_control_entry.value {
+                                Some(_) if !directives.is_empty() => bail!(
+                                    "cache control value for pattern {} can not be used along with structured directives",
+                                    pattern
+                                ),
+                                Some(value) => HeaderValue::from_str(value).with_context(|| {
+                                    format!("invalid cache control header value: {}", value)
+                                })?,
+                                None => directives.header_value().with_context(|| {
+                                    format!("invalid cache control directives for pattern: {}", pattern)
+                                })?,
+                            };
+
+                            cache_control_vec.push(CacheControl { matcher, value });
+                        }
+                        Some(cache_control_vec)
+                    }
+                    _ => None,
+                };
+
+                // 8. No-store assignment
+                let no_store_entries = match advanced.no_store {
+                    Some(no_store_entries) => {
+                        let mut no_store_vec: Vec<NoStore> = Vec::new();
+
+                        // Compile a glob pattern for each no-store sources entry
+                        // following the case-insensitive paths option of the file lookup
+                        for no_store_entry in no_store_entries.iter() {
+                            let source = globset::GlobBuilder::new(&no_store_entry.source)
+                                .case_insensitive(case_insensitive_paths)
+                                .build()
+                                .with_context(|| {
+                                    format!(
+                                        "can not compile glob pattern for no-store source: {}",
+                                        &no_store_entry.source
+                                    )
+                                })?
+                                .compile_matcher();
+
+                            let exclude = exclude_matchers(
+                                &no_store_entry.exclude,
+                                "no-store",
+                                case_insensitive_paths,
+                            )?;
+
+                            no_store_vec.push(NoStore { source, exclude });
+                        }
+                        Some(no_store_vec)
+                    }
+                    _ => None,
+                };
+
+                // 9. Compression rules assignment
+                #[cfg(feature = "compression")]
+                let compression_entries = match advanced.compression {
+                    Some(compression_entries) => {
+                        let mut compression_vec: Vec<CompressionRule> = Vec::new();
+
+                        // Compile a glob pattern for each compression sources entry
+                        for compression_entry in compression_entries.iter() {
+                            let source = Glob::new(&compression_entry.source)
+                                .with_context(|| {
+                                    format!(
+                                        "can not compile glob pattern for compression source: {}",
+                                        &compression_entry.source
+                                    )
+                                })?
+                                .compile_matcher();
+
+                            let exclude =
+                                exclude_matchers(&compression_entry.exclude, "compression", false)?;
+
+                            compression_vec.push(CompressionRule {
+                                source,
+                                exclude,
+                                enabled: compression_entry.enabled,
+                            });
+                        }
+                        Some(compression_vec)
+                    }
+                    _ => None,
+                };
+
+                // 10. Basic auth assignment
+                #[cfg(feature = "basic-auth")]
+                let basic_auth_entries = match advanced.basic_auth {
+                    Some(basic_auth_entries) => {
+                        let mut basic_auth_vec: Vec<BasicAuth> = Vec::new();
+
+                        // Compile a glob pattern for each basic auth sources entry
+                        // following the case-insensitive paths option of the file lookup
+                        for basic_auth_entry in basic_auth_entries.iter() {
+                            let source = globset::GlobBuilder::new(&basic_auth_entry.source)
+                                .case_insensitive(case_insensitive_paths)
+                                .build()
+                                .with_context(|| {
+                                    format!(
+                                        "can not compile glob pattern for basic auth source: {}",
+                                        &basic_auth_entry.source
+                                    )
+                                })?
+                                .compile_matcher();
+
+                            let (user_id, password) = match basic_auth_entry
+                                .credentials
+                                .trim()
+                                .split_once(':')
+                            {
+                                Some((user_id, password)) => {
+                                    (user_id.to_owned(), password.to_owned())
+                                }
+                                None => bail!(
+                                    "invalid basic auth credentials for source {}, expected `user-id:password` pairs",
+                                    &basic_auth_entry.source
+                                ),
+                            };
+
+                            let realm = basic_auth_entry
+                                .realm
+                                .as_deref()
+                                .unwrap_or(basic_auth::DEFAULT_REALM);
+                            let www_authenticate = basic_auth::www_authenticate(realm)?;
+
+                            let exclude = exclude_matchers(
+                                &basic_auth_entry.exclude,
+                                "basic auth",
+                                case_insensitive_paths,
+                            )?;
+
+                            basic_auth_vec.push(BasicAuth {
+                                source,
+                                exclude,
+                                user_id,
+                                password,
+                                www_authenticate,
+                            });
+                        }
+                        Some(basic_auth_vec)
+                    }
+                    _ => None,
+                };
+
+                // 11. Directory listing rules assignment
+                #[cfg(feature = "directory-listing")]
+                let directory_listing_entries = match advanced.directory_listing {
+                    Some(directory_listing_entries) => {
+                        let mut directory_listing_vec: Vec<DirectoryListingRule> = Vec::new();
+
+                        // Compile a glob pattern for each directory listing sources entry
+                        for directory_listing_entry in directory_listing_entries.iter() {
+                            let source = Glob::new(&directory_listing_entry.source)
+                                .with_context(|| {
+                                    format!(
+                                        "can not compile glob pattern for directory listing source: {}",
+                                        &directory_listing_entry.source
+                                    )
+                                })?
+                                .compile_matcher();
+
+                            let exclude = exclude_matchers(
+                                &directory_listing_entry.exclude,
+                                "directory listing",
+                                false,
+                            )?;
+
+                            directory_listing_vec.push(DirectoryListingRule {
+                                source,
+                                exclude,
+                                enabled: directory_listing_entry.enabled,
+                            });
+                        }
+                        Some(directory_listing_vec)
+                    }
+                    _ => None,
+                };
+
+                // 12. Trailing slash rules assignment
+                let trailing_slash_entries = match advanced.trailing_slash {
+                    Some(mut trailing_slash_entries) => {
+                        // First matching entry wins so sort them by priority
+                        // keeping the declaration order of equal priorities
+                        trailing_slash_entries
+                            .sort_by_key(|entry| Reverse(entry.priority.unwrap_or_default()));
+
+                        let mut trailing_slash_vec: Vec<TrailingSlashRule> = Vec::new();
+
+                        // Compile a glob pattern for each trailing slash sources entry
+                        for trailing_slash_entry in trailing_slash_entries.iter() {
+                            let source = Glob::new(&trailing_slash_entry.source)
+                                .with_context(|| {
+                                    format!(
+                                        "can not compile glob pattern for trailing slash source: {}",
+                                        &trailing_slash_entry.source
+                                    )
+                                })?
+                                .compile_matcher();
+
+                            let exclude = exclude_matchers(
+                                &trailing_slash_entry.exclude,
+                                "trailing slash",
+                                false,
+                            )?;
+
+                            trailing_slash_vec.push(TrailingSlashRule {
+                                source,
+                                exclude,
+                                policy: trailing_slash_entry.policy,
+                            });
+                        }
+                        Some(trailing_slash_vec)
+                    }
+                    _ => None,
+                };
+
+                // 13. Allowed methods rules assignment
+                let allowed_methods_entries = match advanced.allowed_methods {
+                    Some(allowed_methods_entries) => {
+                        let mut allowed_methods_vec: Vec<AllowedMethodsRule> = Vec::new();
+
+                        // Compile a glob pattern for each allowed methods sources entry
+                        for allowed_methods_entry in allowed_methods_entries.iter() {
+                            let source = Glob::new(&allowed_methods_entry.source)
+                                .with_context(|| {
+                                    format!(
+                                        "can not compile glob pattern for allowed methods source: {}",
+                                        &allowed_methods_entry.source
+                                    )
+                                })?
+                                .compile_matcher();
+
+                            let exclude = exclude_matchers(
+                                &allowed_methods_entry.exclude,
+                                "allowed methods",
+                                false,
+                            )?;
+
+                            let methods =
+                                allowed_methods::parse_methods(&allowed_methods_entry.methods)
+                                    .with_context(|| {
+                                        format!(
+                                            "invalid methods for allowed methods source: {}",
+                                            &allowed_methods_entry.source
+                                        )
+                                    })?;
+
+                            allowed_methods_vec.push(AllowedMethodsRule {
+                                source,
+                                exclude,
+                                methods,
+                            });
+                        }
+                        Some(allowed_methods_vec)
+                    }
+                    _ => None,
+                };
+
                 settings_advanced = Some(Advanced {
                     headers: headers_entries,
                     rewrites: rewrites_entries,
                     redirects: redirects_entries,
                     virtual_hosts: vhosts_entries,
+                    downloads: downloads_entries,
+                    early_hints: early_hints_entries,
+                    preload_manifest,
+                    cache_control: cache_control_entries,
+                    no_store: no_store_entries,
+                    trailing_slash: trailing_slash_entries,
+                    allowed_methods: allowed_methods_entries,
+                    #[cfg(feature = "compression")]
+                    compression: compression_entries,
+                    #[cfg(feature = "basic-auth")]
+                    basic_auth: basic_auth_entries,
+                    #[cfg(feature = "directory-listing")]
+                    directory_listing: directory_listing_entries,
                 });
             }
+
+            // Global response headers are applied as a leading catch-all headers entry
+            // so the matching advanced headers entries merge with and override them
+            if let Some(headers) = response_headers.filter(|headers| !headers.is_empty()) {
+                tracing::debug!("global response headers: {:?}", headers);
+                settings_advanced
+                    .get_or_insert_with(Advanced::default)
+                    .headers
+                    .get_or_insert_with(Vec::new)
+                    .insert(
+                        0,
+                        Headers {
+                            source: Glob::new("**")?.compile_matcher(),
+                            exclude: vec![],
+                            headers,
+                        },
+                    );
+            }
         } else if log_init {
             // Logging system initialization
-            logger::init(log_level.as_str())?;
+            logger::init(log_level.as_str(), &log_file)?;
         }
 
         Ok(Settings {
@@ -456,13 +1469,26 @@ impl Settings {
                 host,
                 port,
                 root,
-                log_level,
+                log_level: Some(log_level),
+                verbose: opts.verbose,
+                quiet: opts.quiet,
                 config_file,
+                print_config: opts.print_config,
                 cache_control_headers,
                 #[cfg(feature = "compression")]
                 compression,
                 #[cfg(feature = "compression")]
                 compression_static,
+                #[cfg(feature = "compression")]
+                compression_buffer_size,
+                #[cfg(feature = "compression")]
+                compression_min_size,
+                #[cfg(feature = "compression")]
+                compression_types,
+                #[cfg(feature = "compression")]
+                compression_encodings,
+                #[cfg(feature = "compression")]
+                compression_user_agent_denylist,
                 page404,
                 page50x,
                 #[cfg(feature = "http2")]
@@ -479,6 +1505,10 @@ impl Settings {
                 https_redirect_from_port,
                 #[cfg(feature = "http2")]
                 https_redirect_from_hosts,
+                #[cfg(feature = "http2")]
+                https_redirect_acme_challenge_path,
+                #[cfg(feature = "http2")]
+                https_redirect_acme_challenge_dir,
                 security_headers,
                 cors_allow_origins,
                 cors_allow_headers,
@@ -489,18 +1519,129 @@ impl Settings {
                 directory_listing_order,
                 #[cfg(feature = "directory-listing")]
                 directory_listing_format,
+                #[cfg(feature = "directory-listing")]
+                directory_listing_size_format,
+                #[cfg(feature = "directory-listing")]
+                directory_listing_ignore,
                 #[cfg(feature = "basic-auth")]
                 basic_auth,
                 fd,
+                socket_activation,
                 threads_multiplier,
+                threads,
                 max_blocking_threads,
                 grace_period,
                 #[cfg(feature = "fallback-page")]
                 page_fallback,
+                #[cfg(feature = "fallback-page")]
+                page_fallback_status,
                 log_remote_address,
                 redirect_trailing_slash,
                 ignore_hidden_files,
                 health,
+                case_insensitive_paths,
+                canonical_host,
+                server_header,
+                disable_range_requests,
+                etag_mode,
+                health_liveness_path,
+                health_readiness_path,
+                maintenance_mode,
+                maintenance_page,
+                maintenance_retry_after,
+                maintenance_allowed_paths,
+                max_connections,
+                max_connections_mode,
+                header_read_timeout,
+                disable_keep_alive,
+                url_prefix,
+                request_id,
+                request_id_trusted_proxies,
+                #[cfg(feature = "acme")]
+                acme,
+                #[cfg(feature = "acme")]
+                acme_tos_agreed,
+                #[cfg(feature = "acme")]
+                acme_email,
+                #[cfg(feature = "acme")]
+                acme_domains,
+                #[cfg(feature = "acme")]
+                acme_cache_dir,
+                #[cfg(feature = "acme")]
+                acme_directory_url,
+                #[cfg(feature = "basic-auth")]
+                basic_auth_realm,
+                response_timeout,
+                response_timeout_body,
+                cache_control_visibility,
+                cache_control_max_age,
+                cache_control_s_maxage,
+                cache_control_stale_while_revalidate,
+                cache_control_must_revalidate,
+                tcp_nodelay,
+                reuse_port,
+                default_robots_txt,
+                default_favicon,
+                version_endpoint,
+                #[cfg(feature = "directory-listing")]
+                directory_listing_date_format,
+                #[cfg(feature = "directory-listing")]
+                directory_listing_timezone,
+                index_files,
+                slow_request_threshold_ms,
+                extensionless_html,
+                log_file,
+                allowed_methods,
+                cache_control_icons_max_age,
+                #[cfg(feature = "compression")]
+                compression_http10,
+                #[cfg(feature = "http2")]
+                http2_plain_http,
+                #[cfg(feature = "http2")]
+                http2_tls_fallback,
+                #[cfg(feature = "http2")]
+                http2_tls_client_ca,
+                #[cfg(feature = "http2")]
+                http2_tls_client_auth,
+                #[cfg(feature = "directory-listing")]
+                directory_listing_show_symlinks,
+                #[cfg(feature = "http2")]
+                http2_tls_min_version,
+                #[cfg(feature = "http2")]
+                http2_tls_ciphers,
+                #[cfg(feature = "fallback-page")]
+                page_fallback_nested,
+                overlay_root,
+                #[cfg(feature = "directory-listing")]
+                directory_listing_merge_overlay,
+                not_found_cache_ttl,
+                not_found_cache_size,
+                not_found_rate_limit,
+                #[cfg(feature = "http2")]
+                http2_keep_alive_interval,
+                #[cfg(feature = "http2")]
+                http2_keep_alive_timeout,
+                root_page,
+                internal_path_prefix,
+                internal_path_trusted_proxies,
+                request_body_max_size,
+                allowed_hosts,
+                allowed_hosts_status,
+                keep_alive_max_requests,
+                sitemap,
+                sitemap_ttl,
+                sitemap_ignore,
+                disable_index,
+                alt_svc,
+                upgrade_protocols,
+                canonical_base_url,
+                canonical_skip_query,
+                #[cfg(feature = "compression")]
+                adaptive_compression,
+                #[cfg(feature = "compression")]
+                adaptive_compression_idle_requests,
+                #[cfg(feature = "compression")]
+                adaptive_compression_busy_requests,
 
                 // Windows-only options and commands
                 #[cfg(windows)]
@@ -511,6 +1652,68 @@ impl Settings {
             advanced: settings_advanced,
         })
     }
+
+    /// Returns the effective settings in the given format once the CLI, environment
+    /// and config file options are merged.
+    /// The `advanced` rules are only summarized by their number of entries.
+    pub fn effective_config(&self, format: PrintConfigFmt) -> Result<String> {
+        #[derive(Serialize)]
+        struct EffectiveConfig<'a> {
+            general: &'a General,
+            advanced: BTreeMap<&'static str, usize>,
+        }
+
+        let mut advanced = BTreeMap::new();
+        if let Some(opts) = &self.advanced {
+            let mut add = |name: &'static str, len: Option<usize>| {
+                if let Some(len) = len.filter(|len| *len > 0) {
+                    advanced.insert(name, len);
+                }
+            };
+            add("headers", opts.headers.as_ref().map(Vec::len));
+            add("rewrites", opts.rewrites.as_ref().map(Vec::len));
+            add("redirects", opts.redirects.as_ref().map(Vec::len));
+            add("virtual-hosts", opts.virtual_hosts.as_ref().map(Vec::len));
+            add("downloads", opts.downloads.as_ref().map(Vec::len));
+            add("early-hints", opts.early_hints.as_ref().map(Vec::len));
+            add(
+                "preload-manifest",
+                opts.preload_manifest.as_ref().map(|_| 1),
+            );
+            add("cache-control", opts.cache_control.as_ref().map(Vec::len));
+            add("no-store", opts.no_store.as_ref().map(Vec::len));
+            add("trailing-slash", opts.trailing_slash.as_ref().map(Vec::len));
+            add(
+                "allowed-methods",
+                opts.allowed_methods.as_ref().map(Vec::len),
+            );
+            #[cfg(feature = "compression")]
+            add("compression", opts.compression.as_ref().map(Vec::len));
+            #[cfg(feature = "basic-auth")]
+            add("basic-auth", opts.basic_auth.as_ref().map(Vec::len));
+            #[cfg(feature = "directory-listing")]
+            add(
+                "directory-listing",
+                opts.directory_listing.as_ref().map(Vec::len),
+            );
+        }
+
+        let config = EffectiveConfig {
+            general: &self.general,
+            advanced,
+        };
+        let output = match format {
+            PrintConfigFmt::Toml => toml::to_string(&config)
+                .with_context(|| "unable to serialize the effective configuration as toml")?,
+            PrintConfigFmt::Json => {
+                let mut json = serde_json::to_string_pretty(&config)
+                    .with_context(|| "unable to serialize the effective configuration as json")?;
+                json.push('\n');
+                json
+            }
+        };
+        Ok(output)
+    }
 }
 
 fn get_file_settings(file_path_opt: Option<PathBuf>) -> Result<Option<(FileSettings, PathBuf)>> {
@@ -529,3 +1732,27 @@ fn get_file_settings(file_path_opt: Option<PathBuf>) -> Result<Option<(FileSetti
     }
     Ok(None)
 }
+
+/// Compiles the exclude glob patterns of an advanced rule.
+fn exclude_matchers(
+    exclude: &Option<Exclude>,
+    kind: &str,
+    case_insensitive: bool,
+) -> Result<Vec<GlobMatcher>> {
+    let patterns = match exclude {
+        Some(exclude) => exclude.patterns(),
+        None => return Ok(vec![]),
+    };
+    patterns
+        .iter()
+        .map(|pattern| {
+            globset::GlobBuilder::new(pattern)
+                .case_insensitive(case_insensitive)
+                .build()
+                .with_context(|| {
+                    format!("can not compile glob pattern for {kind} exclude: {pattern}")
+                })
+                .map(|glob| glob.compile_matcher())
+        })
+        .collect()
+}
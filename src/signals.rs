@@ -3,11 +3,14 @@
 // See https://static-web-server.net/ for more information
 // Copyright (C) 2019-present Jose Quintana <joseluisq.net>
 
-//! The module provides signals support like `SIGTERM`, `SIGINT` and `SIGQUIT`.
+//! The module provides signals support like `SIGTERM`, `SIGINT`, `SIGQUIT`, `SIGHUP` and `SIGUSR1`.
 //!
 
+use std::sync::Arc;
 use tokio::time::{sleep, Duration};
 
+use crate::readiness::Readiness;
+
 #[cfg(unix)]
 use {
     crate::Result, futures_util::stream::StreamExt, signal_hook::consts::signal::*,
@@ -15,7 +18,7 @@ use {
 };
 
 #[cfg(windows)]
-use {std::sync::Arc, tokio::sync::watch::Receiver, tokio::sync::Mutex};
+use {tokio::sync::watch::Receiver, tokio::sync::Mutex};
 
 #[cfg(unix)]
 #[cfg_attr(docsrs, doc(cfg(unix)))]
@@ -26,7 +29,8 @@ pub fn create_signals() -> Result<Signals> {
 
 #[cfg(unix)]
 /// It waits for a specific type of incoming signals included `ctrl+c`.
-pub async fn wait_for_signals(signals: Signals, grace_period_secs: u8) {
+/// The server is marked as draining once a termination signal is caught.
+pub async fn wait_for_signals(signals: Signals, grace_period_secs: u8, ready: Arc<Readiness>) {
     let mut signals = signals.fuse();
     while let Some(signal) = signals.next().await {
         match signal {
@@ -42,10 +46,42 @@ pub async fn wait_for_signals(signals: Signals, grace_period_secs: u8) {
         }
     }
     // NOTE: once loop above is done then an upstream graceful shutdown should come next.
+    ready.set_draining();
     delay_graceful_shutdown(grace_period_secs).await;
     tracing::info!("delegating server's graceful shutdown");
 }
 
+#[cfg(unix)]
+/// It waits for incoming `SIGHUP` signals calling the given reload function on every one.
+pub async fn wait_for_sighup<F: Fn()>(reload_fn: F) -> Result {
+    let mut signals = Signals::new([SIGHUP])?;
+    while let Some(signal) = signals.next().await {
+        if signal == SIGHUP {
+            tracing::info!("SIGHUP caught, reloading");
+            reload_fn();
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+/// It creates a signals stream for `SIGUSR1` to be observed.
+pub fn create_sigusr1_signals() -> Result<Signals> {
+    Ok(Signals::new([SIGUSR1])?)
+}
+
+#[cfg(unix)]
+/// It waits for incoming `SIGUSR1` signals calling the given reopen function on every one.
+pub async fn wait_for_sigusr1<F: Fn()>(signals: Signals, reopen_fn: F) {
+    let mut signals = signals.fuse();
+    while let Some(signal) = signals.next().await {
+        if signal == SIGUSR1 {
+            tracing::debug!("SIGUSR1 caught, re-opening log files");
+            reopen_fn();
+        }
+    }
+}
+
 /// Function intended to delay the server's graceful shutdown providing a grace period in seconds.
 async fn delay_graceful_shutdown(grace_period_secs: u8) {
     if grace_period_secs > 0 {
@@ -61,11 +97,17 @@ async fn delay_graceful_shutdown(grace_period_secs: u8) {
 #[cfg(windows)]
 #[cfg_attr(docsrs, doc(cfg(windows)))]
 /// It waits for an incoming `ctrl+c` signal on Windows.
-pub async fn wait_for_ctrl_c(cancel_recv: Arc<Mutex<Option<Receiver<()>>>>, grace_period_secs: u8) {
+/// The server is marked as draining once the signal is caught.
+pub async fn wait_for_ctrl_c(
+    cancel_recv: Arc<Mutex<Option<Receiver<()>>>>,
+    grace_period_secs: u8,
+    ready: Arc<Readiness>,
+) {
     if let Some(receiver) = &mut *cancel_recv.lock().await {
         receiver.changed().await.ok();
     }
 
+    ready.set_draining();
     delay_graceful_shutdown(grace_period_secs).await;
     tracing::info!("delegating server's graceful shutdown");
 }
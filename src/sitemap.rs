@@ -0,0 +1,300 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to serve a `sitemap.xml` file generated from the HTML files of the root directory.
+//!
+//! The generated sitemap is cached for a time to live and regenerated afterwards
+//! or once a `SIGHUP` signal is caught. A real `sitemap.xml` file always takes precedence.
+//!
+
+use globset::GlobMatcher;
+use headers::{ContentLength, ContentType, HeaderMapExt};
+use hyper::{Body, Response, StatusCode, Uri};
+use mime_guess::mime;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{helpers, Result};
+
+/// Request path of the generated sitemap.
+pub const SITEMAP_PATH: &str = "/sitemap.xml";
+
+/// Maximum number of URLs of a sitemap file according to the sitemaps protocol.
+const MAX_URLS: usize = 50_000;
+
+/// Characters percent-encoded in the URL path segments.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Generated sitemap along with its generation time.
+type Cache = Option<(Instant, Arc<str>)>;
+
+/// Generated sitemap options and cache.
+#[derive(Debug, Clone)]
+pub struct Sitemap {
+    /// Base URL of the sitemap locations without a trailing slash.
+    base_url: String,
+    /// Time to live of the generated sitemap, `None` to keep it until a reload.
+    ttl: Option<Duration>,
+    /// Glob matchers of the request paths excluded from the sitemap.
+    ignore: Arc<[GlobMatcher]>,
+    /// Generated sitemap cache.
+    cache: Arc<Mutex<Cache>>,
+}
+
+impl Sitemap {
+    /// Creates the sitemap options from the base URL (e.g. `https://example.com`),
+    /// the time to live in seconds (`0` to keep it until a reload) and a comma-separated list
+    /// of ignored path globs. It returns `None` if the base URL is empty.
+    pub fn new(base_url: &str, ttl_secs: u64, ignore: &str) -> Result<Option<Self>> {
+        let base_url = base_url.trim();
+        if base_url.is_empty() {
+            return Ok(None);
+        }
+        let valid = base_url.parse::<Uri>().is_ok_and(|uri| {
+            matches!(uri.scheme_str(), Some("http" | "https"))
+                && uri.host().is_some_and(|host| !host.is_empty())
+                && uri.query().is_none()
+        });
+        if !valid {
+            bail!("invalid sitemap base url: {:?}", base_url);
+        }
+        Ok(Some(Self {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            ttl: (ttl_secs > 0).then(|| Duration::from_secs(ttl_secs)),
+            ignore: helpers::parse_glob_list(ignore)?.into(),
+            cache: Arc::new(Mutex::new(None)),
+        }))
+    }
+
+    /// Discards the generated sitemap so it's generated again on the next request.
+    pub fn reload(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            *cache = None;
+        }
+    }
+
+    /// Returns the sitemap response of the given root directory, generating it if needed.
+    /// The request paths for which `excluded` returns `true` are not listed.
+    /// The body is omitted for `HEAD` requests.
+    /// It returns a `500 Internal Server Error` status if the generation fails.
+    pub async fn response<F>(
+        &self,
+        root: PathBuf,
+        index_files: Vec<String>,
+        head: bool,
+        excluded: F,
+    ) -> Result<Response<Body>, StatusCode>
+    where
+        F: Fn(&str) -> bool + Send + 'static,
+    {
+        let xml = self.get_or_generate(root, index_files, excluded).await?;
+        let mut resp = Response::new(if head {
+            Body::empty()
+        } else {
+            Body::from(xml.to_string())
+        });
+        resp.headers_mut()
+            .typed_insert(ContentType::from(mime::TEXT_XML));
+        resp.headers_mut()
+            .typed_insert(ContentLength(xml.len() as u64));
+        Ok(resp)
+    }
+
+    fn cached(&self) -> Option<Arc<str>> {
+        let cache = match self.cache.lock() {
+            Ok(cache) => cache,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        match cache.as_ref() {
+            Some((generated, xml)) if self.ttl.is_none_or(|ttl| generated.elapsed() < ttl) => {
+                Some(xml.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the cached sitemap or generates it on the blocking thread pool
+    /// since walking the root directory performs blocking file system calls.
+    /// The cache is not locked during the generation.
+    async fn get_or_generate<F>(
+        &self,
+        root: PathBuf,
+        index_files: Vec<String>,
+        excluded: F,
+    ) -> Result<Arc<str>, StatusCode>
+    where
+        F: Fn(&str) -> bool + Send + 'static,
+    {
+        if let Some(xml) = self.cached() {
+            return Ok(xml);
+        }
+
+        let sitemap = self.clone();
+        let generated =
+            tokio::task::spawn_blocking(move || sitemap.generate(&root, &index_files, excluded))
+                .await;
+        let xml: Arc<str> = match generated {
+            Ok(xml) => xml.into(),
+            Err(err) => {
+                tracing::error!("sitemap generation task failed: {:?}", err);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+        let mut cache = match self.cache.lock() {
+            Ok(cache) => cache,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        *cache = Some((Instant::now(), xml.clone()));
+        Ok(xml)
+    }
+
+    /// Generates the sitemap XML listing the HTML files of the root directory.
+    /// Index files are listed as their directory URL (e.g. `/docs/`) and hidden files
+    /// or directories are skipped, as well as the request paths for which `excluded` returns `true`.
+    pub fn generate(
+        &self,
+        root: &Path,
+        index_files: &[String],
+        excluded: impl Fn(&str) -> bool,
+    ) -> String {
+        let mut paths = Vec::new();
+        collect_html_paths(root, "/", index_files, &mut paths);
+        paths.retain(|path| !self.ignore.iter().any(|glob| glob.is_match(path)) && !excluded(path));
+        if paths.len() > MAX_URLS {
+            tracing::warn!(
+                "sitemap truncated to {} urls out of {}",
+                MAX_URLS,
+                paths.len()
+            );
+            paths.truncate(MAX_URLS);
+        }
+        tracing::debug!("sitemap generated with {} urls", paths.len());
+
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+        );
+        for path in paths {
+            let loc = path
+                .split('/')
+                .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            xml.push_str("  <url><loc>");
+            xml.push_str(&self.base_url);
+            xml.push_str(&loc);
+            xml.push_str("</loc></url>\n");
+        }
+        xml.push_str("</urlset>\n");
+        xml
+    }
+}
+
+/// Collects the request paths of the HTML files of a directory recursively, sorted by name.
+/// Symbolic links to directories are not followed to prevent loops.
+fn collect_html_paths(dir: &Path, base: &str, index_files: &[String], paths: &mut Vec<String>) {
+    let mut entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).collect::<Vec<_>>(),
+        Err(err) => {
+            tracing::error!(
+                "unable to read sitemap directory {:?}: {}",
+                dir.display(),
+                err
+            );
+            return;
+        }
+    };
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let name = entry.file_name();
+        let name = match name.to_str() {
+            Some(name) if !name.starts_with('.') => name.to_owned(),
+            _ => continue,
+        };
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => {
+                let base = [base, &name, "/"].concat();
+                collect_html_paths(&entry.path(), &base, index_files, &mut files);
+            }
+            Ok(_) if entry.path().is_file() => {
+                if index_files.contains(&name) {
+                    // The directory URL comes first, only once
+                    if !paths.iter().any(|path| path == base) {
+                        paths.push(base.to_owned());
+                    }
+                } else if name.ends_with(".html") {
+                    files.push([base, &name].concat());
+                }
+            }
+            _ => {}
+        }
+    }
+    paths.append(&mut files);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sitemap;
+
+    #[test]
+    fn sitemap_generation() {
+        assert!(Sitemap::new("", 60, "").unwrap().is_none());
+        for url in [
+            "example.com",
+            "ftp://example.com",
+            "https://",
+            "http://a.b?x=1",
+        ] {
+            assert!(Sitemap::new(url, 60, "").is_err(), "{url}");
+        }
+
+        let root = std::env::temp_dir().join("sws-sitemap-test");
+        std::fs::create_dir_all(root.join("docs/drafts")).unwrap();
+        std::fs::create_dir_all(root.join("private")).unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        for file in [
+            "index.html",
+            "about us.html",
+            "style.css",
+            ".hidden.html",
+            ".git/index.html",
+            "docs/index.html",
+            "docs/a&b.html",
+            "docs/drafts/wip.html",
+            "private/index.html",
+            "private/secret.html",
+        ] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+
+        let sitemap = Sitemap::new("https://example.com/", 60, "/docs/drafts/**")
+            .unwrap()
+            .unwrap();
+        let xml = sitemap.generate(&root, &["index.html".to_owned()], |path| {
+            path.starts_with("/private/")
+        });
+        assert_eq!(
+            xml,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n  \
+            <url><loc>https://example.com/</loc></url>\n  \
+            <url><loc>https://example.com/about%20us.html</loc></url>\n  \
+            <url><loc>https://example.com/docs/</loc></url>\n  \
+            <url><loc>https://example.com/docs/a%26b.html</loc></url>\n\
+            </urlset>\n"
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -10,14 +10,15 @@
 // https://github.com/seanmonstar/warp/blob/master/src/filters/fs.rs
 
 use bytes::{Bytes, BytesMut};
-use futures_util::future::{Either, Future};
-use futures_util::{future, Stream};
+use futures_util::Stream;
 use headers::{
-    AcceptRanges, ContentLength, ContentRange, ContentType, HeaderMap, HeaderMapExt, HeaderValue,
-    IfModifiedSince, IfRange, IfUnmodifiedSince, LastModified, Range,
+    AcceptRanges, ContentLength, ContentRange, ContentType, ETag, HeaderMap, HeaderMapExt,
+    HeaderValue, IfMatch, IfModifiedSince, IfNoneMatch, IfRange, IfUnmodifiedSince, LastModified,
+    Range,
 };
 use http::header::CONTENT_LENGTH;
 use hyper::{header::CONTENT_ENCODING, Body, Method, Response, StatusCode};
+use mime_guess::Mime;
 use percent_encoding::percent_decode_str;
 use std::fs::{File, Metadata};
 use std::io::{self, BufReader, Read, Seek, SeekFrom};
@@ -29,16 +30,34 @@ use std::task::{Context, Poll};
 #[cfg(feature = "compression")]
 use crate::compression_static;
 
+use crate::case_insensitive;
+use crate::etag::{self, EtagMode};
 use crate::exts::http::{MethodExt, HTTP_SUPPORTED_METHODS};
 use crate::exts::path::PathExt;
-use crate::Result;
+use crate::settings::file::TrailingSlashPolicy;
+use crate::{trailing_slash, Result};
 
 #[cfg(feature = "directory-listing")]
-use crate::{
-    directory_listing,
-    directory_listing::{DirListFmt, DirListOpts},
+use {
+    crate::{
+        directory_listing,
+        directory_listing::{DirListFmt, DirListOpts, DirListSizeFmt, DirListTimezone},
+    },
+    globset::GlobMatcher,
 };
 
+/// File extensions of the supported pre-compressed file variants.
+const PRECOMPRESSED_EXTENSIONS: [&str; 3] = ["br", "gz", "zst"];
+
+/// Resolved file of a static file response which is attached to its extensions.
+#[derive(Debug, Clone)]
+pub struct ResolvedFile {
+    /// File path served.
+    pub path: PathBuf,
+    /// File size in bytes.
+    pub size: u64,
+}
+
 /// Defines all options needed by the static-files handler.
 pub struct HandleOpts<'a> {
     /// Request method.
@@ -47,6 +66,8 @@ pub struct HandleOpts<'a> {
     pub headers: &'a HeaderMap<HeaderValue>,
     /// Request base path.
     pub base_path: &'a PathBuf,
+    /// Overlay directory looked up before the base path.
+    pub overlay_root: Option<&'a PathBuf>,
     /// Request base path.
     pub uri_path: &'a str,
     /// Request URI query.
@@ -63,35 +84,183 @@ pub struct HandleOpts<'a> {
     #[cfg(feature = "directory-listing")]
     #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
     pub dir_listing_format: &'a DirListFmt,
+    /// Directory listing size format feature.
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    pub dir_listing_size_format: &'a DirListSizeFmt,
+    /// Directory listing date format.
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    pub dir_listing_date_format: &'a str,
+    /// Directory listing timezone.
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    pub dir_listing_timezone: &'a DirListTimezone,
+    /// Directory listing ignore patterns feature.
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    pub dir_listing_ignore: &'a [GlobMatcher],
+    /// Directory listing symbolic links flagging feature.
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    pub dir_listing_show_symlinks: bool,
+    /// Directory listing merge of the overlay directory and base path entries.
+    #[cfg(feature = "directory-listing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "directory-listing")))]
+    pub dir_listing_merge_overlay: bool,
+    /// Index file names of directories in order of preference.
+    pub index_files: &'a [String],
+    /// Allowed request methods.
+    pub allowed_methods: &'a [Method],
+    /// Extensionless HTML paths feature.
+    pub extensionless_html: bool,
     /// Redirect trailing slash feature.
     pub redirect_trailing_slash: bool,
+    /// Trailing slash policy of the rule matching the request path if any.
+    pub trailing_slash_policy: Option<TrailingSlashPolicy>,
     /// Compression static feature.
     pub compression_static: bool,
     /// Ignore hidden files feature.
     pub ignore_hidden_files: bool,
+    /// Case-insensitive paths feature.
+    pub case_insensitive_paths: bool,
+    /// Disable range requests feature.
+    pub disable_range_requests: bool,
+    /// Entity tags generation mode.
+    pub etag_mode: EtagMode,
 }
 
 /// The server entry point to handle incoming requests which map to specific files
 /// on file system and return a file response.
+///
+/// If an overlay directory is set, the requested path (or its index files) is looked up there first
+/// and then in the base path. A directory listing is only served if no file is found in any of them,
+/// using the first directory containing the requested path.
 pub async fn handle<'a>(opts: &HandleOpts<'a>) -> Result<(Response<Body>, bool), StatusCode> {
+    let overlay_root = match opts.overlay_root {
+        Some(overlay_root) => overlay_root,
+        None => return handle_root(opts, None).await,
+    };
+
+    for base_path in [overlay_root, opts.base_path] {
+        match handle_root(
+            &HandleOpts {
+                base_path,
+                overlay_root: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                ..*opts
+            },
+            None,
+        )
+        .await
+        {
+            Err(status) if status == StatusCode::NOT_FOUND => continue,
+            res => return res,
+        }
+    }
+
+    #[cfg(feature = "directory-listing")]
+    if opts.dir_listing {
+        let merge_root = opts
+            .dir_listing_merge_overlay
+            .then_some(opts.base_path.as_path());
+        let overlay_opts = HandleOpts {
+            base_path: overlay_root,
+            overlay_root: None,
+            ..*opts
+        };
+        match handle_root(&overlay_opts, merge_root).await {
+            Err(status) if status == StatusCode::NOT_FOUND => {}
+            res => return res,
+        }
+        return handle_root(
+            &HandleOpts {
+                overlay_root: None,
+                ..*opts
+            },
+            None,
+        )
+        .await;
+    }
+
+    Err(StatusCode::NOT_FOUND)
+}
+
+/// Handles the request under the base path only.
+/// The directory listing entries of the same directory under `merge_root` are merged if any.
+#[cfg_attr(not(feature = "directory-listing"), allow(unused_variables))]
+async fn handle_root<'a>(
+    opts: &HandleOpts<'a>,
+    merge_root: Option<&Path>,
+) -> Result<(Response<Body>, bool), StatusCode> {
     let method = opts.method;
     let uri_path = opts.uri_path;
 
-    // Check if current HTTP method for incoming request is supported
-    if !method.is_allowed() {
+    // Check if current HTTP method for incoming request is supported and allowed
+    if !method.is_allowed() || !opts.allowed_methods.contains(method) {
         return Err(StatusCode::METHOD_NOT_ALLOWED);
     }
 
     let headers_opt = opts.headers;
-    let compression_static_opt = opts.compression_static;
-    let mut file_path = sanitize_path(opts.base_path, uri_path)?;
+    let ranges = !opts.disable_range_requests;
+    let etag_mode = opts.etag_mode;
+
+    #[cfg(feature = "compression")]
+    let precompressed_ext = if opts.compression_static {
+        compression_static::precompressed_extension(headers_opt)
+    } else {
+        None
+    };
+    #[cfg(not(feature = "compression"))]
+    let precompressed_ext = None;
+
+    // The file lookup performs blocking file system calls,
+    // so it runs on the blocking thread pool to not stall the runtime workers
+    let base_path = opts.base_path.to_owned();
+    let lookup_path = uri_path.to_owned();
+    let index_files = opts.index_files.to_vec();
+    let case_insensitive_paths = opts.case_insensitive_paths;
+    let redirect_trailing_slash = opts.redirect_trailing_slash;
+    let extensionless_html = opts.extensionless_html;
+    let lookup = tokio::task::spawn_blocking(move || {
+        let mut file_path = sanitize_path(&base_path, &lookup_path)?;
+
+        // Fall back to a case-insensitive lookup only when the exact-case path misses
+        if case_insensitive_paths && !file_path.exists() {
+            if let Some(resolved) = case_insensitive::resolve_path(&base_path, &file_path) {
+                file_path = resolved;
+            }
+        }
+
+        // A trailing slash is only valid for directories,
+        // so file paths like `/file.html/` are not found instead of being served or redirected
+        if redirect_trailing_slash && lookup_path.ends_with('/') && !file_path.is_dir() {
+            tracing::trace!("uri ends with a slash but it does not point to a directory");
+            return Err(StatusCode::NOT_FOUND);
+        }
+
+        composed_file_metadata(
+            file_path,
+            precompressed_ext,
+            &index_files,
+            extensionless_html,
+        )
+    });
 
     let FileMetadata {
         file_path,
         metadata,
         is_dir,
         precompressed_variant,
-    } = composed_file_metadata(&mut file_path, headers_opt, compression_static_opt).await?;
+    } = match lookup.await {
+        Ok(result) => result?,
+        Err(err) => {
+            tracing::error!("file lookup task failed: {:?}", err);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    let file_path = &file_path;
 
     // Check for a hidden file/directory (dotfile) and ignore it if feature enabled
     if opts.ignore_hidden_files && file_path.is_hidden() {
@@ -105,6 +274,15 @@ pub async fn handle<'a>(opts: &HandleOpts<'a>) -> Result<(Response<Body>, bool),
         // Check for a trailing slash on the current directory path
         // and redirect if that path doesn't end with the slash char
         if opts.redirect_trailing_slash && !uri_path.ends_with('/') {
+            // The `add` trailing slash rules redirect with a `301` keeping the query string
+            if opts.trailing_slash_policy == Some(TrailingSlashPolicy::Add) {
+                if let Some(resp) =
+                    trailing_slash::redirect(TrailingSlashPolicy::Add, uri_path, opts.uri_query)
+                {
+                    return Ok((resp, is_precompressed));
+                }
+            }
+
             let uri = [uri_path, "/"].concat();
             let loc = match HeaderValue::from_str(uri.as_str()) {
                 Ok(val) => val,
@@ -121,62 +299,177 @@ pub async fn handle<'a>(opts: &HandleOpts<'a>) -> Result<(Response<Body>, bool),
             tracing::trace!("uri doesn't end with a slash so redirecting permanently");
             return Ok((resp, is_precompressed));
         }
+    }
 
-        // Respond with the permitted communication options
-        if method.is_options() {
-            let mut resp = Response::new(Body::empty());
-            *resp.status_mut() = StatusCode::NO_CONTENT;
-            resp.headers_mut()
-                .typed_insert(headers::Allow::from_iter(HTTP_SUPPORTED_METHODS.clone()));
-            resp.headers_mut().typed_insert(AcceptRanges::bytes());
-
-            return Ok((resp, is_precompressed));
-        }
+    // Respond with the permitted communication options of the existing resource
+    if method.is_options() {
+        return Ok((
+            options_response(ranges, opts.allowed_methods),
+            is_precompressed,
+        ));
+    }
 
-        // Directory listing
-        // Check if "directory listing" feature is enabled,
-        // if current path is a valid directory and
-        // if it does not contain an `index.html` file (if a proper auto index is generated)
+    // A directory without an index file is resolved to its directory listing
+    // if enabled or otherwise to a `404 Not Found` (so the fallback page can be served)
+    if is_dir && metadata.is_dir() {
         #[cfg(feature = "directory-listing")]
-        if opts.dir_listing && !file_path.exists() {
+        if opts.dir_listing {
             let resp = directory_listing::auto_index(DirListOpts {
                 method,
+                headers: headers_opt,
                 current_path: uri_path,
                 uri_query: opts.uri_query,
                 filepath: file_path,
                 dir_listing_order: opts.dir_listing_order,
                 dir_listing_format: opts.dir_listing_format,
+                dir_listing_size_format: opts.dir_listing_size_format,
+                dir_listing_date_format: opts.dir_listing_date_format,
+                dir_listing_timezone: opts.dir_listing_timezone,
+                dir_listing_ignore: opts.dir_listing_ignore,
+                dir_listing_show_symlinks: opts.dir_listing_show_symlinks,
+                merge_filepath: match merge_root {
+                    Some(root) => Some(sanitize_path(root, uri_path)?),
+                    None => None,
+                },
                 ignore_hidden_files: opts.ignore_hidden_files,
             })
             .await?;
 
             return Ok((resp, is_precompressed));
         }
+
+        tracing::debug!("dir: no index file found: {:?}", file_path.display());
+        return Err(StatusCode::NOT_FOUND);
     }
 
     // Check for a pre-compressed file variant if present under the `opts.compression_static` context
     if let Some(precompressed_meta) = precompressed_variant {
         let (precomp_path, precomp_ext) = precompressed_meta;
-        let mut resp = file_reply(headers_opt, file_path, &metadata, Some(precomp_path)).await?;
-
-        // Prepare corresponding headers to let know how to decode the payload
-        resp.headers_mut().remove(CONTENT_LENGTH);
+        let resolved = ResolvedFile {
+            path: precomp_path.clone(),
+            size: metadata.len(),
+        };
+        let mut resp = file_reply(
+            method,
+            headers_opt,
+            file_path,
+            &metadata,
+            Some(precomp_path),
+            ranges,
+            etag_mode,
+        )
+        .await?;
+        resp.extensions_mut().insert(resolved);
+
+        // Prepare corresponding headers to let know how to decode the payload.
+        // Partial responses keep their length since ranges apply to the compressed bytes
+        if resp.status() != StatusCode::PARTIAL_CONTENT {
+            resp.headers_mut().remove(CONTENT_LENGTH);
+        }
         resp.headers_mut()
             .insert(CONTENT_ENCODING, precomp_ext.parse().unwrap());
 
         return Ok((resp, is_precompressed));
     }
 
-    let resp = file_reply(headers_opt, file_path, &metadata, None).await?;
+    let mut resp = file_reply(
+        method,
+        headers_opt,
+        file_path,
+        &metadata,
+        None,
+        ranges,
+        etag_mode,
+    )
+    .await?;
+    resp.extensions_mut().insert(ResolvedFile {
+        path: file_path.to_owned(),
+        size: metadata.len(),
+    });
 
     Ok((resp, is_precompressed))
 }
 
+/// Parses a comma-separated list of directory index file names (e.g. `index.html,index.htm`).
+pub fn parse_index_files(index_files: &str) -> Result<Vec<String>> {
+    index_files
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            if s == "." || s == ".." || s.contains(['/', '\\']) {
+                bail!("invalid index file name: {}", s);
+            }
+            Ok(s.to_owned())
+        })
+        .collect()
+}
+
+/// Parses the root page file path relative to the root directory into a request path.
+/// An empty value disables the root page whereas paths containing `..` segments are rejected.
+pub fn parse_root_page(root_page: &str) -> Result<Option<String>> {
+    let root_page = root_page.trim().trim_start_matches('/');
+    if root_page.is_empty() {
+        return Ok(None);
+    }
+    if root_page.contains('\\') || root_page.split('/').any(|s| s == "..") {
+        bail!("invalid root page path: {}", root_page);
+    }
+    Ok(Some(["/", root_page].concat()))
+}
+
+/// Parses a comma-separated list of allowed HTTP methods (e.g. `GET,HEAD`).
+/// Only the methods supported by the server are accepted.
+pub fn parse_allowed_methods(methods: &str) -> Result<Vec<Method>> {
+    let mut allowed: Vec<Method> = Vec::new();
+    for name in methods
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+    {
+        let method = match name.to_uppercase().parse::<Method>() {
+            Ok(method) if method.is_allowed() => method,
+            _ => bail!("unsupported allowed method: {}", name),
+        };
+        if !allowed.contains(&method) {
+            allowed.push(method);
+        }
+    }
+    if allowed.is_empty() {
+        bail!("at least one allowed method is required");
+    }
+    Ok(allowed)
+}
+
+/// It returns the `Allow` header of the given allowed methods.
+/// Methods not supported by the server (e.g. from allowed methods rules) are listed last.
+pub fn allow_header(allowed_methods: &[Method]) -> headers::Allow {
+    headers::Allow::from_iter(
+        HTTP_SUPPORTED_METHODS
+            .iter()
+            .filter(|m| allowed_methods.contains(m))
+            .chain(allowed_methods.iter().filter(|m| !m.is_allowed()))
+            .cloned(),
+    )
+}
+
+/// It returns a `204 No Content` response with the permitted communication options.
+pub fn options_response(ranges: bool, allowed_methods: &[Method]) -> Response<Body> {
+    let mut resp = Response::new(Body::empty());
+    *resp.status_mut() = StatusCode::NO_CONTENT;
+    resp.headers_mut()
+        .typed_insert(allow_header(allowed_methods));
+    if ranges {
+        resp.headers_mut().typed_insert(AcceptRanges::bytes());
+    }
+    resp
+}
+
 /// It defines a composed file metadata structure containing the current file
 /// and its optional compressed variant.
-struct FileMetadata<'a> {
-    /// The current file path reference.
-    pub file_path: &'a PathBuf,
+struct FileMetadata {
+    /// The current file path.
+    pub file_path: PathBuf,
     /// The metadata of current `file_path` by default.
     /// Note that if `precompressed_variant` has some value
     /// then the `metadata` value will correspond to the `precompressed_variant`.
@@ -184,93 +477,91 @@ struct FileMetadata<'a> {
     // If either `file_path` or `precompressed_variant` is a directory.
     pub is_dir: bool,
     // The precompressed file variant for the current `file_path`.
-    pub precompressed_variant: Option<(PathBuf, &'a str)>,
+    pub precompressed_variant: Option<(PathBuf, &'static str)>,
 }
 
 /// Returns the result of trying to append a `.html` to the file path.
-/// * If the suffixed html path exists, it mutates the path to the suffixed one and returns the `Metadata`
-/// * If the suffixed html path doesn't exist, it reverts the path to it's original value
-fn suffix_file_html_metadata(file_path: &mut PathBuf) -> (&mut PathBuf, Option<Metadata>) {
+/// * If the suffixed html file exists, it mutates the path to the suffixed one and returns the `Metadata`
+/// * If the suffixed html file doesn't exist (or it's a directory), it reverts the path to it's original value
+fn suffix_file_html_metadata(file_path: &mut PathBuf) -> Option<Metadata> {
     tracing::debug!("file: appending .html to the path");
     if let Some(filename) = file_path.file_name() {
         let owned_filename = filename.to_os_string();
         let mut owned_filename_with_html = owned_filename.clone();
         owned_filename_with_html.push(".html");
         file_path.set_file_name(owned_filename_with_html);
-        if let Ok(meta_res) = file_metadata(file_path) {
-            let (meta, _) = meta_res;
-            return (file_path, Some(meta));
+        if let Ok((meta, false)) = file_metadata(file_path) {
+            return Some(meta);
         } else {
             // We roll-back to the previous filename
             file_path.set_file_name(owned_filename);
         }
     }
-    (file_path, None)
+    None
 }
 
 /// Returns the final composed metadata containing
 /// the current `file_path` with its file metadata
 /// as well as its optional pre-compressed variant.
-async fn composed_file_metadata<'a>(
-    mut file_path: &'a mut PathBuf,
-    _headers: &'a HeaderMap<HeaderValue>,
-    _compression_static: bool,
-) -> Result<FileMetadata<'a>, StatusCode> {
+/// The `precompressed_ext` param is the extension of the pre-compressed variant to look for if any.
+///
+/// Note that this function performs blocking file system calls.
+#[cfg_attr(not(feature = "compression"), allow(unused_variables))]
+fn composed_file_metadata(
+    mut file_path: PathBuf,
+    precompressed_ext: Option<&'static str>,
+    index_files: &[String],
+    extensionless_html: bool,
+) -> Result<FileMetadata, StatusCode> {
     tracing::trace!("getting metadata for file {}", file_path.display());
 
-    match file_metadata(file_path) {
-        Ok((mut metadata, is_dir)) => {
+    match file_metadata(&file_path) {
+        Ok((metadata, is_dir)) => {
             if is_dir {
-                // Append a HTML index page by default if it's a directory path (`autoindex`)
-                tracing::debug!("dir: appending an index.html to the directory path");
-                file_path.push("index.html");
-
-                // Pre-compressed variant check for the autoindex
-                #[cfg(feature = "compression")]
-                if _compression_static {
-                    if let Some(p) =
-                        compression_static::precompressed_variant(file_path, _headers).await
+                // Directory resolution chain: the index files in order of preference,
+                // otherwise the directory itself
+                for index_file in index_files {
+                    tracing::debug!("dir: appending {} to the directory path", index_file);
+                    file_path.push(index_file);
+
+                    // Pre-compressed variant check for the index file
+                    #[cfg(feature = "compression")]
+                    if let Some(p) = precompressed_ext
+                        .and_then(|ext| compression_static::precompressed_variant(&file_path, ext))
                     {
                         return Ok(FileMetadata {
                             file_path,
                             metadata: p.metadata,
-                            is_dir: false,
+                            is_dir: true,
                             precompressed_variant: Some((p.file_path, p.extension)),
                         });
                     }
-                }
 
-                // Otherwise, just fallback to finding the index.html
-                // and overwrite the current `meta`
-                // Also noting that it's still a directory request
-                if let Ok(meta_res) = file_metadata(file_path) {
-                    (metadata, _) = meta_res
-                } else {
-                    // We remove the appended index.html
-                    file_path.pop();
-                    let new_meta: Option<Metadata>;
-                    (file_path, new_meta) = suffix_file_html_metadata(file_path);
-                    if let Some(new_meta) = new_meta {
-                        metadata = new_meta;
-                    } else {
-                        // We append the index.html to preserve previous behavior
-                        file_path.push("index.html");
+                    // Overwrite the current `meta` noting that it's still a directory request
+                    if let Ok((index_meta, false)) = file_metadata(&file_path) {
+                        return Ok(FileMetadata {
+                            file_path,
+                            metadata: index_meta,
+                            is_dir: true,
+                            precompressed_variant: None,
+                        });
                     }
+
+                    // We remove the appended index file
+                    file_path.pop();
                 }
             } else {
                 // Fallback pre-compressed variant check for the specific file
                 #[cfg(feature = "compression")]
-                if _compression_static {
-                    if let Some(p) =
-                        compression_static::precompressed_variant(file_path, _headers).await
-                    {
-                        return Ok(FileMetadata {
-                            file_path,
-                            metadata: p.metadata,
-                            is_dir: false,
-                            precompressed_variant: Some((p.file_path, p.extension)),
-                        });
-                    }
+                if let Some(p) = precompressed_ext
+                    .and_then(|ext| compression_static::precompressed_variant(&file_path, ext))
+                {
+                    return Ok(FileMetadata {
+                        file_path,
+                        metadata: p.metadata,
+                        is_dir: false,
+                        precompressed_variant: Some((p.file_path, p.extension)),
+                    });
                 }
             }
 
@@ -284,52 +575,25 @@ async fn composed_file_metadata<'a>(
         Err(err) => {
             // Pre-compressed variant check for the file not found
             #[cfg(feature = "compression")]
-            if _compression_static {
-                if let Some(p) =
-                    compression_static::precompressed_variant(file_path, _headers).await
-                {
-                    return Ok(FileMetadata {
-                        file_path,
-                        metadata: p.metadata,
-                        is_dir: false,
-                        precompressed_variant: Some((p.file_path, p.extension)),
-                    });
-                }
+            if let Some(p) = precompressed_ext
+                .and_then(|ext| compression_static::precompressed_variant(&file_path, ext))
+            {
+                return Ok(FileMetadata {
+                    file_path,
+                    metadata: p.metadata,
+                    is_dir: false,
+                    precompressed_variant: Some((p.file_path, p.extension)),
+                });
             }
 
-            // Otherwise, if the file path doesn't exist then
+            // Otherwise, if the file path doesn't exist and has no extension then
             // we try to find the path suffixed with `.html`.
             // For example: `/posts/article` will fallback to `/posts/article.html`
-            let new_meta: Option<Metadata>;
-            (file_path, new_meta) = suffix_file_html_metadata(file_path);
-
-            #[cfg(feature = "compression")]
-            match new_meta {
-                Some(new_meta) => {
-                    return Ok(FileMetadata {
-                        file_path,
-                        metadata: new_meta,
-                        is_dir: false,
-                        precompressed_variant: None,
-                    })
-                }
-                _ => {
-                    // Last pre-compressed variant check or the suffixed file not found
-                    if _compression_static {
-                        if let Some(p) =
-                            compression_static::precompressed_variant(file_path, _headers).await
-                        {
-                            return Ok(FileMetadata {
-                                file_path,
-                                metadata: p.metadata,
-                                is_dir: false,
-                                precompressed_variant: Some((p.file_path, p.extension)),
-                            });
-                        }
-                    }
-                }
+            let mut new_meta: Option<Metadata> = None;
+            if extensionless_html && file_path.extension().is_none() {
+                new_meta = suffix_file_html_metadata(&mut file_path);
             }
-            #[cfg(not(feature = "compression"))]
+
             if let Some(new_meta) = new_meta {
                 return Ok(FileMetadata {
                     file_path,
@@ -339,6 +603,19 @@ async fn composed_file_metadata<'a>(
                 });
             }
 
+            // Last pre-compressed variant check or the suffixed file not found
+            #[cfg(feature = "compression")]
+            if let Some(p) = precompressed_ext
+                .and_then(|ext| compression_static::precompressed_variant(&file_path, ext))
+            {
+                return Ok(FileMetadata {
+                    file_path,
+                    metadata: p.metadata,
+                    is_dir: false,
+                    precompressed_variant: Some((p.file_path, p.extension)),
+                });
+            }
+
             Err(err)
         }
     }
@@ -365,18 +642,44 @@ pub fn file_metadata(file_path: &Path) -> Result<(Metadata, bool), StatusCode> {
 /// the `meta` param value should corresponds to it.
 /// However, if `path_precompressed` contains some value then
 /// the `meta` param  value will belong to the `path_precompressed` (precompressed file variant).
-fn file_reply<'a>(
-    headers: &'a HeaderMap<HeaderValue>,
-    path: &'a PathBuf,
-    meta: &'a Metadata,
+/// If `ranges` is `false` then range requests are ignored and the full content is served instead.
+/// The entity tag is computed from the served file according to the `etag_mode` param.
+async fn file_reply(
+    method: &Method,
+    headers: &HeaderMap<HeaderValue>,
+    path: &PathBuf,
+    meta: &Metadata,
     path_precompressed: Option<PathBuf>,
-) -> impl Future<Output = Result<Response<Body>, StatusCode>> + Send + 'a {
-    let conditionals = get_conditional_headers(headers);
+    ranges: bool,
+    etag_mode: EtagMode,
+) -> Result<Response<Body>, StatusCode> {
+    let mut conditionals = get_conditional_headers(method, headers);
+    if !ranges {
+        conditionals.range = None;
+        conditionals.if_range = None;
+    }
 
-    let file_path = path_precompressed.as_ref().unwrap_or(path);
+    let file_path = path_precompressed.as_ref().unwrap_or(path).to_owned();
 
-    match File::open(file_path) {
-        Ok(file) => Either::Left(response_body(file, path, meta, conditionals)),
+    let open_path = file_path.clone();
+    let opened = match tokio::task::spawn_blocking(move || File::open(open_path)).await {
+        Ok(opened) => opened,
+        Err(err) => {
+            tracing::error!(
+                "file open task failed (path={:?}): {:?}",
+                path.display(),
+                err
+            );
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    match opened {
+        Ok(file) => {
+            let mime = guess_content_type(&file_path, path_precompressed.is_some());
+            let etag = etag::file_etag(&file_path, meta, etag_mode).await;
+            response_body(file, mime, meta, etag, conditionals, ranges).await
+        }
         Err(err) => {
             let status = match err.kind() {
                 io::ErrorKind::NotFound => {
@@ -392,18 +695,38 @@ fn file_reply<'a>(
                     StatusCode::INTERNAL_SERVER_ERROR
                 }
             };
-            Either::Right(future::err(status))
+            Err(status)
         }
     }
 }
 
-fn get_conditional_headers(header_list: &HeaderMap<HeaderValue>) -> Conditionals {
+/// Guesses the content type of a file path.
+///
+/// For pre-compressed file variants (e.g. `styles.css.br` or `app.js.gz`) the compression
+/// extension is stripped first so the original file name drives the content type.
+/// Unknown (inner) extensions fall back to `application/octet-stream`.
+fn guess_content_type(path: &Path, precompressed: bool) -> Mime {
+    let path = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if precompressed && PRECOMPRESSED_EXTENSIONS.contains(&ext) => {
+            path.with_extension("")
+        }
+        _ => path.to_owned(),
+    };
+    mime_guess::from_path(path).first_or_octet_stream()
+}
+
+fn get_conditional_headers(method: &Method, header_list: &HeaderMap<HeaderValue>) -> Conditionals {
+    let if_match = header_list.typed_get::<IfMatch>();
+    let if_none_match = header_list.typed_get::<IfNoneMatch>();
     let if_modified_since = header_list.typed_get::<IfModifiedSince>();
     let if_unmodified_since = header_list.typed_get::<IfUnmodifiedSince>();
     let if_range = header_list.typed_get::<IfRange>();
     let range = header_list.typed_get::<Range>();
 
     Conditionals {
+        safe_method: method.is_get() || method.is_head(),
+        if_match,
+        if_none_match,
         if_modified_since,
         if_unmodified_since,
         if_range,
@@ -412,12 +735,15 @@ fn get_conditional_headers(header_list: &HeaderMap<HeaderValue>) -> Conditionals
 }
 
 /// Sanitizes a base/tail paths and then it returns an unified one.
-fn sanitize_path(base: &Path, tail: &str) -> Result<PathBuf, StatusCode> {
+///
+/// The tail is percent-decoded exactly once, so `%2520` stays a literal `%20` and `+`
+/// is not a space. Invalid UTF-8 sequences result in a `400 Bad Request`.
+pub(crate) fn sanitize_path(base: &Path, tail: &str) -> Result<PathBuf, StatusCode> {
     let path_decoded = match percent_decode_str(tail.trim_start_matches('/')).decode_utf8() {
         Ok(p) => p,
         Err(err) => {
             tracing::debug!("dir: failed to decode route={:?}: {:?}", tail, err);
-            return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+            return Err(StatusCode::BAD_REQUEST);
         }
     };
 
@@ -452,6 +778,9 @@ fn sanitize_path(base: &Path, tail: &str) -> Result<PathBuf, StatusCode> {
 
 #[derive(Debug)]
 struct Conditionals {
+    safe_method: bool,
+    if_match: Option<IfMatch>,
+    if_none_match: Option<IfNoneMatch>,
     if_modified_since: Option<IfModifiedSince>,
     if_unmodified_since: Option<IfUnmodifiedSince>,
     if_range: Option<IfRange>,
@@ -464,11 +793,29 @@ enum Cond {
 }
 
 impl Conditionals {
-    fn check(self, last_modified: Option<LastModified>) -> Cond {
-        if let Some(since) = self.if_unmodified_since {
+    /// Evaluates the request preconditions following the RFC 7232 precedence order.
+    /// See <https://www.rfc-editor.org/rfc/rfc7232#section-6>
+    fn check(self, last_modified: Option<LastModified>, etag: Option<&ETag>) -> Cond {
+        // 1. `If-Match` or otherwise 2. `If-Unmodified-Since`
+        if let Some(if_match) = self.if_match {
+            let precondition = etag
+                .map(|etag| if_match.precondition_passes(etag))
+                // no etag means that only the `*` value matches the existing file
+                .unwrap_or_else(|| if_match.is_any());
+
+            tracing::trace!("if-match? {:?} vs {:?} = {}", if_match, etag, precondition);
+            if !precondition {
+                return Cond::NoBody(precondition_response(
+                    StatusCode::PRECONDITION_FAILED,
+                    last_modified,
+                    etag,
+                ));
+            }
+        } else if let Some(since) = self.if_unmodified_since {
             let precondition = last_modified
                 .map(|time| since.precondition_passes(time.into()))
-                .unwrap_or(false);
+                // no last_modified means the header is ignored
+                .unwrap_or(true);
 
             tracing::trace!(
                 "if-unmodified-since? {:?} vs {:?} = {}",
@@ -477,13 +824,36 @@ impl Conditionals {
                 precondition
             );
             if !precondition {
-                let mut res = Response::new(Body::empty());
-                *res.status_mut() = StatusCode::PRECONDITION_FAILED;
-                return Cond::NoBody(res);
+                return Cond::NoBody(precondition_response(
+                    StatusCode::PRECONDITION_FAILED,
+                    last_modified,
+                    etag,
+                ));
             }
         }
 
-        if let Some(since) = self.if_modified_since {
+        // 3. `If-None-Match` or otherwise 4. `If-Modified-Since`
+        if let Some(if_none_match) = self.if_none_match {
+            let precondition = etag
+                .map(|etag| if_none_match.precondition_passes(etag))
+                // no etag means that only the `*` value matches the existing file
+                .unwrap_or_else(|| if_none_match != IfNoneMatch::any());
+
+            tracing::trace!(
+                "if-none-match? {:?} vs {:?} = {}",
+                if_none_match,
+                etag,
+                precondition
+            );
+            if !precondition {
+                let status = if self.safe_method {
+                    StatusCode::NOT_MODIFIED
+                } else {
+                    StatusCode::PRECONDITION_FAILED
+                };
+                return Cond::NoBody(precondition_response(status, last_modified, etag));
+            }
+        } else if let Some(since) = self.if_modified_since.filter(|_| self.safe_method) {
             tracing::trace!(
                 "if-modified-since? header = {:?}, file = {:?}",
                 since,
@@ -494,15 +864,28 @@ impl Conditionals {
                 // no last_modified means its always modified
                 .unwrap_or(false);
             if unmodified {
-                let mut res = Response::new(Body::empty());
-                *res.status_mut() = StatusCode::NOT_MODIFIED;
-                return Cond::NoBody(res);
+                return Cond::NoBody(precondition_response(
+                    StatusCode::NOT_MODIFIED,
+                    last_modified,
+                    etag,
+                ));
             }
         }
 
+        // 5. `If-Range`
         if let Some(if_range) = self.if_range {
-            tracing::trace!("if-range? {:?} vs {:?}", if_range, last_modified);
-            let can_range = !if_range.is_modified(None, last_modified.as_ref());
+            tracing::trace!(
+                "if-range? {:?} vs {:?} or {:?}",
+                if_range,
+                last_modified,
+                etag
+            );
+            // The range is only served if the validator matches exactly (strong comparison),
+            // otherwise the full content is served.
+            // Weak entity tags never match, see RFC 9110 section 13.1.5.
+            let can_range = last_modified
+                .is_some_and(|time| if_range == IfRange::date(time.into()))
+                || etag.is_some_and(|etag| !if_range.is_modified(Some(etag), None));
             if !can_range {
                 return Cond::WithBody(None);
             }
@@ -512,6 +895,23 @@ impl Conditionals {
     }
 }
 
+/// Returns an empty response with the given precondition status and the file validators.
+fn precondition_response(
+    status: StatusCode,
+    last_modified: Option<LastModified>,
+    etag: Option<&ETag>,
+) -> Response<Body> {
+    let mut resp = Response::new(Body::empty());
+    *resp.status_mut() = status;
+    if let Some(etag) = etag {
+        resp.headers_mut().typed_insert(etag.to_owned());
+    }
+    if let Some(last_modified) = last_modified {
+        resp.headers_mut().typed_insert(last_modified);
+    }
+    resp
+}
+
 #[cfg(unix)]
 const READ_BUF_SIZE: usize = 4_096;
 
@@ -544,14 +944,16 @@ impl<T: Read + Unpin> Stream for FileStream<T> {
 
 async fn response_body(
     mut file: File,
-    path: &PathBuf,
+    mime: Mime,
     meta: &Metadata,
+    etag: Option<ETag>,
     conditionals: Conditionals,
+    ranges: bool,
 ) -> Result<Response<Body>, StatusCode> {
     let mut len = meta.len();
     let modified = meta.modified().ok().map(LastModified::from);
 
-    match conditionals.check(modified) {
+    match conditionals.check(modified, etag.as_ref()) {
         Cond::NoBody(resp) => Ok(resp),
         Cond::WithBody(range) => {
             bytes_range(range, len)
@@ -590,15 +992,18 @@ async fn response_body(
                         len = sub_len;
                     }
 
-                    let mime = mime_guess::from_path(path).first_or_octet_stream();
-
                     resp.headers_mut().typed_insert(ContentLength(len));
                     resp.headers_mut().typed_insert(ContentType::from(mime));
-                    resp.headers_mut().typed_insert(AcceptRanges::bytes());
+                    if ranges {
+                        resp.headers_mut().typed_insert(AcceptRanges::bytes());
+                    }
 
                     if let Some(last_modified) = modified {
                         resp.headers_mut().typed_insert(last_modified);
                     }
+                    if let Some(etag) = etag {
+                        resp.headers_mut().typed_insert(etag);
+                    }
 
                     Ok(resp)
                 })
@@ -656,8 +1061,12 @@ fn bytes_range(range: Option<Range>, max_len: u64) -> Result<(u64, u64), BadRang
 
 #[cfg(test)]
 mod tests {
-    use super::sanitize_path;
-    use std::path::PathBuf;
+    use super::{
+        guess_content_type, parse_allowed_methods, parse_index_files, parse_root_page,
+        sanitize_path,
+    };
+    use hyper::Method;
+    use std::path::{Path, PathBuf};
 
     fn root_dir() -> PathBuf {
         PathBuf::from("docker/public/")
@@ -687,4 +1096,71 @@ mod tests {
             expected_path
         );
     }
+
+    #[test]
+    fn content_type_of_precompressed_variants() {
+        for ext in ["br", "gz", "zst"] {
+            let path = format!("assets/styles.css.{ext}");
+            assert_eq!(guess_content_type(Path::new(&path), true), "text/css");
+
+            let path = format!("assets/main.min.js.{ext}");
+            assert_eq!(
+                guess_content_type(Path::new(&path), true),
+                "application/javascript"
+            );
+
+            // Unknown inner extensions
+            let path = format!("assets/data.unknown.{ext}");
+            assert_eq!(
+                guess_content_type(Path::new(&path), true),
+                "application/octet-stream"
+            );
+        }
+
+        // Regular files keep their own content type
+        assert_eq!(
+            guess_content_type(Path::new("archive.tar.gz"), false),
+            "application/gzip"
+        );
+        assert_eq!(
+            guess_content_type(Path::new("index.html"), true),
+            "text/html"
+        );
+    }
+
+    #[test]
+    fn index_files_list() {
+        assert_eq!(
+            parse_index_files(" index.html, index.htm ,").unwrap(),
+            ["index.html", "index.htm"]
+        );
+        assert!(parse_index_files("").unwrap().is_empty());
+        assert!(parse_index_files("index.html,../secret.html").is_err());
+        assert!(parse_index_files("..").is_err());
+    }
+
+    #[test]
+    fn root_page_path() {
+        assert_eq!(parse_root_page("").unwrap(), None);
+        assert_eq!(
+            parse_root_page(" home.html ").unwrap().as_deref(),
+            Some("/home.html")
+        );
+        assert_eq!(
+            parse_root_page("/pages/home.html").unwrap().as_deref(),
+            Some("/pages/home.html")
+        );
+        assert!(parse_root_page("../home.html").is_err());
+        assert!(parse_root_page("pages\\home.html").is_err());
+    }
+
+    #[test]
+    fn allowed_methods_list() {
+        assert_eq!(
+            parse_allowed_methods(" get, HEAD ,get,").unwrap(),
+            vec![Method::GET, Method::HEAD]
+        );
+        assert!(parse_allowed_methods("GET,POST").is_err());
+        assert!(parse_allowed_methods("").is_err());
+    }
 }
@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to count the connections, requests, bytes sent and response statuses
+//! since the server startup using lightweight atomic counters.
+//! A summary of them is logged once the server is shut down.
+//!
+//! The number of in-flight requests is tracked as well to estimate the current server load.
+//!
+
+use hyper::StatusCode;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Lowest HTTP status code counted.
+const STATUS_MIN: u16 = 100;
+/// Number of HTTP status codes counted (`100` to `599`).
+const STATUS_COUNT: usize = 500;
+
+/// Server counters shared by the connections and the request service.
+#[derive(Debug)]
+pub struct Stats {
+    started: Instant,
+    connections: AtomicU64,
+    requests: AtomicU64,
+    bytes_sent: AtomicU64,
+    statuses: [AtomicU64; STATUS_COUNT],
+    in_flight: AtomicU64,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            connections: AtomicU64::new(0),
+            requests: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+            statuses: std::array::from_fn(|_| AtomicU64::new(0)),
+            in_flight: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Stats {
+    /// Counts an accepted connection.
+    pub fn add_connection(&self) {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a served request along with its response status.
+    pub fn add_request(&self, status: StatusCode) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if let Some(count) = self
+            .statuses
+            .get(usize::from(status.as_u16().saturating_sub(STATUS_MIN)))
+        {
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Counts the bytes written to a connection.
+    pub fn add_bytes_sent(&self, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Counts an in-flight request until the returned guard is dropped.
+    pub fn track_request(self: &Arc<Self>) -> InFlightRequest {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlightRequest(self.clone())
+    }
+
+    /// Returns the number of in-flight requests.
+    pub fn in_flight_requests(&self) -> u64 {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Returns a single line summary of the counters,
+    /// e.g. `uptime_secs=60 connections=2 requests=3 bytes_sent=2048 statuses=200:2,404:1`.
+    pub fn summary(&self) -> String {
+        let statuses = self
+            .statuses
+            .iter()
+            .zip(STATUS_MIN..)
+            .filter_map(|(count, status)| match count.load(Ordering::Relaxed) {
+                0 => None,
+                count => Some(format!("{status}:{count}")),
+            })
+            .collect::<Vec<_>>();
+        format!(
+            "uptime_secs={} connections={} requests={} bytes_sent={} statuses={}",
+            self.started.elapsed().as_secs(),
+            self.connections.load(Ordering::Relaxed),
+            self.requests.load(Ordering::Relaxed),
+            self.bytes_sent.load(Ordering::Relaxed),
+            if statuses.is_empty() {
+                String::from("-")
+            } else {
+                statuses.join(",")
+            },
+        )
+    }
+}
+
+/// Guard of an in-flight request, uncounted once dropped.
+#[derive(Debug)]
+pub struct InFlightRequest(Arc<Stats>);
+
+impl Drop for InFlightRequest {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stats;
+    use hyper::StatusCode;
+    use std::sync::Arc;
+
+    #[test]
+    fn stats_summary() {
+        let stats = Stats::default();
+        assert_eq!(
+            stats.summary(),
+            "uptime_secs=0 connections=0 requests=0 bytes_sent=0 statuses=-"
+        );
+
+        stats.add_connection();
+        stats.add_request(StatusCode::OK);
+        stats.add_request(StatusCode::NOT_FOUND);
+        stats.add_request(StatusCode::OK);
+        stats.add_bytes_sent(1024);
+        stats.add_bytes_sent(512);
+        assert_eq!(
+            stats.summary(),
+            "uptime_secs=0 connections=1 requests=3 bytes_sent=1536 statuses=200:2,404:1"
+        );
+    }
+
+    #[test]
+    fn stats_in_flight_requests() {
+        let stats = Arc::new(Stats::default());
+        let first = stats.track_request();
+        let second = stats.track_request();
+        assert_eq!(stats.in_flight_requests(), 2);
+        drop(first);
+        assert_eq!(stats.in_flight_requests(), 1);
+        drop(second);
+        assert_eq!(stats.in_flight_requests(), 0);
+    }
+}
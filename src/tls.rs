@@ -8,6 +8,7 @@
 
 // Most of the file is borrowed from https://github.com/seanmonstar/warp/blob/master/src/tls.rs
 
+use clap::ValueEnum;
 use futures_util::ready;
 use hyper::server::accept::Accept;
 use hyper::server::conn::{AddrIncoming, AddrStream};
@@ -17,16 +18,138 @@ use std::io::{self, BufReader, Cursor, Read};
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::task::{Context, Poll};
-use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
 use tokio_rustls::rustls::{
-    server::{AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient, NoClientAuth},
-    Certificate, Error as TlsError, PrivateKey, RootCertStore, ServerConfig,
+    server::{
+        AllowAnyAnonymousOrAuthenticatedClient, AllowAnyAuthenticatedClient, NoClientAuth,
+        ResolvesServerCert,
+    },
+    version::{TLS12, TLS13},
+    Certificate, Error as TlsError, PrivateKey, RootCertStore, ServerConfig, SupportedCipherSuite,
+    SupportedProtocolVersion, ALL_CIPHER_SUITES, DEFAULT_CIPHER_SUITES,
 };
 
 use crate::transport::Transport;
 
+/// Maximum size of the plain HTTP request head read to build its response.
+const PLAIN_HTTP_MAX_HEAD_SIZE: usize = 8 * 1024;
+/// Maximum time spent reading the plain HTTP request head.
+const PLAIN_HTTP_READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// Body of the response sent to plain HTTP requests.
+const PLAIN_HTTP_BODY: &str = "Client sent an HTTP request to an HTTPS server.\n";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ValueEnum)]
+/// Minimum TLS protocol version accepted by the server.
+pub enum TlsMinVersion {
+    /// TLS 1.2 and 1.3 are accepted (default).
+    #[serde(rename = "1.2")]
+    #[value(name = "1.2")]
+    Tls12,
+    /// Only TLS 1.3 is accepted.
+    #[serde(rename = "1.3")]
+    #[value(name = "1.3")]
+    Tls13,
+}
+
+impl TlsMinVersion {
+    /// Returns the TLS protocol versions accepted from this minimum version.
+    fn versions(&self) -> &'static [&'static SupportedProtocolVersion] {
+        static TLS12_VERSIONS: &[&SupportedProtocolVersion] = &[&TLS13, &TLS12];
+        static TLS13_VERSIONS: &[&SupportedProtocolVersion] = &[&TLS13];
+        match self {
+            TlsMinVersion::Tls12 => TLS12_VERSIONS,
+            TlsMinVersion::Tls13 => TLS13_VERSIONS,
+        }
+    }
+}
+
+/// Parses a comma-separated list of cipher suite names (e.g. `TLS13_AES_256_GCM_SHA384`).
+/// An empty list means the default cipher suites.
+pub fn parse_cipher_suites(names: &str) -> crate::Result<Vec<SupportedCipherSuite>> {
+    let mut suites = Vec::new();
+    for name in names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        match ALL_CIPHER_SUITES
+            .iter()
+            .find(|suite| format!("{:?}", suite.suite()).eq_ignore_ascii_case(name))
+        {
+            Some(suite) if !suites.contains(suite) => suites.push(*suite),
+            Some(_) => {}
+            None => bail!("unsupported tls cipher suite: {}", name),
+        }
+    }
+    Ok(suites)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+/// Behavior of plain HTTP requests sent to the TLS listener.
+pub enum PlainHttpMode {
+    /// Close the connection without any response.
+    Close,
+    /// Reply with a `400 Bad Request` explaining HTTPS is required.
+    BadRequest,
+    /// Redirect the request to its `https` URL.
+    Redirect,
+}
+
+/// Options to redirect plain HTTP requests sent to the TLS listener.
+#[derive(Debug, Clone)]
+pub struct PlainHttpRedirectOpts {
+    /// HTTPS hostname to redirect to.
+    pub https_hostname: String,
+    /// HTTPS hostname port to redirect to.
+    pub https_port: u16,
+    /// Hostnames or IPs to redirect from.
+    pub allowed_hosts: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+/// Client certificate authentication mode of the TLS listener.
+pub enum TlsClientAuthMode {
+    /// Only clients presenting a certificate signed by the client CA are accepted.
+    Required,
+    /// Anonymous clients are accepted too but presented certificates must be valid.
+    Optional,
+}
+
+/// Identity of the verified certificate of a TLS client,
+/// attached to the request extensions when the client authentication is enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCert {
+    /// Distinguished name of the certificate subject, e.g. `CN=client, O=Example`.
+    pub subject: String,
+    /// Common name (CN) of the certificate subject if any.
+    pub common_name: Option<String>,
+}
+
+impl ClientCert {
+    /// Parses the subject of a DER-encoded client certificate.
+    pub fn from_der(der: &[u8]) -> Option<Self> {
+        let (_, cert) = x509_parser::parse_x509_certificate(der).ok()?;
+        let subject = cert.subject();
+        let common_name = subject
+            .iter_common_name()
+            .next()
+            .and_then(|cn| cn.as_str().ok())
+            .map(str::to_owned);
+        Some(Self {
+            subject: subject.to_string(),
+            common_name,
+        })
+    }
+}
+
+/// Client certificate of a TLS connection, set once its handshake completes.
+pub type ClientCertSlot = Arc<OnceLock<ClientCert>>;
+
 /// Represents errors that can occur building the TlsConfig
 #[derive(Debug)]
 pub enum TlsConfigError {
@@ -42,6 +165,8 @@ pub enum TlsConfigError {
     UnknownPrivateKeyFormat,
     /// An error from an invalid key
     InvalidKey(TlsError),
+    /// An error from an unsupported combination of protocol versions and cipher suites
+    InvalidProtocol(TlsError),
 }
 
 impl std::fmt::Display for TlsConfigError {
@@ -53,6 +178,9 @@ impl std::fmt::Display for TlsConfigError {
             TlsConfigError::UnknownPrivateKeyFormat => write!(f, "unknown private key format"),
             TlsConfigError::EmptyKey => write!(f, "key contains no private key"),
             TlsConfigError::InvalidKey(err) => write!(f, "key contains an invalid key, {err}"),
+            TlsConfigError::InvalidProtocol(err) => {
+                write!(f, "invalid protocol versions or cipher suites, {err}")
+            }
         }
     }
 }
@@ -75,6 +203,9 @@ pub struct TlsConfigBuilder {
     key: Box<dyn Read + Send + Sync>,
     client_auth: TlsClientAuth,
     ocsp_resp: Vec<u8>,
+    cert_resolver: Option<Arc<dyn ResolvesServerCert>>,
+    min_version: TlsMinVersion,
+    cipher_suites: Vec<SupportedCipherSuite>,
 }
 
 impl std::fmt::Debug for TlsConfigBuilder {
@@ -91,6 +222,9 @@ impl TlsConfigBuilder {
             cert: Box::new(io::empty()),
             client_auth: TlsClientAuth::Off,
             ocsp_resp: Vec::new(),
+            cert_resolver: None,
+            min_version: TlsMinVersion::Tls12,
+            cipher_suites: Vec::new(),
         }
     }
 
@@ -176,42 +310,30 @@ impl TlsConfigBuilder {
         self
     }
 
-    /// Builds TLS configuration.
-    pub fn build(mut self) -> Result<ServerConfig, TlsConfigError> {
-        let mut cert_rdr = BufReader::new(self.cert);
-        let cert = rustls_pemfile::certs(&mut cert_rdr)
-            .map_err(|_e| TlsConfigError::CertParseError)?
-            .into_iter()
-            .map(Certificate)
-            .collect();
-
-        // convert it to Vec<u8> to allow reading it again if key is RSA
-        let mut key_vec = Vec::new();
-        self.key
-            .read_to_end(&mut key_vec)
-            .map_err(TlsConfigError::Io)?;
-
-        if key_vec.is_empty() {
-            return Err(TlsConfigError::EmptyKey);
-        }
+    /// Sets a resolver to pick the certificate at runtime (e.g. ACME certificates).
+    ///
+    /// If provided, the certificate, key and OCSP response of the builder are not used.
+    pub fn cert_resolver(mut self, resolver: Arc<dyn ResolvesServerCert>) -> Self {
+        self.cert_resolver = Some(resolver);
+        self
+    }
 
-        let mut key = None;
-        let mut reader = std::io::Cursor::new(key_vec);
-        for item in rustls_pemfile::read_all(&mut reader)
-            .map_err(|_e| TlsConfigError::InvalidIdentityPem)?
-        {
-            match item {
-                rustls_pemfile::Item::RSAKey(k) => key = Some(PrivateKey(k)),
-                rustls_pemfile::Item::PKCS8Key(k) => key = Some(PrivateKey(k)),
-                rustls_pemfile::Item::ECKey(k) => key = Some(PrivateKey(k)),
-                _ => return Err(TlsConfigError::UnknownPrivateKeyFormat),
-            }
-        }
-        let key = match key {
-            Some(k) => k,
-            _ => return Err(TlsConfigError::EmptyKey),
-        };
+    /// Sets the minimum TLS protocol version accepted. Default TLS 1.2.
+    pub fn min_version(mut self, version: TlsMinVersion) -> Self {
+        self.min_version = version;
+        self
+    }
+
+    /// Sets the allowed cipher suites in order of preference.
+    ///
+    /// If empty, the Rustls safe default cipher suites are used.
+    pub fn cipher_suites(mut self, suites: &[SupportedCipherSuite]) -> Self {
+        self.cipher_suites = suites.to_vec();
+        self
+    }
 
+    /// Builds TLS configuration.
+    pub fn build(self) -> Result<ServerConfig, TlsConfigError> {
         fn read_trust_anchor(
             trust_anchor: Box<dyn Read + Send + Sync>,
         ) -> Result<RootCertStore, TlsConfigError> {
@@ -239,16 +361,121 @@ impl TlsConfigBuilder {
             }
         };
 
-        let mut config = ServerConfig::builder()
-            .with_safe_defaults()
-            .with_client_cert_verifier(client_auth)
-            .with_single_cert_with_ocsp_and_sct(cert, key, self.ocsp_resp, Vec::new())
-            .map_err(TlsConfigError::InvalidKey)?;
+        let cipher_suites = match self.cipher_suites.as_slice() {
+            [] => DEFAULT_CIPHER_SUITES,
+            suites => suites,
+        };
+        let config = ServerConfig::builder()
+            .with_cipher_suites(cipher_suites)
+            .with_safe_default_kx_groups()
+            .with_protocol_versions(self.min_version.versions())
+            .map_err(TlsConfigError::InvalidProtocol)?
+            .with_client_cert_verifier(client_auth);
+        let mut config = match self.cert_resolver {
+            Some(resolver) => config.with_cert_resolver(resolver),
+            None => {
+                let cert = read_certs(self.cert)?;
+                let key = read_private_key(self.key)?;
+                config
+                    .with_single_cert_with_ocsp_and_sct(cert, key, self.ocsp_resp, Vec::new())
+                    .map_err(TlsConfigError::InvalidKey)?
+            }
+        };
+        // Prefer HTTP/2 but let HTTP/1.1-only clients negotiate a fallback.
+        // The server then serves each connection based on its protocol preface,
+        // which matches the negotiated ALPN value (or HTTP/1.1 if none was negotiated).
         config.alpn_protocols = vec!["h2".into(), "http/1.1".into()];
         Ok(config)
     }
 }
 
+/// Reads a PEM-encoded certificate chain.
+pub fn read_certs(rdr: impl Read) -> Result<Vec<Certificate>, TlsConfigError> {
+    let mut cert_rdr = BufReader::new(rdr);
+    let certs = rustls_pemfile::certs(&mut cert_rdr)
+        .map_err(|_e| TlsConfigError::CertParseError)?
+        .into_iter()
+        .map(Certificate)
+        .collect();
+    Ok(certs)
+}
+
+/// Reads a PEM-encoded RSA, PKCS8 or EC private key.
+pub fn read_private_key(mut rdr: impl Read) -> Result<PrivateKey, TlsConfigError> {
+    // convert it to Vec<u8> to allow reading it again if key is RSA
+    let mut key_vec = Vec::new();
+    rdr.read_to_end(&mut key_vec).map_err(TlsConfigError::Io)?;
+
+    if key_vec.is_empty() {
+        return Err(TlsConfigError::EmptyKey);
+    }
+
+    let mut key = None;
+    let mut reader = std::io::Cursor::new(key_vec);
+    for item in
+        rustls_pemfile::read_all(&mut reader).map_err(|_e| TlsConfigError::InvalidIdentityPem)?
+    {
+        match item {
+            rustls_pemfile::Item::RSAKey(k) => key = Some(PrivateKey(k)),
+            rustls_pemfile::Item::PKCS8Key(k) => key = Some(PrivateKey(k)),
+            rustls_pemfile::Item::ECKey(k) => key = Some(PrivateKey(k)),
+            _ => return Err(TlsConfigError::UnknownPrivateKeyFormat),
+        }
+    }
+    match key {
+        Some(k) => Ok(k),
+        _ => Err(TlsConfigError::EmptyKey),
+    }
+}
+
+/// Checks that the certificate and private key files exist and contain a valid
+/// PEM-encoded certificate chain and a supported private key respectively.
+/// The returned error names the missing or invalid file.
+pub fn validate_cert_key_files(cert: Option<&Path>, key: Option<&Path>) -> crate::Result {
+    let cert = match cert {
+        Some(cert) if !cert.is_file() => bail!("tls cert file not found: {}", cert.display()),
+        Some(cert) => cert,
+        None => bail!("tls cert file not specified"),
+    };
+    let key = match key {
+        Some(key) if !key.is_file() => bail!("tls key file not found: {}", key.display()),
+        Some(key) => key,
+        None => bail!("tls key file not specified"),
+    };
+
+    let certs = match File::open(cert)
+        .map_err(TlsConfigError::Io)
+        .and_then(read_certs)
+    {
+        Ok(certs) => certs,
+        Err(err) => bail!("invalid tls cert file: {}: {}", cert.display(), err),
+    };
+    if certs.is_empty() {
+        bail!(
+            "invalid tls cert file: {}: no PEM-encoded certificate found",
+            cert.display()
+        );
+    }
+    if let Some(err) = certs
+        .iter()
+        .find_map(|cert| x509_parser::parse_x509_certificate(&cert.0).err())
+    {
+        bail!("invalid tls cert file: {}: {}", cert.display(), err);
+    }
+
+    let private_key = match File::open(key)
+        .map_err(TlsConfigError::Io)
+        .and_then(read_private_key)
+    {
+        Ok(private_key) => private_key,
+        Err(err) => bail!("invalid tls key file: {}: {}", key.display(), err),
+    };
+    if let Err(err) = tokio_rustls::rustls::sign::any_supported_type(&private_key) {
+        bail!("invalid tls key file: {}: {}", key.display(), err);
+    }
+    Ok(())
+}
+
 impl Default for TlsConfigBuilder {
     fn default() -> Self {
         Self::new()
@@ -286,11 +513,18 @@ impl Transport for TlsStream {
     fn remote_addr(&self) -> Option<SocketAddr> {
         Some(self.remote_addr)
     }
+
+    fn client_cert(&self) -> Option<ClientCertSlot> {
+        Some(self.client_cert.clone())
+    }
 }
 
 enum State {
+    Detecting(Option<AddrStream>),
     Handshaking(tokio_rustls::Accept<AddrStream>),
     Streaming(tokio_rustls::server::TlsStream<AddrStream>),
+    PlainHttp(Pin<Box<dyn Future<Output = io::Result<()>> + Send>>),
+    Closed,
 }
 
 /// TlsStream implements AsyncRead/AsyncWrite handshaking tokio_rustls::Accept first.
@@ -300,17 +534,80 @@ enum State {
 pub struct TlsStream {
     state: State,
     remote_addr: SocketAddr,
+    config: Arc<ServerConfig>,
+    plain_http: PlainHttpMode,
+    plain_http_redirect: Option<Arc<PlainHttpRedirectOpts>>,
+    client_cert: ClientCertSlot,
 }
 
 impl TlsStream {
-    fn new(stream: AddrStream, config: Arc<ServerConfig>) -> TlsStream {
+    fn new(
+        stream: AddrStream,
+        config: Arc<ServerConfig>,
+        plain_http: PlainHttpMode,
+        plain_http_redirect: Option<Arc<PlainHttpRedirectOpts>>,
+    ) -> TlsStream {
         let remote_addr = stream.remote_addr();
-        let accept = tokio_rustls::TlsAcceptor::from(config).accept(stream);
         TlsStream {
-            state: State::Handshaking(accept),
+            state: State::Detecting(Some(stream)),
             remote_addr,
+            config,
+            plain_http,
+            plain_http_redirect,
+            client_cert: ClientCertSlot::default(),
+        }
+    }
+
+    /// Keeps the verified client certificate (if any) once the handshake completes.
+    fn handshaked(&self, stream: &tokio_rustls::server::TlsStream<AddrStream>) {
+        let cert = stream
+            .get_ref()
+            .1
+            .peer_certificates()
+            .and_then(|certs| certs.first())
+            .and_then(|cert| ClientCert::from_der(&cert.0));
+        if let Some(cert) = cert {
+            tracing::debug!("tls client certificate verified: {}", cert.subject);
+            let _ = self.client_cert.set(cert);
         }
     }
+
+    /// Peeks the first byte sent by the client to tell a plain HTTP request
+    /// (an HTTP method) apart from a TLS handshake (a `0x16` record type).
+    fn poll_detect(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let State::Detecting(Some(ref mut stream)) = self.state {
+            let mut byte = [0u8; 1];
+            let mut buf = ReadBuf::new(&mut byte);
+            ready!(stream.poll_peek(cx, &mut buf))?;
+            let is_plain_http = buf.filled().first().is_some_and(u8::is_ascii_uppercase);
+
+            if let State::Detecting(Some(stream)) =
+                std::mem::replace(&mut self.state, State::Closed)
+            {
+                self.state = if is_plain_http {
+                    tracing::debug!(
+                        "plain http request received on the tls listener from {}",
+                        self.remote_addr
+                    );
+                    match self.plain_http {
+                        PlainHttpMode::Close => State::Closed,
+                        PlainHttpMode::BadRequest => {
+                            State::PlainHttp(Box::pin(plain_http_respond(stream, None)))
+                        }
+                        PlainHttpMode::Redirect => State::PlainHttp(Box::pin(plain_http_respond(
+                            stream,
+                            self.plain_http_redirect.clone(),
+                        ))),
+                    }
+                } else {
+                    State::Handshaking(
+                        tokio_rustls::TlsAcceptor::from(self.config.clone()).accept(stream),
+                    )
+                };
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
 }
 
 impl AsyncRead for TlsStream {
@@ -320,9 +617,11 @@ impl AsyncRead for TlsStream {
         buf: &mut ReadBuf<'_>,
     ) -> Poll<io::Result<()>> {
         let pin = self.get_mut();
+        ready!(pin.poll_detect(cx))?;
         match pin.state {
             State::Handshaking(ref mut accept) => match ready!(Pin::new(accept).poll(cx)) {
                 Ok(mut stream) => {
+                    pin.handshaked(&stream);
                     let result = Pin::new(&mut stream).poll_read(cx, buf);
                     pin.state = State::Streaming(stream);
                     result
@@ -330,6 +629,13 @@ impl AsyncRead for TlsStream {
                 Err(err) => Poll::Ready(Err(err)),
             },
             State::Streaming(ref mut stream) => Pin::new(stream).poll_read(cx, buf),
+            // The connection is reported as closed once the plain HTTP response is sent
+            State::PlainHttp(ref mut respond) => {
+                let result = ready!(respond.as_mut().poll(cx));
+                pin.state = State::Closed;
+                Poll::Ready(result)
+            }
+            State::Detecting(_) | State::Closed => Poll::Ready(Ok(())),
         }
     }
 }
@@ -341,9 +647,11 @@ impl AsyncWrite for TlsStream {
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
         let pin = self.get_mut();
+        ready!(pin.poll_detect(cx))?;
         match pin.state {
             State::Handshaking(ref mut accept) => match ready!(Pin::new(accept).poll(cx)) {
                 Ok(mut stream) => {
+                    pin.handshaked(&stream);
                     let result = Pin::new(&mut stream).poll_write(cx, buf);
                     pin.state = State::Streaming(stream);
                     result
@@ -351,28 +659,101 @@ impl AsyncWrite for TlsStream {
                 Err(err) => Poll::Ready(Err(err)),
             },
             State::Streaming(ref mut stream) => Pin::new(stream).poll_write(cx, buf),
+            State::Detecting(_) | State::PlainHttp(_) | State::Closed => {
+                Poll::Ready(Err(io::ErrorKind::NotConnected.into()))
+            }
         }
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
         match self.state {
-            State::Handshaking(_) => Poll::Ready(Ok(())),
             State::Streaming(ref mut stream) => Pin::new(stream).poll_flush(cx),
+            _ => Poll::Ready(Ok(())),
         }
     }
 
     fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
         match self.state {
-            State::Handshaking(_) => Poll::Ready(Ok(())),
             State::Streaming(ref mut stream) => Pin::new(stream).poll_shutdown(cx),
+            _ => Poll::Ready(Ok(())),
         }
     }
 }
 
+/// Reads the head of a plain HTTP request and replies to it with a redirect
+/// if redirect options are given or with a `400 Bad Request` otherwise.
+async fn plain_http_respond(
+    mut stream: AddrStream,
+    redirect: Option<Arc<PlainHttpRedirectOpts>>,
+) -> io::Result<()> {
+    let mut head = Vec::with_capacity(1024);
+    let read_head = async {
+        let mut buf = [0u8; 1024];
+        // The end of the head may arrive in the middle of a read along with the body
+        while head.len() < PLAIN_HTTP_MAX_HEAD_SIZE && !head.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            head.extend_from_slice(&buf[..n]);
+        }
+        io::Result::Ok(())
+    };
+    if tokio::time::timeout(PLAIN_HTTP_READ_TIMEOUT, read_head)
+        .await
+        .is_err()
+    {
+        return Ok(());
+    }
+
+    let location = redirect.and_then(|opts| plain_http_location(&head, &opts));
+    let resp = match location {
+        Some(location) => format!(
+            "HTTP/1.1 301 Moved Permanently\r\nlocation: {location}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+        ),
+        None => format!(
+            "HTTP/1.1 400 Bad Request\r\ncontent-type: text/plain; charset=utf-8\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{PLAIN_HTTP_BODY}",
+            PLAIN_HTTP_BODY.len()
+        ),
+    };
+    stream.write_all(resp.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Builds the `https` URL of a plain HTTP request head
+/// if its `Host` header matches one of the allowed hosts.
+fn plain_http_location(head: &[u8], opts: &PlainHttpRedirectOpts) -> Option<String> {
+    let head = std::str::from_utf8(head).ok()?;
+    let mut lines = head.split("\r\n");
+    let path = lines.next()?.split(' ').nth(1)?;
+    if !path.starts_with('/') || path.bytes().any(|b| b.is_ascii_control()) {
+        return None;
+    }
+    let host = lines.find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case("host").then(|| value.trim())
+    })?;
+    let hostname = host.parse::<hyper::http::uri::Authority>().ok()?;
+    if !opts
+        .allowed_hosts
+        .iter()
+        .any(|s| s.as_str() == hostname.host())
+    {
+        tracing::debug!("plain http redirect host is not allowed: {}", host);
+        return None;
+    }
+    Some(format!(
+        "https://{}:{}{}",
+        opts.https_hostname, opts.https_port, path
+    ))
+}
+
 /// Type to intercept Tls incoming connections.
 pub struct TlsAcceptor {
     config: Arc<ServerConfig>,
     incoming: AddrIncoming,
+    plain_http: PlainHttpMode,
+    plain_http_redirect: Option<Arc<PlainHttpRedirectOpts>>,
 }
 
 impl TlsAcceptor {
@@ -381,8 +762,23 @@ impl TlsAcceptor {
         TlsAcceptor {
             config: Arc::new(config),
             incoming,
+            plain_http: PlainHttpMode::BadRequest,
+            plain_http_redirect: None,
         }
     }
+
+    /// Sets how plain HTTP requests sent to the TLS listener are handled.
+    pub fn plain_http(mut self, mode: PlainHttpMode) -> Self {
+        self.plain_http = mode;
+        self
+    }
+
+    /// Sets the options to redirect plain HTTP requests sent to the TLS listener.
+    /// Requests are replied with a `400 Bad Request` in redirect mode if not set.
+    pub fn plain_http_redirect(mut self, opts: PlainHttpRedirectOpts) -> Self {
+        self.plain_http_redirect = Some(Arc::new(opts));
+        self
+    }
 }
 
 impl Accept for TlsAcceptor {
@@ -395,7 +791,12 @@ impl Accept for TlsAcceptor {
     ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
         let pin = self.get_mut();
         match ready!(Pin::new(&mut pin.incoming).poll_accept(cx)) {
-            Some(Ok(sock)) => Poll::Ready(Some(Ok(TlsStream::new(sock, pin.config.clone())))),
+            Some(Ok(sock)) => Poll::Ready(Some(Ok(TlsStream::new(
+                sock,
+                pin.config.clone(),
+                pin.plain_http,
+                pin.plain_http_redirect.clone(),
+            )))),
             Some(Err(e)) => Poll::Ready(Some(Err(e))),
             None => Poll::Ready(None),
         }
@@ -447,4 +848,99 @@ mod tests {
             .build()
             .unwrap();
     }
+
+    #[test]
+    fn min_version_cipher_suites() {
+        let builder = || {
+            TlsConfigBuilder::new()
+                .cert_path("tests/tls/local.dev_cert.pem")
+                .key_path("tests/tls/local.dev_key.pem")
+        };
+
+        let suites = parse_cipher_suites(
+            "TLS13_AES_256_GCM_SHA384, tls13_chacha20_poly1305_sha256,TLS13_AES_256_GCM_SHA384",
+        )
+        .unwrap();
+        assert_eq!(suites.len(), 2);
+        assert!(builder()
+            .min_version(TlsMinVersion::Tls13)
+            .cipher_suites(&suites)
+            .build()
+            .is_ok());
+
+        assert!(parse_cipher_suites("").unwrap().is_empty());
+        assert!(parse_cipher_suites("TLS13_AES_256_GCM_SHA384,RC4_MD5").is_err());
+
+        // TLS 1.3 only along with TLS 1.2 only cipher suites
+        let suites = parse_cipher_suites("TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384").unwrap();
+        assert!(matches!(
+            builder()
+                .min_version(TlsMinVersion::Tls13)
+                .cipher_suites(&suites)
+                .build(),
+            Err(TlsConfigError::InvalidProtocol(_))
+        ));
+        assert!(builder().cipher_suites(&suites).build().is_ok());
+    }
+
+    #[test]
+    fn client_auth_ca() {
+        let builder = || {
+            TlsConfigBuilder::new()
+                .cert_path("tests/tls/local.dev_cert.pem")
+                .key_path("tests/tls/local.dev_key.pem")
+        };
+        assert!(builder()
+            .client_auth_required_path("tests/tls/client_ca.pem")
+            .build()
+            .is_ok());
+        assert!(builder()
+            .client_auth_optional_path("tests/tls/client_ca.pem")
+            .build()
+            .is_ok());
+        // A trust anchor without any certificate
+        assert!(matches!(
+            builder()
+                .client_auth_required_path("tests/tls/client_key.pem")
+                .build(),
+            Err(TlsConfigError::CertParseError)
+        ));
+
+        let cert = read_certs(File::open("tests/tls/client_cert.pem").unwrap()).unwrap();
+        assert_eq!(
+            ClientCert::from_der(&cert[0].0),
+            Some(ClientCert {
+                subject: String::from("O=SWS, CN=sws-client"),
+                common_name: Some(String::from("sws-client")),
+            })
+        );
+        assert_eq!(ClientCert::from_der(b"invalid"), None);
+    }
+
+    #[test]
+    fn plain_http_redirect_location() {
+        let opts = PlainHttpRedirectOpts {
+            https_hostname: String::from("example.com"),
+            https_port: 8443,
+            allowed_hosts: vec![String::from("localhost"), String::from("example.com")],
+        };
+        let location = |head: &str| plain_http_location(head.as_bytes(), &opts);
+
+        assert_eq!(
+            location("GET /a?b=1 HTTP/1.1\r\nHost: localhost:8443\r\n\r\n").as_deref(),
+            Some("https://example.com:8443/a?b=1")
+        );
+        assert_eq!(
+            location("GET / HTTP/1.1\r\nhost: example.com\r\n\r\n").as_deref(),
+            Some("https://example.com:8443/")
+        );
+        // Hosts not allowed, missing or invalid are not redirected
+        assert_eq!(location("GET / HTTP/1.1\r\nHost: evil.com\r\n\r\n"), None);
+        assert_eq!(location("GET / HTTP/1.1\r\n\r\n"), None);
+        assert_eq!(location("GET / HTTP/1.1\r\nHost: local host\r\n\r\n"), None);
+        assert_eq!(
+            location("GET http://x/ HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+            None
+        );
+    }
 }
@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to enforce per-path trailing slash policies.
+//!
+//! The `[[advanced.trailing-slash]]` rules override the global `redirect-trailing-slash`
+//! option for the matching request paths. Paths are redirected permanently (301)
+//! to remove (`remove`) their trailing slash before the file lookup, directory paths
+//! to add (`add`) it after the file lookup, or served as requested (`ignore`).
+//!
+
+use hyper::{header::LOCATION, Body, Response, StatusCode};
+
+use crate::settings::{file::TrailingSlashPolicy, TrailingSlashRule};
+
+/// Returns the trailing slash policy of the first rule matching the given request path.
+/// Rules are expected to be sorted by priority.
+///
+/// Rules match the path with or without its trailing slash,
+/// so a `/docs` source applies to both `/docs` and `/docs/`.
+pub fn find_policy(
+    uri_path: &str,
+    rules: &Option<Vec<TrailingSlashRule>>,
+) -> Option<TrailingSlashPolicy> {
+    let trimmed = match uri_path.trim_end_matches('/') {
+        "" => uri_path,
+        trimmed => trimmed,
+    };
+    rules
+        .as_ref()?
+        .iter()
+        .find(|rule| {
+            [uri_path, trimmed].iter().any(|path| {
+                rule.source.is_match(path) && !rule.exclude.iter().any(|m| m.is_match(path))
+            })
+        })
+        .map(|rule| rule.policy)
+}
+
+/// Returns whether the static file handler should redirect directory paths missing
+/// their trailing slash (and reject file paths with it) under the given policy.
+///
+/// Only the `add` policy keeps that redirection, the other ones disable it
+/// so their paths are never redirected back and forth.
+pub fn redirects_to_slash(policy: Option<TrailingSlashPolicy>, default: bool) -> bool {
+    match policy {
+        Some(TrailingSlashPolicy::Add) => true,
+        Some(TrailingSlashPolicy::Remove | TrailingSlashPolicy::Ignore) => false,
+        None => default,
+    }
+}
+
+/// It returns a permanent redirect response to the request path with its trailing slash
+/// added or removed according to the given policy, otherwise `None`.
+///
+/// The root path is never redirected and the query string is preserved.
+pub fn redirect(
+    policy: TrailingSlashPolicy,
+    uri_path: &str,
+    uri_query: Option<&str>,
+) -> Option<Response<Body>> {
+    if uri_path.is_empty() || uri_path == "/" {
+        return None;
+    }
+
+    let path = match policy {
+        TrailingSlashPolicy::Add if !uri_path.ends_with('/') => [uri_path, "/"].concat(),
+        TrailingSlashPolicy::Remove if uri_path.ends_with('/') => {
+            uri_path.trim_end_matches('/').to_owned()
+        }
+        _ => return None,
+    };
+    let loc = match uri_query {
+        Some(query) => [path.as_str(), "?", query].concat(),
+        None => path,
+    };
+
+    let loc = match loc.parse() {
+        Ok(val) => val,
+        Err(err) => {
+            tracing::error!("invalid header value from current uri: {:?}", err);
+            return None;
+        }
+    };
+    let mut resp = Response::new(Body::empty());
+    resp.headers_mut().insert(LOCATION, loc);
+    *resp.status_mut() = StatusCode::MOVED_PERMANENTLY;
+    tracing::trace!("trailing slash policy {:?} redirecting permanently", policy);
+    Some(resp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{redirect, redirects_to_slash, TrailingSlashPolicy};
+
+    fn location(policy: TrailingSlashPolicy, path: &str, query: Option<&str>) -> Option<String> {
+        redirect(policy, path, query).map(|resp| {
+            assert_eq!(resp.status(), 301);
+            resp.headers()["location"].to_str().unwrap().to_owned()
+        })
+    }
+
+    #[test]
+    fn trailing_slash_redirects() {
+        use TrailingSlashPolicy::*;
+
+        assert_eq!(location(Add, "/docs", None).as_deref(), Some("/docs/"));
+        assert_eq!(location(Add, "/docs/", None), None);
+        assert_eq!(
+            location(Add, "/docs", Some("a=1")).as_deref(),
+            Some("/docs/?a=1")
+        );
+        assert_eq!(location(Remove, "/app/", None).as_deref(), Some("/app"));
+        assert_eq!(location(Remove, "/app", None), None);
+        assert_eq!(location(Ignore, "/app/", None), None);
+        assert_eq!(location(Ignore, "/app", None), None);
+
+        // The root path is never redirected
+        assert_eq!(location(Remove, "/", None), None);
+        assert_eq!(location(Add, "", None), None);
+
+        assert!(redirects_to_slash(Some(Add), false));
+        assert!(!redirects_to_slash(Some(Remove), true));
+        assert!(!redirects_to_slash(Some(Ignore), true));
+        assert!(redirects_to_slash(None, true));
+    }
+}
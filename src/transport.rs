@@ -16,10 +16,19 @@ use std::task::{Context, Poll};
 use hyper::server::conn::AddrStream;
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
+#[cfg(feature = "http2")]
+use crate::tls::ClientCertSlot;
+
 /// Transport trait that supports the remote (peer) address.
 pub trait Transport: AsyncRead + AsyncWrite {
     /// Returns the remote (peer) address of this connection.
     fn remote_addr(&self) -> Option<SocketAddr>;
+
+    /// Returns the client certificate of this connection if it's a TLS one.
+    #[cfg(feature = "http2")]
+    fn client_cert(&self) -> Option<ClientCertSlot> {
+        None
+    }
 }
 
 impl Transport for AddrStream {
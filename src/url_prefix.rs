@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to serve files under a base URL prefix (subpath) like `/myapp`.
+//!
+//! The prefix is stripped from incoming request paths before the static file lookup
+//! and prepended to the path-absolute `Location` headers of redirect responses.
+//!
+
+use hyper::{header::LOCATION, Body, Response};
+
+use crate::Result;
+
+/// Normalizes a URL prefix value like `myapp/` into `/myapp`.
+/// An empty value or a single slash means no prefix.
+pub fn parse(prefix: &str) -> Result<String> {
+    let prefix = prefix.trim().trim_matches('/');
+    if prefix.is_empty() {
+        return Ok(String::new());
+    }
+    if prefix.contains(['?', '#']) || prefix.split('/').any(|s| s.is_empty() || s == "..") {
+        bail!("invalid url prefix value: {:?}", prefix);
+    }
+    Ok(["/", prefix].concat())
+}
+
+/// Strips the URL prefix from the given request path.
+/// It returns `None` if the path is not under the prefix.
+///
+/// Note that the prefix itself (e.g. `/myapp`) results in an empty path.
+pub fn strip<'a>(prefix: &str, uri_path: &'a str) -> Option<&'a str> {
+    match uri_path.strip_prefix(prefix) {
+        Some(path) if path.is_empty() || path.starts_with('/') => Some(path),
+        _ => None,
+    }
+}
+
+/// Prepends the URL prefix to the path-absolute `Location` header of the current response if any.
+/// Absolute or scheme-relative locations are left untouched.
+pub fn prepend_location(prefix: &str, resp: &mut Response<Body>) {
+    if prefix.is_empty() {
+        return;
+    }
+    let loc = match resp.headers().get(LOCATION).and_then(|v| v.to_str().ok()) {
+        Some(loc) if loc.starts_with('/') && !loc.starts_with("//") => [prefix, loc].concat(),
+        _ => return,
+    };
+    match loc.parse() {
+        Ok(val) => {
+            resp.headers_mut().insert(LOCATION, val);
+        }
+        Err(err) => tracing::error!("invalid location header value with url prefix: {:?}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, prepend_location, strip};
+    use hyper::{header::LOCATION, Body, Response};
+
+    #[test]
+    fn parse_prefix() {
+        assert_eq!(parse("").unwrap(), "");
+        assert_eq!(parse("/").unwrap(), "");
+        assert_eq!(parse("myapp/").unwrap(), "/myapp");
+        assert_eq!(parse("/my/app").unwrap(), "/my/app");
+        assert!(parse("/my//app").is_err());
+        assert!(parse("/my/../app").is_err());
+    }
+
+    #[test]
+    fn strip_prefix() {
+        assert_eq!(strip("/myapp", "/myapp/index.html"), Some("/index.html"));
+        assert_eq!(strip("/myapp", "/myapp/"), Some("/"));
+        assert_eq!(strip("/myapp", "/myapp"), Some(""));
+        assert_eq!(strip("/myapp", "/myapp2/index.html"), None);
+        assert_eq!(strip("/myapp", "/index.html"), None);
+    }
+
+    #[test]
+    fn prepend_prefix_location() {
+        let mut resp = Response::new(Body::empty());
+        resp.headers_mut()
+            .insert(LOCATION, "/assets/".parse().unwrap());
+        prepend_location("/myapp", &mut resp);
+        assert_eq!(resp.headers()[LOCATION], "/myapp/assets/");
+
+        let mut resp = Response::new(Body::empty());
+        resp.headers_mut()
+            .insert(LOCATION, "https://example.com/".parse().unwrap());
+        prepend_location("/myapp", &mut resp);
+        assert_eq!(resp.headers()[LOCATION], "https://example.com/");
+    }
+}
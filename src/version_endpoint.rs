@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// This file is part of Static Web Server.
+// See https://static-web-server.net/ for more information
+// Copyright (C) 2019-present Jose Quintana <joseluisq.net>
+
+//! Module to expose the server version, build and runtime information as JSON.
+//!
+
+use headers::{ContentType, HeaderMapExt};
+use hyper::{Body, Method, Response};
+use serde::Serialize;
+use std::time::Instant;
+
+use crate::{exts::http::MethodExt, Result};
+
+/// Git commit hash of the current build if available.
+pub const GIT_HASH: &str = env!("SWS_GIT_HASH");
+
+/// Version endpoint options.
+pub struct VersionEndpoint {
+    /// Request path of the endpoint (e.g. `/version`).
+    pub path: String,
+    /// Server start time used to compute the uptime.
+    pub started: Instant,
+}
+
+#[derive(Serialize)]
+struct VersionInfo<'a> {
+    version: &'a str,
+    git_hash: Option<&'a str>,
+    features: Vec<&'a str>,
+    uptime_secs: u64,
+}
+
+impl VersionEndpoint {
+    /// Creates the version endpoint options using the given request path.
+    /// It returns `None` if the path is empty.
+    pub fn new(path: &str) -> Result<Option<Self>> {
+        let path = path.trim();
+        if path.is_empty() {
+            return Ok(None);
+        }
+        if !path.starts_with('/') {
+            bail!("version endpoint path must start with a slash: {}", path);
+        }
+        Ok(Some(Self {
+            path: path.to_owned(),
+            started: Instant::now(),
+        }))
+    }
+
+    /// Checks if the given request matches the version endpoint.
+    pub fn is_match(&self, uri_path: &str, method: &Method) -> bool {
+        uri_path == self.path && (method.is_get() || method.is_head())
+    }
+
+    /// Returns the JSON response of the version endpoint.
+    pub fn response(&self, method: &Method) -> Result<Response<Body>> {
+        let info = VersionInfo {
+            version: env!("CARGO_PKG_VERSION"),
+            git_hash: Some(GIT_HASH).filter(|h| !h.is_empty()),
+            features: features(),
+            uptime_secs: self.started.elapsed().as_secs(),
+        };
+        let body = if method.is_head() {
+            Body::empty()
+        } else {
+            Body::from(serde_json::to_vec(&info)?)
+        };
+        let mut resp = Response::new(body);
+        resp.headers_mut().typed_insert(ContentType::json());
+        Ok(resp)
+    }
+}
+
+/// Returns the Cargo features enabled in the current build.
+pub fn features() -> Vec<&'static str> {
+    [
+        ("compression", cfg!(feature = "compression")),
+        ("compression-brotli", cfg!(feature = "compression-brotli")),
+        ("compression-deflate", cfg!(feature = "compression-deflate")),
+        ("compression-gzip", cfg!(feature = "compression-gzip")),
+        ("compression-zstd", cfg!(feature = "compression-zstd")),
+        ("http2", cfg!(feature = "http2")),
+        ("directory-listing", cfg!(feature = "directory-listing")),
+        ("basic-auth", cfg!(feature = "basic-auth")),
+        ("fallback-page", cfg!(feature = "fallback-page")),
+        ("archive", cfg!(feature = "archive")),
+        ("acme", cfg!(feature = "acme")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
@@ -12,7 +12,9 @@ mod tests {
     use std::path::PathBuf;
 
     #[cfg(feature = "directory-listing")]
-    use static_web_server::directory_listing::DirListFmt;
+    use static_web_server::directory_listing::{DirListFmt, DirListSizeFmt, DirListTimezone};
+    use static_web_server::etag::EtagMode;
+    use static_web_server::exts::http::HTTP_SUPPORTED_METHODS;
     use static_web_server::static_files::{self, HandleOpts};
 
     fn public_dir() -> PathBuf {
@@ -36,6 +38,7 @@ mod tests {
             method: &Method::GET,
             headers: &headers,
             base_path: &public_dir(),
+            overlay_root: None,
             uri_path: "index.html",
             uri_query: None,
             #[cfg(feature = "directory-listing")]
@@ -44,10 +47,29 @@ mod tests {
             dir_listing_order: 6,
             #[cfg(feature = "directory-listing")]
             dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
             redirect_trailing_slash: true,
+            trailing_slash_policy: None,
             #[cfg(feature = "compression")]
             compression_static: true,
             ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_date_format: "%F %T",
+            #[cfg(feature = "directory-listing")]
+            dir_listing_timezone: &DirListTimezone::Local,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_ignore: &[],
+            #[cfg(feature = "directory-listing")]
+            dir_listing_show_symlinks: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_merge_overlay: false,
         })
         .await
         .expect("unexpected error response on `handle` function");
@@ -94,6 +116,7 @@ mod tests {
             method: &Method::GET,
             headers: &headers,
             base_path: &public_dir().join("assets/"),
+            overlay_root: None,
             uri_path: "index.html",
             uri_query: None,
             #[cfg(feature = "directory-listing")]
@@ -102,10 +125,29 @@ mod tests {
             dir_listing_order: 6,
             #[cfg(feature = "directory-listing")]
             dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
             redirect_trailing_slash: true,
+            trailing_slash_policy: None,
             #[cfg(feature = "compression")]
             compression_static: true,
             ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_date_format: "%F %T",
+            #[cfg(feature = "directory-listing")]
+            dir_listing_timezone: &DirListTimezone::Local,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_ignore: &[],
+            #[cfg(feature = "directory-listing")]
+            dir_listing_show_symlinks: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_merge_overlay: false,
         })
         .await
         .expect("unexpected error response on `handle` function");
@@ -150,16 +192,208 @@ mod tests {
             method: &Method::GET,
             headers: &headers,
             base_path: &base_path,
+            overlay_root: None,
             uri_path: "/",
             uri_query: None,
             dir_listing: true,
             dir_listing_order: 6,
             dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
+            redirect_trailing_slash: true,
+            trailing_slash_policy: None,
+            compression_static: true,
+            ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_date_format: "%F %T",
+            #[cfg(feature = "directory-listing")]
+            dir_listing_timezone: &DirListTimezone::Local,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_ignore: &[],
+            #[cfg(feature = "directory-listing")]
+            dir_listing_show_symlinks: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_merge_overlay: false,
+        })
+        .await
+        .expect("unexpected error response on `handle` function");
+    }
+
+    #[tokio::test]
+    async fn compression_static_file_ranges() {
+        let base_path = std::env::temp_dir().join("sws-compression-static-ranges-test");
+        std::fs::create_dir_all(&base_path).unwrap();
+        std::fs::copy(
+            "tests/fixtures/public/index.html.gz",
+            base_path.join("index.html.gz"),
+        )
+        .expect("unexpected error copying fixture file");
+        let index_gz_buf = std::fs::read(base_path.join("index.html.gz")).unwrap();
+        let len = index_gz_buf.len();
+
+        // Ranges apply to the bytes of the pre-compressed variant
+        let mut headers = HeaderMap::new();
+        headers.insert(http::header::ACCEPT_ENCODING, "gzip".parse().unwrap());
+        headers.insert(http::header::RANGE, "bytes=10-99".parse().unwrap());
+        let (mut resp, _) = static_files::handle(&HandleOpts {
+            method: &Method::GET,
+            headers: &headers,
+            base_path: &base_path,
+            overlay_root: None,
+            uri_path: "index.html",
+            uri_query: None,
+            #[cfg(feature = "directory-listing")]
+            dir_listing: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_order: 6,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
+            redirect_trailing_slash: true,
+            trailing_slash_policy: None,
+            #[cfg(feature = "compression")]
+            compression_static: true,
+            ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_date_format: "%F %T",
+            #[cfg(feature = "directory-listing")]
+            dir_listing_timezone: &DirListTimezone::Local,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_ignore: &[],
+            #[cfg(feature = "directory-listing")]
+            dir_listing_show_symlinks: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_merge_overlay: false,
+        })
+        .await
+        .expect("unexpected error response on `handle` function");
+        assert_eq!(resp.status(), 206);
+        assert_eq!(resp.headers()["content-encoding"], "gzip");
+        assert_eq!(resp.headers()["content-type"], "text/html");
+        assert_eq!(
+            resp.headers()["content-range"],
+            format!("bytes 10-99/{len}").as_str()
+        );
+        assert_eq!(resp.headers()["content-length"], "90");
+        let body = hyper::body::to_bytes(resp.body_mut()).await.unwrap();
+        assert_eq!(body, Bytes::copy_from_slice(&index_gz_buf[10..100]));
+
+        // A range matching the compressed entity tag is served partially
+        let etag = resp.headers()["etag"].clone();
+        headers.insert(http::header::IF_RANGE, etag);
+        headers.insert(http::header::RANGE, "bytes=-32".parse().unwrap());
+        let (mut resp, _) = static_files::handle(&HandleOpts {
+            method: &Method::GET,
+            headers: &headers,
+            base_path: &base_path,
+            overlay_root: None,
+            uri_path: "index.html",
+            uri_query: None,
+            #[cfg(feature = "directory-listing")]
+            dir_listing: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_order: 6,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
+            redirect_trailing_slash: true,
+            trailing_slash_policy: None,
+            #[cfg(feature = "compression")]
+            compression_static: true,
+            ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_date_format: "%F %T",
+            #[cfg(feature = "directory-listing")]
+            dir_listing_timezone: &DirListTimezone::Local,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_ignore: &[],
+            #[cfg(feature = "directory-listing")]
+            dir_listing_show_symlinks: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_merge_overlay: false,
+        })
+        .await
+        .expect("unexpected error response on `handle` function");
+        assert_eq!(resp.status(), 206);
+        assert_eq!(resp.headers()["content-encoding"], "gzip");
+        assert_eq!(
+            resp.headers()["content-range"],
+            format!("bytes {}-{}/{len}", len - 32, len - 1).as_str()
+        );
+        let body = hyper::body::to_bytes(resp.body_mut()).await.unwrap();
+        assert_eq!(body, Bytes::copy_from_slice(&index_gz_buf[len - 32..]));
+
+        // Ranges beyond the compressed size are not satisfiable
+        headers.remove(http::header::IF_RANGE);
+        headers.insert(
+            http::header::RANGE,
+            format!("bytes={len}-").parse().unwrap(),
+        );
+        let (resp, _) = static_files::handle(&HandleOpts {
+            method: &Method::GET,
+            headers: &headers,
+            base_path: &base_path,
+            overlay_root: None,
+            uri_path: "index.html",
+            uri_query: None,
+            #[cfg(feature = "directory-listing")]
+            dir_listing: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_order: 6,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
             redirect_trailing_slash: true,
+            trailing_slash_policy: None,
+            #[cfg(feature = "compression")]
             compression_static: true,
             ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_date_format: "%F %T",
+            #[cfg(feature = "directory-listing")]
+            dir_listing_timezone: &DirListTimezone::Local,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_ignore: &[],
+            #[cfg(feature = "directory-listing")]
+            dir_listing_show_symlinks: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_merge_overlay: false,
         })
         .await
         .expect("unexpected error response on `handle` function");
+        assert_eq!(resp.status(), 416);
+        assert_eq!(
+            resp.headers()["content-range"],
+            format!("bytes */{len}").as_str()
+        );
+
+        std::fs::remove_dir_all(base_path).unwrap();
     }
 }
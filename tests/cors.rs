@@ -73,8 +73,49 @@ mod tests {
         let methods = [Method::GET, Method::HEAD, Method::OPTIONS];
         for method in methods {
             let res = cors.check_request(&method, &headers);
-            assert!(res.is_err());
-            assert!(matches!(res.unwrap_err(), cors::Forbidden::Origin))
+            if method == Method::OPTIONS {
+                assert!(matches!(res.unwrap_err(), cors::Forbidden::Origin))
+            } else {
+                // Not allowed origins get no CORS headers
+                let (headers, state) = res.unwrap();
+                assert!(matches!(state, cors::Validated::NotCors));
+                assert!(headers.get("access-control-allow-origin").is_none());
+                assert_eq!(headers["vary"], "origin");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn origin_allowed_echo() {
+        let cors = cors::new("https://localhost, https://example.com", "", "").unwrap();
+        for origin in ["https://localhost", "https://example.com"] {
+            let mut headers = HeaderMap::new();
+            headers.insert("origin", origin.parse().unwrap());
+            headers.insert("access-control-request-method", "GET".parse().unwrap());
+            for method in [Method::GET, Method::HEAD, Method::OPTIONS] {
+                let (headers, _) = cors.check_request(&method, &headers).unwrap();
+                assert_eq!(headers["access-control-allow-origin"], origin);
+                assert_eq!(headers["vary"], "origin");
+            }
+        }
+
+        // Responses to requests without origin vary as well
+        let (headers, state) = cors.check_request(&Method::GET, &HeaderMap::new()).unwrap();
+        assert!(matches!(state, cors::Validated::NotCors));
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers["vary"], "origin");
+    }
+
+    #[tokio::test]
+    async fn origin_wildcard() {
+        let cors = cors::new("*", "", "").unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("origin", "https://localhost".parse().unwrap());
+        headers.insert("access-control-request-method", "GET".parse().unwrap());
+        for method in [Method::GET, Method::HEAD, Method::OPTIONS] {
+            let (headers, _) = cors.check_request(&method, &headers).unwrap();
+            assert_eq!(headers["access-control-allow-origin"], "*");
+            assert!(headers.get("vary").is_none());
         }
     }
 
@@ -11,8 +11,12 @@ mod tests {
     use serde::{Deserialize, Serialize};
     use std::path::{Path, PathBuf};
 
+    #[cfg(feature = "compression")]
+    use static_web_server::compression;
     use static_web_server::{
-        directory_listing::DirListFmt,
+        directory_listing::{DirListFmt, DirListSizeFmt, DirListTimezone},
+        etag::EtagMode,
+        exts::http::HTTP_SUPPORTED_METHODS,
         static_files::{self, HandleOpts},
     };
 
@@ -41,14 +45,28 @@ mod tests {
                 method: &method,
                 headers: &HeaderMap::new(),
                 base_path: &root_dir("docker/public/"),
+                overlay_root: None,
                 uri_path: "/assets",
                 uri_query: None,
                 dir_listing: true,
                 dir_listing_order: 6,
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                dir_listing_date_format: "%F %T",
+                dir_listing_timezone: &DirListTimezone::Local,
+                dir_listing_ignore: &[],
+                dir_listing_show_symlinks: false,
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -71,14 +89,28 @@ mod tests {
                 method: &method,
                 headers: &HeaderMap::new(),
                 base_path: &root_dir("docs/"),
+                overlay_root: None,
                 uri_path: "/content/",
                 uri_query: None,
                 dir_listing: true,
                 dir_listing_order: 6,
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                dir_listing_date_format: "%F %T",
+                dir_listing_timezone: &DirListTimezone::Local,
+                dir_listing_ignore: &[],
+                dir_listing_show_symlinks: false,
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -111,14 +143,28 @@ mod tests {
                 method: &method,
                 headers: &HeaderMap::new(),
                 base_path: &root_dir("docs/"),
+                overlay_root: None,
                 uri_path: "/content",
                 uri_query: None,
                 dir_listing: true,
                 dir_listing_order: 6,
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: false,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                dir_listing_date_format: "%F %T",
+                dir_listing_timezone: &DirListTimezone::Local,
+                dir_listing_ignore: &[],
+                dir_listing_show_symlinks: false,
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -151,14 +197,28 @@ mod tests {
                 method: &method,
                 headers: &HeaderMap::new(),
                 base_path: &root_dir("docs/"),
+                overlay_root: None,
                 uri_path: "/README.md",
                 uri_query: None,
                 dir_listing: true,
                 dir_listing_order: 6,
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: false,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                dir_listing_date_format: "%F %T",
+                dir_listing_timezone: &DirListTimezone::Local,
+                dir_listing_ignore: &[],
+                dir_listing_show_symlinks: false,
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -181,14 +241,28 @@ mod tests {
                 method: &method,
                 headers: &HeaderMap::new(),
                 base_path: &root_dir("tests/fixtures/public/"),
+                overlay_root: None,
                 uri_path: "/",
                 uri_query: None,
                 dir_listing: true,
                 dir_listing_order: 6,
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                dir_listing_date_format: "%F %T",
+                dir_listing_timezone: &DirListTimezone::Local,
+                dir_listing_ignore: &[],
+                dir_listing_show_symlinks: false,
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -216,6 +290,146 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn dir_listing_html_escaped_names() {
+        let dir = std::env::temp_dir().join("sws-dir-listing-escaped-names");
+        std::fs::create_dir_all(dir.join("<dir>")).unwrap();
+        for name in ["<b>&\"'.txt", "a+b.txt", "<dir>/<i>.txt"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        async fn listing(dir: &Path, uri_path: &str) -> String {
+            let (res, _) = static_files::handle(&HandleOpts {
+                method: &Method::GET,
+                headers: &HeaderMap::new(),
+                base_path: &dir.to_path_buf(),
+                overlay_root: None,
+                uri_path,
+                uri_query: None,
+                dir_listing: true,
+                dir_listing_order: 6,
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                dir_listing_date_format: "%F %T",
+                dir_listing_timezone: &DirListTimezone::Local,
+                dir_listing_ignore: &[],
+                dir_listing_show_symlinks: false,
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            .expect("unexpected error response on `handle` function");
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            String::from_utf8(body.to_vec()).unwrap()
+        }
+
+        // Names are percent-encoded in links and HTML-escaped in texts
+        let body = listing(&dir, "/").await;
+        assert!(
+            body.contains(r#"<a href="%3Cb%3E%26%22%27%2Etxt">&lt;b&gt;&amp;&quot;&#39;.txt</a>"#)
+        );
+        assert!(body.contains(r#"<a href="%3Cdir%3E/">&lt;dir&gt;/</a>"#));
+        assert!(body.contains(r#"<a href="a%2Bb%2Etxt">a+b.txt</a>"#));
+        assert!(!body.contains("<b>"));
+
+        let body = listing(&dir, "/%3Cdir%3E/").await;
+        assert!(body.contains("<h1>Index of /&lt;dir&gt;/</h1>"));
+        assert!(body.contains(r#"<a href="%3Ci%3E%2Etxt">&lt;i&gt;.txt</a>"#));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn dir_listing_show_symlinks() {
+        let dir = std::env::temp_dir().join("sws-dir-listing-show-symlinks");
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(dir.join("docs")).unwrap();
+        std::fs::write(dir.join("file.txt"), "abc").unwrap();
+        std::os::unix::fs::symlink("file.txt", dir.join("file-link")).unwrap();
+        std::os::unix::fs::symlink("docs", dir.join("docs-link")).unwrap();
+        std::os::unix::fs::symlink("missing.txt", dir.join("broken-link")).unwrap();
+
+        async fn listing(dir: &Path, format: &DirListFmt, show_symlinks: bool) -> String {
+            let (res, _) = static_files::handle(&HandleOpts {
+                method: &Method::GET,
+                headers: &HeaderMap::new(),
+                base_path: &dir.to_path_buf(),
+                overlay_root: None,
+                uri_path: "/",
+                uri_query: None,
+                dir_listing: true,
+                dir_listing_order: 0,
+                dir_listing_format: format,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                dir_listing_size_format: &DirListSizeFmt::Bytes,
+                dir_listing_date_format: "%F %T",
+                dir_listing_timezone: &DirListTimezone::Local,
+                dir_listing_ignore: &[],
+                dir_listing_show_symlinks: show_symlinks,
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            .unwrap();
+            let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            String::from_utf8(body.to_vec()).unwrap()
+        }
+
+        // Symlinks are resolved silently and broken ones omitted by default
+        let body = listing(&dir, &DirListFmt::Html, false).await;
+        assert!(body.contains(r#"<a href="file%2Dlink">file-link</a></td>"#));
+        assert!(body.contains(r#"<a href="docs%2Dlink/">docs-link/</a></td>"#));
+        assert!(!body.contains("broken-link"));
+        assert!(body.contains("directories: 2, files: 2"));
+
+        let body = listing(&dir, &DirListFmt::Html, true).await;
+        assert!(body.contains(r#"<a href="file%2Dlink">file-link</a> &rarr; file.txt</td>"#));
+        assert!(body.contains(r#"<a href="docs%2Dlink/">docs-link/</a> &rarr; docs</td>"#));
+        assert!(body.contains("<td>broken-link &rarr; missing.txt (broken)</td>"));
+        assert!(body.contains("directories: 2, files: 3"));
+
+        let body = listing(&dir, &DirListFmt::Json, true).await;
+        let entries: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        let entry = |name: &str| {
+            entries
+                .iter()
+                .find(|entry| entry["name"] == name)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(entry("file-link")["type"], "file");
+        assert_eq!(entry("file-link")["symlink"], true);
+        assert_eq!(entry("file-link")["target"], "file.txt");
+        assert_eq!(entry("docs-link")["type"], "directory");
+        assert_eq!(entry("docs-link")["target"], "docs");
+        assert_eq!(entry("broken-link")["target"], "missing.txt");
+        assert_eq!(entry("broken-link")["broken"], true);
+        assert!(entry("file.txt").get("symlink").is_none());
+        assert!(entry("file-link").get("broken").is_none());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
     #[tokio::test]
     async fn dir_listing_json_format() {
         #[derive(Serialize, Deserialize)]
@@ -232,20 +446,37 @@ mod tests {
                 method: &method,
                 headers: &HeaderMap::new(),
                 base_path: &root_dir("tests/fixtures/public/"),
+                overlay_root: None,
                 uri_path: "/",
                 uri_query: None,
                 dir_listing: true,
                 dir_listing_order: 1,
                 dir_listing_format: &DirListFmt::Json,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: true,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                dir_listing_date_format: "%F %T",
+                dir_listing_timezone: &DirListTimezone::Local,
+                dir_listing_ignore: &[],
+                dir_listing_show_symlinks: false,
+                dir_listing_merge_overlay: false,
             })
             .await
             {
                 Ok((mut res, _)) => {
                     assert_eq!(res.status(), 200);
-                    assert_eq!(res.headers()["content-type"], "application/json");
+                    assert_eq!(
+                        res.headers()["content-type"],
+                        "application/json; charset=utf-8"
+                    );
 
                     let body = hyper::body::to_bytes(res.body_mut())
                         .await
@@ -301,20 +532,37 @@ mod tests {
                 method: &method,
                 headers: &HeaderMap::new(),
                 base_path: &root_dir(&empty_dir),
+                overlay_root: None,
                 uri_path: "/",
                 uri_query: None,
                 dir_listing: true,
                 dir_listing_order: 1,
                 dir_listing_format: &DirListFmt::Json,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                dir_listing_date_format: "%F %T",
+                dir_listing_timezone: &DirListTimezone::Local,
+                dir_listing_ignore: &[],
+                dir_listing_show_symlinks: false,
+                dir_listing_merge_overlay: false,
             })
             .await
             {
                 Ok((mut res, _)) => {
                     assert_eq!(res.status(), 200);
-                    assert_eq!(res.headers()["content-type"], "application/json");
+                    assert_eq!(
+                        res.headers()["content-type"],
+                        "application/json; charset=utf-8"
+                    );
 
                     let body = hyper::body::to_bytes(res.body_mut())
                         .await
@@ -343,14 +591,28 @@ mod tests {
                 method: &method,
                 headers: &HeaderMap::new(),
                 base_path: &root_dir("tests/fixtures/public"),
+                overlay_root: None,
                 uri_path: "/",
                 uri_query: None,
                 dir_listing: true,
                 dir_listing_order: 1,
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: true,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                dir_listing_date_format: "%F %T",
+                dir_listing_timezone: &DirListTimezone::Local,
+                dir_listing_ignore: &[],
+                dir_listing_show_symlinks: false,
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -376,4 +638,318 @@ mod tests {
             }
         }
     }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn dir_listing_html_compression() {
+        let dir = std::env::temp_dir().join("sws-dir-listing-compression");
+        std::fs::create_dir_all(&dir).unwrap();
+        for i in 0..500 {
+            std::fs::write(dir.join(format!("file-{i:04}.txt")), "").unwrap();
+        }
+
+        let method = &Method::GET;
+        let mut headers = HeaderMap::new();
+        headers.insert(http::header::ACCEPT_ENCODING, "gzip".parse().unwrap());
+
+        match static_files::handle(&HandleOpts {
+            method,
+            headers: &headers,
+            base_path: &dir,
+            overlay_root: None,
+            uri_path: "/",
+            uri_query: None,
+            dir_listing: true,
+            dir_listing_order: 6,
+            dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
+            redirect_trailing_slash: true,
+            trailing_slash_policy: None,
+            compression_static: false,
+            ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            dir_listing_date_format: "%F %T",
+            dir_listing_timezone: &DirListTimezone::Local,
+            dir_listing_ignore: &[],
+            dir_listing_show_symlinks: false,
+            dir_listing_merge_overlay: false,
+        })
+        .await
+        {
+            Ok((res, _)) => {
+                let res = compression::auto(
+                    method,
+                    &headers,
+                    res,
+                    &compression::parse_types("").unwrap(),
+                    &compression::parse_encodings("").unwrap(),
+                    compression::CompressionLevel::Default,
+                )
+                .expect("unexpected bytes error during body compression");
+
+                assert_eq!(res.status(), 200);
+                assert_eq!(res.headers()["content-encoding"], "gzip");
+                assert_eq!(res.headers()["content-type"], "text/html; charset=utf-8");
+                assert!(res.headers().get("content-length").is_none());
+            }
+            Err(_) => {
+                panic!("unexpected status error")
+            }
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn dir_listing_content_length() {
+        for (format, query) in [(&DirListFmt::Html, None), (&DirListFmt::Json, Some(""))] {
+            for method in [Method::GET, Method::HEAD] {
+                let (mut res, _) = static_files::handle(&HandleOpts {
+                    method: &method,
+                    headers: &HeaderMap::new(),
+                    base_path: &root_dir("tests/fixtures/public/"),
+                    overlay_root: None,
+                    uri_path: "/",
+                    uri_query: query,
+                    dir_listing: true,
+                    dir_listing_order: 6,
+                    dir_listing_format: format,
+                    index_files: &["index.html".to_owned()],
+                    allowed_methods: HTTP_SUPPORTED_METHODS,
+                    extensionless_html: true,
+                    redirect_trailing_slash: true,
+                    trailing_slash_policy: None,
+                    compression_static: false,
+                    ignore_hidden_files: false,
+                    case_insensitive_paths: false,
+                    disable_range_requests: false,
+                    etag_mode: EtagMode::Metadata,
+                    dir_listing_size_format: &DirListSizeFmt::Human,
+                    dir_listing_date_format: "%F %T",
+                    dir_listing_timezone: &DirListTimezone::Local,
+                    dir_listing_ignore: &[],
+                    dir_listing_show_symlinks: false,
+                    dir_listing_merge_overlay: false,
+                })
+                .await
+                .expect("unexpected error response on `handle` function");
+
+                assert_eq!(res.status(), 200);
+                let content_length: usize = res.headers()["content-length"]
+                    .to_str()
+                    .unwrap()
+                    .parse()
+                    .unwrap();
+                assert!(content_length > 0);
+
+                let body = hyper::body::to_bytes(res.body_mut())
+                    .await
+                    .expect("unexpected bytes error during `body` conversion");
+                if method == Method::GET {
+                    assert_eq!(body.len(), content_length);
+                } else {
+                    assert!(body.is_empty());
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn dir_listing_etag() {
+        let dir = std::env::temp_dir().join("sws-dir-listing-etag");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+
+        async fn listing(
+            dir: &Path,
+            format: &DirListFmt,
+            if_none_match: Option<&str>,
+        ) -> (StatusCode, String) {
+            let mut headers = HeaderMap::new();
+            if let Some(etag) = if_none_match {
+                headers.insert(http::header::IF_NONE_MATCH, etag.parse().unwrap());
+            }
+            let (res, _) = static_files::handle(&HandleOpts {
+                method: &Method::GET,
+                headers: &headers,
+                base_path: &dir.to_path_buf(),
+                overlay_root: None,
+                uri_path: "/",
+                uri_query: None,
+                dir_listing: true,
+                dir_listing_order: 6,
+                dir_listing_format: format,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                dir_listing_date_format: "%F %T",
+                dir_listing_timezone: &DirListTimezone::Local,
+                dir_listing_ignore: &[],
+                dir_listing_show_symlinks: false,
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            .expect("unexpected error response on `handle` function");
+            let etag = res.headers()["etag"].to_str().unwrap().to_owned();
+            (res.status(), etag)
+        }
+
+        let (status, etag) = listing(&dir, &DirListFmt::Html, None).await;
+        assert_eq!(status, 200);
+        assert!(etag.starts_with("W/\""));
+
+        // Unchanged listings are not modified
+        let (status, etag_304) = listing(&dir, &DirListFmt::Html, Some(&etag)).await;
+        assert_eq!(status, StatusCode::NOT_MODIFIED);
+        assert_eq!(etag_304, etag);
+
+        // Each content format has its own entity tag
+        let (status, etag_json) = listing(&dir, &DirListFmt::Json, Some(&etag)).await;
+        assert_eq!(status, 200);
+        assert_ne!(etag_json, etag);
+        let (status, _) = listing(&dir, &DirListFmt::Json, Some(&etag_json)).await;
+        assert_eq!(status, StatusCode::NOT_MODIFIED);
+
+        // Added, modified or removed entries invalidate the entity tag
+        std::fs::write(dir.join("b.txt"), "b").unwrap();
+        let (status, etag_added) = listing(&dir, &DirListFmt::Html, Some(&etag)).await;
+        assert_eq!(status, 200);
+        assert_ne!(etag_added, etag);
+
+        std::fs::write(dir.join("b.txt"), "bb").unwrap();
+        let (status, etag_modified) = listing(&dir, &DirListFmt::Html, Some(&etag_added)).await;
+        assert_eq!(status, 200);
+        assert_ne!(etag_modified, etag_added);
+
+        std::fs::remove_file(dir.join("b.txt")).unwrap();
+        let (status, _) = listing(&dir, &DirListFmt::Html, Some(&etag_modified)).await;
+        assert_eq!(status, 200);
+        let (status, _) = listing(&dir, &DirListFmt::Html, Some(&etag)).await;
+        assert_eq!(status, StatusCode::NOT_MODIFIED);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn dir_listing_ignore_patterns() {
+        let ignore = [globset::Glob::new("*.gz").unwrap().compile_matcher()];
+
+        let (mut res, _) = static_files::handle(&HandleOpts {
+            method: &Method::GET,
+            headers: &HeaderMap::new(),
+            base_path: &root_dir("tests/fixtures/public/"),
+            overlay_root: None,
+            uri_path: "/",
+            uri_query: None,
+            dir_listing: true,
+            dir_listing_order: 6,
+            dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
+            redirect_trailing_slash: true,
+            trailing_slash_policy: None,
+            compression_static: false,
+            ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            dir_listing_date_format: "%F %T",
+            dir_listing_timezone: &DirListTimezone::Local,
+            dir_listing_ignore: &ignore,
+            dir_listing_show_symlinks: false,
+            dir_listing_merge_overlay: false,
+        })
+        .await
+        .expect("unexpected error response on `handle` function");
+
+        assert_eq!(res.status(), 200);
+
+        let body = hyper::body::to_bytes(res.body_mut())
+            .await
+            .expect("unexpected bytes error during `body` conversion");
+        let body_str = std::str::from_utf8(&body).unwrap();
+
+        assert!(!body_str.contains("index.html.gz"));
+        assert!(body_str.contains(".dotfile"));
+        assert!(body_str.contains("spécial directöry/"));
+    }
+
+    #[tokio::test]
+    async fn dir_listing_date_format_timezone() {
+        let modified = std::fs::metadata("tests/fixtures/public/index.html.gz")
+            .unwrap()
+            .modified()
+            .unwrap();
+        let modified = chrono::DateTime::<chrono::Utc>::from(modified);
+
+        for (format, date_format, expected) in [
+            (
+                DirListFmt::Html,
+                "%d/%m/%Y %H:%M:%S UTC",
+                modified
+                    .format("<td>%d/%m/%Y %H:%M:%S UTC</td>")
+                    .to_string(),
+            ),
+            // JSON entries always use RFC 3339 UTC dates
+            (
+                DirListFmt::Json,
+                "%d/%m/%Y",
+                modified.format("\"mtime\":\"%FT%TZ\"").to_string(),
+            ),
+        ] {
+            let (mut res, _) = static_files::handle(&HandleOpts {
+                method: &Method::GET,
+                headers: &HeaderMap::new(),
+                base_path: &root_dir("tests/fixtures/public/"),
+                overlay_root: None,
+                uri_path: "/",
+                uri_query: None,
+                dir_listing: true,
+                dir_listing_order: 6,
+                dir_listing_format: &format,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                dir_listing_date_format: date_format,
+                dir_listing_timezone: &DirListTimezone::Utc,
+                dir_listing_ignore: &[],
+                dir_listing_show_symlinks: false,
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            .expect("unexpected error response on `handle` function");
+
+            assert_eq!(res.status(), 200);
+
+            let body = hyper::body::to_bytes(res.body_mut())
+                .await
+                .expect("unexpected bytes error during `body` conversion");
+            let body_str = std::str::from_utf8(&body).unwrap();
+
+            assert!(body_str.contains(&expected), "{body_str}");
+        }
+    }
 }
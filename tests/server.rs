@@ -0,0 +1,3680 @@
+#![forbid(unsafe_code)]
+#![deny(warnings)]
+#![deny(rust_2018_idioms)]
+#![deny(dead_code)]
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::process::{Child, Command, Stdio};
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    struct ServerProcess(Child);
+
+    impl Drop for ServerProcess {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    fn start_server(args: &[&str]) -> (ServerProcess, String) {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_static-web-server"));
+        cmd.args(["--host", "127.0.0.1", "--port", &port.to_string()]);
+        if !args.contains(&"--root") {
+            cmd.args(["--root", "docker/public"]);
+        }
+        let child = cmd
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let server = ServerProcess(child);
+
+        let addr = format!("127.0.0.1:{port}");
+        let started = Instant::now();
+        while TcpStream::connect(&addr).is_err() {
+            assert!(
+                started.elapsed() < Duration::from_secs(10),
+                "server start up"
+            );
+            sleep(Duration::from_millis(50));
+        }
+        (server, addr)
+    }
+
+    #[test]
+    fn header_read_timeout_drops_slow_clients() {
+        let (_server, addr) = start_server(&["--header-read-timeout", "1"]);
+
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_millis(100)))
+            .unwrap();
+
+        // Send the request head byte by byte without ever completing it
+        let started = Instant::now();
+        let mut buf = [0; 64];
+        let mut closed = false;
+        for byte in
+            b"GET / HTTP/1.1\r\nHost: localhost\r\nX-Slow-Header: abcdefghijklmnopqrstuvwxyz"
+        {
+            if stream.write_all(&[*byte]).is_err() {
+                closed = true;
+                break;
+            }
+            match stream.read(&mut buf) {
+                // Connection dropped with no response
+                Ok(0) => {
+                    closed = true;
+                    break;
+                }
+                Ok(_) => panic!("unexpected response received"),
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {}
+                Err(err) if err.kind() == std::io::ErrorKind::TimedOut => {}
+                Err(_) => {
+                    closed = true;
+                    break;
+                }
+            }
+            sleep(Duration::from_millis(100));
+        }
+
+        assert!(closed, "connection should be dropped");
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn header_read_timeout_complete_request() {
+        let (_server, addr) = start_server(&["--header-read-timeout", "1"]);
+
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 200 OK"));
+    }
+
+    #[test]
+    fn disable_keep_alive() {
+        let (_server, addr) = start_server(&["--disable-keep-alive"]);
+
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+
+        // The server closes the connection right after the response
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 200 OK"));
+        assert!(resp.to_lowercase().contains("\r\nconnection: close\r\n"));
+    }
+
+    #[test]
+    fn keep_alive_by_default() {
+        let (_server, addr) = start_server(&[]);
+
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_millis(500)))
+            .unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+
+        // The connection remains open after the response
+        let mut resp = Vec::new();
+        let err = stream.read_to_end(&mut resp).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+        ));
+        assert!(String::from_utf8_lossy(&resp).starts_with("HTTP/1.1 200 OK"));
+    }
+
+    #[test]
+    fn options_asterisk_request() {
+        let (_server, addr) = start_server(&[]);
+
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .write_all(b"OPTIONS * HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 204 No Content"));
+        assert!(resp
+            .to_lowercase()
+            .contains("\r\nallow: options, head, get\r\n"));
+    }
+
+    #[test]
+    fn options_asterisk_request_url_prefix() {
+        let (_server, addr) = start_server(&["--url-prefix", "/app"]);
+
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .write_all(b"OPTIONS * HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+
+        // The asterisk-form is not subject to the URL prefix
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 204 No Content"), "{resp}");
+        let resp = resp.to_lowercase();
+        assert!(resp.contains("\r\nallow: options, head, get\r\n"), "{resp}");
+        assert!(!resp.contains("\r\naccept-ranges:"), "{resp}");
+    }
+
+    #[test]
+    fn allowed_methods() {
+        let request = |addr: &str, method: &str, path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("{method} {path} HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp.to_lowercase()
+        };
+
+        let (server, addr) = start_server(&[]);
+        for method in ["POST", "DELETE", "PUT"] {
+            let resp = request(&addr, method, "/index.html");
+            assert!(
+                resp.starts_with("http/1.1 405 method not allowed"),
+                "{resp}"
+            );
+            assert!(resp.contains("\r\nallow: options, head, get\r\n"), "{resp}");
+        }
+        assert!(request(&addr, "GET", "/index.html").starts_with("http/1.1 200 ok"));
+        drop(server);
+
+        let (_server, addr) = start_server(&["--allowed-methods", "get,head"]);
+        let resp = request(&addr, "OPTIONS", "/index.html");
+        assert!(
+            resp.starts_with("http/1.1 405 method not allowed"),
+            "{resp}"
+        );
+        assert!(resp.contains("\r\nallow: head, get\r\n"), "{resp}");
+        let resp = request(&addr, "POST", "/assets/");
+        assert!(
+            resp.starts_with("http/1.1 405 method not allowed"),
+            "{resp}"
+        );
+        assert!(request(&addr, "HEAD", "/index.html").starts_with("http/1.1 200 ok"));
+    }
+
+    #[test]
+    fn url_prefix() {
+        let (_server, addr) = start_server(&["--url-prefix", "/myapp"]);
+
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp.to_lowercase()
+        };
+
+        assert!(request("/myapp/index.html").starts_with("http/1.1 200 ok"));
+        assert!(request("/myapp/").starts_with("http/1.1 200 ok"));
+
+        // Redirects keep the prefix
+        let resp = request("/myapp");
+        assert!(resp.starts_with("http/1.1 308 permanent redirect"));
+        assert!(resp.contains("\r\nlocation: /myapp/\r\n"));
+        let resp = request("/myapp/assets");
+        assert!(resp.starts_with("http/1.1 308 permanent redirect"));
+        assert!(resp.contains("\r\nlocation: /myapp/assets/\r\n"));
+
+        // Requests outside of the prefix are not found
+        assert!(request("/index.html").starts_with("http/1.1 404 not found"));
+        assert!(request("/myapp2/index.html").starts_with("http/1.1 404 not found"));
+    }
+
+    #[test]
+    fn no_store_paths() {
+        let config_dir = std::env::temp_dir().join("sws-no-store-test");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let config_file = config_dir.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[[advanced.headers]]
+source = "**/*"
+headers = { Cache-Control = "public, max-age=3600", Expires = "Wed, 21 Oct 2015 07:28:00 GMT" }
+
+[[advanced.no-store]]
+source = "/index.html"
+"#,
+        )
+        .unwrap();
+        let (_server, addr) = start_server(&["--config-file", config_file.to_str().unwrap()]);
+
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp.to_lowercase()
+        };
+
+        let resp = request("/index.html");
+        assert!(resp.starts_with("http/1.1 200 ok"));
+        assert!(resp.contains("\r\ncache-control: no-store, no-cache\r\n"));
+        assert!(resp.contains("\r\npragma: no-cache\r\n"));
+        assert!(!resp.contains("max-age"));
+        assert!(!resp.contains("\r\nexpires:"));
+
+        // Percent-encoded variants of the path are covered as well
+        for path in ["/%69ndex.html", "//index.html", "/./index.html"] {
+            let resp = request(path);
+            assert!(resp.starts_with("http/1.1 200 ok"), "{path}");
+            assert!(
+                resp.contains("\r\ncache-control: no-store, no-cache\r\n"),
+                "{path}"
+            );
+        }
+
+        // Other paths keep their caching headers
+        let resp = request("/assets/main.css");
+        assert!(resp.contains("\r\ncache-control: public, max-age=3600\r\n"));
+        assert!(!resp.contains("\r\npragma:"));
+
+        // The patterns follow the case-insensitive paths option
+        let (_server, ci_addr) = start_server(&[
+            "--config-file",
+            config_file.to_str().unwrap(),
+            "--case-insensitive-paths",
+        ]);
+        let mut stream = TcpStream::connect(&ci_addr).unwrap();
+        stream
+            .write_all(b"GET /INDEX.html HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+        let resp = resp.to_lowercase();
+        assert!(resp.starts_with("http/1.1 200 ok"));
+        assert!(resp.contains("\r\ncache-control: no-store, no-cache\r\n"));
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reuse_port_instances() {
+        let (_server, addr) = start_server(&["--reuse-port"]);
+        let port = addr.rsplit(':').next().unwrap();
+
+        // A second instance can bind the same address and port
+        let mut second = ServerProcess(
+            Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+                .args([
+                    "--host",
+                    "127.0.0.1",
+                    "--port",
+                    port,
+                    "--root",
+                    "docker/public",
+                ])
+                .arg("--reuse-port")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .unwrap(),
+        );
+        sleep(Duration::from_secs(1));
+        assert!(second.0.try_wait().unwrap().is_none());
+
+        // Instances without the option can not
+        let status = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args([
+                "--host",
+                "127.0.0.1",
+                "--port",
+                port,
+                "--root",
+                "docker/public",
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(!status.success());
+    }
+
+    #[test]
+    fn default_files_when_missing() {
+        let request = |addr: &str, path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = Vec::new();
+            stream.read_to_end(&mut resp).unwrap();
+            String::from_utf8_lossy(&resp).to_lowercase()
+        };
+        let favicon_len = std::fs::metadata("docker/public/assets/favicon.ico")
+            .unwrap()
+            .len();
+
+        let (_server, addr) = start_server(&[
+            "--default-robots-txt",
+            "disallow-all",
+            "--default-favicon",
+            "docker/public/assets/favicon.ico",
+        ]);
+        let resp = request(&addr, "/robots.txt");
+        assert!(resp.starts_with("http/1.1 200 ok"));
+        assert!(resp.contains("\r\ncontent-type: text/plain; charset=utf-8\r\n"));
+        assert!(resp.ends_with("\r\n\r\nuser-agent: *\ndisallow: /\n"));
+        let resp = request(&addr, "/favicon.ico");
+        assert!(resp.starts_with("http/1.1 200 ok"));
+        assert!(resp.contains("\r\ncontent-type: image/x-icon\r\n"));
+        assert!(resp.contains(&format!("\r\ncontent-length: {favicon_len}\r\n")));
+        assert!(request(&addr, "/assets/robots.txt").starts_with("http/1.1 404 not found"));
+
+        // Real files always win
+        let (_server, addr) = start_server(&[
+            "--root",
+            "docker/public/assets",
+            "--default-favicon",
+            "docker/public/index.html",
+        ]);
+        let resp = request(&addr, "/favicon.ico");
+        assert!(resp.contains("\r\ncontent-type: image/x-icon\r\n"));
+        assert!(request(&addr, "/robots.txt").starts_with("http/1.1 404 not found"));
+    }
+
+    #[test]
+    fn version_endpoint() {
+        let request = |addr: &str, method: &str, path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!(
+                        "{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+                    )
+                    .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        let (server, addr) = start_server(&["--version-endpoint", "/version"]);
+        let resp = request(&addr, "GET", "/version");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(resp.contains("\r\ncontent-type: application/json\r\n"));
+        let body = resp.split_once("\r\n\r\n").unwrap().1;
+        let info: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+        assert!(info["uptime_secs"].is_u64());
+        assert!(info["features"].is_array());
+        let resp = request(&addr, "HEAD", "/version");
+        assert!(resp.ends_with("\r\n\r\n"), "{resp}");
+        assert!(request(&addr, "POST", "/version").starts_with("HTTP/1.1 405"));
+        drop(server);
+
+        // Disabled by default
+        let (server, addr) = start_server(&[]);
+        assert!(request(&addr, "GET", "/version").starts_with("HTTP/1.1 404 Not Found"));
+        drop(server);
+
+        // Protected by the basic authentication
+        #[cfg(feature = "basic-auth")]
+        {
+            let (_server, addr) = start_server(&[
+                "--version-endpoint",
+                "/version",
+                "--basic-auth",
+                "jq:$2y$05$32zazJ1yzhlDHnt26L3MFOgY0HVqPmDUvG0KUx6cjf9RDiUGp/M9q",
+            ]);
+            assert!(request(&addr, "GET", "/version").starts_with("HTTP/1.1 401 Unauthorized"));
+        }
+    }
+
+    #[test]
+    fn cache_control_directives() {
+        let config_dir = std::env::temp_dir().join("sws-cache-control-directives-test");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let config_file = config_dir.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[general]
+cache-control-must-revalidate = true
+cache-control-stale-while-revalidate = 86400
+
+[[advanced.cache-control]]
+pattern = "text/html"
+max-age = 60
+stale-while-revalidate = 86400
+"#,
+        )
+        .unwrap();
+        let (_server, addr) = start_server(&["--config-file", config_file.to_str().unwrap()]);
+
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp.to_lowercase()
+        };
+
+        let resp = request("/index.html");
+        assert!(resp.contains("\r\ncache-control: max-age=60, stale-while-revalidate=86400\r\n"));
+
+        // The global directives are composed with the file type defaults
+        let resp = request("/assets/main.css");
+        assert!(resp.contains(
+            "\r\ncache-control: public, max-age=31536000, must-revalidate, stale-while-revalidate=86400\r\n"
+        ));
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[test]
+    fn cache_control_icons() {
+        let request = |addr: &str, path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = Vec::new();
+            stream.read_to_end(&mut resp).unwrap();
+            let pos = resp.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+            String::from_utf8_lossy(&resp[..pos]).to_lowercase()
+        };
+
+        let (server, addr) = start_server(&[
+            "--root",
+            "docker/public/assets",
+            "--cache-control-icons-max-age",
+            "600",
+        ]);
+        let head = request(&addr, "/favicon.ico");
+        assert!(head.starts_with("http/1.1 200 ok"));
+        assert!(
+            head.contains("\r\ncache-control: public, max-age=600, immutable"),
+            "{head}"
+        );
+        let head = request(&addr, "/main.css");
+        assert!(
+            head.contains("\r\ncache-control: public, max-age=31536000"),
+            "{head}"
+        );
+        assert!(!head.contains("immutable"), "{head}");
+        drop(server);
+
+        // Default files are covered as well
+        let (_server, addr) = start_server(&[
+            "--default-favicon",
+            "docker/public/assets/favicon.ico",
+            "--cache-control-icons-max-age",
+            "600",
+        ]);
+        let head = request(&addr, "/favicon.ico");
+        assert!(head.starts_with("http/1.1 200 ok"));
+        assert!(
+            head.contains("\r\ncache-control: public, max-age=600, immutable"),
+            "{head}"
+        );
+    }
+
+    #[cfg(feature = "basic-auth")]
+    #[test]
+    fn basic_auth_realms() {
+        let config_dir = std::env::temp_dir().join("sws-basic-auth-realms-test");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let config_file = config_dir.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[[advanced.basic-auth]]
+source = "/assets/**"
+credentials = "jq:$2y$05$32zazJ1yzhlDHnt26L3MFOgY0HVqPmDUvG0KUx6cjf9RDiUGp/M9q"
+realm = "Assets"
+
+[[advanced.basic-auth]]
+source = "/index.html"
+credentials = "jq:$2y$05$32zazJ1yzhlDHnt26L3MFOgY0HVqPmDUvG0KUx6cjf9RDiUGp/M9q"
+"#,
+        )
+        .unwrap();
+        let (_server, addr) = start_server(&["--config-file", config_file.to_str().unwrap()]);
+
+        let request = |path: &str, extra_headers: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n{extra_headers}Connection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        let resp = request("/assets/main.css", "");
+        assert!(resp.starts_with("HTTP/1.1 401 Unauthorized"));
+        assert!(
+            resp.contains("\r\nwww-authenticate: Basic realm=\"Assets\", charset=\"UTF-8\"\r\n")
+        );
+        let resp = request("/index.html", "");
+        assert!(resp.starts_with("HTTP/1.1 401 Unauthorized"));
+        assert!(resp.contains(
+            "\r\nwww-authenticate: Basic realm=\"Static Web Server\", charset=\"UTF-8\"\r\n"
+        ));
+
+        let resp = request("/assets/main.css", "Authorization: Basic anE6anE=\r\n");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"));
+        // Paths without rules are not protected
+        assert!(request("/", "").starts_with("HTTP/1.1 200 OK"));
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[cfg(feature = "basic-auth")]
+    #[test]
+    fn basic_auth_before_file_lookup() {
+        let config_dir = std::env::temp_dir().join("sws-basic-auth-lookup-test");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let config_file = config_dir.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[[advanced.basic-auth]]
+source = "/assets/**"
+credentials = "jq:$2y$05$32zazJ1yzhlDHnt26L3MFOgY0HVqPmDUvG0KUx6cjf9RDiUGp/M9q"
+"#,
+        )
+        .unwrap();
+
+        let request = |addr: &str, path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+        let assert_unauthorized = |resp: String| {
+            assert!(resp.starts_with("HTTP/1.1 401 Unauthorized"), "{resp}");
+            assert!(resp.contains("\r\nwww-authenticate: Basic realm="));
+            // The error page does not depend on the file existence
+            resp.split_once("\r\n\r\n").unwrap().1.to_owned()
+        };
+
+        // Global credentials
+        let (server, addr) = start_server(&[
+            "--basic-auth",
+            "jq:$2y$05$32zazJ1yzhlDHnt26L3MFOgY0HVqPmDUvG0KUx6cjf9RDiUGp/M9q",
+        ]);
+        let body = assert_unauthorized(request(&addr, "/index.html"));
+        assert_eq!(assert_unauthorized(request(&addr, "/missing.html")), body);
+        assert_eq!(assert_unauthorized(request(&addr, "/assets")), body);
+        drop(server);
+
+        // Path-based credentials
+        let (_server, addr) = start_server(&["--config-file", config_file.to_str().unwrap()]);
+        let body = assert_unauthorized(request(&addr, "/assets/main.css"));
+        for path in [
+            "/assets/missing.css",
+            "/assets",
+            "/assets/",
+            "/%61ssets/main.css",
+            "//assets/main.css",
+        ] {
+            assert_eq!(assert_unauthorized(request(&addr, path)), body, "{path}");
+        }
+        assert!(request(&addr, "/index.html").starts_with("HTTP/1.1 200 OK"));
+        assert!(request(&addr, "/missing.html").starts_with("HTTP/1.1 404 Not Found"));
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[cfg(feature = "basic-auth")]
+    #[test]
+    fn basic_auth_invalid_realm() {
+        let output = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args([
+                "--root",
+                "docker/public",
+                "--basic-auth",
+                "jq:$2y$05$32zazJ1yzhlDHnt26L3MFOgY0HVqPmDUvG0KUx6cjf9RDiUGp/M9q",
+                "--basic-auth-realm",
+                "my \"realm\"",
+            ])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("invalid basic authentication realm"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn response_timeout() {
+        // Opening a named pipe without a writer blocks like a stalled file system
+        let root = std::env::temp_dir().join("sws-response-timeout-test");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("index.html"), "ok").unwrap();
+        let fifo = root.join("stalled.html");
+        if !fifo.exists() {
+            assert!(Command::new("mkfifo")
+                .arg(&fifo)
+                .status()
+                .unwrap()
+                .success());
+        }
+        let (_server, addr) =
+            start_server(&["--root", root.to_str().unwrap(), "--response-timeout", "1"]);
+
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        let started = Instant::now();
+        assert!(request("/stalled.html").starts_with("HTTP/1.1 504 Gateway Timeout"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(request("/index.html").starts_with("HTTP/1.1 200 OK"));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn response_timeout_small_blocking_pool() {
+        // Hashing the strong entity tag needs a blocking thread besides the file lookup
+        let (_server, addr) = start_server(&[
+            "--response-timeout",
+            "3",
+            "--etag-mode",
+            "strong",
+            "--max-blocking-threads",
+            "1",
+        ]);
+
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .write_all(b"GET /index.html HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+    }
+
+    #[cfg(feature = "basic-auth")]
+    #[test]
+    fn slow_requests_log() {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let child = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args(["--host", "127.0.0.1", "--port", &port.to_string()])
+            .args(["--root", "docker/public", "--log-level", "warn"])
+            // Verifying the bcrypt hash makes every request exceed the 1ms threshold
+            .args(["--slow-request-threshold-ms", "1", "--basic-auth"])
+            .arg("jq:$2y$05$32zazJ1yzhlDHnt26L3MFOgY0HVqPmDUvG0KUx6cjf9RDiUGp/M9q")
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut server = ServerProcess(child);
+
+        let addr = format!("127.0.0.1:{port}");
+        let started = Instant::now();
+        let mut stream = loop {
+            if let Ok(stream) = TcpStream::connect(&addr) {
+                break stream;
+            }
+            assert!(
+                started.elapsed() < Duration::from_secs(10),
+                "server start up"
+            );
+            sleep(Duration::from_millis(50));
+        };
+        stream
+            .write_all(
+                b"GET /index.html HTTP/1.1\r\nHost: localhost\r\nAuthorization: Basic anE6eA==\r\nConnection: close\r\n\r\n",
+            )
+            .unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 401 Unauthorized"));
+
+        server.0.kill().unwrap();
+        let mut stderr = String::new();
+        server
+            .0
+            .stderr
+            .take()
+            .unwrap()
+            .read_to_string(&mut stderr)
+            .unwrap();
+        assert!(
+            stderr.contains("slow request: method=GET uri=/index.html status=401 duration_ms="),
+            "{stderr}"
+        );
+        assert!(stderr.contains("file=- size=-"));
+    }
+
+    /// Waits until the given log file contains a message returning its contents.
+    fn wait_for_log(log_file: &std::path::Path, message: &str) -> String {
+        let started = Instant::now();
+        loop {
+            let logs = std::fs::read_to_string(log_file).unwrap_or_default();
+            if logs.contains(message) {
+                return logs;
+            }
+            assert!(started.elapsed() < Duration::from_secs(5), "{logs}");
+            sleep(Duration::from_millis(50));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn log_file_reopen() {
+        let dir = std::env::temp_dir().join("sws-log-file-reopen");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let log_file = dir.join("sws.log");
+        let rotated_file = dir.join("sws.log.1");
+
+        let (server, _) = start_server(&[
+            "--log-level",
+            "info",
+            "--log-file",
+            log_file.to_str().unwrap(),
+        ]);
+        let logs = wait_for_log(&log_file, "log file: ");
+        assert!(!logs.contains("\x1b["), "{logs}");
+
+        std::fs::rename(&log_file, &rotated_file).unwrap();
+        let status = Command::new("kill")
+            .args(["-USR1", &server.0.id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        wait_for_log(&log_file, "log file re-opened: ");
+        let rotated_logs = std::fs::read_to_string(&rotated_file).unwrap();
+        assert!(!rotated_logs.contains("log file re-opened: "));
+
+        drop(server);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn log_file_flushed_on_shutdown() {
+        let dir = std::env::temp_dir().join("sws-log-file-shutdown");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let log_file = dir.join("sws.log");
+
+        let (mut server, _) = start_server(&[
+            "--log-level",
+            "info",
+            "--log-file",
+            log_file.to_str().unwrap(),
+        ]);
+        let status = Command::new("kill")
+            .args(["-TERM", &server.0.id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        assert!(server.0.wait().unwrap().success());
+
+        let logs = std::fs::read_to_string(&log_file).unwrap();
+        assert!(
+            logs.contains("delegating server's graceful shutdown"),
+            "{logs}"
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stats_summary_on_shutdown() {
+        let dir = std::env::temp_dir().join("sws-stats-summary");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let log_file = dir.join("sws.log");
+
+        let (mut server, addr) = start_server(&["--log-file", log_file.to_str().unwrap()]);
+        let mut body_len = 0;
+        for path in ["/index.html", "/missing.html"] {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = Vec::new();
+            stream.read_to_end(&mut resp).unwrap();
+            body_len += resp.len();
+        }
+
+        let status = Command::new("kill")
+            .args(["-TERM", &server.0.id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        assert!(server.0.wait().unwrap().success());
+
+        let logs = std::fs::read_to_string(&log_file).unwrap();
+        let summary = logs
+            .lines()
+            .find_map(|line| line.split_once("server stats: ").map(|(_, s)| s))
+            .unwrap_or_else(|| panic!("{logs}"));
+        assert!(summary.contains(" requests=2 "), "{summary}");
+        assert!(
+            summary.contains(&format!(" bytes_sent={body_len} ")),
+            "{summary}"
+        );
+        assert!(summary.ends_with(" statuses=200:1,404:1"), "{summary}");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn log_file_stdout() {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let child = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args(["--host", "127.0.0.1", "--port", &port.to_string()])
+            .args(["--root", "docker/public", "--log-level", "info"])
+            .args(["--log-file", "stdout"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let mut server = ServerProcess(child);
+
+        let addr = format!("127.0.0.1:{port}");
+        let started = Instant::now();
+        while TcpStream::connect(&addr).is_err() {
+            assert!(
+                started.elapsed() < Duration::from_secs(10),
+                "server start up"
+            );
+            sleep(Duration::from_millis(50));
+        }
+
+        server.0.kill().unwrap();
+        let mut stdout = String::new();
+        server
+            .0
+            .stdout
+            .take()
+            .unwrap()
+            .read_to_string(&mut stdout)
+            .unwrap();
+        assert!(stdout.contains("log file: stdout"), "{stdout}");
+        assert!(!std::path::Path::new("stdout").exists());
+    }
+
+    #[test]
+    fn path_normalization() {
+        let (_server, addr) = start_server(&[]);
+
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        let expected = std::fs::read_to_string("docker/public/assets/main.css").unwrap();
+        for path in [
+            "/assets/main.css",
+            "//assets//main.css",
+            "/assets/./main.css",
+            "/a/b/../../assets/main.css",
+            "/a/%2e%2e/assets/main.css",
+            "/a/%2E%2E/assets/%2e/main.css",
+            "/a%2f..%2fassets/main.css",
+            "/../../assets/main.css",
+        ] {
+            let resp = request(path);
+            assert!(resp.starts_with("HTTP/1.1 200 OK"), "{path}");
+            assert!(resp.ends_with(&expected), "{path}");
+        }
+
+        // Dot segments can not escape the root directory
+        let resp = request("/%2e%2e/%2e%2e/%2e%2e/etc/passwd");
+        assert!(resp.starts_with("HTTP/1.1 404 Not Found"));
+    }
+
+    #[test]
+    fn request_id() {
+        let (_server, addr) =
+            start_server(&["--request-id", "--request-id-trusted-proxies", "127.0.0.1"]);
+
+        let request = |extra_headers: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET / HTTP/1.1\r\nHost: localhost\r\n{extra_headers}Connection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp.to_lowercase()
+        };
+
+        // A new request id is generated by default
+        let resp = request("");
+        let request_id = resp
+            .lines()
+            .find_map(|l| l.strip_prefix("x-request-id: "))
+            .unwrap();
+        assert_eq!(request_id.len(), 36);
+
+        // The inbound request id of a trusted proxy is preserved
+        let resp = request("X-Request-Id: abc-123\r\n");
+        assert!(resp.contains("\r\nx-request-id: abc-123\r\n"));
+    }
+
+    #[cfg(feature = "http2")]
+    fn tls_client(
+        addr: &str,
+        alpn_protocols: &[&[u8]],
+    ) -> rustls::StreamOwned<rustls::ClientConnection, TcpStream> {
+        tls_client_with_cert(addr, alpn_protocols, None).unwrap()
+    }
+
+    /// Connects a TLS client presenting the given certificate and key files if any.
+    #[cfg(feature = "http2")]
+    fn tls_client_with_cert(
+        addr: &str,
+        alpn_protocols: &[&[u8]],
+        client_cert: Option<(&str, &str)>,
+    ) -> std::io::Result<rustls::StreamOwned<rustls::ClientConnection, TcpStream>> {
+        use rustls::client::{ServerCertVerified, ServerCertVerifier};
+        use std::sync::Arc;
+
+        // The test certificate is signed by a local CA so skip its verification
+        struct NoCertVerification;
+
+        impl ServerCertVerifier for NoCertVerification {
+            fn verify_server_cert(
+                &self,
+                _end_entity: &rustls::Certificate,
+                _intermediates: &[rustls::Certificate],
+                _server_name: &rustls::ServerName,
+                _scts: &mut dyn Iterator<Item = &[u8]>,
+                _ocsp_response: &[u8],
+                _now: std::time::SystemTime,
+            ) -> Result<ServerCertVerified, rustls::Error> {
+                Ok(ServerCertVerified::assertion())
+            }
+        }
+
+        let config = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(NoCertVerification));
+        let mut config = match client_cert {
+            Some((cert, key)) => {
+                use static_web_server::tls::{read_certs, read_private_key};
+                let cert = read_certs(std::fs::File::open(cert).unwrap()).unwrap();
+                let key = read_private_key(std::fs::File::open(key).unwrap()).unwrap();
+                config.with_client_auth_cert(cert, key).unwrap()
+            }
+            None => config.with_no_client_auth(),
+        };
+        config.alpn_protocols = alpn_protocols.iter().map(|p| p.to_vec()).collect();
+
+        let conn = rustls::ClientConnection::new(Arc::new(config), "localhost".try_into().unwrap())
+            .unwrap();
+        let mut stream = rustls::StreamOwned::new(conn, TcpStream::connect(addr).unwrap());
+        stream
+            .sock
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream.conn.complete_io(&mut stream.sock)?;
+        Ok(stream)
+    }
+
+    #[cfg(feature = "http2")]
+    #[test]
+    fn http2_alpn_negotiation() {
+        let (_server, addr) = start_server(&[
+            "--http2=true",
+            "--http2-tls-cert",
+            "tests/tls/local.dev_cert.pem",
+            "--http2-tls-key",
+            "tests/tls/local.dev_key.pem",
+        ]);
+
+        // HTTP/1.1-only clients fall back to HTTP/1.1
+        let mut stream = tls_client(&addr, &[b"http/1.1"]);
+        assert_eq!(stream.conn.alpn_protocol(), Some(&b"http/1.1"[..]));
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut resp = Vec::new();
+        // The server may close the connection without a TLS close notify
+        let _ = stream.read_to_end(&mut resp);
+        assert!(String::from_utf8_lossy(&resp).starts_with("HTTP/1.1 200 OK"));
+
+        // Clients without ALPN are served via HTTP/1.1 too
+        let mut stream = tls_client(&addr, &[]);
+        assert_eq!(stream.conn.alpn_protocol(), None);
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut resp = Vec::new();
+        let _ = stream.read_to_end(&mut resp);
+        assert!(String::from_utf8_lossy(&resp).starts_with("HTTP/1.1 200 OK"));
+
+        // HTTP/2 is preferred if offered
+        let stream = tls_client(&addr, &[b"h2", b"http/1.1"]);
+        assert_eq!(stream.conn.alpn_protocol(), Some(&b"h2"[..]));
+    }
+
+    #[cfg(feature = "http2")]
+    #[test]
+    fn http2_keep_alive_pings() {
+        let (_server, addr) = start_server(&[
+            "--http2=true",
+            "--http2-tls-cert",
+            "tests/tls/local.dev_cert.pem",
+            "--http2-tls-key",
+            "tests/tls/local.dev_key.pem",
+            "--http2-keep-alive-interval",
+            "1",
+            "--http2-keep-alive-timeout",
+            "1",
+        ]);
+
+        // Connection preface followed by an empty SETTINGS frame
+        let mut stream = tls_client(&addr, &[b"h2"]);
+        stream
+            .write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\0\0\0\x04\0\0\0\0\0")
+            .unwrap();
+
+        // Idle connections get pinged and closed once the pings are not acknowledged
+        let started = Instant::now();
+        let mut pinged = false;
+        let mut header = [0u8; 9];
+        while stream.read_exact(&mut header).is_ok() {
+            let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+            let mut payload = vec![0u8; len];
+            if stream.read_exact(&mut payload).is_err() {
+                break;
+            }
+            // PING frame without the ACK flag
+            if header[3] == 0x6 && header[4] & 0x1 == 0 {
+                pinged = true;
+            }
+        }
+        assert!(pinged);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(feature = "http2")]
+    #[test]
+    fn https_redirect_acme_challenge() {
+        let challenge_dir = std::env::temp_dir().join("sws-acme-challenge-test");
+        std::fs::create_dir_all(&challenge_dir).unwrap();
+        std::fs::write(challenge_dir.join("token123"), "token123.thumbprint").unwrap();
+
+        let redirect_port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+            .to_string();
+        let (_server, _) = start_server(&[
+            "--http2=true",
+            "--http2-tls-cert",
+            "tests/tls/local.dev_cert.pem",
+            "--http2-tls-key",
+            "tests/tls/local.dev_key.pem",
+            "--https-redirect",
+            "--https-redirect-from-port",
+            &redirect_port,
+            "--https-redirect-acme-challenge-path",
+            "/.well-known/acme-challenge/",
+            "--https-redirect-acme-challenge-dir",
+            challenge_dir.to_str().unwrap(),
+        ]);
+
+        let addr = format!("127.0.0.1:{redirect_port}");
+        let request = |path: &str, host: &str| {
+            let started = Instant::now();
+            let mut stream = loop {
+                match TcpStream::connect(&addr) {
+                    Ok(stream) => break stream,
+                    Err(_) if started.elapsed() < Duration::from_secs(10) => {
+                        sleep(Duration::from_millis(50))
+                    }
+                    Err(err) => panic!("redirect server start up: {err}"),
+                }
+            };
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        // Challenge files are served over plain HTTP for any host
+        let resp = request("/.well-known/acme-challenge/token123", "example.com");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"));
+        assert!(resp.ends_with("\r\n\r\ntoken123.thumbprint"));
+        let resp = request("/.well-known/acme-challenge/unknown", "localhost");
+        assert!(resp.starts_with("HTTP/1.1 404 Not Found"));
+
+        // Other paths are still redirected
+        let resp = request("/index.html", "localhost");
+        assert!(resp.starts_with("HTTP/1.1 301 Moved Permanently"));
+        assert!(resp.contains("\r\nlocation: https://localhost:"));
+
+        std::fs::remove_dir_all(challenge_dir).unwrap();
+    }
+
+    #[cfg(feature = "http2")]
+    #[test]
+    fn http2_plain_http() {
+        let request_host = |addr: &str, host: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            // The head and a body are sent at once
+            stream
+                .write_all(
+                    format!(
+                        "POST /assets/main.js?v=1 HTTP/1.1\r\nHost: {host}\r\nContent-Length: 2\r\n\r\n{{}}"
+                    )
+                    .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = Vec::new();
+            let _ = stream.read_to_end(&mut resp);
+            String::from_utf8_lossy(&resp).into_owned()
+        };
+        let tls_args = |mode: &'static str| {
+            [
+                "--http2=true",
+                "--http2-tls-cert",
+                "tests/tls/local.dev_cert.pem",
+                "--http2-tls-key",
+                "tests/tls/local.dev_key.pem",
+                "--http2-plain-http",
+                mode,
+            ]
+        };
+
+        let request = |addr: &str| request_host(addr, "localhost:8443");
+
+        let (_server, addr) = start_server(&tls_args("bad-request"));
+        let resp = request(&addr);
+        assert!(resp.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+        assert!(resp.ends_with("\r\n\r\nClient sent an HTTP request to an HTTPS server.\n"));
+
+        let (_server, addr) = start_server(&tls_args("redirect"));
+        let port = addr.rsplit(':').next().unwrap();
+        let resp = request(&addr);
+        assert!(resp.starts_with("HTTP/1.1 301 Moved Permanently\r\n"));
+        assert!(resp.contains(&format!(
+            "\r\nlocation: https://localhost:{port}/assets/main.js?v=1\r\n"
+        )));
+        // Hosts not allowed to redirect from are rejected
+        let resp = request_host(&addr, "evil.com");
+        assert!(resp.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+
+        let (_server, addr) = start_server(&tls_args("close"));
+        assert_eq!(request(&addr), "");
+    }
+
+    #[cfg(all(feature = "directory-listing", feature = "compression"))]
+    #[test]
+    fn dir_listing_json_compressed() {
+        let (_server, addr) = start_server(&[
+            "--root",
+            "tests/fixtures/public",
+            "--directory-listing",
+            "--directory-listing-format",
+            "json",
+            "--compression-min-size",
+            "0",
+        ]);
+
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nAccept-Encoding: gzip\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut resp = Vec::new();
+        stream.read_to_end(&mut resp).unwrap();
+
+        let pos = resp.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let head = String::from_utf8_lossy(&resp[..pos]).to_lowercase();
+        assert!(head.starts_with("http/1.1 200 ok"));
+        assert!(head.contains("\r\ncontent-type: application/json; charset=utf-8"));
+        assert!(head.contains("\r\ncontent-encoding: gzip"));
+        assert!(head.contains("\r\nvary: accept-encoding"));
+
+        // Small listings are sent with a content length
+        let mut body = String::new();
+        flate2::read::GzDecoder::new(&resp[pos + 4..])
+            .read_to_string(&mut body)
+            .unwrap();
+        let entries: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert!(entries.as_array().is_some_and(|e| !e.is_empty()));
+    }
+
+    #[cfg(feature = "compression-gzip")]
+    #[test]
+    fn compression_rules() {
+        let config_dir = std::env::temp_dir().join("sws-compression-rules-test");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let config_file = config_dir.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[[advanced.compression]]
+source = "/index.html"
+enabled = false
+
+[[advanced.compression]]
+source = "**/*.ico"
+enabled = true
+"#,
+        )
+        .unwrap();
+
+        let request = |addr: &str, path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nAccept-Encoding: gzip\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = Vec::new();
+            stream.read_to_end(&mut resp).unwrap();
+            let pos = resp.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+            String::from_utf8_lossy(&resp[..pos]).to_lowercase()
+        };
+
+        let (server, addr) = start_server(&[
+            "--config-file",
+            config_file.to_str().unwrap(),
+            "--compression-min-size",
+            "0",
+        ]);
+        // Forced off even if the client accepts gzip
+        let head = request(&addr, "/index.html");
+        assert!(head.starts_with("http/1.1 200 ok"));
+        assert!(!head.contains("\r\ncontent-encoding:"), "{head}");
+        let head = request(&addr, "/assets/index.html");
+        assert!(head.contains("\r\ncontent-encoding: gzip"), "{head}");
+        drop(server);
+
+        // Forced on overriding the global option and the MIME types allowlist
+        let (_server, addr) = start_server(&[
+            "--config-file",
+            config_file.to_str().unwrap(),
+            "--compression=false",
+        ]);
+        let head = request(&addr, "/assets/favicon.ico");
+        assert!(head.contains("\r\ncontent-encoding: gzip"), "{head}");
+        assert!(head.contains("\r\nvary: accept-encoding"), "{head}");
+        let head = request(&addr, "/assets/main.css");
+        assert!(!head.contains("\r\ncontent-encoding:"), "{head}");
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[cfg(feature = "compression-gzip")]
+    #[test]
+    fn compression_already_encoded() {
+        let root = std::env::temp_dir().join("sws-compression-already-encoded-test");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\"><circle r=\"10\"/></svg>".repeat(64);
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(svg.as_bytes()).unwrap();
+        std::fs::write(root.join("logo.svgz"), encoder.finish().unwrap()).unwrap();
+        let config_file = root.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[[advanced.headers]]
+source = "**/*.svgz"
+headers = { Content-Encoding = "gzip" }
+"#,
+        )
+        .unwrap();
+
+        let (_server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--config-file",
+            config_file.to_str().unwrap(),
+            "--compression-min-size",
+            "0",
+        ]);
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .write_all(b"GET /logo.svgz HTTP/1.1\r\nHost: localhost\r\nAccept-Encoding: gzip, br\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut resp = Vec::new();
+        stream.read_to_end(&mut resp).unwrap();
+
+        // The response is passed through with its original encoding only
+        let pos = resp.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let head = String::from_utf8_lossy(&resp[..pos]).to_lowercase();
+        assert!(head.starts_with("http/1.1 200 ok"), "{head}");
+        assert!(head.contains("\r\ncontent-type: image/svg+xml"), "{head}");
+        assert!(head.contains("\r\ncontent-encoding: gzip\r\n"), "{head}");
+        let mut body = String::new();
+        flate2::read::GzDecoder::new(&resp[pos + 4..])
+            .read_to_string(&mut body)
+            .unwrap();
+        assert_eq!(body, svg);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compression_identity_refused() {
+        let (_server, addr) = start_server(&[]);
+        let request = |accept_encoding: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET /index.html HTTP/1.1\r\nHost: localhost\r\nAccept-Encoding: {accept_encoding}\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = Vec::new();
+            stream.read_to_end(&mut resp).unwrap();
+            let pos = resp.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+            String::from_utf8_lossy(&resp[..pos]).to_lowercase()
+        };
+
+        let head = request("identity;q=0");
+        assert!(head.starts_with("http/1.1 406 not acceptable"), "{head}");
+        let head = request("*;q=0");
+        assert!(head.starts_with("http/1.1 406 not acceptable"), "{head}");
+
+        // Small files are compressed rather than refused
+        let head = request("gzip, identity;q=0");
+        assert!(head.starts_with("http/1.1 200 ok"), "{head}");
+        assert!(head.contains("\r\ncontent-encoding: gzip"), "{head}");
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compression_identity_preferred() {
+        let request = |addr: &str, accept_encoding: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET /index.html HTTP/1.1\r\nHost: localhost\r\nAccept-Encoding: {accept_encoding}\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = Vec::new();
+            stream.read_to_end(&mut resp).unwrap();
+            let pos = resp.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+            String::from_utf8_lossy(&resp[..pos]).to_lowercase()
+        };
+
+        // Auto compression and pre-compressed files
+        let (_server, addr) = start_server(&["--compression-min-size", "0"]);
+        let root = std::env::temp_dir().join("sws-compression-identity-test");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("index.html"), "<h1>index</h1>").unwrap();
+        std::fs::copy(
+            "tests/fixtures/public/index.html.gz",
+            root.join("index.html.gz"),
+        )
+        .unwrap();
+        let (_server_static, addr_static) =
+            start_server(&["--root", root.to_str().unwrap(), "--compression-static"]);
+        for addr in [addr, addr_static] {
+            // Explicit identity preferences never get a compressed response
+            for accept_encoding in ["identity", "identity, gzip", "gzip;q=0.5, identity"] {
+                let head = request(&addr, accept_encoding);
+                assert!(head.starts_with("http/1.1 200 ok"), "{head}");
+                assert!(
+                    !head.contains("\r\ncontent-encoding:"),
+                    "{accept_encoding}: {head}"
+                );
+            }
+
+            let head = request(&addr, "gzip, identity;q=0.5");
+            assert!(head.contains("\r\ncontent-encoding: gzip"), "{head}");
+        }
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(all(feature = "compression", feature = "fallback-page"))]
+    #[test]
+    fn compression_identity_refused_fallback_page() {
+        let (_server, addr) = start_server(&["--page-fallback", "docker/public/index.html"]);
+        let request = |accept_encoding: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET /app/route HTTP/1.1\r\nHost: localhost\r\nAccept: text/html\r\nAccept-Encoding: {accept_encoding}\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = Vec::new();
+            stream.read_to_end(&mut resp).unwrap();
+            let pos = resp.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+            String::from_utf8_lossy(&resp[..pos]).to_lowercase()
+        };
+
+        // The fallback page is checked like any other response
+        let head = request("*;q=0");
+        assert!(head.starts_with("http/1.1 406 not acceptable"), "{head}");
+
+        let head = request("gzip, identity;q=0");
+        assert!(head.starts_with("http/1.1 200 ok"), "{head}");
+        assert!(head.contains("\r\ncontent-encoding: gzip"), "{head}");
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compression_http10() {
+        let request = |addr: &str, version: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET /index.html {version}\r\nHost: localhost\r\nAccept-Encoding: gzip\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = Vec::new();
+            stream.read_to_end(&mut resp).unwrap();
+            let pos = resp.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+            String::from_utf8_lossy(&resp[..pos]).to_lowercase()
+        };
+
+        // Compressed regardless of the version by default
+        let (server, addr) = start_server(&["--compression-min-size", "0"]);
+        let head = request(&addr, "HTTP/1.0");
+        assert!(head.contains("\r\ncontent-encoding: gzip"), "{head}");
+        drop(server);
+
+        let (_server, addr) =
+            start_server(&["--compression-min-size", "0", "--compression-http10=false"]);
+        let head = request(&addr, "HTTP/1.0");
+        assert!(head.starts_with("http/1.0 200 ok"), "{head}");
+        assert!(!head.contains("\r\ncontent-encoding:"), "{head}");
+        assert!(head.contains("\r\ncontent-length:"), "{head}");
+        let head = request(&addr, "HTTP/1.1");
+        assert!(head.contains("\r\ncontent-encoding: gzip"), "{head}");
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compression_user_agent_denylist() {
+        let request = |addr: &str, user_agent: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET /index.html HTTP/1.1\r\nHost: localhost\r\nUser-Agent: {user_agent}\r\nAccept-Encoding: gzip\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = Vec::new();
+            stream.read_to_end(&mut resp).unwrap();
+            let pos = resp.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+            String::from_utf8_lossy(&resp[..pos]).to_lowercase()
+        };
+
+        let (_server, addr) = start_server(&[
+            "--compression-min-size",
+            "0",
+            "--compression-user-agent-denylist",
+            "*msie [1-6].*,legacy-bot/*",
+        ]);
+
+        let head = request(&addr, "Mozilla/4.0 (compatible; MSIE 6.0; Windows NT 5.1)");
+        assert!(head.starts_with("http/1.1 200 ok"), "{head}");
+        assert!(!head.contains("\r\ncontent-encoding:"), "{head}");
+        assert!(head.contains("\r\ncontent-length:"), "{head}");
+        let head = request(&addr, "Legacy-Bot/1.2");
+        assert!(!head.contains("\r\ncontent-encoding:"), "{head}");
+
+        let head = request(&addr, "Mozilla/4.0 (compatible; MSIE 8.0; Windows NT 6.1)");
+        assert!(head.contains("\r\ncontent-encoding: gzip"), "{head}");
+        let head = request(&addr, "curl/8.0.1");
+        assert!(head.contains("\r\ncontent-encoding: gzip"), "{head}");
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compression_no_transform() {
+        let config_dir = std::env::temp_dir().join("sws-compression-no-transform-test");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let config_file = config_dir.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[[advanced.headers]]
+source = "/index.html"
+headers = { Cache-Control = "public, No-Transform" }
+
+[[advanced.compression]]
+source = "/index.html"
+enabled = true
+"#,
+        )
+        .unwrap();
+
+        let request = |addr: &str, path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nAccept-Encoding: gzip\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = Vec::new();
+            stream.read_to_end(&mut resp).unwrap();
+            let pos = resp.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+            String::from_utf8_lossy(&resp[..pos]).to_lowercase()
+        };
+
+        let (_server, addr) = start_server(&[
+            "--config-file",
+            config_file.to_str().unwrap(),
+            "--compression-min-size",
+            "0",
+        ]);
+        let head = request(&addr, "/index.html");
+        assert!(head.starts_with("http/1.1 200 ok"));
+        assert!(
+            head.contains("\r\ncache-control: public, no-transform"),
+            "{head}"
+        );
+        assert!(!head.contains("\r\ncontent-encoding:"), "{head}");
+        assert!(head.contains("\r\ncontent-length:"), "{head}");
+        let head = request(&addr, "/assets/index.html");
+        assert!(head.contains("\r\ncontent-encoding: gzip"), "{head}");
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[cfg(feature = "directory-listing")]
+    #[test]
+    fn directory_listing_rules() {
+        let root = std::env::temp_dir().join("sws-directory-listing-rules-test");
+        std::fs::create_dir_all(root.join("downloads/private")).unwrap();
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("index.html"), "home").unwrap();
+        std::fs::write(root.join("downloads/file.zip"), "zip").unwrap();
+        let config_file = root.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[[advanced.directory-listing]]
+source = "/downloads/**"
+exclude = "/downloads/private/**"
+enabled = true
+
+[[advanced.directory-listing]]
+source = "/docs/**"
+enabled = false
+"#,
+        )
+        .unwrap();
+
+        let request = |addr: &str, path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        let (server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--config-file",
+            config_file.to_str().unwrap(),
+        ]);
+        // The root directory resolves to its index file
+        let resp = request(&addr, "/");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"));
+        assert!(resp.ends_with("home"));
+        // Listings are enabled for the matching directories only
+        let resp = request(&addr, "/downloads/");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"));
+        assert!(resp.contains("Index of /downloads/"));
+        assert!(resp.contains("file.zip"));
+        assert!(request(&addr, "/downloads/private/").starts_with("HTTP/1.1 404 Not Found"));
+        assert!(request(&addr, "/docs/").starts_with("HTTP/1.1 404 Not Found"));
+        drop(server);
+
+        // Rules override the global option which applies to the other directories
+        let (_server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--config-file",
+            config_file.to_str().unwrap(),
+            "--directory-listing",
+        ]);
+        assert!(request(&addr, "/").ends_with("home"));
+        assert!(request(&addr, "/downloads/private/").contains("Index of /downloads/private/"));
+        assert!(request(&addr, "/docs/").starts_with("HTTP/1.1 404 Not Found"));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn response_headers() {
+        let config_dir = std::env::temp_dir().join("sws-response-headers-test");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let config_file = config_dir.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[general]
+response-headers = { X-Frame-Options = "DENY", X-Custom = "global" }
+
+[[advanced.headers]]
+source = "**/*.css"
+headers = { X-Custom = "css" }
+"#,
+        )
+        .unwrap();
+
+        let (_server, addr) = start_server(&["--config-file", config_file.to_str().unwrap()]);
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        // Global headers are appended to all responses
+        let resp = request("/index.html");
+        assert!(resp.contains("\r\nx-frame-options: DENY\r\n"));
+        assert!(resp.contains("\r\nx-custom: global\r\n"));
+
+        // Matching advanced headers override them
+        let resp = request("/assets/main.css");
+        assert!(resp.contains("\r\nx-frame-options: DENY\r\n"));
+        assert!(resp.contains("\r\nx-custom: css\r\n"));
+        assert!(!resp.contains("x-custom: global"));
+
+        // Invalid header values are rejected at startup
+        std::fs::write(
+            &config_file,
+            "[general]\nresponse-headers = { X-Custom = \"a\\nb\" }\n",
+        )
+        .unwrap();
+        let output = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args(["--config-file", config_file.to_str().unwrap()])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("general.response-headers"));
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[test]
+    fn rewrites_redirects_priority() {
+        let root = std::env::temp_dir().join("sws-rewrites-redirects-priority-test");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("a.html"), "a").unwrap();
+        std::fs::write(root.join("b.html"), "b").unwrap();
+        std::fs::write(root.join("c.html"), "c").unwrap();
+        let config_file = root.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[[advanced.redirects]]
+source = "/docs/**"
+destination = "/manual/"
+kind = 302
+
+[[advanced.redirects]]
+source = "/docs/legacy/**"
+destination = "/archive/"
+kind = 301
+priority = 10
+
+[[advanced.rewrites]]
+source = "/pages/*.html"
+destination = "/a.html"
+
+[[advanced.rewrites]]
+source = "/pages/c.html"
+destination = "/c.html"
+
+[[advanced.rewrites]]
+source = "/pages/b.html"
+destination = "/b.html"
+priority = 1
+
+[[advanced.rewrites]]
+source = "/docs/index.html"
+destination = "/a.html"
+"#,
+        )
+        .unwrap();
+
+        let (_server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--config-file",
+            config_file.to_str().unwrap(),
+        ]);
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        // Higher priority redirects are evaluated first
+        let resp = request("/docs/legacy/page.html");
+        assert!(resp.starts_with("HTTP/1.1 301 Moved Permanently"));
+        assert!(resp.contains("\r\nlocation: /archive/\r\n"));
+        let resp = request("/docs/page.html");
+        assert!(resp.starts_with("HTTP/1.1 302 Found"));
+        assert!(resp.contains("\r\nlocation: /manual/\r\n"));
+
+        // Redirects are evaluated before rewrites
+        let resp = request("/docs/index.html");
+        assert!(resp.starts_with("HTTP/1.1 302 Found"));
+
+        // The first matching rewrite wins, unless another one has a higher priority
+        assert!(request("/pages/a.html").ends_with("\r\n\r\na"));
+        assert!(request("/pages/b.html").ends_with("\r\n\r\nb"));
+        assert!(request("/pages/c.html").ends_with("\r\n\r\na"));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn directory_resolution_chain() {
+        let root = std::env::temp_dir().join("sws-directory-resolution-test");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("both")).unwrap();
+        std::fs::create_dir_all(root.join("htm")).unwrap();
+        std::fs::create_dir_all(root.join("empty")).unwrap();
+        std::fs::write(root.join("both/index.html"), "index.html").unwrap();
+        std::fs::write(root.join("both/index.htm"), "index.htm").unwrap();
+        std::fs::write(root.join("htm/index.htm"), "index.htm").unwrap();
+        std::fs::write(root.join("fallback.html"), "fallback").unwrap();
+        let root_str = root.to_str().unwrap();
+
+        let request = |addr: &str, path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nAccept: text/html\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+        let status_body = |resp: &str| {
+            let (head, body) = resp.split_once("\r\n\r\n").unwrap();
+            (head.lines().next().unwrap().to_owned(), body.to_owned())
+        };
+
+        // Index files are tried in order
+        let (server, addr) =
+            start_server(&["--root", root_str, "--index-files", "index.html,index.htm"]);
+        assert_eq!(status_body(&request(&addr, "/both/")).1, "index.html");
+        assert_eq!(status_body(&request(&addr, "/htm/")).1, "index.htm");
+        drop(server);
+        let (server, addr) =
+            start_server(&["--root", root_str, "--index-files", "index.htm,index.html"]);
+        assert_eq!(status_body(&request(&addr, "/both/")).1, "index.htm");
+        drop(server);
+
+        // No index file and nothing else configured
+        let (server, addr) = start_server(&["--root", root_str]);
+        assert_eq!(
+            status_body(&request(&addr, "/htm/")).0,
+            "HTTP/1.1 404 Not Found"
+        );
+        assert_eq!(
+            status_body(&request(&addr, "/empty/")).0,
+            "HTTP/1.1 404 Not Found"
+        );
+        drop(server);
+
+        // Directory listing
+        #[cfg(feature = "directory-listing")]
+        {
+            let (_server, addr) = start_server(&["--root", root_str, "--directory-listing"]);
+            let (status, body) = status_body(&request(&addr, "/empty/"));
+            assert_eq!(status, "HTTP/1.1 200 OK");
+            assert!(body.contains("Index of /empty/"));
+            assert_eq!(status_body(&request(&addr, "/both/")).1, "index.html");
+        }
+
+        // Fallback page
+        #[cfg(feature = "fallback-page")]
+        {
+            let (_server, addr) = start_server(&[
+                "--root",
+                root_str,
+                "--page-fallback",
+                root.join("fallback.html").to_str().unwrap(),
+            ]);
+            assert_eq!(status_body(&request(&addr, "/empty/")).1, "fallback");
+            assert_eq!(status_body(&request(&addr, "/both/")).1, "index.html");
+        }
+
+        // Directory listing takes precedence over the fallback page
+        #[cfg(all(feature = "directory-listing", feature = "fallback-page"))]
+        {
+            let (_server, addr) = start_server(&[
+                "--root",
+                root_str,
+                "--directory-listing",
+                "--page-fallback",
+                root.join("fallback.html").to_str().unwrap(),
+            ]);
+            assert!(status_body(&request(&addr, "/empty/"))
+                .1
+                .contains("Index of /empty/"));
+            assert_eq!(status_body(&request(&addr, "/missing/")).1, "fallback");
+        }
+
+        // Index files can be disabled
+        #[cfg(feature = "directory-listing")]
+        {
+            let (_server, addr) = start_server(&[
+                "--root",
+                root_str,
+                "--index-files",
+                "",
+                "--directory-listing",
+            ]);
+            assert!(status_body(&request(&addr, "/both/"))
+                .1
+                .contains("Index of /both/"));
+        }
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn socket_activation_requires_systemd_env() {
+        let output = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args(["--socket-activation", "--root", "docker/public"])
+            .env_remove("LISTEN_PID")
+            .env_remove("LISTEN_FDS")
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("socket activation requires the `LISTEN_PID` environment variable"));
+
+        // Variables meant for another process are rejected
+        let output = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args(["--socket-activation", "--root", "docker/public"])
+            .env("LISTEN_PID", "1")
+            .env("LISTEN_FDS", "1")
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("does not match the current process id"));
+    }
+
+    #[cfg(feature = "acme")]
+    #[test]
+    fn acme_requires_https_redirect() {
+        let output = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args([
+                "--root",
+                "docker/public",
+                "--http2=true",
+                "--acme=true",
+                "--acme-domains",
+                "example.com",
+            ])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("acme requires the http2 and https-redirect options to be enabled"));
+    }
+
+    #[cfg(feature = "acme")]
+    #[test]
+    fn acme_requires_tos_agreed() {
+        let output = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args([
+                "--root",
+                "docker/public",
+                "--http2=true",
+                "--https-redirect=true",
+                "--acme=true",
+                "--acme-domains",
+                "example.com",
+            ])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("acme requires agreeing to the terms of service"),
+            "{stderr}"
+        );
+    }
+
+    #[test]
+    fn print_config() {
+        let config_dir = std::env::temp_dir().join("sws-print-config-test");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let config_file = config_dir.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[general]
+port = 8080
+log-level = "warn"
+
+[[advanced.rewrites]]
+source = "/old"
+destination = "/new"
+"#,
+        )
+        .unwrap();
+
+        let print_config = |format: &str| {
+            let output = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+                .args([
+                    "--root",
+                    "docker/public",
+                    "--port",
+                    "9090",
+                    "--config-file",
+                    config_file.to_str().unwrap(),
+                    format,
+                ])
+                .env("SERVER_GRACE_PERIOD", "7")
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            String::from_utf8(output.stdout).unwrap()
+        };
+
+        // Config file options take precedence over the CLI and env ones
+        let stdout = print_config("--print-config");
+        assert!(stdout.contains("\nport = 8080\n"), "{stdout}");
+        assert!(stdout.contains("\nlog-level = \"warn\"\n"), "{stdout}");
+        assert!(stdout.contains("\ngrace-period = 7\n"), "{stdout}");
+        assert!(stdout.contains("\n[advanced]\nrewrites = 1\n"), "{stdout}");
+
+        let stdout = print_config("--print-config=json");
+        let config: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(config["general"]["port"], 8080);
+        assert_eq!(config["general"]["root"], "docker/public");
+        assert_eq!(config["advanced"]["rewrites"], 1);
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[cfg(feature = "basic-auth")]
+    #[test]
+    fn print_config_redacted() {
+        let output = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args([
+                "--root",
+                "docker/public",
+                "--basic-auth",
+                "user:$2y$05$hash",
+                "--print-config",
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            stdout.contains("\nbasic-auth = \"<redacted>\"\n"),
+            "{stdout}"
+        );
+        assert!(!stdout.contains("user:"), "{stdout}");
+    }
+
+    #[test]
+    fn trailing_slash_rules() {
+        let root = std::env::temp_dir().join("sws-trailing-slash-rules-test");
+        for dir in [
+            "blog/post",
+            "blog/archive",
+            "blog/legacy",
+            "app",
+            "docs",
+            "other",
+        ] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            std::fs::write(root.join(dir).join("index.html"), dir).unwrap();
+        }
+        std::fs::write(root.join("blog/feed.xml"), "feed").unwrap();
+        let config_file = root.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[[advanced.trailing-slash]]
+source = "/blog/**"
+policy = "add"
+
+[[advanced.trailing-slash]]
+source = "/app"
+policy = "remove"
+
+[[advanced.trailing-slash]]
+source = "/docs"
+policy = "ignore"
+
+[[advanced.trailing-slash]]
+source = "/blog/archive"
+policy = "remove"
+
+[[advanced.trailing-slash]]
+source = "/blog/legacy"
+policy = "remove"
+priority = 1
+"#,
+        )
+        .unwrap();
+
+        let (_server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--config-file",
+            config_file.to_str().unwrap(),
+        ]);
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp.to_lowercase()
+        };
+
+        // `add` redirects to the trailing slash and keeps the query string
+        let resp = request("/blog/post?page=2");
+        assert!(resp.starts_with("http/1.1 301 "), "{resp}");
+        assert!(
+            resp.contains("\r\nlocation: /blog/post/?page=2\r\n"),
+            "{resp}"
+        );
+        let resp = request("/blog/post/");
+        assert!(resp.starts_with("http/1.1 200 "), "{resp}");
+        assert!(resp.ends_with("\r\n\r\nblog/post"), "{resp}");
+
+        // `add` only applies to directories, files are served as is
+        let resp = request("/blog/feed.xml");
+        assert!(resp.starts_with("http/1.1 200 "), "{resp}");
+        assert!(resp.ends_with("\r\n\r\nfeed"), "{resp}");
+        let resp = request("/blog/feed.xml/");
+        assert!(resp.starts_with("http/1.1 404 "), "{resp}");
+
+        // The first matching rule wins unless another one has a higher priority
+        let resp = request("/blog/archive");
+        assert!(resp.starts_with("http/1.1 301 "), "{resp}");
+        assert!(resp.contains("\r\nlocation: /blog/archive/\r\n"), "{resp}");
+        let resp = request("/blog/legacy/");
+        assert!(resp.starts_with("http/1.1 301 "), "{resp}");
+        assert!(resp.contains("\r\nlocation: /blog/legacy\r\n"), "{resp}");
+
+        // `remove` redirects to the path without the trailing slash which is served as is
+        let resp = request("/app/");
+        assert!(resp.starts_with("http/1.1 301 "), "{resp}");
+        assert!(resp.contains("\r\nlocation: /app\r\n"), "{resp}");
+        let resp = request("/app");
+        assert!(resp.starts_with("http/1.1 200 "), "{resp}");
+        assert!(resp.ends_with("\r\n\r\napp"), "{resp}");
+
+        // `ignore` serves both paths without redirecting
+        for path in ["/docs", "/docs/"] {
+            let resp = request(path);
+            assert!(resp.starts_with("http/1.1 200 "), "{resp}");
+            assert!(resp.ends_with("\r\n\r\ndocs"), "{resp}");
+        }
+
+        // Other paths follow the global `redirect-trailing-slash` option
+        let resp = request("/other");
+        assert!(resp.starts_with("http/1.1 308 "), "{resp}");
+        assert!(resp.contains("\r\nlocation: /other/\r\n"), "{resp}");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(feature = "fallback-page")]
+    #[test]
+    fn page_fallback_nested() {
+        let root = std::env::temp_dir().join("sws-page-fallback-nested-test");
+        std::fs::create_dir_all(root.join("t/42/assets")).unwrap();
+        std::fs::create_dir_all(root.join("other")).unwrap();
+        std::fs::write(root.join("t/42/index.html"), "tenant 42").unwrap();
+        std::fs::write(root.join("index.html"), "root").unwrap();
+
+        let (_server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--page-fallback-nested",
+            "/t/**",
+        ]);
+        let request = |path: &str, accept: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nAccept: {accept}\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        // Deep links fall back to the nearest index file
+        for path in [
+            "/t/42/settings",
+            "/t/42/settings/profile/",
+            "/t/42/assets/page",
+        ] {
+            let resp = request(path, "text/html");
+            assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+            assert!(resp.ends_with("\r\n\r\ntenant 42"), "{resp}");
+        }
+
+        // Walking up the path stops at the root directory
+        let resp = request("/t/7/settings", "text/html");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(resp.ends_with("\r\n\r\nroot"), "{resp}");
+
+        // Non-navigation requests and other paths are not found
+        let resp = request("/t/42/app.js", "*/*");
+        assert!(resp.starts_with("HTTP/1.1 404 Not Found"), "{resp}");
+        let resp = request("/other/settings", "text/html");
+        assert!(resp.starts_with("HTTP/1.1 404 Not Found"), "{resp}");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn overlay_root() {
+        let dir = std::env::temp_dir().join("sws-overlay-root-test");
+        let (base, overlay) = (dir.join("base"), dir.join("overlay"));
+        for path in ["docs", "list"] {
+            std::fs::create_dir_all(base.join(path)).unwrap();
+            std::fs::create_dir_all(overlay.join(path)).unwrap();
+        }
+        std::fs::write(base.join("index.html"), "base index").unwrap();
+        std::fs::write(base.join("style.css"), "base css").unwrap();
+        std::fs::write(base.join("docs/index.html"), "base docs").unwrap();
+        std::fs::write(base.join("list/a.txt"), "a").unwrap();
+        std::fs::write(overlay.join("style.css"), "overlay css").unwrap();
+        std::fs::write(overlay.join("new.txt"), "overlay new").unwrap();
+        std::fs::write(overlay.join("docs/extra.txt"), "extra").unwrap();
+        std::fs::write(overlay.join("list/b.txt"), "b").unwrap();
+
+        let request = |addr: &str, path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        let args = [
+            "--root",
+            base.to_str().unwrap(),
+            "--overlay-root",
+            overlay.to_str().unwrap(),
+            "--directory-listing",
+        ];
+        let (server, addr) = start_server(&args);
+
+        // Overlay files take precedence over the root ones
+        for (path, body) in [
+            ("/style.css", "overlay css"),
+            ("/new.txt", "overlay new"),
+            ("/", "base index"),
+            ("/docs/extra.txt", "extra"),
+            // Index files of any root take precedence over the directory listing
+            ("/docs/", "base docs"),
+        ] {
+            let resp = request(&addr, path);
+            assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+            assert!(resp.ends_with(&format!("\r\n\r\n{body}")), "{resp}");
+        }
+        let resp = request(&addr, "/missing.txt");
+        assert!(resp.starts_with("HTTP/1.1 404 Not Found"), "{resp}");
+
+        // Only the overlay directory is listed by default
+        let resp = request(&addr, "/list/");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(resp.contains(">b.txt<"), "{resp}");
+        assert!(!resp.contains(">a.txt<"), "{resp}");
+        drop(server);
+
+        let (_server, addr) =
+            start_server(&[&args[..], &["--directory-listing-merge-overlay"]].concat());
+        let resp = request(&addr, "/list/");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(resp.contains(">b.txt<"), "{resp}");
+        assert!(resp.contains(">a.txt<"), "{resp}");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn not_found_cache_rate_limit() {
+        let root = std::env::temp_dir().join("sws-not-found-cache-test");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("index.html"), "index").unwrap();
+
+        let (_server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--not-found-cache-ttl",
+            "60",
+            "--not-found-rate-limit",
+            "3",
+        ]);
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        // Cached missing paths are evicted once their file appears
+        assert!(request("/new.txt").starts_with("HTTP/1.1 404 Not Found"));
+        std::fs::write(root.join("new.txt"), "new").unwrap();
+        let resp = request("/new.txt");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+
+        // Clients exceeding the not found responses rate limit get throttled
+        assert!(request("/missing-1").starts_with("HTTP/1.1 404 Not Found"));
+        assert!(request("/missing-2").starts_with("HTTP/1.1 404 Not Found"));
+        let resp = request("/");
+        assert!(resp.starts_with("HTTP/1.1 429 Too Many Requests"), "{resp}");
+        assert!(
+            resp.to_lowercase().contains("\r\nretry-after: 1\r\n"),
+            "{resp}"
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn root_page() {
+        let root = std::env::temp_dir().join("sws-root-page-test");
+        std::fs::create_dir_all(root.join("other")).unwrap();
+        std::fs::write(root.join("index.html"), "index").unwrap();
+        std::fs::write(root.join("home.html"), "home").unwrap();
+        std::fs::write(root.join("other/index.html"), "other").unwrap();
+
+        let (_server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--root-page",
+            "home.html",
+            "--directory-listing",
+        ]);
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        // The root path serves the root page bypassing the index files and the directory listing
+        let resp = request("/");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(resp.contains("\r\ncontent-type: text/html"), "{resp}");
+        assert!(resp.ends_with("\r\n\r\nhome"), "{resp}");
+
+        // Deeper paths are not affected
+        for (path, body) in [("/other/", "other"), ("/index.html", "index")] {
+            let resp = request(path);
+            assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+            assert!(resp.ends_with(&format!("\r\n\r\n{body}")), "{resp}");
+        }
+        let resp = request("/other");
+        assert!(
+            resp.starts_with("HTTP/1.1 308 Permanent Redirect"),
+            "{resp}"
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn root_dir_missing() {
+        let dir = std::env::temp_dir().join("sws-root-missing-test");
+        let root = dir.join("public");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("index.html"), "v1").unwrap();
+
+        let (_server, addr) = start_server(&["--root", root.to_str().unwrap()]);
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        let resp = request("/");
+        assert!(resp.ends_with("\r\n\r\nv1"), "{resp}");
+
+        // The root directory removed at runtime is temporarily unavailable
+        std::fs::remove_dir_all(&root).unwrap();
+        for path in ["/", "/index.html", "/missing.txt"] {
+            let resp = request(path);
+            assert!(
+                resp.starts_with("HTTP/1.1 503 Service Unavailable"),
+                "{resp}"
+            );
+            assert!(resp.contains("\r\nretry-after: 1\r\n"), "{resp}");
+        }
+
+        // It's served again once recreated, without restarting
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("index.html"), "v2").unwrap();
+        let resp = request("/");
+        assert!(resp.ends_with("\r\n\r\nv2"), "{resp}");
+        let resp = request("/missing.txt");
+        assert!(resp.starts_with("HTTP/1.1 404 Not Found"), "{resp}");
+
+        // Atomic swaps of a symlinked root directory are picked up as well
+        #[cfg(unix)]
+        {
+            let link = dir.join("current");
+            let v3 = dir.join("v3");
+            std::fs::create_dir_all(&v3).unwrap();
+            std::fs::write(v3.join("index.html"), "v3").unwrap();
+            let _ = std::fs::remove_file(&link);
+            std::os::unix::fs::symlink(&root, &link).unwrap();
+
+            let (_server, addr) = start_server(&["--root", link.to_str().unwrap()]);
+            let request = |path: &str| {
+                let mut stream = TcpStream::connect(&addr).unwrap();
+                stream
+                    .write_all(
+                        format!(
+                            "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+                        )
+                        .as_bytes(),
+                    )
+                    .unwrap();
+                let mut resp = String::new();
+                stream.read_to_string(&mut resp).unwrap();
+                resp
+            };
+            assert!(request("/").ends_with("\r\n\r\nv2"));
+
+            let tmp_link = dir.join("current.tmp");
+            std::os::unix::fs::symlink(&v3, &tmp_link).unwrap();
+            std::fs::rename(&tmp_link, &link).unwrap();
+            assert!(request("/").ends_with("\r\n\r\nv3"));
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn internal_redirect() {
+        let root = std::env::temp_dir().join("sws-internal-redirect-test");
+        std::fs::create_dir_all(root.join("internal")).unwrap();
+        std::fs::write(root.join("internal/report.txt"), "report").unwrap();
+        std::fs::write(root.join("secret.txt"), "secret").unwrap();
+
+        let request = |addr: &str, path: &str, redirect: Option<&str>| {
+            let redirect = redirect.map_or(String::new(), |v| format!("X-Accel-Redirect: {v}\r\n"));
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!(
+                        "GET {path} HTTP/1.1\r\nHost: localhost\r\n{redirect}Connection: close\r\n\r\n"
+                    )
+                    .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        let (_server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--internal-path-prefix",
+            "/internal",
+            "--internal-path-trusted-proxies",
+            "127.0.0.1",
+        ]);
+
+        // Direct requests to internal paths are not found, including encoded variants
+        for path in [
+            "/internal/report.txt",
+            "/internal/",
+            "/internal",
+            "/%69nternal/report.txt",
+            "/internal%2freport.txt",
+        ] {
+            let resp = request(&addr, path, None);
+            assert!(resp.starts_with("HTTP/1.1 404 Not Found"), "{resp}");
+        }
+
+        // A trusted proxy can name an internal path
+        let resp = request(&addr, "/download", Some("/internal/report.txt"));
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(resp.ends_with("\r\n\r\nreport"), "{resp}");
+
+        // Paths escaping the internal prefix are ignored
+        let resp = request(&addr, "/download", Some("/internal/../secret.txt"));
+        assert!(resp.starts_with("HTTP/1.1 404 Not Found"), "{resp}");
+
+        // The header is ignored from untrusted addresses
+        let (_server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--internal-path-prefix",
+            "/internal",
+            "--internal-path-trusted-proxies",
+            "10.0.0.1",
+        ]);
+        let resp = request(&addr, "/internal/report.txt", Some("/internal/report.txt"));
+        assert!(resp.starts_with("HTTP/1.1 404 Not Found"), "{resp}");
+        let resp = request(&addr, "/secret.txt", Some("/internal/report.txt"));
+        assert!(resp.ends_with("\r\n\r\nsecret"), "{resp}");
+
+        // Case variants are internal too when the file lookup is case-insensitive
+        let (_server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--internal-path-prefix",
+            "/internal",
+            "--case-insensitive-paths",
+        ]);
+        for path in ["/INTERNAL/report.txt", "/%49nternal/Report.txt"] {
+            let resp = request(&addr, path, None);
+            assert!(resp.starts_with("HTTP/1.1 404 Not Found"), "{resp}");
+        }
+        let resp = request(&addr, "/SECRET.txt", None);
+        assert!(resp.ends_with("\r\n\r\nsecret"), "{resp}");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn request_body_discarded() {
+        let root = std::env::temp_dir().join("sws-request-body-test");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("a.txt"), "aaa").unwrap();
+        std::fs::write(root.join("b.txt"), "bbb").unwrap();
+
+        let (_server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--request-body-max-size",
+            "16",
+        ]);
+        let request = |data: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream.write_all(data.as_bytes()).unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        // The body is discarded and the next pipelined request is handled correctly
+        let resp = request(
+            "GET /a.txt HTTP/1.1\r\nHost: localhost\r\nContent-Length: 11\r\n\r\nGET /b.txt \
+            GET /b.txt HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        );
+        assert_eq!(resp.matches("HTTP/1.1 200 OK").count(), 2, "{resp}");
+        assert!(resp.contains("\r\n\r\naaaHTTP/1.1 200 OK"), "{resp}");
+        assert!(resp.ends_with("\r\n\r\nbbb"), "{resp}");
+
+        // Chunked bodies too
+        let resp = request(
+            "GET /a.txt HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n\
+            5\r\nhello\r\n0\r\n\r\n\
+            GET /b.txt HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        );
+        assert_eq!(resp.matches("HTTP/1.1 200 OK").count(), 2, "{resp}");
+        assert!(resp.ends_with("\r\n\r\nbbb"), "{resp}");
+
+        // Larger bodies are rejected and the connection is closed
+        for headers in ["Content-Length: 17", "Transfer-Encoding: chunked"] {
+            let resp = request(&format!(
+                "GET /a.txt HTTP/1.1\r\nHost: localhost\r\n{headers}\r\n\r\n\
+                11\r\n{}\r\n0\r\n\r\n\
+                GET /b.txt HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+                "x".repeat(17)
+            ));
+            assert!(resp.starts_with("HTTP/1.1 400 Bad Request"), "{resp}");
+            assert!(resp.contains("\r\nconnection: close\r\n"), "{resp}");
+            assert_eq!(resp.matches("HTTP/1.1").count(), 1, "{resp}");
+        }
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn allowed_methods_rules() {
+        let config_dir = std::env::temp_dir().join("sws-allowed-methods-test");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let config_file = config_dir.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[general]
+allowed-methods = "GET,HEAD,OPTIONS"
+
+[[advanced.allowed-methods]]
+source = "/assets/*.css"
+methods = ["GET"]
+
+[[advanced.allowed-methods]]
+source = "/assets/*.js"
+methods = ["GET", "POST"]
+"#,
+        )
+        .unwrap();
+
+        let (_server, addr) = start_server(&["--config-file", config_file.to_str().unwrap()]);
+        let request = |method: &str, path: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!(
+                        "{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+                    )
+                    .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        // A rule allowing only GET
+        assert!(request("GET", "/assets/main.css").starts_with("HTTP/1.1 200 OK"));
+        for method in ["HEAD", "OPTIONS", "POST"] {
+            let resp = request(method, "/assets/main.css");
+            assert!(
+                resp.starts_with("HTTP/1.1 405 Method Not Allowed"),
+                "{resp}"
+            );
+            assert!(resp.contains("\r\nallow: GET\r\n"), "{resp}");
+        }
+
+        // A rule allowing GET and POST, the latter is advertised but not implemented
+        assert!(request("GET", "/assets/main.js").starts_with("HTTP/1.1 200 OK"));
+        let resp = request("POST", "/assets/main.js");
+        assert!(resp.starts_with("HTTP/1.1 501 Not Implemented"), "{resp}");
+        assert!(resp.contains("\r\nallow: GET, POST\r\n"), "{resp}");
+        let resp = request("HEAD", "/assets/main.js");
+        assert!(
+            resp.starts_with("HTTP/1.1 405 Method Not Allowed"),
+            "{resp}"
+        );
+        assert!(resp.contains("\r\nallow: GET, POST\r\n"), "{resp}");
+
+        // Other paths keep the global allowed methods
+        let resp = request("OPTIONS", "/assets/favicon.ico");
+        assert!(resp.starts_with("HTTP/1.1 204 No Content"), "{resp}");
+        assert!(resp.contains("\r\nallow: OPTIONS, HEAD, GET\r\n"), "{resp}");
+        let resp = request("POST", "/index.html");
+        assert!(
+            resp.starts_with("HTTP/1.1 405 Method Not Allowed"),
+            "{resp}"
+        );
+        assert!(resp.contains("\r\nallow: OPTIONS, HEAD, GET\r\n"), "{resp}");
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[test]
+    fn allowed_hosts() {
+        let config_dir = std::env::temp_dir().join("sws-allowed-hosts-test");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let config_file = config_dir.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[general]
+allowed-hosts = "example.com, *.example.org"
+
+[[advanced.virtual-hosts]]
+host = "static.test"
+root = "docker/public/assets"
+"#,
+        )
+        .unwrap();
+
+        let request = |addr: &str, host: Option<&str>| {
+            let host = host.map_or(String::new(), |host| format!("Host: {host}\r\n"));
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET /index.html HTTP/1.1\r\n{host}Connection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        let (server, addr) = start_server(&["--config-file", config_file.to_str().unwrap()]);
+        for host in [
+            "example.com",
+            "EXAMPLE.COM:8787",
+            "www.example.org",
+            "static.test",
+        ] {
+            let resp = request(&addr, Some(host));
+            assert!(resp.starts_with("HTTP/1.1 200 OK"), "{host}: {resp}");
+        }
+        for host in [
+            Some("evil.com"),
+            Some("www.example.com"),
+            Some("localhost"),
+            None,
+        ] {
+            let resp = request(&addr, host);
+            assert!(
+                resp.starts_with("HTTP/1.1 421 Misdirected Request"),
+                "{host:?}: {resp}"
+            );
+        }
+        drop(server);
+
+        // A custom status code
+        let (_server, addr) = start_server(&[
+            "--allowed-hosts",
+            "example.com",
+            "--allowed-hosts-status",
+            "400",
+        ]);
+        let resp = request(&addr, Some("evil.com"));
+        assert!(resp.starts_with("HTTP/1.1 400 Bad Request"), "{resp}");
+        assert!(request(&addr, Some("example.com")).starts_with("HTTP/1.1 200 OK"));
+
+        // Invalid patterns are rejected at startup
+        let output = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args([
+                "--root",
+                "docker/public",
+                "--allowed-hosts",
+                "example.com:80",
+            ])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("invalid allowed host pattern"), "{stderr}");
+
+        std::fs::remove_dir_all(config_dir).unwrap();
+    }
+
+    #[test]
+    fn log_level_verbosity_flags() {
+        let startup_logs = |args: &[&str]| {
+            let port = TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap()
+                .port();
+            let child = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+                .args(["--host", "127.0.0.1", "--port", &port.to_string()])
+                .args(["--root", "docker/public"])
+                .args(args)
+                .env_remove("SERVER_LOG_LEVEL")
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .spawn()
+                .unwrap();
+            let mut server = ServerProcess(child);
+
+            let addr = format!("127.0.0.1:{port}");
+            let started = Instant::now();
+            while TcpStream::connect(&addr).is_err() {
+                assert!(
+                    started.elapsed() < Duration::from_secs(10),
+                    "server start up"
+                );
+                sleep(Duration::from_millis(50));
+            }
+
+            server.0.kill().unwrap();
+            let mut stderr = String::new();
+            server
+                .0
+                .stderr
+                .take()
+                .unwrap()
+                .read_to_string(&mut stderr)
+                .unwrap();
+            stderr
+        };
+
+        assert!(startup_logs(&["-v"]).contains("log level: debug"));
+        assert!(startup_logs(&["-vv"]).contains("log level: trace"));
+        assert!(startup_logs(&["--verbose", "--verbose"]).contains("log level: trace"));
+        // An explicit log level takes precedence over the flags
+        assert!(startup_logs(&["-vv", "--log-level", "info"]).contains("log level: info"));
+        assert!(startup_logs(&["-v", "--log-level", "error"]).contains("log level: error"));
+        assert!(startup_logs(&["--quiet"]).contains("log level: error"));
+        assert!(startup_logs(&["--quiet", "-g", "info"]).contains("log level: info"));
+        // `-q` remains the short form of the grace period option
+        let logs = startup_logs(&["-q", "3", "-g", "info"]);
+        assert!(logs.contains("grace period before graceful shutdown: 3s"));
+
+        // The flags are mutually exclusive
+        let output = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args(["--root", "docker/public", "-v", "--quiet"])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+    }
+
+    #[test]
+    fn keep_alive_max_requests() {
+        let (_server, addr) = start_server(&["--keep-alive-max-requests", "3"]);
+
+        // Four pipelined requests on a connection limited to three
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        stream
+            .write_all(
+                "GET /index.html HTTP/1.1\r\nHost: localhost\r\n\r\n"
+                    .repeat(4)
+                    .as_bytes(),
+            )
+            .unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+
+        let responses = resp.split("HTTP/1.1 ").skip(1).collect::<Vec<_>>();
+        assert_eq!(responses.len(), 3, "{resp}");
+        for (i, resp) in responses.iter().enumerate() {
+            assert!(resp.starts_with("200 OK"), "{resp}");
+            assert_eq!(resp.contains("\r\nconnection: close\r\n"), i == 2, "{resp}");
+        }
+
+        // The limit is counted per connection
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .write_all(b"GET /index.html HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+    }
+
+    #[test]
+    fn sitemap() {
+        let root = std::env::temp_dir().join("sws-sitemap-server-test");
+        std::fs::create_dir_all(root.join("drafts")).unwrap();
+        std::fs::write(root.join("index.html"), "index").unwrap();
+        std::fs::write(root.join("about.html"), "about").unwrap();
+        std::fs::write(root.join("drafts/wip.html"), "wip").unwrap();
+        let _ = std::fs::remove_file(root.join("contact.html"));
+        let _ = std::fs::remove_file(root.join("sitemap.xml"));
+
+        let (server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--sitemap",
+            "https://example.com",
+            "--sitemap-ignore",
+            "/drafts/**",
+        ]);
+        let request = |method: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!(
+                        "{method} /sitemap.xml HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+                    )
+                    .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        let resp = request("GET");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(resp.contains("\r\ncontent-type: text/xml\r\n"), "{resp}");
+        assert!(resp.contains("<loc>https://example.com/</loc>"), "{resp}");
+        assert!(
+            resp.contains("<loc>https://example.com/about.html</loc>"),
+            "{resp}"
+        );
+        assert!(!resp.contains("wip.html"), "{resp}");
+        let resp = request("HEAD");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(resp.ends_with("\r\n\r\n"), "{resp}");
+
+        // The generated sitemap is cached until its time to live expires or a SIGHUP
+        std::fs::write(root.join("contact.html"), "contact").unwrap();
+        assert!(!request("GET").contains("contact.html"));
+        #[cfg(unix)]
+        {
+            assert!(Command::new("kill")
+                .args(["-HUP", &server.0.id().to_string()])
+                .status()
+                .unwrap()
+                .success());
+            let started = Instant::now();
+            while !request("GET").contains("<loc>https://example.com/contact.html</loc>") {
+                assert!(started.elapsed() < Duration::from_secs(5), "sitemap reload");
+                sleep(Duration::from_millis(50));
+            }
+        }
+
+        // A real sitemap file takes precedence
+        std::fs::write(root.join("sitemap.xml"), "real").unwrap();
+        assert!(request("GET").ends_with("\r\n\r\nreal"));
+
+        drop(server);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(feature = "basic-auth")]
+    #[test]
+    fn sitemap_excluded_paths() {
+        let root = std::env::temp_dir().join("sws-sitemap-excluded-test");
+        std::fs::create_dir_all(root.join("internal")).unwrap();
+        std::fs::create_dir_all(root.join("members")).unwrap();
+        std::fs::write(root.join("index.html"), "index").unwrap();
+        std::fs::write(root.join("internal/report.html"), "report").unwrap();
+        std::fs::write(root.join("members/index.html"), "members").unwrap();
+        std::fs::write(root.join("members/profile.html"), "profile").unwrap();
+        let config_file = root.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[[advanced.basic-auth]]
+source = "/members/**"
+credentials = "jq:$2y$05$32zazJ1yzhlDHnt26L3MFOgY0HVqPmDUvG0KUx6cjf9RDiUGp/M9q"
+"#,
+        )
+        .unwrap();
+
+        let (server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--config-file",
+            config_file.to_str().unwrap(),
+            "--sitemap",
+            "https://example.com",
+            "--internal-path-prefix",
+            "/internal",
+        ]);
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .write_all(b"GET /sitemap.xml HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(resp.contains("<loc>https://example.com/</loc>"), "{resp}");
+        assert!(!resp.contains("/internal/"), "{resp}");
+        assert!(!resp.contains("/members/"), "{resp}");
+
+        drop(server);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn disable_index() {
+        let root = std::env::temp_dir().join("sws-disable-index-test");
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/index.html"), "docs index").unwrap();
+
+        let request = |addr: &str, path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        // Directories get the listing even if they contain an index file
+        let (_server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--directory-listing",
+            "--disable-index",
+        ]);
+        let resp = request(&addr, "/docs/");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(resp.contains("Index of /docs/"), "{resp}");
+        assert!(resp.contains(">index.html</a>"), "{resp}");
+        assert!(!resp.contains("docs index"), "{resp}");
+
+        // The index file itself is still served when requested explicitly
+        let resp = request(&addr, "/docs/index.html");
+        assert!(resp.ends_with("\r\n\r\ndocs index"), "{resp}");
+
+        // Without the directory listing, directories are not found
+        let (_server, addr) = start_server(&["--root", root.to_str().unwrap(), "--disable-index"]);
+        let resp = request(&addr, "/docs/");
+        assert!(resp.starts_with("HTTP/1.1 404 Not Found"), "{resp}");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(feature = "http2")]
+    #[test]
+    fn tls_client_auth() {
+        let dir = std::env::temp_dir().join("sws-tls-client-auth-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let log_file = dir.join("access.log");
+        let _ = std::fs::remove_file(&log_file);
+
+        let request = |addr: &str, client_cert: Option<(&str, &str)>| {
+            let mut stream = tls_client_with_cert(addr, &[b"http/1.1"], client_cert)?;
+            stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")?;
+            let mut resp = Vec::new();
+            // The server may close the connection without a TLS close notify
+            let result = stream.read_to_end(&mut resp);
+            match String::from_utf8_lossy(&resp).into_owned() {
+                resp if resp.is_empty() => result.map(|_| resp),
+                resp => Ok(resp),
+            }
+        };
+        let valid = Some(("tests/tls/client_cert.pem", "tests/tls/client_key.pem"));
+        let untrusted = Some((
+            "tests/tls/client_untrusted_cert.pem",
+            "tests/tls/client_untrusted_key.pem",
+        ));
+        let tls_args = [
+            "--http2=true",
+            "--http2-tls-cert",
+            "tests/tls/local.dev_cert.pem",
+            "--http2-tls-key",
+            "tests/tls/local.dev_key.pem",
+            "--http2-tls-client-ca",
+            "tests/tls/client_ca.pem",
+        ];
+
+        // Only clients with a certificate signed by the client CA are accepted
+        let (server, addr) = start_server(
+            &[
+                &tls_args[..],
+                &[
+                    "--log-level",
+                    "info",
+                    "--log-file",
+                    log_file.to_str().unwrap(),
+                ],
+            ]
+            .concat(),
+        );
+        let resp = request(&addr, valid).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(request(&addr, untrusted).is_err());
+        assert!(request(&addr, None).is_err());
+
+        // The client certificate common name is logged
+        drop(server);
+        let logs = std::fs::read_to_string(&log_file).unwrap();
+        assert!(
+            logs.contains("request completed: method=GET uri=/ status=200 ")
+                && logs.contains(" client_cn=sws-client\n"),
+            "{logs}"
+        );
+
+        // Anonymous clients are accepted if the client authentication is optional
+        let (_server, addr) =
+            start_server(&[&tls_args[..], &["--http2-tls-client-auth", "optional"]].concat());
+        for client_cert in [valid, None] {
+            let resp = request(&addr, client_cert).unwrap();
+            assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        }
+        assert!(request(&addr, untrusted).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn protocol_hints() {
+        let (_server, addr) = start_server(&[
+            "--alt-svc",
+            "h3=\":443\"; ma=86400",
+            "--upgrade-protocols",
+            "h2c",
+            "--disable-keep-alive",
+        ]);
+        let request = |head: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream.write_all(head.as_bytes()).unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        let resp = request("GET / HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(
+            resp.contains("\r\nalt-svc: h3=\":443\"; ma=86400\r\n"),
+            "{resp}"
+        );
+        assert!(resp.contains("\r\nupgrade: h2c\r\n"), "{resp}");
+        assert!(
+            resp.contains("\r\nconnection: close, upgrade\r\n"),
+            "{resp}"
+        );
+
+        // Error responses advertise the alternative service too
+        let resp = request("GET /missing HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert!(resp.starts_with("HTTP/1.1 404 Not Found"), "{resp}");
+        assert!(
+            resp.contains("\r\nalt-svc: h3=\":443\"; ma=86400\r\n"),
+            "{resp}"
+        );
+
+        // The upgrade hint is specific to HTTP/1.1 connections
+        let resp = request("GET / HTTP/1.0\r\n\r\n");
+        assert!(resp.contains("\r\nalt-svc: "), "{resp}");
+        assert!(!resp.contains("\r\nupgrade: "), "{resp}");
+
+        // Invalid values are rejected at startup
+        let status = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args([
+                "--port",
+                "0",
+                "--root",
+                "docker/public",
+                "--alt-svc",
+                "h3=443",
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(!status.success());
+    }
+
+    #[cfg(feature = "http2")]
+    #[test]
+    fn http2_tls_files_validation() {
+        let dir = std::env::temp_dir().join("sws-http2-tls-files-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let malformed = dir.join("malformed.pem");
+        std::fs::write(
+            &malformed,
+            "-----BEGIN CERTIFICATE-----\nnot base64!\n-----END CERTIFICATE-----\n",
+        )
+        .unwrap();
+        let missing = dir.join("missing.pem");
+        let malformed = malformed.to_str().unwrap();
+        let missing = missing.to_str().unwrap();
+
+        // Exits before binding with an error naming the missing or invalid file
+        let startup_error = |cert: &str, key: &str| {
+            let output = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+                .args(["--port", "0", "--root", "docker/public", "--http2=true"])
+                .args(["--http2-tls-cert", cert, "--http2-tls-key", key])
+                .env_remove("SERVER_LOG_LEVEL")
+                .output()
+                .unwrap();
+            assert!(!output.status.success());
+            String::from_utf8_lossy(&output.stderr).into_owned()
+        };
+        let cert = "tests/tls/local.dev_cert.pem";
+        let key = "tests/tls/local.dev_key.pem";
+        let err = startup_error(missing, key);
+        assert!(
+            err.contains(&format!("tls cert file not found: {missing}")),
+            "{err}"
+        );
+        let err = startup_error(cert, missing);
+        assert!(
+            err.contains(&format!("tls key file not found: {missing}")),
+            "{err}"
+        );
+        let err = startup_error(malformed, key);
+        assert!(
+            err.contains(&format!("invalid tls cert file: {malformed}")),
+            "{err}"
+        );
+        let err = startup_error(cert, malformed);
+        assert!(
+            err.contains(&format!("invalid tls key file: {malformed}")),
+            "{err}"
+        );
+        // A private key isn't a certificate
+        let err = startup_error(key, key);
+        assert!(
+            err.contains(&format!("invalid tls cert file: {key}")),
+            "{err}"
+        );
+
+        // The fallback is refused when the client certificate authentication is configured
+        let output = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args(["--port", "0", "--root", "docker/public", "--http2=true"])
+            .args(["--http2-tls-cert", missing, "--http2-tls-key", key])
+            .args(["--http2-tls-fallback", "--http2-tls-client-ca", cert])
+            .env_remove("SERVER_LOG_LEVEL")
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let err = String::from_utf8_lossy(&output.stderr);
+        assert!(err.contains("tls fallback refused"), "{err}");
+
+        // The fallback option serves plain HTTP instead
+        let (_server, addr) = start_server(&[
+            "--http2=true",
+            "--http2-tls-cert",
+            missing,
+            "--http2-tls-key",
+            key,
+            "--http2-tls-fallback",
+        ]);
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        // HSTS only makes sense over TLS, the other security headers are kept
+        let resp = resp.to_lowercase();
+        assert!(!resp.contains("\r\nstrict-transport-security:"), "{resp}");
+        assert!(resp.contains("\r\nx-frame-options: deny\r\n"), "{resp}");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn canonical_link() {
+        let (_server, addr) = start_server(&["--canonical-base-url", "https://example.com"]);
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        // HTML responses point to the normalized path without the query string
+        for path in ["/", "/?utm_source=x", "//./"] {
+            let resp = request(path);
+            assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+            assert!(
+                resp.contains("\r\nlink: <https://example.com/>; rel=\"canonical\"\r\n"),
+                "{resp}"
+            );
+        }
+
+        // Other content types are not affected
+        let resp = request("/assets/main.css");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(resp.contains("\r\ncontent-type: text/css"), "{resp}");
+        assert!(!resp.contains("\r\nlink: "), "{resp}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn access_log_completed_requests() {
+        let dir = std::env::temp_dir().join("sws-access-log-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("public")).unwrap();
+        let log_file = dir.join("sws.log");
+        std::fs::write(dir.join("public/index.html"), "index").unwrap();
+        std::fs::write(dir.join("public/large.bin"), vec![0u8; 32 * 1024 * 1024]).unwrap();
+        // Opening a socket file fails with an unexpected error
+        let _socket = std::os::unix::net::UnixListener::bind(dir.join("public/socket")).unwrap();
+
+        let (_server, addr) = start_server(&[
+            "--root",
+            dir.join("public").to_str().unwrap(),
+            "--log-level",
+            "info",
+            "--log-file",
+            log_file.to_str().unwrap(),
+            "--log-remote-address",
+        ]);
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nX-Forwarded-For: 203.0.113.195\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        };
+
+        let resp = request("/");
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        wait_for_log(
+            &log_file,
+            "request completed: method=GET uri=/ status=200 duration_ms=",
+        );
+        let logs = wait_for_log(&log_file, " bytes_sent=5 remote_addr=127.0.0.1:");
+        assert!(logs.contains(" real_remote_ip=203.0.113.195\n"), "{logs}");
+        assert!(!logs.contains("aborted=true"), "{logs}");
+
+        let resp = request("/socket");
+        assert!(
+            resp.starts_with("HTTP/1.1 500 Internal Server Error"),
+            "{resp}"
+        );
+        wait_for_log(
+            &log_file,
+            "request completed: method=GET uri=/socket status=500 ",
+        );
+
+        // The connection is closed before the response body is fully sent
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .write_all(b"GET /large.bin HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut buf = [0u8; 1024];
+        stream.read_exact(&mut buf).unwrap();
+        drop(stream);
+        let logs = wait_for_log(&log_file, "uri=/large.bin status=200 ");
+        let line = logs
+            .lines()
+            .find(|line| line.contains("request completed: method=GET uri=/large.bin"))
+            .unwrap();
+        assert!(line.ends_with(" aborted=true"), "{line}");
+
+        // Every request is logged exactly once at the info level
+        let logs = std::fs::read_to_string(&log_file).unwrap();
+        assert_eq!(logs.matches("request completed: ").count(), 3, "{logs}");
+        assert_eq!(
+            logs.lines()
+                .filter(|line| line.contains(" INFO ") && line.contains(" uri="))
+                .count(),
+            3,
+            "{logs}"
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn adaptive_compression() {
+        // The idle threshold must be lower than the busy one
+        let output = Command::new(env!("CARGO_BIN_EXE_static-web-server"))
+            .args(["--port", "0", "--root", "docker/public"])
+            .args([
+                "--adaptive-compression",
+                "--adaptive-compression-idle-requests=8",
+            ])
+            .args(["--adaptive-compression-busy-requests", "8"])
+            .env_remove("SERVER_LOG_LEVEL")
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let err = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            err.contains(
+                "adaptive compression idle requests (8) must be lower than the busy requests (8)"
+            ),
+            "{err}"
+        );
+
+        let (_server, addr) = start_server(&[
+            "--compression-min-size",
+            "0",
+            "--adaptive-compression",
+            "--adaptive-compression-idle-requests",
+            "1",
+            "--adaptive-compression-busy-requests",
+            "2",
+        ]);
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        stream
+            .write_all(b"GET /index.html HTTP/1.1\r\nHost: localhost\r\nAccept-Encoding: gzip\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut resp = Vec::new();
+        stream.read_to_end(&mut resp).unwrap();
+        let pos = resp.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let head = String::from_utf8_lossy(&resp[..pos]).to_lowercase();
+        assert!(head.starts_with("http/1.1 200 ok"), "{head}");
+        assert!(head.contains("\r\ncontent-encoding: gzip"), "{head}");
+    }
+
+    #[test]
+    fn early_hints_rules() {
+        let root = std::env::temp_dir().join("sws-early-hints-rules-test");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("index.html"), "home").unwrap();
+        std::fs::write(root.join("about.html"), "about").unwrap();
+        let config_file = root.join("config.toml");
+        std::fs::write(
+            &config_file,
+            r#"
+[[advanced.early-hints]]
+source = "/index.html"
+links = [
+    "</assets/main.css>; rel=preload; as=style",
+    "</assets/app.js>; rel=preload; as=script",
+]
+"#,
+        )
+        .unwrap();
+
+        let request = |addr: &str, path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(
+                    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                        .as_bytes(),
+                )
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp.to_lowercase()
+        };
+
+        let (_server, addr) = start_server(&[
+            "--root",
+            root.to_str().unwrap(),
+            "--config-file",
+            config_file.to_str().unwrap(),
+        ]);
+        // Matching paths get the preload links in order
+        let resp = request(&addr, "/index.html");
+        assert!(resp.starts_with("http/1.1 200 ok"), "{resp}");
+        let links = resp
+            .lines()
+            .filter_map(|line| line.strip_prefix("link: "))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            [
+                "</assets/main.css>; rel=preload; as=style",
+                "</assets/app.js>; rel=preload; as=script"
+            ]
+        );
+        // Other paths don't
+        let resp = request(&addr, "/about.html");
+        assert!(resp.starts_with("http/1.1 200 ok"), "{resp}");
+        assert!(!resp.contains("\r\nlink:"), "{resp}");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    fn health_status(addr: &str, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .write_all(
+                format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                    .as_bytes(),
+            )
+            .unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+        resp.lines().next().unwrap_or_default().to_owned()
+    }
+
+    #[test]
+    fn health_endpoints_maintenance() {
+        let (_server, addr) = start_server(&["--health", "--maintenance-mode"]);
+        // Only the readiness endpoint is unhealthy during maintenance
+        assert_eq!(health_status(&addr, "/livez"), "HTTP/1.1 200 OK");
+        assert_eq!(
+            health_status(&addr, "/readyz"),
+            "HTTP/1.1 503 Service Unavailable"
+        );
+        assert_eq!(
+            health_status(&addr, "/index.html"),
+            "HTTP/1.1 503 Service Unavailable"
+        );
+
+        let (_server, addr) = start_server(&["--health"]);
+        assert_eq!(health_status(&addr, "/livez"), "HTTP/1.1 200 OK");
+        assert_eq!(health_status(&addr, "/readyz"), "HTTP/1.1 200 OK");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn health_endpoints_draining() {
+        let (mut server, addr) = start_server(&["--health", "--grace-period", "2"]);
+        assert_eq!(health_status(&addr, "/readyz"), "HTTP/1.1 200 OK");
+
+        let status = Command::new("kill")
+            .args(["-TERM", &server.0.id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        // Only the readiness endpoint is unhealthy during the grace period
+        let started = Instant::now();
+        while health_status(&addr, "/readyz") == "HTTP/1.1 200 OK" {
+            assert!(started.elapsed() < Duration::from_secs(1), "draining");
+            sleep(Duration::from_millis(20));
+        }
+        assert_eq!(
+            health_status(&addr, "/readyz"),
+            "HTTP/1.1 503 Service Unavailable"
+        );
+        assert_eq!(health_status(&addr, "/livez"), "HTTP/1.1 200 OK");
+        assert!(server.0.wait().unwrap().success());
+    }
+
+    #[cfg(feature = "http2")]
+    #[test]
+    fn https_redirect_max_connections() {
+        let redirect_port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+            .to_string();
+        let (_server, _) = start_server(&[
+            "--http2=true",
+            "--http2-tls-cert",
+            "tests/tls/local.dev_cert.pem",
+            "--http2-tls-key",
+            "tests/tls/local.dev_key.pem",
+            "--https-redirect",
+            "--https-redirect-from-port",
+            &redirect_port,
+            "--max-connections",
+            "1",
+            "--max-connections-mode",
+            "close",
+        ]);
+
+        let addr = format!("127.0.0.1:{redirect_port}");
+        // Sends a keep-alive request returning the response head if any
+        let request = |stream: &mut TcpStream| {
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            stream
+                .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+                .ok()?;
+            let mut buf = [0; 1024];
+            match stream.read(&mut buf) {
+                Ok(n) if n > 0 => Some(String::from_utf8_lossy(&buf[..n]).into_owned()),
+                _ => None,
+            }
+        };
+        let started = Instant::now();
+        let mut first = loop {
+            match TcpStream::connect(&addr) {
+                Ok(stream) => break stream,
+                Err(_) if started.elapsed() < Duration::from_secs(10) => {
+                    sleep(Duration::from_millis(50))
+                }
+                Err(err) => panic!("redirect server start up: {err}"),
+            }
+        };
+        let resp = request(&mut first).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 301 Moved Permanently"), "{resp}");
+
+        // Connections above the limit are closed while the first one is open
+        let mut second = TcpStream::connect(&addr).unwrap();
+        assert_eq!(request(&mut second), None);
+
+        drop(first);
+        sleep(Duration::from_millis(100));
+        let mut third = TcpStream::connect(&addr).unwrap();
+        let resp = request(&mut third).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 301 Moved Permanently"), "{resp}");
+    }
+}
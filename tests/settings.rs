@@ -25,4 +25,78 @@ mod tests {
             }
         }
     }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn toml_file_compression_types() {
+        let settings = Settings::read(Path::new("tests/toml/config.toml")).unwrap();
+        let types = settings.compression.unwrap().types.unwrap();
+        assert_eq!(types.len(), 5);
+        assert_eq!(types[0], "text/*");
+    }
+
+    #[tokio::test]
+    async fn toml_file_exclude_patterns() {
+        let settings = Settings::read(Path::new("tests/toml/config.toml")).unwrap();
+        let advanced = settings.advanced.unwrap();
+        let headers = advanced.headers.unwrap();
+        assert_eq!(
+            headers[0].exclude.as_ref().unwrap().patterns(),
+            ["/vendor/**"]
+        );
+        assert!(headers[1].exclude.is_none());
+        let rewrites = advanced.rewrites.unwrap();
+        assert_eq!(
+            rewrites[3].exclude.as_ref().unwrap().patterns(),
+            ["/abc/private/**", "**/*.tmp.svg"]
+        );
+    }
+
+    #[tokio::test]
+    async fn toml_file_include_replace() {
+        let settings = Settings::read(Path::new("tests/toml/include/override.toml")).unwrap();
+        let general = settings.general.unwrap();
+        assert_eq!(general.host.unwrap(), "::");
+        assert_eq!(general.port.unwrap(), 8080);
+        assert_eq!(general.root.unwrap(), PathBuf::from("docker/public"));
+        assert_eq!(general.log_level.unwrap().name(), "info");
+
+        let headers = settings.advanced.unwrap().headers.unwrap();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].source, "**/*.css");
+    }
+
+    #[tokio::test]
+    async fn toml_file_include_append() {
+        let settings = Settings::read(Path::new("tests/toml/include/append.toml")).unwrap();
+        let general = settings.general.unwrap();
+        assert_eq!(general.port.unwrap(), 8081);
+        assert_eq!(general.log_level.unwrap().name(), "error");
+
+        let headers = settings.advanced.unwrap().headers.unwrap();
+        let sources: Vec<_> = headers.iter().map(|h| h.source.as_str()).collect();
+        assert_eq!(sources, ["**/*.html", "**/*.css"]);
+    }
+
+    #[tokio::test]
+    async fn toml_file_headers_list_values() {
+        let settings = Settings::read(Path::new("tests/toml/headers.toml")).unwrap();
+        let headers = settings.advanced.unwrap().headers.unwrap();
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[0].headers.get_all("link").iter().count(), 1);
+        let links: Vec<_> = headers[1].headers.get_all("link").iter().collect();
+        assert_eq!(
+            links,
+            [
+                "</assets/main.css>; rel=preload; as=style",
+                "</assets/main.js>; rel=preload; as=script"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn toml_file_include_circular() {
+        let err = Settings::read(Path::new("tests/toml/include/circular-a.toml")).unwrap_err();
+        assert!(format!("{err:?}").contains("circular configuration file include"));
+    }
 }
@@ -6,7 +6,7 @@
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
-    use headers::HeaderMap;
+    use headers::{HeaderMap, HeaderMapExt};
     use http::{Method, StatusCode};
     use std::fs;
     use std::path::PathBuf;
@@ -15,7 +15,9 @@ mod tests {
     use static_web_server::compression;
 
     #[cfg(feature = "directory-listing")]
-    use static_web_server::directory_listing::DirListFmt;
+    use static_web_server::directory_listing::{DirListFmt, DirListSizeFmt, DirListTimezone};
+    use static_web_server::etag::EtagMode;
+    use static_web_server::exts::http::HTTP_SUPPORTED_METHODS;
     use static_web_server::static_files::{self, HandleOpts};
 
     fn root_dir() -> PathBuf {
@@ -28,6 +30,7 @@ mod tests {
             method: &Method::GET,
             headers: &HeaderMap::new(),
             base_path: &root_dir(),
+            overlay_root: None,
             uri_path: "index.html",
             uri_query: None,
             #[cfg(feature = "directory-listing")]
@@ -36,9 +39,28 @@ mod tests {
             dir_listing_order: 6,
             #[cfg(feature = "directory-listing")]
             dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
             redirect_trailing_slash: true,
+            trailing_slash_policy: None,
             compression_static: false,
             ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_date_format: "%F %T",
+            #[cfg(feature = "directory-listing")]
+            dir_listing_timezone: &DirListTimezone::Local,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_ignore: &[],
+            #[cfg(feature = "directory-listing")]
+            dir_listing_show_symlinks: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_merge_overlay: false,
         })
         .await
         .expect("unexpected error response on `handle` function");
@@ -69,6 +91,7 @@ mod tests {
             method: &Method::HEAD,
             headers: &HeaderMap::new(),
             base_path: &root_dir(),
+            overlay_root: None,
             uri_path: "index.html",
             uri_query: None,
             #[cfg(feature = "directory-listing")]
@@ -77,9 +100,28 @@ mod tests {
             dir_listing_order: 6,
             #[cfg(feature = "directory-listing")]
             dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
             redirect_trailing_slash: true,
+            trailing_slash_policy: None,
             compression_static: false,
             ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_date_format: "%F %T",
+            #[cfg(feature = "directory-listing")]
+            dir_listing_timezone: &DirListTimezone::Local,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_ignore: &[],
+            #[cfg(feature = "directory-listing")]
+            dir_listing_show_symlinks: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_merge_overlay: false,
         })
         .await
         .expect("unexpected error response on `handle` function");
@@ -111,6 +153,7 @@ mod tests {
                 method: &method,
                 headers: &HeaderMap::new(),
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "xyz.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -119,9 +162,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -141,6 +203,7 @@ mod tests {
             method: &Method::GET,
             headers: &HeaderMap::new(),
             base_path: &root_dir(),
+            overlay_root: None,
             uri_path: "assets",
             uri_query: None,
             #[cfg(feature = "directory-listing")]
@@ -149,9 +212,28 @@ mod tests {
             dir_listing_order: 0,
             #[cfg(feature = "directory-listing")]
             dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
             redirect_trailing_slash: true,
+            trailing_slash_policy: None,
             compression_static: false,
             ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_date_format: "%F %T",
+            #[cfg(feature = "directory-listing")]
+            dir_listing_timezone: &DirListTimezone::Local,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_ignore: &[],
+            #[cfg(feature = "directory-listing")]
+            dir_listing_show_symlinks: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_merge_overlay: false,
         })
         .await
         .expect("unexpected error response on `handle` function");
@@ -172,6 +254,7 @@ mod tests {
             method: &Method::GET,
             headers: &HeaderMap::new(),
             base_path: &root_dir(),
+            overlay_root: None,
             uri_path: "assets",
             uri_query: None,
             #[cfg(feature = "directory-listing")]
@@ -180,9 +263,28 @@ mod tests {
             dir_listing_order: 0,
             #[cfg(feature = "directory-listing")]
             dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
             redirect_trailing_slash: true,
+            trailing_slash_policy: None,
             compression_static: false,
             ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_date_format: "%F %T",
+            #[cfg(feature = "directory-listing")]
+            dir_listing_timezone: &DirListTimezone::Local,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_ignore: &[],
+            #[cfg(feature = "directory-listing")]
+            dir_listing_show_symlinks: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_merge_overlay: false,
         })
         .await
         {
@@ -202,6 +304,7 @@ mod tests {
             method: &Method::GET,
             headers: &HeaderMap::new(),
             base_path: &root_dir(),
+            overlay_root: None,
             uri_path: "assets",
             uri_query: None,
             #[cfg(feature = "directory-listing")]
@@ -210,9 +313,28 @@ mod tests {
             dir_listing_order: 0,
             #[cfg(feature = "directory-listing")]
             dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
             redirect_trailing_slash: false,
+            trailing_slash_policy: None,
             compression_static: false,
             ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_date_format: "%F %T",
+            #[cfg(feature = "directory-listing")]
+            dir_listing_timezone: &DirListTimezone::Local,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_ignore: &[],
+            #[cfg(feature = "directory-listing")]
+            dir_listing_show_symlinks: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_merge_overlay: false,
         })
         .await
         {
@@ -237,6 +359,7 @@ mod tests {
                     method: &method,
                     headers: &HeaderMap::new(),
                     base_path: &root_dir(),
+                    overlay_root: None,
                     uri_path: uri,
                     uri_query: None,
                     #[cfg(feature = "directory-listing")]
@@ -245,9 +368,28 @@ mod tests {
                     dir_listing_order: 6,
                     #[cfg(feature = "directory-listing")]
                     dir_listing_format: &DirListFmt::Html,
+                    index_files: &["index.html".to_owned()],
+                    allowed_methods: HTTP_SUPPORTED_METHODS,
+                    extensionless_html: true,
                     redirect_trailing_slash: true,
+                    trailing_slash_policy: None,
                     compression_static: false,
                     ignore_hidden_files: false,
+                    case_insensitive_paths: false,
+                    disable_range_requests: false,
+                    etag_mode: EtagMode::Metadata,
+                    #[cfg(feature = "directory-listing")]
+                    dir_listing_size_format: &DirListSizeFmt::Human,
+                    #[cfg(feature = "directory-listing")]
+                    dir_listing_date_format: "%F %T",
+                    #[cfg(feature = "directory-listing")]
+                    dir_listing_timezone: &DirListTimezone::Local,
+                    #[cfg(feature = "directory-listing")]
+                    dir_listing_ignore: &[],
+                    #[cfg(feature = "directory-listing")]
+                    dir_listing_show_symlinks: false,
+                    #[cfg(feature = "directory-listing")]
+                    dir_listing_merge_overlay: false,
                 })
                 .await
                 {
@@ -287,6 +429,7 @@ mod tests {
                 method: &method,
                 headers: &HeaderMap::new(),
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "/index%2ehtml",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -295,9 +438,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -319,6 +481,7 @@ mod tests {
                 method: &method,
                 headers: &HeaderMap::new(),
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "/%2E%2e.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -327,9 +490,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -354,6 +536,7 @@ mod tests {
                 method: &method,
                 headers: &HeaderMap::new(),
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -362,9 +545,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -389,6 +591,7 @@ mod tests {
                 method: &method,
                 headers: &headers,
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -397,9 +600,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -427,6 +649,7 @@ mod tests {
                 method: &method,
                 headers: &headers,
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -435,9 +658,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -463,6 +705,7 @@ mod tests {
                 method: &method,
                 headers: &HeaderMap::new(),
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -471,9 +714,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -497,6 +759,7 @@ mod tests {
                 method: &method,
                 headers: &headers,
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -505,9 +768,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -530,6 +812,7 @@ mod tests {
                 method: &method,
                 headers: &headers,
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -538,9 +821,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -560,6 +862,326 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn handle_encoded_file_names() {
+        let dir = std::env::temp_dir().join("sws-encoded-file-names");
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["my file.txt", "café.html", "a+b.txt", "100%.txt", "%20.txt"] {
+            fs::write(dir.join(name), name).unwrap();
+        }
+
+        async fn request(dir: &std::path::Path, uri_path: &str) -> Result<Bytes, StatusCode> {
+            let (res, _) = static_files::handle(&HandleOpts {
+                method: &Method::GET,
+                headers: &HeaderMap::new(),
+                base_path: &dir.to_path_buf(),
+                overlay_root: None,
+                uri_path,
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await?;
+            Ok(hyper::body::to_bytes(res.into_body()).await.unwrap())
+        }
+
+        // Spaces and UTF-8 sequences are decoded
+        assert_eq!(
+            request(&dir, "/my%20file.txt").await.unwrap(),
+            "my file.txt"
+        );
+        assert_eq!(request(&dir, "/caf%C3%A9.html").await.unwrap(), "café.html");
+        assert_eq!(request(&dir, "/café.html").await.unwrap(), "café.html");
+
+        // A `+` is a literal plus sign in the path component, not a space
+        assert_eq!(request(&dir, "/a+b.txt").await.unwrap(), "a+b.txt");
+        assert_eq!(request(&dir, "/a%2Bb.txt").await.unwrap(), "a+b.txt");
+        assert_eq!(
+            request(&dir, "/a%20b.txt").await,
+            Err(StatusCode::NOT_FOUND)
+        );
+
+        // Paths are decoded only once
+        assert_eq!(request(&dir, "/100%25.txt").await.unwrap(), "100%.txt");
+        assert_eq!(request(&dir, "/%2520.txt").await.unwrap(), "%20.txt");
+
+        // Decoded dot segments can not escape the root directory
+        assert_eq!(
+            request(&dir, "/%2e%2e/sws-encoded-file-names/a+b.txt").await,
+            Err(StatusCode::NOT_FOUND)
+        );
+        assert_eq!(
+            request(&dir, "/%2E%2E%2Fcaf%C3%A9.html").await.unwrap(),
+            "café.html"
+        );
+
+        // Invalid UTF-8 sequences
+        assert_eq!(
+            request(&dir, "/caf%C3.html").await,
+            Err(StatusCode::BAD_REQUEST)
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn handle_extensionless_html() {
+        let dir = std::env::temp_dir().join("sws-extensionless-html");
+        fs::create_dir_all(dir.join("b")).unwrap();
+        fs::create_dir_all(dir.join("c")).unwrap();
+        fs::create_dir_all(dir.join("d.html")).unwrap();
+        for name in ["a.html", "b.html", "b/index.html", "c.html", "v1.2.html"] {
+            fs::write(dir.join(name), name).unwrap();
+        }
+
+        async fn request(
+            dir: &std::path::Path,
+            uri_path: &str,
+            extensionless_html: bool,
+        ) -> Result<(StatusCode, HeaderMap, Bytes), StatusCode> {
+            let (res, _) = static_files::handle(&HandleOpts {
+                method: &Method::GET,
+                headers: &HeaderMap::new(),
+                base_path: &dir.to_path_buf(),
+                overlay_root: None,
+                uri_path,
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await?;
+            let (parts, body) = res.into_parts();
+            let body = hyper::body::to_bytes(body).await.unwrap();
+            Ok((parts.status, parts.headers, body))
+        }
+
+        // `/a` -> `/a.html`
+        let (status, headers, body) = request(&dir, "/a", true).await.unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(headers["content-type"], "text/html");
+        assert_eq!(body, "a.html");
+
+        // A trailing slash is only valid for directories
+        assert_eq!(request(&dir, "/a/", true).await, Err(StatusCode::NOT_FOUND));
+
+        // Real directories are not shadowed by the `.html` file
+        let (status, headers, _) = request(&dir, "/b", true).await.unwrap();
+        assert_eq!(status, 308);
+        assert_eq!(headers["location"], "/b/");
+        let (_, _, body) = request(&dir, "/b/", true).await.unwrap();
+        assert_eq!(body, "b/index.html");
+
+        // Even without an index file
+        for extensionless_html in [true, false] {
+            let (status, headers, _) = request(&dir, "/c", extensionless_html).await.unwrap();
+            assert_eq!(status, 308);
+            assert_eq!(headers["location"], "/c/");
+            assert_eq!(
+                request(&dir, "/c/", extensionless_html).await,
+                Err(StatusCode::NOT_FOUND)
+            );
+        }
+
+        // Paths with an extension and `.html` suffixed directories are not considered
+        assert_eq!(
+            request(&dir, "/v1.2", true).await,
+            Err(StatusCode::NOT_FOUND)
+        );
+        assert_eq!(request(&dir, "/d", true).await, Err(StatusCode::NOT_FOUND));
+
+        // Disabled feature
+        assert_eq!(request(&dir, "/a", false).await, Err(StatusCode::NOT_FOUND));
+        let (_, _, body) = request(&dir, "/a.html", false).await.unwrap();
+        assert_eq!(body, "a.html");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn handle_preconditions_matrix() {
+        async fn status(
+            method: &Method,
+            headers: &[(&'static str, &str)],
+        ) -> (StatusCode, HeaderMap) {
+            let mut header_map = HeaderMap::new();
+            for (name, value) in headers {
+                header_map.insert(*name, value.parse().unwrap());
+            }
+            match static_files::handle(&HandleOpts {
+                method,
+                headers: &header_map,
+                base_path: &root_dir(),
+                overlay_root: None,
+                uri_path: "index.html",
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            {
+                Ok((res, _)) => (res.status(), res.headers().to_owned()),
+                Err(status) => panic!("unexpected status error {status}"),
+            }
+        }
+
+        let (_, headers) = status(&Method::GET, &[]).await;
+        let etag = headers["etag"].to_str().unwrap().to_owned();
+        let weak_etag = format!("W/{etag}");
+        let last_modified = headers["last-modified"].to_str().unwrap().to_owned();
+        let old = "Mon, 18 Nov 1974 00:00:00 GMT";
+        let other = "\"other\"";
+
+        let cases: Vec<(Vec<(&'static str, &str)>, u16)> = vec![
+            (vec![], 200),
+            // If-Match (strong comparison)
+            (vec![("if-match", &etag)], 200),
+            (vec![("if-match", "*")], 200),
+            (vec![("if-match", other)], 412),
+            (vec![("if-match", &weak_etag)], 412),
+            // If-Unmodified-Since
+            (vec![("if-unmodified-since", &last_modified)], 200),
+            (vec![("if-unmodified-since", old)], 412),
+            // If-Match takes precedence over If-Unmodified-Since
+            (vec![("if-match", &etag), ("if-unmodified-since", old)], 200),
+            (
+                vec![("if-match", other), ("if-unmodified-since", &last_modified)],
+                412,
+            ),
+            // If-None-Match (weak comparison)
+            (vec![("if-none-match", &etag)], 304),
+            (vec![("if-none-match", &weak_etag)], 304),
+            (vec![("if-none-match", "*")], 304),
+            (vec![("if-none-match", other)], 200),
+            // If-Modified-Since
+            (vec![("if-modified-since", &last_modified)], 304),
+            (vec![("if-modified-since", old)], 200),
+            // If-None-Match takes precedence over If-Modified-Since
+            (
+                vec![
+                    ("if-none-match", other),
+                    ("if-modified-since", &last_modified),
+                ],
+                200,
+            ),
+            (
+                vec![("if-none-match", &etag), ("if-modified-since", old)],
+                304,
+            ),
+            // If-Match and If-Unmodified-Since are evaluated first
+            (vec![("if-match", other), ("if-none-match", other)], 412),
+            (vec![("if-match", &etag), ("if-none-match", &etag)], 304),
+            (
+                vec![("if-unmodified-since", old), ("if-modified-since", old)],
+                412,
+            ),
+            // If-Range
+            (vec![("range", "bytes=0-9"), ("if-range", &etag)], 206),
+            (vec![("range", "bytes=0-9"), ("if-range", &weak_etag)], 200),
+            (vec![("range", "bytes=0-9"), ("if-range", other)], 200),
+            (
+                vec![("range", "bytes=0-9"), ("if-range", &last_modified)],
+                206,
+            ),
+            (
+                vec![
+                    ("range", "bytes=0-9"),
+                    ("if-match", other),
+                    ("if-range", &etag),
+                ],
+                412,
+            ),
+        ];
+
+        for method in [Method::HEAD, Method::GET] {
+            for (headers, expected) in &cases {
+                let (status, resp_headers) = status(&method, headers).await;
+                assert_eq!(status, *expected, "{method} {headers:?}");
+                if status == StatusCode::NOT_MODIFIED {
+                    assert_eq!(resp_headers["etag"], etag.as_str());
+                    assert_eq!(resp_headers["last-modified"], last_modified.as_str());
+                }
+            }
+        }
+    }
+
     #[tokio::test]
     async fn handle_file_allowed_disallowed_methods() {
         let methods = [
@@ -577,6 +1199,7 @@ mod tests {
                 method: &method,
                 headers: &HeaderMap::new(),
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -585,9 +1208,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -638,6 +1280,7 @@ mod tests {
                 method,
                 headers: &headers,
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -646,15 +1289,41 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
                 Ok((res, _)) => {
-                    let res = compression::auto(method, &headers, res)
-                        .expect("unexpected bytes error during body compression");
+                    let res = compression::auto(
+                        method,
+                        &headers,
+                        res,
+                        &compression::parse_types("").unwrap(),
+                        &compression::parse_encodings("").unwrap(),
+                        compression::CompressionLevel::Default,
+                    )
+                    .expect("unexpected bytes error during body compression");
 
                     let buf = fs::read(root_dir().join("index.html"))
                         .expect("unexpected error during index.html reading");
@@ -668,11 +1337,14 @@ mod tests {
                         "gzip" | "deflate" | "br" | "zstd" => {
                             assert!(res.headers().get("content-length").is_none());
                             assert_eq!(res.headers()["content-encoding"], enc);
+                            // The entity tag of a compressed body is weak
+                            assert!(res.headers()["etag"].to_str().unwrap().starts_with("W/\""));
                         }
                         _ => {
                             // otherwise the compression doesn't happen because unsupported `accept-encoding`
                             assert_eq!(res.headers()["content-length"], buf.len().to_string());
                             assert!(res.headers().get("content-encoding").is_none());
+                            assert!(res.headers()["etag"].to_str().unwrap().starts_with('"'));
                         }
                     };
 
@@ -687,20 +1359,34 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "compression")]
     #[tokio::test]
-    async fn handle_byte_ranges_single() {
-        let mut headers = HeaderMap::new();
-        headers.insert("range", "bytes=0-0".parse().unwrap());
+    async fn handle_file_compression_deflate() {
+        use std::io::Read;
 
-        let buf = fs::read(root_dir().join("index.html"))
-            .expect("unexpected error during index.html reading");
-        let buf = Bytes::from(buf);
+        let method = &Method::GET;
+        let cases = [
+            // A deflate-only client
+            ("deflate", "", "deflate"),
+            // The configured precedence resolves the ties
+            ("gzip, deflate", "", "gzip"),
+            ("gzip, deflate", "deflate,gzip", "deflate"),
+            // Preferred encodings not available
+            ("gzip, deflate", "zstd,br,deflate", "deflate"),
+        ];
 
-        for method in [Method::HEAD, Method::GET] {
-            match static_files::handle(&HandleOpts {
-                method: &method,
+        for (accept_encoding, encodings, expected) in cases {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                http::header::ACCEPT_ENCODING,
+                accept_encoding.parse().unwrap(),
+            );
+
+            let (res, _) = static_files::handle(&HandleOpts {
+                method,
                 headers: &headers,
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -709,45 +1395,72 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
-            {
-                Ok((mut res, _)) => {
-                    assert_eq!(res.status(), 206);
-                    assert_eq!(
-                        res.headers()["content-range"],
-                        format!("bytes 0-0/{}", buf.len())
-                    );
-                    assert_eq!(res.headers()["content-length"], "1");
-                    let body = hyper::body::to_bytes(res.body_mut())
-                        .await
-                        .expect("unexpected bytes error during `body` conversion");
-                    assert_eq!(body, &buf[..=0]);
-                }
-                Err(_) => {
-                    panic!("expected a normal response rather than a status error")
-                }
+            .expect("unexpected error response on `handle` function");
+
+            let res = compression::auto(
+                method,
+                &headers,
+                res,
+                &compression::parse_types("").unwrap(),
+                &compression::parse_encodings(encodings).unwrap(),
+                compression::CompressionLevel::Default,
+            )
+            .expect("unexpected bytes error during body compression");
+            assert_eq!(
+                res.headers()["content-encoding"],
+                expected,
+                "{accept_encoding}"
+            );
+            if expected != "deflate" {
+                continue;
             }
+
+            // The deflate coding is a zlib stream
+            let compressed = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let mut decoded = vec![];
+            flate2::read::ZlibDecoder::new(compressed.as_ref())
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, fs::read(root_dir().join("index.html")).unwrap());
         }
     }
-
+    #[cfg(feature = "compression")]
     #[tokio::test]
-    async fn handle_byte_ranges_multiple() {
+    async fn handle_file_compressions_content_length() {
+        let method = &Method::GET;
         let mut headers = HeaderMap::new();
-        headers.insert("range", "bytes=100-200".parse().unwrap());
-
-        let buf = fs::read(root_dir().join("index.html"))
-            .expect("unexpected error during index.html reading");
-        let buf = Bytes::from(buf);
+        headers.insert(http::header::ACCEPT_ENCODING, "gzip".parse().unwrap());
 
-        for method in [Method::HEAD, Method::GET] {
-            match static_files::handle(&HandleOpts {
-                method: &method,
+        for buffer_size in [65536, 0] {
+            let (res, _) = static_files::handle(&HandleOpts {
+                method,
                 headers: &headers,
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -756,46 +1469,180 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
-            {
-                Ok((mut res, _)) => {
-                    assert_eq!(res.status(), 206);
-                    assert_eq!(
-                        res.headers()["content-range"],
-                        format!("bytes 100-200/{}", buf.len())
-                    );
-                    assert_eq!(res.headers()["content-length"], "101");
-                    let body = hyper::body::to_bytes(res.body_mut())
-                        .await
-                        .expect("unexpected bytes error during `body` conversion");
-                    assert_eq!(body, &buf[100..=200]);
-                }
-                Err(_) => {
-                    panic!("expected a normal response rather than a status error")
-                }
+            .expect("unexpected error response on `handle` function");
+
+            let mut res = compression::auto_sized(
+                method,
+                &headers,
+                res,
+                buffer_size,
+                0,
+                &compression::parse_types("").unwrap(),
+                &compression::parse_encodings("").unwrap(),
+                compression::CompressionLevel::Default,
+            )
+            .await
+            .expect("unexpected bytes error during body compression");
+
+            assert_eq!(res.status(), 200);
+            assert_eq!(res.headers()["content-encoding"], "gzip");
+
+            if buffer_size > 0 {
+                // Small files get a definite content length of the compressed body
+                let content_length = res.headers()["content-length"].to_owned();
+                let body = hyper::body::to_bytes(res.body_mut())
+                    .await
+                    .expect("unexpected bytes error during `body` conversion");
+                assert_eq!(content_length, body.len().to_string());
+            } else {
+                assert!(res.headers().get("content-length").is_none());
             }
         }
     }
 
+    #[cfg(feature = "compression")]
     #[tokio::test]
-    async fn handle_byte_ranges_out_of_range() {
+    async fn handle_file_compressions_large_file_streamed() {
+        use hyper::body::HttpBody;
+        use std::io::Read;
+
+        let method = &Method::GET;
         let mut headers = HeaderMap::new();
-        headers.insert("range", "bytes=100-100000".parse().unwrap());
+        headers.insert(http::header::ACCEPT_ENCODING, "gzip".parse().unwrap());
+
+        // Barely compressible content so the compressed body stays large too
+        let base_path = std::env::temp_dir().join("sws-compression-large-file-test");
+        fs::create_dir_all(&base_path).unwrap();
+        let mut seed = 0x2545_f491_u32;
+        let content = (0..4 * 1024 * 1024)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                b'a' + (seed % 26) as u8
+            })
+            .collect::<Vec<u8>>();
+        fs::write(base_path.join("large.txt"), &content).unwrap();
+
+        let (res, _) = static_files::handle(&HandleOpts {
+            method,
+            headers: &headers,
+            base_path: &base_path,
+            overlay_root: None,
+            uri_path: "large.txt",
+            uri_query: None,
+            #[cfg(feature = "directory-listing")]
+            dir_listing: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_order: 6,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
+            redirect_trailing_slash: true,
+            trailing_slash_policy: None,
+            compression_static: false,
+            ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_date_format: "%F %T",
+            #[cfg(feature = "directory-listing")]
+            dir_listing_timezone: &DirListTimezone::Local,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_ignore: &[],
+            #[cfg(feature = "directory-listing")]
+            dir_listing_show_symlinks: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_merge_overlay: false,
+        })
+        .await
+        .expect("unexpected error response on `handle` function");
 
-        let buf = fs::read(root_dir().join("index.html"))
-            .expect("unexpected error during index.html reading");
-        let buf = Bytes::from(buf);
+        let mut res = compression::auto_sized(
+            method,
+            &headers,
+            res,
+            65536,
+            1024,
+            &compression::parse_types("").unwrap(),
+            &compression::parse_encodings("").unwrap(),
+            compression::CompressionLevel::Default,
+        )
+        .await
+        .expect("unexpected bytes error during body compression");
+
+        // Files above the buffer size are streamed using a chunked transfer encoding
+        assert_eq!(res.headers()["content-encoding"], "gzip");
+        assert!(res.headers().get("content-length").is_none());
+
+        // The compressed body is produced incrementally in bounded chunks
+        let mut chunks = 0;
+        let mut compressed = vec![];
+        while let Some(chunk) = res.body_mut().data().await {
+            let chunk = chunk.unwrap();
+            assert!(chunk.len() <= 65536, "chunk of {} bytes", chunk.len());
+            compressed.extend_from_slice(&chunk);
+            chunks += 1;
+        }
+        assert!(chunks > 16, "{chunks} chunks");
 
-        for method in [Method::HEAD, Method::GET] {
-            match static_files::handle(&HandleOpts {
-                method: &method,
+        let mut decoded = vec![];
+        flate2::read::GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert!(decoded == content);
+
+        fs::remove_dir_all(base_path).unwrap();
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn handle_file_compressions_min_size() {
+        let method = &Method::GET;
+        let mut headers = HeaderMap::new();
+        headers.insert(http::header::ACCEPT_ENCODING, "gzip".parse().unwrap());
+
+        let base_path = std::env::temp_dir().join("sws-compression-min-size-test");
+        fs::create_dir_all(&base_path).unwrap();
+        fs::write(base_path.join("small.txt"), "a".repeat(100)).unwrap();
+        fs::write(base_path.join("large.txt"), "a".repeat(10 * 1024)).unwrap();
+
+        for (uri_path, compressed) in [("small.txt", false), ("large.txt", true)] {
+            let (res, _) = static_files::handle(&HandleOpts {
+                method,
                 headers: &headers,
-                base_path: &root_dir(),
-                uri_path: "index.html",
+                base_path: &base_path,
+                overlay_root: None,
+                uri_path,
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
                 dir_listing: false,
@@ -803,47 +1650,190 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
-            {
-                Ok((mut res, _)) => {
-                    assert_eq!(res.status(), 416);
-                    assert_eq!(
-                        res.headers()["content-range"],
-                        format!("bytes */{}", buf.len())
-                    );
-                    assert_eq!(res.headers().get("content-length"), None);
-                    let body = hyper::body::to_bytes(res.body_mut())
-                        .await
-                        .expect("unexpected bytes error during `body` conversion");
-                    assert_eq!(body, "");
-                }
-                Err(_) => {
-                    panic!("expected a normal response rather than a status error")
-                }
+            .expect("unexpected error response on `handle` function");
+
+            let res = compression::auto_sized(
+                method,
+                &headers,
+                res,
+                65536,
+                1024,
+                &compression::parse_types("").unwrap(),
+                &compression::parse_encodings("").unwrap(),
+                compression::CompressionLevel::Default,
+            )
+            .await
+            .expect("unexpected bytes error during body compression");
+
+            assert_eq!(res.status(), 200);
+            if compressed {
+                assert_eq!(res.headers()["content-encoding"], "gzip");
+            } else {
+                // Small files are sent as is
+                assert!(res.headers().get("content-encoding").is_none());
+                assert_eq!(res.headers()["content-length"], "100");
             }
         }
+
+        fs::remove_dir_all(base_path).unwrap();
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn handle_file_compressions_negotiation() {
+        use headers::ContentCoding;
+
+        let defaults = compression::parse_encodings("").unwrap();
+        let cases = [
+            // Ties are resolved using the server order of preference
+            ("gzip, deflate, br, zstd", Some(ContentCoding::ZSTD)),
+            ("gzip, br", Some(ContentCoding::BROTLI)),
+            // Higher quality values win
+            ("zstd;q=0.5, gzip", Some(ContentCoding::GZIP)),
+            ("br;q=0.8, zstd;q=0.9", Some(ContentCoding::ZSTD)),
+            ("*;q=0.1, gzip;q=0.5", Some(ContentCoding::GZIP)),
+            ("*", Some(ContentCoding::ZSTD)),
+            // Rejected or unsupported encodings
+            ("zstd;q=0, gzip;q=0", None),
+            ("identity, xyz", None),
+            ("", None),
+        ];
+        for (accept_encoding, expected) in cases {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                http::header::ACCEPT_ENCODING,
+                accept_encoding.parse().unwrap(),
+            );
+            assert_eq!(
+                compression::negotiate_encoding(&headers, &defaults),
+                expected,
+                "{accept_encoding}"
+            );
+        }
+
+        // Custom order of preference without unlisted encodings
+        let encodings = compression::parse_encodings("gzip, br").unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            http::header::ACCEPT_ENCODING,
+            "zstd, br, gzip".parse().unwrap(),
+        );
+        assert_eq!(
+            compression::negotiate_encoding(&headers, &encodings),
+            Some(ContentCoding::GZIP)
+        );
+        headers.insert(http::header::ACCEPT_ENCODING, "zstd".parse().unwrap());
+        assert_eq!(compression::negotiate_encoding(&headers, &encodings), None);
+
+        assert!(compression::parse_encodings("gzip, xz").is_err());
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn handle_file_compressions_identity() {
+        use headers::ContentCoding;
+
+        let defaults = compression::parse_encodings("").unwrap();
+
+        // Identity is acceptable by default without an `accept-encoding` header
+        let mut headers = HeaderMap::new();
+        assert!(compression::is_identity_acceptable(&headers));
+        assert_eq!(compression::negotiate_encoding(&headers, &defaults), None);
+
+        let cases = [
+            // Identity refused without any acceptable alternative
+            ("identity;q=0", false, None),
+            ("*;q=0", false, None),
+            ("identity;q=0, xyz", false, None),
+            // Identity refused with an acceptable alternative
+            ("gzip, identity;q=0", false, Some(ContentCoding::GZIP)),
+            ("gzip, *;q=0", false, Some(ContentCoding::GZIP)),
+            // A more specific `identity` entry wins over the wildcard
+            ("*;q=0, identity", true, None),
+            ("identity", true, None),
+            // Explicit identity preferences disable the compression
+            ("identity, gzip", true, None),
+            ("gzip;q=0.5, identity", true, None),
+            (
+                "zstd;q=0.5, br;q=0.5, deflate;q=0.5, gzip;q=0.5, *",
+                true,
+                None,
+            ),
+            ("gzip, identity;q=0.5", true, Some(ContentCoding::GZIP)),
+            ("*, identity;q=0.5", true, Some(ContentCoding::ZSTD)),
+            ("", true, None),
+        ];
+        for (accept_encoding, identity, expected) in cases {
+            headers.insert(
+                http::header::ACCEPT_ENCODING,
+                accept_encoding.parse().unwrap(),
+            );
+            assert_eq!(
+                compression::is_identity_acceptable(&headers),
+                identity,
+                "{accept_encoding}"
+            );
+            assert_eq!(
+                compression::negotiate_encoding(&headers, &defaults),
+                expected,
+                "{accept_encoding}"
+            );
+        }
     }
 
+    #[cfg(feature = "compression")]
     #[tokio::test]
-    async fn handle_byte_ranges_if_range_too_old() {
+    async fn handle_file_compressions_types() {
+        let method = &Method::GET;
         let mut headers = HeaderMap::new();
-        headers.insert("range", "bytes=100-200".parse().unwrap());
-        headers.insert("if-range", "Mon, 18 Nov 1974 00:00:00 GMT".parse().unwrap());
+        headers.insert(http::header::ACCEPT_ENCODING, "gzip".parse().unwrap());
 
-        let buf = fs::read(root_dir().join("index.html"))
-            .expect("unexpected error during index.html reading");
-        let buf = Bytes::from(buf);
+        let base_path = std::env::temp_dir().join("sws-compression-types-test");
+        fs::create_dir_all(&base_path).unwrap();
+        for file in ["page.html", "data.json", "image.png", "photo.jpg"] {
+            fs::write(base_path.join(file), "a".repeat(10 * 1024)).unwrap();
+        }
 
-        for method in [Method::HEAD, Method::GET] {
-            match static_files::handle(&HandleOpts {
-                method: &method,
+        let cases = [
+            ("", "page.html", true),
+            ("", "data.json", true),
+            ("", "image.png", false),
+            ("", "photo.jpg", false),
+            ("application/json, image/*", "page.html", false),
+            ("application/json, image/*", "data.json", true),
+            ("application/json, image/*", "image.png", true),
+        ];
+        for (types, uri_path, compressed) in cases {
+            let (res, _) = static_files::handle(&HandleOpts {
+                method,
                 headers: &headers,
-                base_path: &root_dir(),
-                uri_path: "index.html",
+                base_path: &base_path,
+                overlay_root: None,
+                uri_path,
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
                 dir_listing: false,
@@ -851,28 +1841,67 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
-            {
-                Ok((res, _)) => {
-                    assert_eq!(res.status(), 200);
-                    assert_eq!(res.headers()["content-length"], buf.len().to_string());
-                    assert_eq!(res.headers().get("content-range"), None);
-                }
-                Err(_) => {
-                    panic!("expected a normal response rather than a status error")
-                }
+            .expect("unexpected error response on `handle` function");
+
+            let types = compression::parse_types(types).unwrap();
+            let encodings = compression::parse_encodings("").unwrap();
+            let res = compression::auto_sized(
+                method,
+                &headers,
+                res,
+                65536,
+                0,
+                &types,
+                &encodings,
+                compression::CompressionLevel::Default,
+            )
+            .await
+            .expect("unexpected bytes error during body compression");
+
+            assert_eq!(res.status(), 200);
+            if compressed {
+                assert_eq!(res.headers()["content-encoding"], "gzip", "{uri_path}");
+            } else {
+                // Other types are passed through unchanged
+                assert!(
+                    res.headers().get("content-encoding").is_none(),
+                    "{uri_path}"
+                );
+                assert_eq!(res.headers()["content-length"], "10240");
             }
         }
+
+        fs::remove_dir_all(base_path).unwrap();
     }
 
     #[tokio::test]
-    async fn handle_byte_ranges_suffix() {
+    async fn handle_byte_ranges_single() {
         let mut headers = HeaderMap::new();
-        headers.insert("range", "bytes=100-".parse().unwrap());
+        headers.insert("range", "bytes=0-0".parse().unwrap());
 
         let buf = fs::read(root_dir().join("index.html"))
             .expect("unexpected error during index.html reading");
@@ -883,6 +1912,7 @@ mod tests {
                 method: &method,
                 headers: &headers,
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -891,9 +1921,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -901,16 +1950,13 @@ mod tests {
                     assert_eq!(res.status(), 206);
                     assert_eq!(
                         res.headers()["content-range"],
-                        format!("bytes 100-{}/{}", buf.len() - 1, buf.len())
-                    );
-                    assert_eq!(
-                        res.headers()["content-length"],
-                        &buf[100..].len().to_string()
+                        format!("bytes 0-0/{}", buf.len())
                     );
+                    assert_eq!(res.headers()["content-length"], "1");
                     let body = hyper::body::to_bytes(res.body_mut())
                         .await
                         .expect("unexpected bytes error during `body` conversion");
-                    assert_eq!(body, &buf[100..]);
+                    assert_eq!(body, &buf[..=0]);
                 }
                 Err(_) => {
                     panic!("expected a normal response rather than a status error")
@@ -920,9 +1966,9 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn handle_byte_ranges_suffix_2() {
+    async fn handle_byte_ranges_multiple() {
         let mut headers = HeaderMap::new();
-        headers.insert("range", "bytes=-100".parse().unwrap());
+        headers.insert("range", "bytes=100-200".parse().unwrap());
 
         let buf = fs::read(root_dir().join("index.html"))
             .expect("unexpected error during index.html reading");
@@ -933,6 +1979,7 @@ mod tests {
                 method: &method,
                 headers: &headers,
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -941,9 +1988,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -951,13 +2017,13 @@ mod tests {
                     assert_eq!(res.status(), 206);
                     assert_eq!(
                         res.headers()["content-range"],
-                        format!("bytes {}-{}/{}", buf.len() - 100, buf.len() - 1, buf.len())
+                        format!("bytes 100-200/{}", buf.len())
                     );
-                    assert_eq!(res.headers()["content-length"], "100");
+                    assert_eq!(res.headers()["content-length"], "101");
                     let body = hyper::body::to_bytes(res.body_mut())
                         .await
                         .expect("unexpected bytes error during `body` conversion");
-                    assert_eq!(body, &buf[buf.len() - 100..]);
+                    assert_eq!(body, &buf[100..=200]);
                 }
                 Err(_) => {
                     panic!("expected a normal response rather than a status error")
@@ -967,9 +2033,9 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn handle_byte_ranges_bad() {
+    async fn handle_byte_ranges_out_of_range() {
         let mut headers = HeaderMap::new();
-        headers.insert("range", "bytes=100-10".parse().unwrap());
+        headers.insert("range", "bytes=100-100000".parse().unwrap());
 
         let buf = fs::read(root_dir().join("index.html"))
             .expect("unexpected error during index.html reading");
@@ -980,6 +2046,7 @@ mod tests {
                 method: &method,
                 headers: &headers,
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -988,9 +2055,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -1014,22 +2100,21 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn handle_byte_ranges_bad_2() {
+    async fn handle_byte_ranges_if_range_too_old() {
+        let mut headers = HeaderMap::new();
+        headers.insert("range", "bytes=100-200".parse().unwrap());
+        headers.insert("if-range", "Mon, 18 Nov 1974 00:00:00 GMT".parse().unwrap());
+
         let buf = fs::read(root_dir().join("index.html"))
             .expect("unexpected error during index.html reading");
         let buf = Bytes::from(buf);
 
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            "range",
-            format!("bytes=-{}", buf.len() + 1).parse().unwrap(),
-        );
-
         for method in [Method::HEAD, Method::GET] {
             match static_files::handle(&HandleOpts {
                 method: &method,
                 headers: &headers,
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -1038,23 +2123,35 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
-                Ok((mut res, _)) => {
-                    assert_eq!(res.status(), 416);
-                    assert_eq!(
-                        res.headers()["content-range"],
-                        format!("bytes */{}", buf.len())
-                    );
-                    assert_eq!(res.headers().get("content-length"), None);
-                    let body = hyper::body::to_bytes(res.body_mut())
-                        .await
-                        .expect("unexpected bytes error during `body` conversion");
-                    assert_eq!(body, "");
+                Ok((res, _)) => {
+                    assert_eq!(res.status(), 200);
+                    assert_eq!(res.headers()["content-length"], buf.len().to_string());
+                    assert_eq!(res.headers().get("content-range"), None);
                 }
                 Err(_) => {
                     panic!("expected a normal response rather than a status error")
@@ -1064,20 +2161,20 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn handle_byte_ranges_bad_3() {
+    async fn handle_byte_ranges_suffix() {
+        let mut headers = HeaderMap::new();
+        headers.insert("range", "bytes=100-".parse().unwrap());
+
         let buf = fs::read(root_dir().join("index.html"))
             .expect("unexpected error during index.html reading");
         let buf = Bytes::from(buf);
 
-        let mut headers = HeaderMap::new();
-        // Range::Unbounded for beginning and end
-        headers.insert("range", "bytes=".parse().unwrap());
-
         for method in [Method::HEAD, Method::GET] {
             match static_files::handle(&HandleOpts {
                 method: &method,
                 headers: &headers,
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -1086,15 +2183,309 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
                 Ok((mut res, _)) => {
-                    assert_eq!(res.status(), 200);
-                    let body = hyper::body::to_bytes(res.body_mut())
+                    assert_eq!(res.status(), 206);
+                    assert_eq!(
+                        res.headers()["content-range"],
+                        format!("bytes 100-{}/{}", buf.len() - 1, buf.len())
+                    );
+                    assert_eq!(
+                        res.headers()["content-length"],
+                        &buf[100..].len().to_string()
+                    );
+                    let body = hyper::body::to_bytes(res.body_mut())
+                        .await
+                        .expect("unexpected bytes error during `body` conversion");
+                    assert_eq!(body, &buf[100..]);
+                }
+                Err(_) => {
+                    panic!("expected a normal response rather than a status error")
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_byte_ranges_suffix_2() {
+        let mut headers = HeaderMap::new();
+        headers.insert("range", "bytes=-100".parse().unwrap());
+
+        let buf = fs::read(root_dir().join("index.html"))
+            .expect("unexpected error during index.html reading");
+        let buf = Bytes::from(buf);
+
+        for method in [Method::HEAD, Method::GET] {
+            match static_files::handle(&HandleOpts {
+                method: &method,
+                headers: &headers,
+                base_path: &root_dir(),
+                overlay_root: None,
+                uri_path: "index.html",
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            {
+                Ok((mut res, _)) => {
+                    assert_eq!(res.status(), 206);
+                    assert_eq!(
+                        res.headers()["content-range"],
+                        format!("bytes {}-{}/{}", buf.len() - 100, buf.len() - 1, buf.len())
+                    );
+                    assert_eq!(res.headers()["content-length"], "100");
+                    let body = hyper::body::to_bytes(res.body_mut())
+                        .await
+                        .expect("unexpected bytes error during `body` conversion");
+                    assert_eq!(body, &buf[buf.len() - 100..]);
+                }
+                Err(_) => {
+                    panic!("expected a normal response rather than a status error")
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_byte_ranges_bad() {
+        let mut headers = HeaderMap::new();
+        headers.insert("range", "bytes=100-10".parse().unwrap());
+
+        let buf = fs::read(root_dir().join("index.html"))
+            .expect("unexpected error during index.html reading");
+        let buf = Bytes::from(buf);
+
+        for method in [Method::HEAD, Method::GET] {
+            match static_files::handle(&HandleOpts {
+                method: &method,
+                headers: &headers,
+                base_path: &root_dir(),
+                overlay_root: None,
+                uri_path: "index.html",
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            {
+                Ok((mut res, _)) => {
+                    assert_eq!(res.status(), 416);
+                    assert_eq!(
+                        res.headers()["content-range"],
+                        format!("bytes */{}", buf.len())
+                    );
+                    assert_eq!(res.headers().get("content-length"), None);
+                    let body = hyper::body::to_bytes(res.body_mut())
+                        .await
+                        .expect("unexpected bytes error during `body` conversion");
+                    assert_eq!(body, "");
+                }
+                Err(_) => {
+                    panic!("expected a normal response rather than a status error")
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_byte_ranges_bad_2() {
+        let buf = fs::read(root_dir().join("index.html"))
+            .expect("unexpected error during index.html reading");
+        let buf = Bytes::from(buf);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "range",
+            format!("bytes=-{}", buf.len() + 1).parse().unwrap(),
+        );
+
+        for method in [Method::HEAD, Method::GET] {
+            match static_files::handle(&HandleOpts {
+                method: &method,
+                headers: &headers,
+                base_path: &root_dir(),
+                overlay_root: None,
+                uri_path: "index.html",
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            {
+                Ok((mut res, _)) => {
+                    assert_eq!(res.status(), 416);
+                    assert_eq!(
+                        res.headers()["content-range"],
+                        format!("bytes */{}", buf.len())
+                    );
+                    assert_eq!(res.headers().get("content-length"), None);
+                    let body = hyper::body::to_bytes(res.body_mut())
+                        .await
+                        .expect("unexpected bytes error during `body` conversion");
+                    assert_eq!(body, "");
+                }
+                Err(_) => {
+                    panic!("expected a normal response rather than a status error")
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_byte_ranges_bad_3() {
+        let buf = fs::read(root_dir().join("index.html"))
+            .expect("unexpected error during index.html reading");
+        let buf = Bytes::from(buf);
+
+        let mut headers = HeaderMap::new();
+        // Range::Unbounded for beginning and end
+        headers.insert("range", "bytes=".parse().unwrap());
+
+        for method in [Method::HEAD, Method::GET] {
+            match static_files::handle(&HandleOpts {
+                method: &method,
+                headers: &headers,
+                base_path: &root_dir(),
+                overlay_root: None,
+                uri_path: "index.html",
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            {
+                Ok((mut res, _)) => {
+                    assert_eq!(res.status(), 200);
+                    let body = hyper::body::to_bytes(res.body_mut())
                         .await
                         .expect("unexpected bytes error during `body` conversion");
                     assert_eq!(body, buf);
@@ -1121,6 +2512,7 @@ mod tests {
                 method: &method,
                 headers: &headers,
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -1129,9 +2521,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -1175,6 +2586,7 @@ mod tests {
                 method: &method,
                 headers: &headers,
                 base_path: &root_dir(),
+                overlay_root: None,
                 uri_path: "index.html",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -1183,9 +2595,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: false,
                 ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -1221,6 +2652,7 @@ mod tests {
                 method: &method,
                 headers: &headers,
                 base_path: &root_dir,
+                overlay_root: None,
                 uri_path: ".dotfile",
                 uri_query: None,
                 #[cfg(feature = "directory-listing")]
@@ -1229,9 +2661,28 @@ mod tests {
                 dir_listing_order: 6,
                 #[cfg(feature = "directory-listing")]
                 dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
                 redirect_trailing_slash: true,
+                trailing_slash_policy: None,
                 compression_static: true,
                 ignore_hidden_files: true,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
             })
             .await
             {
@@ -1244,4 +2695,643 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn handle_case_insensitive_paths() {
+        for (case_insensitive_paths, expected) in [(true, Some(200)), (false, None)] {
+            let res = static_files::handle(&HandleOpts {
+                method: &Method::GET,
+                headers: &HeaderMap::new(),
+                base_path: &root_dir(),
+                overlay_root: None,
+                uri_path: "/Assets/MAIN.css",
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await;
+
+            match expected {
+                Some(status) => {
+                    let (res, _) = res.expect("unexpected error response on `handle` function");
+                    assert_eq!(res.status(), status);
+                    assert_eq!(res.headers()["content-type"], "text/css");
+                }
+                None => {
+                    assert_eq!(res.unwrap_err(), StatusCode::NOT_FOUND);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_byte_ranges_if_range_matching() {
+        let buf = fs::read(root_dir().join("index.html"))
+            .expect("unexpected error during index.html reading");
+        let buf = Bytes::from(buf);
+        let modified = fs::metadata(root_dir().join("index.html"))
+            .and_then(|m| m.modified())
+            .expect("unexpected error during index.html metadata reading");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("range", "bytes=100-200".parse().unwrap());
+        headers.typed_insert(headers::IfRange::date(modified));
+
+        for method in [Method::HEAD, Method::GET] {
+            match static_files::handle(&HandleOpts {
+                method: &method,
+                headers: &headers,
+                base_path: &root_dir(),
+                overlay_root: None,
+                uri_path: "index.html",
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            {
+                Ok((res, _)) => {
+                    assert_eq!(res.status(), 206);
+                    assert_eq!(
+                        res.headers()["content-range"],
+                        format!("bytes 100-200/{}", buf.len())
+                    );
+                    assert_eq!(res.headers()["content-length"], "101");
+                }
+                Err(_) => {
+                    panic!("expected a normal response rather than a status error")
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_byte_ranges_if_range_etag() {
+        let mut headers = HeaderMap::new();
+        headers.insert("range", "bytes=100-200".parse().unwrap());
+        headers.insert("if-range", "\"xyzzy\"".parse().unwrap());
+
+        let buf = fs::read(root_dir().join("index.html"))
+            .expect("unexpected error during index.html reading");
+        let buf = Bytes::from(buf);
+
+        for method in [Method::HEAD, Method::GET] {
+            match static_files::handle(&HandleOpts {
+                method: &method,
+                headers: &headers,
+                base_path: &root_dir(),
+                overlay_root: None,
+                uri_path: "index.html",
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            {
+                Ok((res, _)) => {
+                    assert_eq!(res.status(), 200);
+                    assert_eq!(res.headers()["content-length"], buf.len().to_string());
+                    assert_eq!(res.headers().get("content-range"), None);
+                }
+                Err(_) => {
+                    panic!("expected a normal response rather than a status error")
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_byte_ranges_if_range_weak_etag() {
+        // Weak entity tags never match the `If-Range` (strong comparison)
+        for (etag_mode, status) in [(EtagMode::Weak, 200), (EtagMode::Strong, 206)] {
+            let headers = HeaderMap::new();
+            let (res, _) = static_files::handle(&HandleOpts {
+                method: &Method::GET,
+                headers: &headers,
+                base_path: &root_dir(),
+                overlay_root: None,
+                uri_path: "index.html",
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            .expect("unexpected error response on index.html");
+            let etag = res.headers()["etag"].clone();
+            assert_eq!(
+                etag.as_bytes().starts_with(b"W/"),
+                etag_mode == EtagMode::Weak
+            );
+
+            let mut headers = HeaderMap::new();
+            headers.insert("range", "bytes=0-9".parse().unwrap());
+            headers.insert("if-range", etag);
+            let (res, _) = static_files::handle(&HandleOpts {
+                method: &Method::GET,
+                headers: &headers,
+                base_path: &root_dir(),
+                overlay_root: None,
+                uri_path: "index.html",
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            .expect("unexpected error response on index.html");
+            assert_eq!(res.status(), status);
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_byte_ranges_disabled() {
+        let mut headers = HeaderMap::new();
+        headers.insert("range", "bytes=100-200".parse().unwrap());
+
+        let buf = fs::read(root_dir().join("index.html"))
+            .expect("unexpected error during index.html reading");
+        let buf = Bytes::from(buf);
+
+        for method in [Method::HEAD, Method::GET] {
+            match static_files::handle(&HandleOpts {
+                method: &method,
+                headers: &headers,
+                base_path: &root_dir(),
+                overlay_root: None,
+                uri_path: "index.html",
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: true,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            {
+                Ok((res, _)) => {
+                    assert_eq!(res.status(), 200);
+                    assert_eq!(res.headers()["content-length"], buf.len().to_string());
+                    assert_eq!(res.headers().get("content-range"), None);
+                    assert_eq!(res.headers().get("accept-ranges"), None);
+                }
+                Err(_) => {
+                    panic!("expected a normal response rather than a status error")
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_trailing_slash_dir_redirect() {
+        let (res, _) = static_files::handle(&HandleOpts {
+            method: &Method::GET,
+            headers: &HeaderMap::new(),
+            base_path: &root_dir(),
+            overlay_root: None,
+            uri_path: "/assets",
+            uri_query: None,
+            #[cfg(feature = "directory-listing")]
+            dir_listing: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_order: 6,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
+            redirect_trailing_slash: true,
+            trailing_slash_policy: None,
+            compression_static: false,
+            ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_date_format: "%F %T",
+            #[cfg(feature = "directory-listing")]
+            dir_listing_timezone: &DirListTimezone::Local,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_ignore: &[],
+            #[cfg(feature = "directory-listing")]
+            dir_listing_show_symlinks: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_merge_overlay: false,
+        })
+        .await
+        .expect("unexpected error response on `handle` function");
+
+        assert_eq!(res.status(), 308);
+        assert_eq!(res.headers()["location"], "/assets/");
+    }
+
+    #[tokio::test]
+    async fn handle_trailing_slash_file_not_found() {
+        for uri_path in ["/index.html/", "/assets/main.css/", "/index/"] {
+            let res = static_files::handle(&HandleOpts {
+                method: &Method::GET,
+                headers: &HeaderMap::new(),
+                base_path: &root_dir(),
+                overlay_root: None,
+                uri_path,
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await;
+
+            assert_eq!(res.unwrap_err(), StatusCode::NOT_FOUND, "{uri_path}");
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_options_method() {
+        for uri_path in ["/index.html", "/assets/", "/"] {
+            let (mut res, _) = static_files::handle(&HandleOpts {
+                method: &Method::OPTIONS,
+                headers: &HeaderMap::new(),
+                base_path: &root_dir(),
+                overlay_root: None,
+                uri_path,
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: true,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode: EtagMode::Metadata,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            .expect("unexpected error response on `handle` function");
+
+            assert_eq!(res.status(), 204, "{uri_path}");
+            assert_eq!(res.headers()["allow"], "OPTIONS, HEAD, GET");
+            assert_eq!(res.headers()["accept-ranges"], "bytes");
+            assert!(!res.headers().contains_key("content-type"));
+
+            let body = hyper::body::to_bytes(res.body_mut())
+                .await
+                .expect("unexpected bytes error during `body` conversion");
+            assert!(body.is_empty());
+        }
+
+        // Not existing resources
+        let res = static_files::handle(&HandleOpts {
+            method: &Method::OPTIONS,
+            headers: &HeaderMap::new(),
+            base_path: &root_dir(),
+            overlay_root: None,
+            uri_path: "/not-found.html",
+            uri_query: None,
+            #[cfg(feature = "directory-listing")]
+            dir_listing: true,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_order: 6,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_format: &DirListFmt::Html,
+            index_files: &["index.html".to_owned()],
+            allowed_methods: HTTP_SUPPORTED_METHODS,
+            extensionless_html: true,
+            redirect_trailing_slash: true,
+            trailing_slash_policy: None,
+            compression_static: false,
+            ignore_hidden_files: false,
+            case_insensitive_paths: false,
+            disable_range_requests: false,
+            etag_mode: EtagMode::Metadata,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_size_format: &DirListSizeFmt::Human,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_date_format: "%F %T",
+            #[cfg(feature = "directory-listing")]
+            dir_listing_timezone: &DirListTimezone::Local,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_ignore: &[],
+            #[cfg(feature = "directory-listing")]
+            dir_listing_show_symlinks: false,
+            #[cfg(feature = "directory-listing")]
+            dir_listing_merge_overlay: false,
+        })
+        .await;
+        assert_eq!(res.unwrap_err(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn handle_etag_modes() {
+        async fn request(etag_mode: EtagMode, if_none_match: Option<&str>) -> (StatusCode, String) {
+            let mut headers = HeaderMap::new();
+            if let Some(etag) = if_none_match {
+                headers.insert(http::header::IF_NONE_MATCH, etag.parse().unwrap());
+            }
+            match static_files::handle(&HandleOpts {
+                method: &Method::GET,
+                headers: &headers,
+                base_path: &root_dir(),
+                overlay_root: None,
+                uri_path: "index.html",
+                uri_query: None,
+                #[cfg(feature = "directory-listing")]
+                dir_listing: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_order: 6,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_format: &DirListFmt::Html,
+                index_files: &["index.html".to_owned()],
+                allowed_methods: HTTP_SUPPORTED_METHODS,
+                extensionless_html: true,
+                redirect_trailing_slash: true,
+                trailing_slash_policy: None,
+                compression_static: false,
+                ignore_hidden_files: false,
+                case_insensitive_paths: false,
+                disable_range_requests: false,
+                etag_mode,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_size_format: &DirListSizeFmt::Human,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_date_format: "%F %T",
+                #[cfg(feature = "directory-listing")]
+                dir_listing_timezone: &DirListTimezone::Local,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_ignore: &[],
+                #[cfg(feature = "directory-listing")]
+                dir_listing_show_symlinks: false,
+                #[cfg(feature = "directory-listing")]
+                dir_listing_merge_overlay: false,
+            })
+            .await
+            {
+                Ok((res, _)) => (
+                    res.status(),
+                    res.headers()["etag"].to_str().unwrap().to_owned(),
+                ),
+                Err(status) => panic!("unexpected status error {status}"),
+            }
+        }
+
+        let (status, metadata) = request(EtagMode::Metadata, None).await;
+        assert_eq!(status, 200);
+        assert!(!metadata.starts_with("W/"));
+
+        let (status, weak) = request(EtagMode::Weak, None).await;
+        assert_eq!(status, 200);
+        assert_eq!(weak, format!("W/{metadata}"));
+
+        let (status, strong) = request(EtagMode::Strong, None).await;
+        assert_eq!(status, 200);
+        assert!(!strong.starts_with("W/"));
+        assert_ne!(strong, metadata);
+
+        // The content hash is stable across requests
+        assert_eq!(request(EtagMode::Strong, None).await.1, strong);
+        let (status, etag) = request(EtagMode::Strong, Some(&strong)).await;
+        assert_eq!(status, 304);
+        assert_eq!(etag, strong);
+        let (status, _) = request(EtagMode::Weak, Some(&weak)).await;
+        assert_eq!(status, 304);
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn handle_file_compressions_levels() {
+        use std::io::Read;
+
+        let method = &Method::GET;
+        let mut headers = HeaderMap::new();
+        headers.insert(http::header::ACCEPT_ENCODING, "gzip".parse().unwrap());
+        let content = fs::read(root_dir().join("index.html"))
+            .expect("unexpected error during index.html reading")
+            .repeat(64);
+
+        let mut sizes = vec![];
+        for level in [
+            compression::CompressionLevel::Fastest,
+            compression::CompressionLevel::Best,
+        ] {
+            let mut res = http::Response::new(hyper::Body::from(content.clone()));
+            res.headers_mut()
+                .insert(http::header::CONTENT_TYPE, "text/html".parse().unwrap());
+            let res = compression::auto(
+                method,
+                &headers,
+                res,
+                &compression::parse_types("").unwrap(),
+                &compression::parse_encodings("").unwrap(),
+                level,
+            )
+            .expect("unexpected bytes error during body compression");
+            assert_eq!(res.headers()["content-encoding"], "gzip");
+
+            let compressed = hyper::body::to_bytes(res.into_body()).await.unwrap();
+            let mut decoded = vec![];
+            flate2::read::GzDecoder::new(compressed.as_ref())
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert!(decoded == content, "{level:?}");
+            sizes.push(compressed.len());
+        }
+        // The best level never produces a larger body than the fastest one
+        assert!(sizes[1] <= sizes[0], "{sizes:?}");
+    }
 }